@@ -1,8 +1,11 @@
+use crate::dart::{dart_post_int, DartPort};
 use crate::raw_object_set::{RawObject, RawObjectSet};
 use crate::txn::IsarDartTxn;
 use crate::{from_c_str, BoolSend, UintSend};
-use isar_core::collection::IsarCollection;
+use isar_core::collection::{IsarCollection, OnConflict};
+use isar_core::error::{illegal_arg, Result};
 use isar_core::index::index_key::IndexKey;
+use isar_core::instance::IsarInstance;
 use serde_json::Value;
 use std::os::raw::c_char;
 
@@ -81,20 +84,31 @@ pub unsafe extern "C" fn isar_get_all_by_index(
     })
 }
 
+fn get_on_conflict(on_conflict: u8) -> Result<OnConflict> {
+    match on_conflict {
+        0 => Ok(OnConflict::Abort),
+        1 => Ok(OnConflict::Replace),
+        2 => Ok(OnConflict::Ignore),
+        3 => Ok(OnConflict::Update),
+        _ => illegal_arg("Unknown conflict mode."),
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_put(
     collection: &'static mut IsarCollection,
     txn: &mut IsarDartTxn,
     object: &'static mut RawObject,
-    replace_on_conflict: bool,
+    on_conflict: u8,
 ) -> i64 {
     isar_try_txn!(txn, move |txn| {
+        let on_conflict = get_on_conflict(on_conflict)?;
         let id = if object.get_id() != i64::MIN {
             Some(object.get_id())
         } else {
             None
         };
-        let id = collection.put(txn, id, object.get_object(), replace_on_conflict)?;
+        let id = collection.put(txn, id, object.get_object(), on_conflict)?;
         object.set_id(id);
         Ok(())
     })
@@ -105,22 +119,47 @@ pub unsafe extern "C" fn isar_put_all(
     collection: &'static IsarCollection,
     txn: &mut IsarDartTxn,
     objects: &'static mut RawObjectSet,
-    replace_on_conflict: bool,
+    on_conflict: u8,
 ) -> i64 {
     isar_try_txn!(txn, move |txn| {
-        for object in objects.get_objects() {
-            let id = if object.get_id() != i64::MIN {
-                Some(object.get_id())
-            } else {
-                None
-            };
-            let id = collection.put(txn, id, object.get_object(), replace_on_conflict)?;
-            object.set_id(id)
+        let on_conflict = get_on_conflict(on_conflict)?;
+        let objects = objects.get_objects();
+        let batch: Vec<_> = objects
+            .iter_mut()
+            .map(|object| {
+                let id = if object.get_id() != i64::MIN {
+                    Some(object.get_id())
+                } else {
+                    None
+                };
+                (id, object.get_object())
+            })
+            .collect();
+        let ids = collection.put_all(txn, &batch, on_conflict)?;
+        for (object, id) in objects.iter_mut().zip(ids) {
+            object.set_id(id);
         }
         Ok(())
     })
 }
 
+// The objects need ascending ids that are greater than the ids of all existing objects.
+#[no_mangle]
+pub unsafe extern "C" fn isar_put_all_sorted(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    objects: &'static mut RawObjectSet,
+) -> i64 {
+    isar_try_txn!(txn, move |txn| {
+        let batch: Vec<_> = objects
+            .get_objects()
+            .iter_mut()
+            .map(|object| (object.get_id(), object.get_object()))
+            .collect();
+        collection.put_all_sorted(txn, &batch)
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_delete(
     collection: &'static IsarCollection,
@@ -173,6 +212,21 @@ pub unsafe extern "C" fn isar_delete_all(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_delete_orphans(
+    isar: &'static IsarInstance,
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    backlink_index: u32,
+    count: &'static mut u32,
+) -> i64 {
+    let count = UintSend(count);
+    isar_try_txn!(txn, move |txn| {
+        *count.0 = isar.delete_orphans(txn, collection, backlink_index as usize)? as u32;
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_delete_all_by_index(
     collection: &'static IsarCollection,
@@ -221,3 +275,70 @@ pub unsafe extern "C" fn isar_json_import(
         collection.import_json(txn, id_name, json, replace_on_conflict)
     })
 }
+
+// 0: ignore ids and use auto increment, 1: keep ids and fail on conflicts, 2: keep ids and
+// replace existing objects. The number of imported objects is posted to the port while the
+// import is running. The JSON is decoded on the given number of threads.
+#[no_mangle]
+pub unsafe extern "C" fn isar_import_json_async(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    id_name: *const c_char,
+    json_bytes: *const u8,
+    json_length: u32,
+    id_strategy: u8,
+    threads: u32,
+    port: DartPort,
+) -> i64 {
+    let id_name = from_c_str(id_name).unwrap();
+    let bytes = std::slice::from_raw_parts(json_bytes, json_length as usize);
+    isar_try! {
+        let (id_name, replace_on_conflict) = match id_strategy {
+            0 => (None, false),
+            1 => (id_name, false),
+            2 => (id_name, true),
+            _ => illegal_arg("Unknown id strategy.")?,
+        };
+        let json = bytes.to_vec();
+        txn.exec(Box::new(move |txn| {
+            collection.import_json_parallel(
+                txn,
+                id_name,
+                json,
+                replace_on_conflict,
+                threads as usize,
+                |count| dart_post_int(port, count as i64),
+            )
+        }))?;
+    }
+}
+
+struct StatsSend(*mut u64);
+
+unsafe impl Send for StatsSend {}
+
+// Writes the object count, object bytes and total bytes followed by the entries and bytes of
+// every index to the stats array which needs 3 + 2 * index count elements.
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_collection_stats(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    stats: *mut u64,
+) -> i64 {
+    let stats = StatsSend(stats);
+    isar_try_txn!(txn, move |txn| {
+        let stats = stats;
+        let collection_stats = collection.get_stats(txn)?;
+        let mut values = vec![
+            collection_stats.object_count,
+            collection_stats.object_size,
+            collection_stats.size,
+        ];
+        for (_, index_stats) in &collection_stats.indexes {
+            values.push(index_stats.entries);
+            values.push(index_stats.size);
+        }
+        std::ptr::copy_nonoverlapping(values.as_ptr(), stats.0, values.len());
+        Ok(())
+    })
+}
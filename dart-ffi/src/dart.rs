@@ -7,6 +7,17 @@ pub fn dart_post_int(port: DartPort, value: i64) {
     dart_post(port, &mut DartCObject::new(value));
 }
 
+// Dart copies the bytes before the call returns. None is posted as null.
+pub fn dart_post_bytes(port: DartPort, bytes: Option<&[u8]>) {
+    let dart_post = DART_POST_C_OBJECT.get().unwrap();
+    let mut object = if let Some(bytes) = bytes {
+        DartCObject::new_bytes(bytes)
+    } else {
+        DartCObject::null()
+    };
+    dart_post(port, &mut object);
+}
+
 pub type DartPort = i64;
 
 pub type DartPostCObjectFnType = extern "C" fn(port_id: DartPort, message: *mut DartCObject) -> i8;
@@ -24,11 +35,40 @@ impl DartCObject {
             value: DartCObjectValue { value },
         }
     }
+
+    fn null() -> Self {
+        DartCObject {
+            ty: 0,
+            value: DartCObjectValue { value: 0 },
+        }
+    }
+
+    fn new_bytes(bytes: &[u8]) -> Self {
+        DartCObject {
+            ty: 7,
+            value: DartCObjectValue {
+                typed_data: DartTypedData {
+                    ty: 2,
+                    length: bytes.len() as isize,
+                    values: bytes.as_ptr(),
+                },
+            },
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct DartTypedData {
+    ty: i32,
+    length: isize,
+    values: *const u8,
 }
 
 #[repr(C)]
 union DartCObjectValue {
     pub value: i64,
+    typed_data: DartTypedData,
     _union_align: [u64; 5usize],
 }
 
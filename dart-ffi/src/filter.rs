@@ -2,7 +2,6 @@ use crate::from_c_str;
 use isar_core::collection::IsarCollection;
 use isar_core::error::illegal_arg;
 use isar_core::object::data_type::DataType;
-use isar_core::object::isar_object::IsarObject;
 use isar_core::query::filter::*;
 use std::os::raw::c_char;
 use std::slice;
@@ -68,25 +67,7 @@ pub unsafe extern "C" fn isar_filter_null(
     isar_try! {
         if let Some((_, property)) = property {
             let query_filter = if !property.data_type.is_scalar() && any_null {
-                match property.data_type {
-                    DataType::ByteList => {
-                        Filter::byte(*property, IsarObject::NULL_BYTE, IsarObject::NULL_BYTE)?
-                    },
-                    DataType::IntList => {
-                        Filter::int(*property, IsarObject::NULL_INT, IsarObject::NULL_INT)?
-                    },
-                    DataType::FloatList => {
-                        Filter::float(*property, IsarObject::NULL_FLOAT, IsarObject::NULL_FLOAT)?
-                    },
-                    DataType::LongList => {
-                        Filter::long(*property, IsarObject::NULL_LONG, IsarObject::NULL_LONG)?
-                    },
-                    DataType::DoubleList => {
-                        Filter::double(*property, IsarObject::NULL_DOUBLE, IsarObject::NULL_DOUBLE)?
-                    },
-                    DataType::StringList => Filter::string(*property, None, None, false)?,
-                    _ => unreachable!()
-                }
+                Filter::any_null_element(*property)?
             } else {
                  Filter::null(*property)
             };
@@ -189,6 +170,8 @@ pub unsafe extern "C" fn isar_filter_long(
                 let lower = lower.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
                 let upper = upper.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
                 num_filter!(int, property, lower, include_lower, upper, include_upper)
+            } else if property.data_type == DataType::DateTime || property.data_type == DataType::DateTimeList {
+                num_filter!(date_time, property, lower, include_lower, upper, include_upper)
             } else {
                 num_filter!(long, property, lower, include_lower, upper, include_upper)
             };
@@ -332,3 +315,46 @@ filter_string_ffi!(string_starts_with, isar_filter_string_starts_with);
 filter_string_ffi!(string_ends_with, isar_filter_string_ends_with);
 filter_string_ffi!(string_contains, isar_filter_string_contains);
 filter_string_ffi!(string_matches, isar_filter_string_matches);
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_geo_within_radius(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    lat: f64,
+    lon: f64,
+    meters: f64,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = Filter::geo_within_radius(*property, lat, lon, meters)?;
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_filter_geo_within_box(
+    collection: &IsarCollection,
+    filter: *mut *const Filter,
+    south: f64,
+    west: f64,
+    north: f64,
+    east: f64,
+    property_index: u32,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    isar_try! {
+        if let Some((_, property)) = property {
+            let query_filter = Filter::geo_within_box(*property, south, west, north, east)?;
+            let ptr = Box::into_raw(Box::new(query_filter));
+            filter.write(ptr);
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
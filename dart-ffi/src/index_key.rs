@@ -1,5 +1,8 @@
 use crate::from_c_str;
+use isar_core::collation::Collation;
+use isar_core::error::{illegal_arg, Result};
 use isar_core::index::index_key::IndexKey;
+use isar_core::index::{normalize_string, round_double, round_float};
 use isar_core::object::isar_object::IsarObject;
 use paste::paste;
 use std::os::raw::c_char;
@@ -36,14 +39,54 @@ pub extern "C" fn isar_key_add_double(key: &mut IndexKey, value: f64) {
     key.add_double(value);
 }
 
+#[no_mangle]
+pub extern "C" fn isar_key_add_rounded_float(key: &mut IndexKey, value: f32, decimals: i8) {
+    key.add_float(round_float(value, decimals));
+}
+
+#[no_mangle]
+pub extern "C" fn isar_key_add_rounded_double(key: &mut IndexKey, value: f64, decimals: i8) {
+    key.add_double(round_double(value, decimals));
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_key_add_string(
     key: &mut IndexKey,
     value: *const c_char,
     case_sensitive: bool,
+    normalize: bool,
 ) {
     let value = from_c_str(value).unwrap();
-    key.add_string(value, case_sensitive)
+    if normalize {
+        let value = value.map(|v| normalize_string(v, case_sensitive));
+        key.add_string(value.as_deref(), true)
+    } else {
+        key.add_string(value, case_sensitive)
+    }
+}
+
+pub(crate) fn get_collation(collation: u8) -> Result<Collation> {
+    match collation {
+        0 => Ok(Collation::Binary),
+        1 => Ok(Collation::Root),
+        2 => Ok(Collation::German),
+        3 => Ok(Collation::Swedish),
+        4 => Ok(Collation::Turkish),
+        _ => illegal_arg("Unknown collation."),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_key_add_collated_string(
+    key: &mut IndexKey,
+    value: *const c_char,
+    collation: u8,
+    case_sensitive: bool,
+) -> i64 {
+    let value = from_c_str(value).unwrap();
+    isar_try! {
+        key.add_collated_string(value, get_collation(collation)?, case_sensitive);
+    }
 }
 
 #[no_mangle]
@@ -51,9 +94,15 @@ pub unsafe extern "C" fn isar_key_add_string_hash(
     key: &mut IndexKey,
     value: *const c_char,
     case_sensitive: bool,
+    normalize: bool,
 ) {
     let value = from_c_str(value).unwrap();
-    let hash = IsarObject::hash_string(value, case_sensitive, 0);
+    let hash = if normalize {
+        let value = value.map(|v| normalize_string(v, case_sensitive));
+        IsarObject::hash_string(value.as_deref(), true, 0)
+    } else {
+        IsarObject::hash_string(value, case_sensitive, 0)
+    };
     key.add_hash(hash);
 }
 
@@ -63,19 +112,26 @@ pub unsafe extern "C" fn isar_key_add_string_list_hash(
     value: *const *const c_char,
     length: u32,
     case_sensitive: bool,
+    normalize: bool,
 ) {
     let value = if !value.is_null() {
         let raw_strings = std::slice::from_raw_parts(value, length as usize);
         let mut strings = vec![];
         for raw_str in raw_strings {
             let str = from_c_str(*raw_str).unwrap();
+            let str = if normalize {
+                str.map(|s| normalize_string(s, case_sensitive))
+            } else {
+                str.map(|s| s.to_string())
+            };
             strings.push(str);
         }
         Some(strings)
     } else {
         None
     };
-    let hash = IsarObject::hash_string_list(value, case_sensitive, 0);
+    let value = value.as_ref().map(|v| v.iter().map(|s| s.as_deref()).collect());
+    let hash = IsarObject::hash_string_list(value, case_sensitive || normalize, 0);
     key.add_hash(hash);
 }
 
@@ -1,34 +1,40 @@
 use crate::dart::{dart_post_int, DartPort};
 use crate::error::DartErrCode;
-use crate::from_c_str;
+use crate::{from_c_path, from_c_str, PathChar};
 use crate::txn::run_async;
 use crate::CharsSend;
 use isar_core::collection::IsarCollection;
 use isar_core::error::{illegal_arg, Result};
 use isar_core::instance::IsarInstance;
 use isar_core::schema::Schema;
+use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
+use std::ptr;
 use std::sync::Arc;
 
 struct IsarInstanceSend(*mut *const IsarInstance);
 
 unsafe impl Send for IsarInstanceSend {}
 
+struct PathSend(*const PathChar);
+
+unsafe impl Send for PathSend {}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_create_instance(
     isar: *mut *const IsarInstance,
     name: *const c_char,
-    path: *const c_char,
+    path: *const PathChar,
     relaxed_durability: bool,
     schema_json: *const c_char,
 ) -> i64 {
     let open = || -> Result<()> {
         let name = from_c_str(name).unwrap().unwrap();
-        let path = from_c_str(path).unwrap().unwrap();
+        let path = from_c_path(path)?;
         let schema_json = from_c_str(schema_json).unwrap().unwrap();
         let schema = Schema::from_json(schema_json.as_bytes())?;
 
-        let instance = IsarInstance::open(name, path, relaxed_durability, schema)?;
+        let instance = IsarInstance::open(name, &path, relaxed_durability, schema)?;
         isar.write(Arc::into_raw(instance));
         Ok(())
     };
@@ -39,18 +45,36 @@ pub unsafe extern "C" fn isar_create_instance(
     }
 }
 
+// Returns whether the schema is valid. Otherwise out_error points to a JSON object with the
+// collection and message of the first error that has to be freed with isar_free_error.
+#[no_mangle]
+pub unsafe extern "C" fn isar_schema_validate(
+    schema_json: *const c_char,
+    out_error: *mut *mut c_char,
+) -> bool {
+    let schema_json = CStr::from_ptr(schema_json).to_bytes();
+    if let Err(err) = Schema::validate_json(schema_json) {
+        let json = serde_json::to_string(&err).unwrap();
+        out_error.write(CString::new(json).unwrap().into_raw());
+        false
+    } else {
+        out_error.write(ptr::null_mut());
+        true
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_create_instance_async(
     isar: *mut *const IsarInstance,
     name: *const c_char,
-    path: *const c_char,
+    path: *const PathChar,
     relaxed_durability: bool,
     schema_json: *const c_char,
     port: DartPort,
 ) {
     let isar = IsarInstanceSend(isar);
     let name = CharsSend(name);
-    let path = CharsSend(path);
+    let path = PathSend(path);
     let schema_json = CharsSend(schema_json);
     run_async(move || {
         let isar = isar;
@@ -60,6 +84,12 @@ pub unsafe extern "C" fn isar_create_instance_async(
     });
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_instance(name: *const c_char) -> *const IsarInstance {
+    let name = from_c_str(name).unwrap().unwrap();
+    IsarInstance::get_instance(name).map_or(ptr::null(), Arc::into_raw)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_close_instance(
     isar: *const IsarInstance,
@@ -73,6 +103,71 @@ pub unsafe extern "C" fn isar_close_instance(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_export_encrypted(
+    isar: &IsarInstance,
+    path: *const PathChar,
+    key: &[u8; 32],
+) -> i64 {
+    isar_try! {
+        let path = from_c_path(path)?;
+        isar.export_encrypted(&path, key)?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_restore_encrypted(
+    path: *const PathChar,
+    key: &[u8; 32],
+    name: *const c_char,
+    dir: *const PathChar,
+) -> i64 {
+    isar_try! {
+        let path = from_c_path(path)?;
+        let name = from_c_str(name).unwrap().unwrap();
+        let dir = from_c_path(dir)?;
+        IsarInstance::restore_encrypted(&path, key, name, &dir)?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_instance_copy_to_file(
+    isar: &IsarInstance,
+    path: *const PathChar,
+) -> i64 {
+    isar_try! {
+        let path = from_c_path(path)?;
+        isar.copy_to_file(&path)?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_verify(isar: &'static IsarInstance, port: DartPort) {
+    run_async(move || {
+        let result = isar_try! {
+            isar.verify()?;
+        };
+        dart_post_int(port, result);
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_compact(
+    isar: &'static IsarInstance,
+    target_path: *const PathChar,
+    port: DartPort,
+) {
+    let target_path = PathSend(target_path);
+    run_async(move || {
+        let target_path = target_path;
+        let result = isar_try! {
+            let path = from_c_path(target_path.0)?;
+            isar.compact_to_file(&path)?;
+        };
+        dart_post_int(port, result);
+    });
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_get_collection<'a>(
     isar: &'a IsarInstance,
@@ -4,6 +4,7 @@ use isar_core::error::{illegal_arg, Result};
 use std::ffi::CStr;
 use std::mem;
 use std::os::raw::c_char;
+use std::path::PathBuf;
 use unicode_segmentation::UnicodeSegmentation;
 
 #[macro_use]
@@ -15,6 +16,7 @@ pub mod filter;
 pub mod index_key;
 pub mod instance;
 pub mod link;
+pub mod object;
 pub mod query;
 pub mod query_aggregation;
 pub mod raw_object_set;
@@ -32,6 +34,38 @@ pub unsafe fn from_c_str<'a>(str: *const c_char) -> Result<Option<&'a str>> {
     }
 }
 
+#[cfg(windows)]
+pub type PathChar = u16;
+
+#[cfg(not(windows))]
+pub type PathChar = c_char;
+
+#[cfg(windows)]
+pub unsafe fn from_c_path(path: *const PathChar) -> Result<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    if path.is_null() {
+        return illegal_arg("The provided path is not valid.");
+    }
+    let mut len = 0;
+    while *path.add(len) != 0 {
+        len += 1;
+    }
+    let wide = std::slice::from_raw_parts(path, len);
+    Ok(PathBuf::from(OsString::from_wide(wide)))
+}
+
+#[cfg(unix)]
+pub unsafe fn from_c_path(path: *const PathChar) -> Result<PathBuf> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    if path.is_null() {
+        return illegal_arg("The provided path is not valid.");
+    }
+    let bytes = CStr::from_ptr(path).to_bytes();
+    Ok(PathBuf::from(OsStr::from_bytes(bytes)))
+}
+
 pub struct UintSend(&'static mut u32);
 
 unsafe impl Send for UintSend {}
@@ -1,6 +1,7 @@
 use crate::txn::IsarDartTxn;
 use isar_core::collection::IsarCollection;
 use isar_core::error::Result;
+use std::{mem, slice};
 
 #[no_mangle]
 pub unsafe extern "C" fn isar_link(
@@ -30,6 +31,26 @@ pub unsafe extern "C" fn isar_link_unlink(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_unlink(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_index: u32,
+    id: i64,
+    target_id: i64,
+) -> i64 {
+    isar_link_unlink(collection, txn, link_index, id, target_id)
+}
+
+// Dart passes a null pointer for empty lists.
+unsafe fn ids_slice<'a>(ids: *const i64, length: usize) -> &'a [i64] {
+    if ids.is_null() || length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ids, length)
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_link_update_all(
     collection: &'static IsarCollection,
@@ -41,7 +62,7 @@ pub unsafe extern "C" fn isar_link_update_all(
     unlink_count: u32,
     replace: bool,
 ) -> i64 {
-    let ids = std::slice::from_raw_parts(ids, (link_count + unlink_count) as usize);
+    let ids = ids_slice(ids, (link_count + unlink_count) as usize);
     isar_try_txn!(txn, move |txn| {
         if replace {
             collection.unlink_all(txn, link_index as usize, id)?;
@@ -59,3 +80,57 @@ pub unsafe extern "C" fn isar_link_update_all(
         Ok(())
     })
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_link_replace_all(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_index: u32,
+    id: i64,
+    ids: *const i64,
+    ids_length: u32,
+) -> i64 {
+    let ids = ids_slice(ids, ids_length as usize);
+    isar_try_txn!(txn, move |txn| {
+        collection.replace_links(txn, link_index as usize, id, ids)
+    })
+}
+
+struct IdsSend(*mut *mut i64);
+unsafe impl Send for IdsSend {}
+
+struct IdsLenSend(*mut u32);
+unsafe impl Send for IdsLenSend {}
+
+// The ids have to be freed with isar_free_linked_ids.
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_linked_ids(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_index: u32,
+    id: i64,
+    ids: *mut *mut i64,
+    ids_length: *mut u32,
+) -> i64 {
+    let ids = IdsSend(ids);
+    let ids_length = IdsLenSend(ids_length);
+    isar_try_txn!(txn, move |txn| {
+        let ids = ids;
+        let ids_length = ids_length;
+        let linked_ids = collection.get_linked_ids(txn, link_index as usize, id)?;
+        let mut linked_ids = linked_ids.into_boxed_slice();
+        ids_length.0.write(linked_ids.len() as u32);
+        ids.0.write(linked_ids.as_mut_ptr());
+        mem::forget(linked_ids);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_free_linked_ids(ids: *mut i64, ids_length: u32) {
+    drop(Vec::from_raw_parts(
+        ids,
+        ids_length as usize,
+        ids_length as usize,
+    ));
+}
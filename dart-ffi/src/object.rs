@@ -0,0 +1,114 @@
+use crate::raw_object_set::RawObject;
+use isar_core::collection::IsarCollection;
+use isar_core::error::{illegal_arg, Result};
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::{IsarObject, Property};
+use std::ptr;
+
+fn get_property(
+    collection: &IsarCollection,
+    object: &RawObject,
+    property_index: u32,
+    data_types: &[DataType],
+) -> Result<Property> {
+    if !object.has_object() {
+        return illegal_arg("Object does not exist.");
+    }
+    match collection.properties.get(property_index as usize) {
+        Some((_, property)) if data_types.contains(&property.data_type) => Ok(*property),
+        Some(_) => illegal_arg("Property has the wrong type."),
+        None => illegal_arg("Property does not exist."),
+    }
+}
+
+// Reads a Byte, Int, Long or DateTime property. Null values are returned as the null long.
+#[no_mangle]
+pub unsafe extern "C" fn isar_obj_read_long(
+    collection: &IsarCollection,
+    object: &RawObject,
+    property_index: u32,
+    value: *mut i64,
+) -> i64 {
+    isar_try! {
+        let property = get_property(
+            collection,
+            object,
+            property_index,
+            &[DataType::Byte, DataType::Int, DataType::Long, DataType::DateTime],
+        )?;
+        let isar_object = object.get_object();
+        let result = match property.data_type {
+            DataType::Byte => isar_object.read_byte(property) as i64,
+            DataType::Int if isar_object.is_null(property) => IsarObject::NULL_LONG,
+            DataType::Int => isar_object.read_int(property) as i64,
+            DataType::Long => isar_object.read_long(property),
+            _ => isar_object.read_date_time(property),
+        };
+        value.write(result);
+    }
+}
+
+// Reads a Float or Double property. Null values are returned as NaN.
+#[no_mangle]
+pub unsafe extern "C" fn isar_obj_read_double(
+    collection: &IsarCollection,
+    object: &RawObject,
+    property_index: u32,
+    value: *mut f64,
+) -> i64 {
+    isar_try! {
+        let property = get_property(
+            collection,
+            object,
+            property_index,
+            &[DataType::Float, DataType::Double],
+        )?;
+        let isar_object = object.get_object();
+        let result = if property.data_type == DataType::Float {
+            isar_object.read_float(property) as f64
+        } else {
+            isar_object.read_double(property)
+        };
+        value.write(result);
+    }
+}
+
+// Points value to the UTF-8 bytes of the string inside the object buffer. Null strings are
+// returned as a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn isar_obj_read_string(
+    collection: &IsarCollection,
+    object: &RawObject,
+    property_index: u32,
+    value: *mut *const u8,
+    length: *mut u32,
+) -> i64 {
+    isar_try! {
+        let property = get_property(collection, object, property_index, &[DataType::String])?;
+        let isar_object = object.get_object();
+        if let Some(str) = isar_object.read_string(property) {
+            value.write(str.as_ptr());
+            length.write(str.len() as u32);
+        } else {
+            value.write(ptr::null());
+            length.write(0);
+        }
+    }
+}
+
+// Reads a GeoPoint property. Null values are returned as NaN.
+#[no_mangle]
+pub unsafe extern "C" fn isar_obj_read_geo_point(
+    collection: &IsarCollection,
+    object: &RawObject,
+    property_index: u32,
+    lat: *mut f64,
+    lon: *mut f64,
+) -> i64 {
+    isar_try! {
+        let property = get_property(collection, object, property_index, &[DataType::GeoPoint])?;
+        let point = object.get_object().read_geo_point(property);
+        lat.write(point.lat);
+        lon.write(point.lon);
+    }
+}
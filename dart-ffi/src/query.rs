@@ -1,13 +1,21 @@
-use super::raw_object_set::{RawObject, RawObjectSet};
+use super::raw_object_set::{RawObject, RawObjectArena, RawObjectSet};
+use crate::index_key::get_collation;
 use crate::txn::IsarDartTxn;
 use crate::{from_c_str, UintSend};
 use isar_core::collection::IsarCollection;
 use isar_core::error::illegal_arg;
 use isar_core::index::index_key::IndexKey;
+use isar_core::instance::IsarInstance;
+use isar_core::object::data_type::DataType;
+use isar_core::object::geo::GeoArea;
+use isar_core::object::isar_object::{Property, PropertyValue};
+use isar_core::object::object_builder::ObjectBuilder;
 use isar_core::query::filter::Filter;
+use isar_core::query::json_export::JsonExport;
 use isar_core::query::query_builder::QueryBuilder;
-use isar_core::query::{Query, Sort};
+use isar_core::query::{DistinctKeep, Query, Sort};
 use std::os::raw::c_char;
+use std::slice;
 
 #[no_mangle]
 pub extern "C" fn isar_qb_create(collection: &IsarCollection) -> *mut QueryBuilder {
@@ -50,6 +58,35 @@ pub unsafe extern "C" fn isar_qb_add_index_where_clause(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_geo_radius_where_clause(
+    builder: &mut QueryBuilder,
+    index_index: u32,
+    lat: f64,
+    lon: f64,
+    meters: f64,
+) -> i64 {
+    isar_try! {
+        let area = GeoArea::radius(lat, lon, meters)?;
+        builder.add_geo_where_clause(index_index as usize, area)?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_geo_box_where_clause(
+    builder: &mut QueryBuilder,
+    index_index: u32,
+    south: f64,
+    west: f64,
+    north: f64,
+    east: f64,
+) -> i64 {
+    isar_try! {
+        let area = GeoArea::bounding_box(south, west, north, east)?;
+        builder.add_geo_where_clause(index_index as usize, area)?;
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_add_link_where_clause(
     builder: &mut QueryBuilder,
@@ -73,12 +110,49 @@ pub unsafe extern "C" fn isar_qb_add_backlink_where_clause(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_link_range_where_clause(
+    builder: &mut QueryBuilder,
+    link_index: u32,
+    start: i64,
+    end: i64,
+) -> i64 {
+    isar_try! {
+        builder.add_link_range_where_clause(link_index as usize, start, end)?;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_backlink_range_where_clause(
+    builder: &mut QueryBuilder,
+    collection: &IsarCollection,
+    link_index: u32,
+    start: i64,
+    end: i64,
+) -> i64 {
+    isar_try! {
+        builder.add_backlink_range_where_clause(collection, link_index as usize, start, end)?;
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_set_filter(builder: &mut QueryBuilder, filter: *mut Filter) {
     let filter = *Box::from_raw(filter);
     builder.set_filter(filter);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_set_direction(builder: &mut QueryBuilder, asc: bool) -> i64 {
+    let direction = if asc {
+        Sort::Ascending
+    } else {
+        Sort::Descending
+    };
+    isar_try! {
+        builder.set_direction(direction)?;
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_add_sort_by(
     collection: &IsarCollection,
@@ -101,6 +175,31 @@ pub unsafe extern "C" fn isar_qb_add_sort_by(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_add_collated_sort_by(
+    collection: &IsarCollection,
+    builder: &mut QueryBuilder,
+    property_index: u32,
+    asc: bool,
+    collation: u8,
+    case_sensitive: bool,
+) -> i64 {
+    let property = collection.properties.get(property_index as usize);
+    let sort = if asc {
+        Sort::Ascending
+    } else {
+        Sort::Descending
+    };
+    isar_try! {
+        if let Some((_, property)) = property {
+            let collation = get_collation(collation)?;
+            builder.add_collated_sort(*property, sort, collation, case_sensitive)?;
+        } else {
+            illegal_arg("Property does not exist.")?;
+        }
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_add_distinct_by(
     collection: &IsarCollection,
@@ -118,6 +217,16 @@ pub unsafe extern "C" fn isar_qb_add_distinct_by(
     }
 }
 
+#[no_mangle]
+pub extern "C" fn isar_qb_set_distinct_keep_last(builder: &mut QueryBuilder, keep_last: bool) {
+    let keep = if keep_last {
+        DistinctKeep::Last
+    } else {
+        DistinctKeep::First
+    };
+    builder.set_distinct_keep(keep);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_set_offset_limit(
     builder: &mut QueryBuilder,
@@ -134,6 +243,22 @@ pub unsafe extern "C" fn isar_qb_set_offset_limit(
     builder.set_limit(limit);
 }
 
+// Like isar_qb_set_offset_limit but rejects a negative offset instead of ignoring it. A negative
+// limit means no limit.
+#[no_mangle]
+pub unsafe extern "C" fn isar_qb_set_offset_limit_checked(
+    builder: &mut QueryBuilder,
+    offset: i64,
+    limit: i64,
+) -> i64 {
+    isar_try! {
+        if offset < 0 {
+            illegal_arg("Offset must not be negative.")?;
+        }
+        isar_qb_set_offset_limit(builder, offset, limit);
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_qb_build(builder: *mut QueryBuilder) -> *mut Query {
     let query = Box::from_raw(builder).build();
@@ -169,6 +294,105 @@ pub unsafe extern "C" fn isar_q_find(
     })
 }
 
+// Copies the results into the arena instead of allocating a RawObject per result.
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_arena(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    arena: &'static mut RawObjectArena,
+    limit: u32,
+) -> i64 {
+    isar_try_txn!(txn, move |txn| arena.fill_from_query(query, txn, limit))
+}
+
+struct JsonBytes(*mut *mut u8);
+unsafe impl Send for JsonBytes {}
+
+struct JsonLen(*mut u32);
+unsafe impl Send for JsonLen {}
+
+macro_rules! property_values {
+    ($values:expr, $variant:ident) => {
+        $values
+            .iter()
+            .filter_map(|value| match value {
+                PropertyValue::$variant(value) => Some(*value),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+}
+
+// Writes the values of a scalar property of all results as an object with a single list
+// property (for example an IntList for Int properties) so only the values are copied. GeoPoints
+// are written as a DoubleList of latitudes and longitudes.
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_find_property(
+    collection: &IsarCollection,
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    property_index: u32,
+    limit: u32,
+    values_bytes: *mut *mut u8,
+    values_length: *mut u32,
+) -> i64 {
+    let property = collection
+        .properties
+        .get(property_index as usize)
+        .map(|(_, p)| *p);
+    let values_bytes = JsonBytes(values_bytes);
+    let values_length = JsonLen(values_length);
+    isar_try_txn!(txn, move |txn| {
+        let values_bytes = values_bytes;
+        let values_length = values_length;
+        let property = if let Some(property) = property {
+            property
+        } else {
+            return illegal_arg("Property does not exist.");
+        };
+
+        let mut values = vec![];
+        query.find_property_while(txn, property, |_, value| {
+            values.push(value);
+            (values.len() as u32) < limit
+        })?;
+
+        let list_type = match property.data_type {
+            DataType::Byte => DataType::ByteList,
+            DataType::Int => DataType::IntList,
+            DataType::Float => DataType::FloatList,
+            DataType::Long => DataType::LongList,
+            DataType::Double | DataType::GeoPoint => DataType::DoubleList,
+            DataType::DateTime => DataType::DateTimeList,
+            _ => DataType::StringList,
+        };
+        let list_property = [Property::new(list_type, 2)];
+        let mut ob = ObjectBuilder::new(&list_property, None);
+        match list_type {
+            DataType::ByteList => ob.write_byte_list(Some(&property_values!(values, Byte))),
+            DataType::IntList => ob.write_int_list(Some(&property_values!(values, Int))),
+            DataType::FloatList => ob.write_float_list(Some(&property_values!(values, Float))),
+            DataType::LongList => ob.write_long_list(Some(&property_values!(values, Long))),
+            DataType::DoubleList if property.data_type == DataType::GeoPoint => {
+                let points = property_values!(values, GeoPoint);
+                let coordinates: Vec<f64> = points.iter().flat_map(|p| [p.lat, p.lon]).collect();
+                ob.write_double_list(Some(&coordinates))
+            }
+            DataType::DoubleList => ob.write_double_list(Some(&property_values!(values, Double))),
+            DataType::DateTimeList => {
+                ob.write_date_time_list(Some(&property_values!(values, DateTime)))
+            }
+            _ => ob.write_string_list(Some(&property_values!(values, String))),
+        }
+
+        let mut bytes = ob.finish().as_bytes().to_vec().into_boxed_slice();
+        values_length.0.write(bytes.len() as u32);
+        values_bytes.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_q_delete(
     query: &'static Query,
@@ -180,25 +404,11 @@ pub unsafe extern "C" fn isar_q_delete(
     let limit = limit as usize;
     let count = UintSend(count);
     isar_try_txn!(txn, move |txn| {
-        let mut ids_to_delete = vec![];
-        query.find_while(txn, |id, _| {
-            ids_to_delete.push(id);
-            ids_to_delete.len() <= limit
-        })?;
-        *count.0 = ids_to_delete.len() as u32;
-        for id in ids_to_delete {
-            collection.delete(txn, id)?;
-        }
+        *count.0 = query.delete(txn, collection, limit)?;
         Ok(())
     })
 }
 
-struct JsonBytes(*mut *mut u8);
-unsafe impl Send for JsonBytes {}
-
-struct JsonLen(*mut u32);
-unsafe impl Send for JsonLen {}
-
 #[no_mangle]
 pub unsafe extern "C" fn isar_q_export_json(
     query: &'static Query,
@@ -224,7 +434,153 @@ pub unsafe extern "C" fn isar_q_export_json(
     })
 }
 
+struct JsonExportSend(*mut *mut JsonExport);
+unsafe impl Send for JsonExportSend {}
+
+// Starts an export that is read with isar_q_export_json_next() and freed with
+// isar_q_export_json_free().
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_export_json_begin(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    id_name: *const c_char,
+    export: *mut *mut JsonExport,
+) -> i64 {
+    let id_name = from_c_str(id_name).unwrap();
+    let export = JsonExportSend(export);
+    isar_try_txn!(txn, move |txn| {
+        let export = export;
+        let json_export = query.export_json_chunked(txn, id_name, true, true)?;
+        export.0.write(Box::into_raw(Box::new(json_export)));
+        Ok(())
+    })
+}
+
+// Writes the next chunk of the JSON array. The length is zero once the export is complete.
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_export_json_next(
+    export: &'static mut JsonExport,
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    chunk_size: u32,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let chunk = export.next_chunk(txn, collection, chunk_size as usize)?;
+        let mut bytes = chunk.unwrap_or_default().into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_export_json_free(export: *mut JsonExport) {
+    drop(Box::from_raw(export));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_export_json_cascade(
+    query: &'static Query,
+    isar: &'static IsarInstance,
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    link_collections: *const &'static IsarCollection,
+    link_indexes: *const u32,
+    links_length: u32,
+    max_depth: u32,
+    id_name: *const c_char,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let id_name = from_c_str(id_name).unwrap();
+    let link_collections = slice::from_raw_parts(link_collections, links_length as usize);
+    let link_indexes = slice::from_raw_parts(link_indexes, links_length as usize);
+    let links: Vec<(&IsarCollection, usize)> = link_collections
+        .iter()
+        .zip(link_indexes)
+        .map(|(col, index)| (*col, *index as usize))
+        .collect();
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let exported_json = query.export_json_cascade(
+            txn,
+            &isar.collections,
+            collection,
+            &links,
+            max_depth as usize,
+            id_name,
+            true,
+            true,
+        )?;
+        let bytes = serde_json::to_vec(&exported_json).unwrap();
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_explain(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let plan = query.explain(txn)?.to_json();
+        let bytes = serde_json::to_vec(&plan).unwrap();
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_explain_analyze(
+    query: &'static Query,
+    txn: &mut IsarDartTxn,
+    json_bytes: *mut *mut u8,
+    json_length: *mut u32,
+) -> i64 {
+    let json = JsonBytes(json_bytes);
+    let json_length = JsonLen(json_length);
+    isar_try_txn!(txn, move |txn| {
+        let json = json;
+        let json_length = json_length;
+        let analysis = query.explain_analyze(txn)?;
+        let bytes = serde_json::to_vec(&analysis).unwrap();
+        let mut bytes = bytes.into_boxed_slice();
+        json_length.0.write(bytes.len() as u32);
+        json.0.write(bytes.as_mut_ptr());
+        std::mem::forget(bytes);
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_free_json(json_bytes: *mut u8, json_length: u32) {
     Vec::from_raw_parts(json_bytes, json_length as usize, json_length as usize);
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_q_free_property(values_bytes: *mut u8, values_length: u32) {
+    Vec::from_raw_parts(values_bytes, values_length as usize, values_length as usize);
+}
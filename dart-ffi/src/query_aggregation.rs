@@ -1,19 +1,13 @@
+use crate::from_c_str;
 use crate::txn::IsarDartTxn;
 use isar_core::collection::IsarCollection;
 use isar_core::error::illegal_arg;
 use isar_core::error::Result;
-use isar_core::object::data_type::DataType;
 use isar_core::object::isar_object::{IsarObject, Property};
+use isar_core::query::aggregation::AggregationResult;
 use isar_core::query::Query;
 use isar_core::txn::IsarTxn;
-use std::cmp::Ordering;
-
-#[derive(Debug)]
-pub enum AggregationResult {
-    Long(i64),
-    Double(f64),
-    Null,
-}
+use std::os::raw::c_char;
 
 #[derive(PartialEq)]
 #[repr(u8)]
@@ -44,101 +38,19 @@ fn aggregate(
     op: AggregationOp,
     property: Option<Property>,
 ) -> Result<AggregationResult> {
-    let mut count = 0usize;
-
-    let (mut long_value, mut double_value) = if op == AggregationOp::Min {
-        (i64::MAX, f64::INFINITY)
-    } else if op == AggregationOp::Max {
-        (i64::MIN, f64::NEG_INFINITY)
-    } else {
-        (0, 0.0)
-    };
-
-    let min_max_cmp = if op == AggregationOp::Max {
-        Ordering::Greater
-    } else {
-        Ordering::Less
-    };
-
-    query.find_while(txn, |_, obj| {
-        if op == AggregationOp::Count {
-            count += 1;
-            return true;
-        }
-
-        let property = property.unwrap();
-        if obj.is_null(property) {
-            return true;
+    let property = match (&op, property) {
+        (AggregationOp::Count, _) => {
+            return Ok(AggregationResult::Long(query.count(txn)? as i64));
         }
-
-        count += 1;
-        match op {
-            AggregationOp::Min | AggregationOp::Max => match property.data_type {
-                DataType::Int | DataType::Long => {
-                    let value = if property.data_type == DataType::Int {
-                        obj.read_int(property) as i64
-                    } else {
-                        obj.read_long(property)
-                    };
-                    if value.cmp(&long_value) == min_max_cmp {
-                        long_value = value;
-                    }
-                }
-                DataType::Float | DataType::Double => {
-                    let value = if property.data_type == DataType::Float {
-                        obj.read_float(property) as f64
-                    } else {
-                        obj.read_double(property)
-                    };
-                    if value > double_value && min_max_cmp == Ordering::Greater {
-                        double_value = value;
-                    } else if value < double_value && min_max_cmp == Ordering::Less {
-                        double_value = value;
-                    }
-                }
-                _ => unreachable!(),
-            },
-            AggregationOp::Sum | AggregationOp::Average => match property.data_type {
-                DataType::Int => {
-                    long_value = long_value.saturating_add(obj.read_int(property) as i64)
-                }
-                DataType::Long => long_value = long_value.saturating_add(obj.read_long(property)),
-                DataType::Float => double_value += obj.read_float(property) as f64,
-                DataType::Double => double_value += obj.read_double(property),
-                _ => unreachable!(),
-            },
-            _ => unreachable!(),
-        }
-        true
-    })?;
-
+        (_, Some(property)) => property,
+        (_, None) => return illegal_arg("Property does not exist."),
+    };
     match op {
-        AggregationOp::Min | AggregationOp::Max | AggregationOp::Average => {
-            if count == 0 {
-                return Ok(AggregationResult::Null);
-            }
-        }
-        _ => {}
+        AggregationOp::Min => query.min(txn, property),
+        AggregationOp::Max => query.max(txn, property),
+        AggregationOp::Sum => query.sum(txn, property),
+        _ => query.average(txn, property),
     }
-
-    let result = match op {
-        AggregationOp::Average => {
-            let result = match property.unwrap().data_type {
-                DataType::Int | DataType::Long => (long_value as f64) / (count as f64),
-                DataType::Float | DataType::Double => double_value / (count as f64),
-                _ => unreachable!(),
-            };
-            AggregationResult::Double(result)
-        }
-        AggregationOp::Count => AggregationResult::Long(count as i64),
-        _ => match property.unwrap().data_type {
-            DataType::Int | DataType::Long => AggregationResult::Long(long_value),
-            DataType::Float | DataType::Double => AggregationResult::Double(double_value),
-            _ => unreachable!(),
-        },
-    };
-
-    Ok(result)
 }
 
 pub struct AggregationResultSend(*mut *const AggregationResult);
@@ -162,15 +74,30 @@ pub unsafe extern "C" fn isar_q_aggregate(
     let result = AggregationResultSend(result);
     isar_try_txn!(txn, move |txn| {
         let result = result;
-        if op != AggregationOp::Count && property.is_none() {
-            return illegal_arg("Property does not exist.");
-        }
         let aggregate_result = aggregate(query, txn, op, property)?;
         result.0.write(Box::into_raw(Box::new(aggregate_result)));
         Ok(())
     })
 }
 
+// Reads the maintained aggregate of the collection schema without running a query.
+#[no_mangle]
+pub unsafe extern "C" fn isar_get_aggregate(
+    collection: &'static IsarCollection,
+    txn: &mut IsarDartTxn,
+    name: *const c_char,
+    result: *mut *const AggregationResult,
+) -> i64 {
+    let result = AggregationResultSend(result);
+    let name = from_c_str(name).unwrap().unwrap().to_string();
+    isar_try_txn!(txn, move |txn| {
+        let result = result;
+        let aggregate_result = collection.get_aggregate(txn, &name)?;
+        result.0.write(Box::into_raw(Box::new(aggregate_result)));
+        Ok(())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_q_aggregate_long_result(result: &AggregationResult) -> i64 {
     match result {
@@ -1,4 +1,7 @@
+use isar_core::error::Result;
 use isar_core::object::isar_object::IsarObject;
+use isar_core::query::Query;
+use isar_core::txn::IsarTxn;
 use std::{ptr, slice};
 
 #[repr(C)]
@@ -25,6 +28,10 @@ impl RawObject {
         IsarObject::from_bytes(bytes)
     }
 
+    pub fn has_object(&self) -> bool {
+        !self.buffer.is_null()
+    }
+
     pub fn get_id(&mut self) -> i64 {
         self.id
     }
@@ -79,3 +86,71 @@ pub unsafe extern "C" fn isar_free_raw_obj_list(ros: &mut RawObjectSet) {
     ros.objects = ptr::null_mut();
     ros.length = 0;
 }
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct RawObjectOffset {
+    id: i64,
+    offset: u32,
+    length: u32,
+}
+
+// Query results copied into one contiguous buffer with an offsets table. The arena is reused
+// between queries so it only allocates when a result is larger than all previous ones.
+#[derive(Default)]
+pub struct RawObjectArena {
+    buffer: Vec<u8>,
+    offsets: Vec<RawObjectOffset>,
+}
+
+impl RawObjectArena {
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.offsets.clear();
+    }
+
+    pub fn push(&mut self, id: i64, object: IsarObject) {
+        let bytes = object.as_bytes();
+        self.offsets.push(RawObjectOffset {
+            id,
+            offset: self.buffer.len() as u32,
+            length: bytes.len() as u32,
+        });
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    pub fn fill_from_query(&mut self, query: &Query, txn: &mut IsarTxn, limit: u32) -> Result<()> {
+        self.clear();
+        query.find_while(txn, |id, object| {
+            self.push(id, object);
+            (self.offsets.len() as u32) < limit
+        })
+    }
+}
+
+// Points into the arena and stays valid until the arena is filled again or freed.
+#[repr(C)]
+pub struct RawObjectArenaView {
+    buffer: *const u8,
+    buffer_length: u32,
+    offsets: *const RawObjectOffset,
+    length: u32,
+}
+
+#[no_mangle]
+pub extern "C" fn isar_arena_create() -> *mut RawObjectArena {
+    Box::into_raw(Box::new(RawObjectArena::default()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_arena_view(arena: &RawObjectArena, view: &mut RawObjectArenaView) {
+    view.buffer = arena.buffer.as_ptr();
+    view.buffer_length = arena.buffer.len() as u32;
+    view.offsets = arena.offsets.as_ptr();
+    view.length = arena.offsets.len() as u32;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_arena_free(arena: *mut RawObjectArena) {
+    drop(Box::from_raw(arena));
+}
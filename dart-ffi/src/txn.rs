@@ -1,15 +1,16 @@
+use crate::dart::{dart_post_int, DartPort};
 use crate::error::DartErrCode;
 use isar_core::error::{IsarError, Result};
 use isar_core::instance::IsarInstance;
-use isar_core::txn::IsarTxn;
+use isar_core::txn::{IsarTxn, TxnPriority};
 use once_cell::sync::Lazy;
 use std::borrow::BorrowMut;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 use threadpool::{Builder, ThreadPool};
-use crate::dart::{dart_post_int, DartPort};
 
 static THREAD_POOL: Lazy<Mutex<ThreadPool>> = Lazy::new(|| Mutex::new(Builder::new().build()));
 
@@ -18,6 +19,7 @@ pub fn run_async<F: FnOnce() + Send + 'static>(job: F) {
 }
 
 type AsyncJob = (Box<dyn FnOnce() + Send + 'static>, bool);
+type TxnJob = Box<dyn FnOnce(&mut IsarTxn) -> Result<()> + Send + 'static>;
 
 #[no_mangle]
 pub unsafe extern "C" fn isar_txn_begin(
@@ -28,11 +30,38 @@ pub unsafe extern "C" fn isar_txn_begin(
     silent: bool,
     port: DartPort,
 ) -> i64 {
+    isar_txn_try_begin(isar, txn, sync, write, silent, false, -1, port)
+}
+
+// Background write transactions wait until no interactive write transaction is queued. A
+// negative timeout waits indefinitely.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn isar_txn_try_begin(
+    isar: &'static IsarInstance,
+    txn: *mut *const IsarDartTxn,
+    sync: bool,
+    write: bool,
+    silent: bool,
+    background: bool,
+    timeout_ms: i64,
+    port: DartPort,
+) -> i64 {
+    let priority = if background {
+        TxnPriority::Background
+    } else {
+        TxnPriority::Interactive
+    };
+    let timeout = if timeout_ms >= 0 {
+        Some(Duration::from_millis(timeout_ms as u64))
+    } else {
+        None
+    };
     isar_try! {
         let new_txn = if sync {
-            IsarDartTxn::begin_sync(isar, write, silent)?
+            IsarDartTxn::begin_sync(isar, write, silent, priority, timeout)?
         } else {
-            IsarDartTxn::begin_async(isar, write, silent, port)
+            IsarDartTxn::begin_async(isar, write, silent, priority, timeout, port)
         };
         let txn_ptr = Box::into_raw(Box::new(new_txn));
         txn.write(txn_ptr);
@@ -47,10 +76,51 @@ pub unsafe extern "C" fn isar_txn_finish(txn: *mut IsarDartTxn, commit: bool) ->
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_txn_savepoint(txn: &mut IsarDartTxn) -> i64 {
+    isar_try_txn!(txn, move |txn| txn.savepoint())
+}
+
+// Releases the last savepoint or rolls back to it.
+#[no_mangle]
+pub unsafe extern "C" fn isar_txn_finish_savepoint(txn: &mut IsarDartTxn, commit: bool) -> i64 {
+    isar_try_txn!(txn, move |txn| {
+        if commit {
+            txn.release_savepoint()
+        } else {
+            txn.rollback_to_savepoint()
+        }
+    })
+}
+
+struct TxnStatsSend(*mut u64);
+
+unsafe impl Send for TxnStatsSend {}
+
+// Writes dirty pages, bytes written, operations and elapsed milliseconds to the four element
+// stats array.
+#[no_mangle]
+pub unsafe extern "C" fn isar_txn_stats(txn: &mut IsarDartTxn, stats: *mut u64) -> i64 {
+    let stats = TxnStatsSend(stats);
+    isar_try_txn!(txn, move |txn| {
+        let stats = stats;
+        let txn_stats = txn.stats()?;
+        let values = [
+            txn_stats.dirty_pages,
+            txn_stats.bytes_written,
+            txn_stats.operations,
+            txn_stats.elapsed.as_millis() as u64,
+        ];
+        std::ptr::copy_nonoverlapping(values.as_ptr(), stats.0, values.len());
+        Ok(())
+    })
+}
+
 pub struct IsarTxnSend(IsarTxn<'static>);
 
 unsafe impl Send for IsarTxnSend {}
 
+#[allow(clippy::large_enum_variant)]
 pub enum IsarDartTxn {
     Sync {
         txn: Option<IsarTxn<'static>>,
@@ -63,9 +133,15 @@ pub enum IsarDartTxn {
 }
 
 impl IsarDartTxn {
-    fn begin_sync(isar: &'static IsarInstance, write: bool, silent: bool) -> Result<IsarDartTxn> {
+    fn begin_sync(
+        isar: &'static IsarInstance,
+        write: bool,
+        silent: bool,
+        priority: TxnPriority,
+        timeout: Option<Duration>,
+    ) -> Result<IsarDartTxn> {
         let sync_txn = IsarDartTxn::Sync {
-            txn: Some(isar.begin_txn(write, silent)?),
+            txn: Some(isar.try_begin_txn(write, silent, priority, timeout)?),
         };
         Ok(sync_txn)
     }
@@ -74,13 +150,15 @@ impl IsarDartTxn {
         isar: &'static IsarInstance,
         write: bool,
         silent: bool,
+        priority: TxnPriority,
+        timeout: Option<Duration>,
         port: DartPort,
     ) -> IsarDartTxn {
         let (tx, rx): (Sender<AsyncJob>, Receiver<AsyncJob>) = mpsc::channel();
         let txn = Arc::new(Mutex::new(None));
         let txn_clone = txn.clone();
         run_async(move || {
-            let new_txn = isar.begin_txn(write, silent);
+            let new_txn = isar.try_begin_txn(write, silent, priority, timeout);
             match new_txn {
                 Ok(new_txn) => {
                     txn_clone.lock().unwrap().replace(IsarTxnSend(new_txn));
@@ -93,7 +171,7 @@ impl IsarDartTxn {
                         }
                     }
                 }
-                Err(e) =>  {
+                Err(e) => {
                     dart_post_int(port, e.into_dart_err_code());
                 }
             }
@@ -118,10 +196,7 @@ impl IsarDartTxn {
         tx.send((Box::new(handle_response_job), stop)).unwrap();
     }
 
-    pub fn exec(
-        &mut self,
-        job: Box<dyn FnOnce(&mut IsarTxn) -> Result<()> + Send + 'static>,
-    ) -> Result<()> {
+    pub fn exec(&mut self, job: TxnJob) -> Result<()> {
         match self.borrow_mut() {
             IsarDartTxn::Sync { ref mut txn } => {
                 if let Some(ref mut txn) = txn {
@@ -1,21 +1,77 @@
+use crate::dart::{dart_post_bytes, dart_post_int, DartPort};
+use crate::from_c_str;
 use isar_core::collection::IsarCollection;
 use isar_core::instance::IsarInstance;
 use isar_core::query::Query;
-use isar_core::watch::WatchHandle;
-use crate::dart::{dart_post_int, DartPort};
+use isar_core::watch::debounce::{debounce, debounce_changes, debounce_payload};
+use isar_core::watch::lifecycle::LifecycleEvent;
+use isar_core::watch::pause::PausePolicy;
+use isar_core::watch::{ChangeKind, ObjectChange, WatchHandle};
+use std::os::raw::c_char;
+use std::time::Duration;
+
+// Every change is encoded as the id (8 bytes) followed by the kind (1 byte).
+fn post_changes(port: DartPort, changes: Option<&[ObjectChange]>) {
+    let bytes = changes.map(|changes| {
+        let mut bytes = Vec::with_capacity(changes.len() * 9);
+        for change in changes {
+            bytes.extend_from_slice(&change.id.to_le_bytes());
+            bytes.push(match change.kind {
+                ChangeKind::Inserted => 0,
+                ChangeKind::Updated => 1,
+                ChangeKind::Deleted => 2,
+            });
+        }
+        bytes
+    });
+    dart_post_bytes(port, bytes.as_deref());
+}
+
+type ChangesCallback = Box<dyn Fn(Option<&[ObjectChange]>) + Send + Sync>;
+
+// Notifications are posted at most once every debounce_ms milliseconds unless it is 0.
+fn debounce_interval(debounce_ms: u32) -> Option<Duration> {
+    if debounce_ms > 0 {
+        Some(Duration::from_millis(debounce_ms as u64))
+    } else {
+        None
+    }
+}
+
+fn post_int_callback(port: DartPort, debounce_ms: u32) -> Box<dyn Fn() + Send + Sync> {
+    let callback = Box::new(move || {
+        dart_post_int(port, 1);
+    });
+    if let Some(interval) = debounce_interval(debounce_ms) {
+        debounce(callback, interval)
+    } else {
+        callback
+    }
+}
+
+fn post_changes_callback(port: DartPort, debounce_ms: u32) -> ChangesCallback {
+    let callback = Box::new(move |changes: Option<&[ObjectChange]>| post_changes(port, changes));
+    if let Some(interval) = debounce_interval(debounce_ms) {
+        debounce_changes(callback, interval)
+    } else {
+        callback
+    }
+}
 
 #[no_mangle]
 pub extern "C" fn isar_watch_collection(
     isar: &IsarInstance,
     collection: &IsarCollection,
     port: DartPort,
+    include_changes: bool,
+    debounce_ms: u32,
 ) -> *mut WatchHandle {
-    let handle = isar.watch_collection(
-        collection,
-        Box::new(move ||  {
-            dart_post_int(port, 1);
-        }),
-    );
+    let handle = if include_changes {
+        // The changed objects are posted for every change, null if they are unknown.
+        isar.watch_collection_with_changes(collection, post_changes_callback(port, debounce_ms))
+    } else {
+        isar.watch_collection(collection, post_int_callback(port, debounce_ms))
+    };
     Box::into_raw(Box::new(handle))
 }
 
@@ -23,15 +79,42 @@ pub extern "C" fn isar_watch_collection(
 pub unsafe extern "C" fn isar_watch_object(
     isar: &IsarInstance,
     collection: &IsarCollection,
-    id: i64,
+    oid: i64,
     port: DartPort,
+    include_object: bool,
+    debounce_ms: u32,
 ) -> *mut WatchHandle {
-    let handle = isar.watch_object(
+    let handle = if include_object {
+        // The new object bytes are posted for every change, null if the object was deleted.
+        let callback = Box::new(move |object: Option<&[u8]>| {
+            dart_post_bytes(port, object);
+        });
+        let callback = if let Some(interval) = debounce_interval(debounce_ms) {
+            debounce_payload(callback, interval)
+        } else {
+            callback
+        };
+        isar.watch_object_with_payload(collection, oid, usize::MAX, callback)
+    } else {
+        isar.watch_object(collection, oid, post_int_callback(port, debounce_ms))
+    };
+    Box::into_raw(Box::new(handle))
+}
+
+#[no_mangle]
+pub extern "C" fn isar_watch_id_range(
+    isar: &IsarInstance,
+    collection: &IsarCollection,
+    lower: i64,
+    upper: i64,
+    port: DartPort,
+    debounce_ms: u32,
+) -> *mut WatchHandle {
+    let handle = isar.watch_id_range(
         collection,
-        id,
-        Box::new(move || {
-            dart_post_int(port, 1);
-        }),
+        lower,
+        upper,
+        post_int_callback(port, debounce_ms),
     );
     Box::into_raw(Box::new(handle))
 }
@@ -42,18 +125,80 @@ pub extern "C" fn isar_watch_query(
     collection: &IsarCollection,
     query: &Query,
     port: DartPort,
+    include_changes: bool,
+    debounce_ms: u32,
 ) -> *mut WatchHandle {
-    let handle = isar.watch_query(
-        collection,
-        query.clone(),
-        Box::new(move ||  {
+    let handle = if include_changes {
+        isar.watch_query_with_changes(
+            collection,
+            query.clone(),
+            post_changes_callback(port, debounce_ms),
+        )
+    } else {
+        isar.watch_query(
+            collection,
+            query.clone(),
+            post_int_callback(port, debounce_ms),
+        )
+    };
+    Box::into_raw(Box::new(handle))
+}
+
+#[no_mangle]
+pub extern "C" fn isar_watch_fill_ratio(
+    isar: &IsarInstance,
+    threshold: f64,
+    port: DartPort,
+) -> *mut WatchHandle {
+    let handle = isar.watch_fill_ratio(
+        threshold,
+        Box::new(move || {
             dart_post_int(port, 1);
         }),
     );
     Box::into_raw(Box::new(handle))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn isar_watch_lifecycle(
+    name: *const c_char,
+    port: DartPort,
+) -> *mut WatchHandle {
+    let name = from_c_str(name).unwrap().unwrap().to_string();
+    let handle = IsarInstance::watch_lifecycle(Box::new(move |instance_name, event| {
+        if instance_name == name {
+            let event = match event {
+                LifecycleEvent::Opened => 0,
+                LifecycleEvent::Closed => 1,
+                LifecycleEvent::MigrationStarted => 2,
+                LifecycleEvent::MigrationFinished => 3,
+                LifecycleEvent::Compacted => 4,
+                LifecycleEvent::MigrationFailed => 5,
+                LifecycleEvent::VerificationPassed => 6,
+                LifecycleEvent::VerificationFailed => 7,
+            };
+            dart_post_int(port, event);
+        }
+    }));
+    Box::into_raw(Box::new(handle))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn isar_stop_watching(handle: *mut WatchHandle) {
     Box::from_raw(handle).stop();
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_pause_watching(handle: &WatchHandle, buffer: bool) {
+    let policy = if buffer {
+        PausePolicy::Buffer
+    } else {
+        PausePolicy::Drop
+    };
+    handle.pause(policy);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn isar_resume_watching(handle: &WatchHandle) {
+    handle.resume();
+}
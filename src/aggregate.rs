@@ -0,0 +1,180 @@
+use crate::cursor::IsarCursors;
+use crate::error::Result;
+use crate::id_key::IdKey;
+use crate::link::IsarLink;
+use crate::mdbx::db::Db;
+use crate::object::data_type::DataType;
+use crate::object::isar_object::{IsarObject, Property};
+use crate::query::aggregation::AggregationResult;
+use crate::query::filter::Filter;
+use byteorder::{ByteOrder, LittleEndian};
+
+// The current value of an aggregate is stored in the info db and updated in the txn that
+// writes or deletes an object. Link aggregates count the links of a link and are updated when
+// links are created or deleted.
+#[derive(Clone)]
+pub(crate) struct IsarAggregate {
+    key: Vec<u8>,
+    info_db: Db,
+    property: Option<Property>,
+    filter: Option<Filter>,
+    link: Option<IsarLink>,
+}
+
+#[derive(Copy, Clone, Default)]
+struct AggregateValue {
+    count: i64,
+    // Kept wider than the result so removing an object always reverses adding it.
+    long_sum: i128,
+    double_sum: f64,
+}
+
+impl AggregateValue {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        AggregateValue {
+            count: LittleEndian::read_i64(bytes),
+            long_sum: LittleEndian::read_i128(&bytes[8..]),
+            double_sum: LittleEndian::read_f64(&bytes[24..]),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        LittleEndian::write_i64(&mut bytes, self.count);
+        LittleEndian::write_i128(&mut bytes[8..], self.long_sum);
+        LittleEndian::write_f64(&mut bytes[24..], self.double_sum);
+        bytes
+    }
+}
+
+impl IsarAggregate {
+    pub fn new(
+        key: Vec<u8>,
+        info_db: Db,
+        property: Option<Property>,
+        filter: Option<Filter>,
+        link: Option<IsarLink>,
+    ) -> Self {
+        IsarAggregate {
+            key,
+            info_db,
+            property,
+            filter,
+            link,
+        }
+    }
+
+    pub fn key(col_name: &str, aggregate_name: &str) -> Vec<u8> {
+        format!("_a_{}_{}", col_name, aggregate_name).into_bytes()
+    }
+
+    fn read(&self, cursors: &IsarCursors) -> Result<Option<AggregateValue>> {
+        let mut cursor = cursors.get_cursor(self.info_db)?;
+        let value = cursor
+            .move_to(&self.key)?
+            .map(|(_, bytes)| AggregateValue::from_bytes(bytes));
+        Ok(value)
+    }
+
+    fn write(&self, cursors: &IsarCursors, value: AggregateValue) -> Result<()> {
+        let mut cursor = cursors.get_cursor(self.info_db)?;
+        cursor.put(&self.key, &value.to_bytes())
+    }
+
+    // Aggregates without a stored value have been added to the schema or changed and have to
+    // be computed from all objects.
+    pub fn is_initialized(&self, cursors: &IsarCursors) -> Result<bool> {
+        Ok(self.read(cursors)?.is_some())
+    }
+
+    pub fn clear(&self, cursors: &IsarCursors) -> Result<()> {
+        self.write(cursors, AggregateValue::default())
+    }
+
+    // Whether the aggregate counts the links stored in link_db.
+    pub fn counts_link(&self, link_db: Db) -> bool {
+        matches!(self.link, Some(link) if link.get_dbs()[0] == link_db)
+    }
+
+    // Counts the existing links of a link aggregate.
+    pub fn init_link_count(&self, cursors: &IsarCursors) -> Result<()> {
+        if let Some(link) = self.link {
+            let (entries, _) = cursors.stat(link.get_dbs()[0])?;
+            let value = AggregateValue {
+                count: entries as i64,
+                ..Default::default()
+            };
+            self.write(cursors, value)?;
+        }
+        Ok(())
+    }
+
+    // Adjusts the number of links of a link aggregate.
+    pub fn update_link_count(&self, cursors: &IsarCursors, change: i64) -> Result<()> {
+        let mut value = self.read(cursors)?.unwrap_or_default();
+        value.count += change;
+        self.write(cursors, value)
+    }
+
+    // Adds the object to the aggregate or removes it again if remove is set. Objects that do not
+    // match the filter and link aggregates are not affected.
+    pub fn update(
+        &self,
+        cursors: &IsarCursors,
+        id_key: &IdKey,
+        object: IsarObject,
+        remove: bool,
+    ) -> Result<()> {
+        if self.link.is_some() {
+            return Ok(());
+        }
+        if let Some(filter) = &self.filter {
+            if !filter.evaluate(id_key, object, Some(cursors))? {
+                return Ok(());
+            }
+        }
+        let mut value = self.read(cursors)?.unwrap_or_default();
+        value.count += if remove { -1 } else { 1 };
+        if let Some(property) = self.property {
+            if !object.is_null(property) {
+                match property.data_type {
+                    DataType::Int | DataType::Long => {
+                        let long_value = if property.data_type == DataType::Int {
+                            object.read_int(property) as i64
+                        } else {
+                            object.read_long(property)
+                        };
+                        value.long_sum += if remove {
+                            -(long_value as i128)
+                        } else {
+                            long_value as i128
+                        };
+                    }
+                    _ => {
+                        let double_value = if property.data_type == DataType::Float {
+                            object.read_float(property) as f64
+                        } else {
+                            object.read_double(property)
+                        };
+                        value.double_sum += if remove { -double_value } else { double_value };
+                    }
+                }
+            }
+        }
+        self.write(cursors, value)
+    }
+
+    pub fn get_result(&self, cursors: &IsarCursors) -> Result<AggregationResult> {
+        let value = self.read(cursors)?.unwrap_or_default();
+        let result = match self.property.map(|p| p.data_type) {
+            None => AggregationResult::Long(value.count),
+            // Integer sums saturate like Query::sum().
+            Some(DataType::Int) | Some(DataType::Long) => {
+                let sum = value.long_sum.clamp(i64::MIN as i128, i64::MAX as i128);
+                AggregationResult::Long(sum as i64)
+            }
+            Some(_) => AggregationResult::Double(value.double_sum),
+        };
+        Ok(result)
+    }
+}
@@ -0,0 +1,230 @@
+use crate::error::{IsarError, Result};
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::NewAead;
+use aes_gcm::{Aes256Gcm, Key};
+use flate2::write::{DeflateDecoder, DeflateEncoder};
+use flate2::Compression;
+use rand::random;
+use std::convert::TryInto;
+use std::fs::{remove_dir_all, DirBuilder};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8] = b"ISARBAK1";
+// The STREAM construction uses 5 bytes of the 12 byte nonce for the chunk counter and the flag
+// that marks the last chunk.
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+const CHUNK_LEN: usize = 64 * 1024;
+
+const TEMP_DIR_NAME: &str = "backup";
+
+// A directory for plaintext copies of the database. It is created inside the instance directory,
+// which already contains the database, and deleted when it is dropped.
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn create(instance_dir: &Path) -> Result<Self> {
+        let path = instance_dir
+            .join(TEMP_DIR_NAME)
+            .join(random::<u64>().to_string());
+        let mut builder = DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(&path).map_err(|_| IsarError::PathError {})?;
+        Ok(TempDir { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Removes copies that were left behind because the process was killed.
+    pub fn clear(instance_dir: &Path) {
+        let _ = remove_dir_all(instance_dir.join(TEMP_DIR_NAME));
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.path);
+    }
+}
+
+// The backup is compressed and encrypted in chunks so the database never has to fit into memory.
+// Every chunk is authenticated together with its position and whether it is the last one, so
+// reordered or truncated backups are rejected.
+pub(crate) fn encrypt<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    key: &[u8; 32],
+) -> Result<()> {
+    let nonce: [u8; NONCE_PREFIX_LEN] = random();
+    writer
+        .write_all(MAGIC)
+        .and_then(|_| writer.write_all(&nonce))
+        .map_err(|_| IsarError::PathError {})?;
+    let cipher = Aes256Gcm::new(&Key::from(*key));
+    let mut encryptor = EncryptorBE32::from_aead(cipher, &nonce.into());
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    let mut buffer = vec![0; CHUNK_LEN];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|_| IsarError::PathError {})?;
+        if read == 0 {
+            break;
+        }
+        encoder
+            .write_all(&buffer[..read])
+            .map_err(|_| backup_error("Could not compress the backup."))?;
+        write_chunks(&mut encryptor, encoder.get_mut(), &mut writer)?;
+    }
+    let mut compressed = encoder
+        .finish()
+        .map_err(|_| backup_error("Could not compress the backup."))?;
+    write_chunks(&mut encryptor, &mut compressed, &mut writer)?;
+
+    let chunk = encryptor
+        .encrypt_last(compressed.as_slice())
+        .map_err(|_| backup_error("Could not encrypt the backup."))?;
+    writer
+        .write_all(&chunk)
+        .and_then(|_| writer.flush())
+        .map_err(|_| IsarError::PathError {})
+}
+
+// Encrypts the full chunks of the compressed data but keeps the remainder because only the last
+// chunk may be shorter.
+fn write_chunks<W: Write>(
+    encryptor: &mut EncryptorBE32<Aes256Gcm>,
+    compressed: &mut Vec<u8>,
+    writer: &mut W,
+) -> Result<()> {
+    let mut start = 0;
+    while compressed.len() - start > CHUNK_LEN {
+        let chunk = encryptor
+            .encrypt_next(&compressed[start..start + CHUNK_LEN])
+            .map_err(|_| backup_error("Could not encrypt the backup."))?;
+        writer
+            .write_all(&chunk)
+            .map_err(|_| IsarError::PathError {})?;
+        start += CHUNK_LEN;
+    }
+    compressed.drain(..start);
+    Ok(())
+}
+
+pub(crate) fn decrypt<R: Read, W: Write>(reader: R, writer: W, key: &[u8; 32]) -> Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut header = [0; MAGIC.len() + NONCE_PREFIX_LEN];
+    if reader.read_exact(&mut header).is_err() || !header.starts_with(MAGIC) {
+        return Err(backup_error("The file is not an encrypted Isar backup."));
+    }
+    let cipher = Aes256Gcm::new(&Key::from(*key));
+    let nonce: [u8; NONCE_PREFIX_LEN] = header[MAGIC.len()..].try_into().unwrap();
+    let mut decryptor = DecryptorBE32::from_aead(cipher, &nonce.into());
+
+    let mut decoder = DeflateDecoder::new(writer);
+    let mut chunk = Vec::with_capacity(CHUNK_LEN + TAG_LEN);
+    loop {
+        chunk.clear();
+        (&mut reader)
+            .take((CHUNK_LEN + TAG_LEN) as u64)
+            .read_to_end(&mut chunk)
+            .map_err(|_| IsarError::PathError {})?;
+        let last = reader
+            .fill_buf()
+            .map_err(|_| IsarError::PathError {})?
+            .is_empty();
+        if last {
+            break;
+        }
+        let compressed = decryptor
+            .decrypt_next(chunk.as_slice())
+            .map_err(|_| backup_error("Wrong key or the backup has been modified."))?;
+        decoder
+            .write_all(&compressed)
+            .map_err(|_| backup_error("Could not decompress the backup."))?;
+    }
+    let compressed = decryptor
+        .decrypt_last(chunk.as_slice())
+        .map_err(|_| backup_error("Wrong key or the backup has been modified."))?;
+    decoder
+        .write_all(&compressed)
+        .map_err(|_| backup_error("Could not decompress the backup."))?;
+    decoder
+        .finish()
+        .map_err(|_| backup_error("Could not decompress the backup."))?
+        .flush()
+        .map_err(|_| IsarError::PathError {})
+}
+
+fn backup_error(message: &str) -> IsarError {
+    IsarError::BackupError {
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt_bytes(data: &[u8], key: &[u8; 32]) -> Vec<u8> {
+        let mut bytes = vec![];
+        encrypt(data, &mut bytes, key).unwrap();
+        bytes
+    }
+
+    fn decrypt_bytes(bytes: &[u8], key: &[u8; 32]) -> Result<Vec<u8>> {
+        let mut data = vec![];
+        decrypt(bytes, &mut data, key)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = [7; 32];
+        let data = b"some database bytes".repeat(100);
+        let bytes = encrypt_bytes(&data, &key);
+        assert!(bytes.starts_with(MAGIC));
+        assert!(bytes.len() < data.len());
+        assert_eq!(decrypt_bytes(&bytes, &key).unwrap(), data);
+
+        assert!(decrypt_bytes(&bytes, &[8; 32]).is_err());
+        assert!(decrypt_bytes(&data, &key).is_err());
+
+        let mut tampered = bytes;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(decrypt_bytes(&tampered, &key).is_err());
+
+        let bytes = encrypt_bytes(&[], &key);
+        assert_eq!(decrypt_bytes(&bytes, &key).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_chunks() {
+        let key = [7; 32];
+        let data: Vec<u8> = (0..CHUNK_LEN * 3 + 5).map(|_| random()).collect();
+        let bytes = encrypt_bytes(&data, &key);
+        assert!(bytes.len() > (CHUNK_LEN + TAG_LEN) * 3);
+        assert_eq!(decrypt_bytes(&bytes, &key).unwrap(), data);
+
+        let header_len = MAGIC.len() + NONCE_PREFIX_LEN;
+        let truncated = &bytes[..header_len + CHUNK_LEN + TAG_LEN];
+        assert!(decrypt_bytes(truncated, &key).is_err());
+
+        let mut reordered = bytes[..header_len].to_vec();
+        reordered.extend_from_slice(&bytes[header_len + CHUNK_LEN + TAG_LEN..]);
+        reordered.extend_from_slice(&bytes[header_len..header_len + CHUNK_LEN + TAG_LEN]);
+        assert!(decrypt_bytes(&reordered, &key).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::collection::{IsarCollection, OnConflict};
+use crate::cursor::IsarCursors;
+use crate::error::{IsarError, Result};
+use crate::id_key::IdKey;
+use crate::keys;
+use crate::mdbx::db::Db;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::object::object_builder::ObjectBuilder;
+use crate::txn::IsarTxn;
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json::{json, Value};
+use std::convert::TryInto;
+use std::io::{BufRead, BufReader, Read, Write};
+
+pub(crate) const CHANGE_LOG_DB_NAME: &str = "_changes";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ApplyPolicy {
+    // Fails without applying any change if a change is invalid, for example because its
+    // collection does not exist.
+    Abort,
+    Skip,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ApplySummary {
+    pub applied: usize,
+    // Changes with a sequence number that has already been applied.
+    pub duplicates: usize,
+    pub skipped: usize,
+    pub last_seq: u64,
+}
+
+enum PreparedChange<'a> {
+    Put(&'a IsarCollection, i64, ObjectBuilder<'a>),
+    Delete(&'a IsarCollection, i64),
+    Clear(&'a IsarCollection),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ChangeOp {
+    Put = 0,
+    Delete = 1,
+    Clear = 2,
+}
+
+// Entries are keyed by the big endian sequence number so they are sorted in the order of the
+// changes. The value contains the collection id, the object id and the operation.
+pub(crate) fn write(
+    cursors: &IsarCursors,
+    db: Db,
+    col_name: &str,
+    op: ChangeOp,
+    id: i64,
+) -> Result<()> {
+    let mut cursor = cursors.get_cursor(db)?;
+    let seq = cursor
+        .move_to_last()?
+        .map_or(0, |(key, _)| u64::from_be_bytes(key.try_into().unwrap()));
+
+    let mut entry = [0; 17];
+    LittleEndian::write_u64(&mut entry, keys::collection_id(col_name));
+    LittleEndian::write_i64(&mut entry[8..], id);
+    entry[16] = op as u8;
+    cursor.put(&(seq + 1).to_be_bytes(), &entry)
+}
+
+// Writes all changes after since_seq as JSON lines and returns the last sequence number. Puts
+// contain the current version of the object and are skipped if it has been deleted since.
+pub(crate) fn export_json<W: Write>(
+    cursors: &IsarCursors,
+    db: Db,
+    collections: &[IsarCollection],
+    since_seq: u64,
+    writer: &mut W,
+) -> Result<u64> {
+    let mut last_seq = since_seq;
+    let mut cursor = cursors.get_cursor(db)?;
+    cursor.iter_between(
+        &since_seq.saturating_add(1).to_be_bytes(),
+        &u64::MAX.to_be_bytes(),
+        false,
+        false,
+        true,
+        |_, key, entry| {
+            let seq = u64::from_be_bytes(key.try_into().unwrap());
+            last_seq = seq;
+            let col_id = LittleEndian::read_u64(entry);
+            let col = collections
+                .iter()
+                .find(|c| keys::collection_id(&c.name) == col_id);
+            let col = if let Some(col) = col {
+                col
+            } else {
+                return Ok(true);
+            };
+
+            let id = LittleEndian::read_i64(&entry[8..]);
+            let change = match entry[16] {
+                0 => {
+                    let mut col_cursor = cursors.get_cursor(col.db)?;
+                    let object = col_cursor.move_to(IdKey::new(id).as_bytes())?;
+                    if let Some((_, object)) = object {
+                        // The job only reads the object itself.
+                        let object = unsafe {
+                            cursors.with_object(object, |object| {
+                                Ok(JsonEncodeDecode::encode(&col.object_info, object, true, false))
+                            })
+                        }?;
+                        json!({"seq": seq, "collection": col.name, "op": "put", "id": id, "object": object})
+                    } else {
+                        return Ok(true);
+                    }
+                }
+                1 => json!({"seq": seq, "collection": col.name, "op": "delete", "id": id}),
+                _ => json!({"seq": seq, "collection": col.name, "op": "clear"}),
+            };
+            write_line(writer, &change)?;
+            Ok(true)
+        },
+    )?;
+    Ok(last_seq)
+}
+
+// Applies changes in the format of export_json(). Puts replace existing objects and deletes of
+// missing objects are ignored so changes can be applied more than once. Changes up to the last
+// applied sequence number are skipped.
+pub(crate) fn apply_json<R: Read>(
+    txn: &mut IsarTxn,
+    instance_id: u64,
+    collections: &[IsarCollection],
+    reader: R,
+    policy: ApplyPolicy,
+) -> Result<ApplySummary> {
+    let mut summary = ApplySummary {
+        last_seq: txn.get_applied_seq(instance_id)?,
+        ..Default::default()
+    };
+    for line in BufReader::new(reader).lines() {
+        let line = line.map_err(|_| IsarError::BackupError {
+            message: "Could not read the changes.".to_string(),
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let change = serde_json::from_str::<Value>(&line).map_err(|_| IsarError::InvalidJson {});
+        let seq = change
+            .as_ref()
+            .ok()
+            .and_then(|change| change.get("seq"))
+            .and_then(|seq| seq.as_u64());
+        if let Some(seq) = seq {
+            if seq <= summary.last_seq {
+                summary.duplicates += 1;
+                continue;
+            }
+            summary.last_seq = seq;
+        }
+
+        let prepared = change.and_then(|change| prepare_change(collections, &change));
+        match prepared {
+            Ok(PreparedChange::Put(col, id, ob)) => {
+                col.put(txn, Some(id), ob.finish(), OnConflict::Replace)?;
+            }
+            Ok(PreparedChange::Delete(col, id)) => {
+                col.delete(txn, id)?;
+            }
+            Ok(PreparedChange::Clear(col)) => col.clear(txn)?,
+            Err(e) => {
+                if policy == ApplyPolicy::Abort {
+                    return Err(e);
+                }
+                summary.skipped += 1;
+                continue;
+            }
+        }
+        summary.applied += 1;
+    }
+    txn.set_applied_seq(instance_id, summary.last_seq)?;
+    Ok(summary)
+}
+
+fn prepare_change<'a>(
+    collections: &'a [IsarCollection],
+    change: &Value,
+) -> Result<PreparedChange<'a>> {
+    if change.get("seq").and_then(|seq| seq.as_u64()).is_none() {
+        return Err(IsarError::InvalidJson {});
+    }
+    let col_name = change.get("collection").and_then(|c| c.as_str());
+    let col = collections
+        .iter()
+        .find(|c| Some(c.name.as_str()) == col_name)
+        .ok_or(IsarError::IllegalArg {
+            message: "The collection of the change does not exist.".to_string(),
+        })?;
+    let id = change.get("id").and_then(|id| id.as_i64());
+    match (change.get("op").and_then(|op| op.as_str()), id) {
+        (Some("put"), Some(id)) => {
+            let object = change.get("object").ok_or(IsarError::InvalidJson {})?;
+            let ob = JsonEncodeDecode::decode(&col.object_info, object, None)?;
+            Ok(PreparedChange::Put(col, id, ob))
+        }
+        (Some("delete"), Some(id)) => Ok(PreparedChange::Delete(col, id)),
+        (Some("clear"), _) => Ok(PreparedChange::Clear(col)),
+        _ => Err(IsarError::InvalidJson {}),
+    }
+}
+
+fn write_line<W: Write>(writer: &mut W, change: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(change).unwrap();
+    line.push(b'\n');
+    writer.write_all(&line).map_err(|_| IsarError::BackupError {
+        message: "Could not write the changes.".to_string(),
+    })
+}
@@ -0,0 +1,58 @@
+use crate::cursor::IsarCursors;
+use crate::error::{IsarError, Result};
+use crate::id_key::IdKey;
+use crate::mdbx::db::Db;
+use xxhash_rust::xxh3::xxh3_64;
+
+pub(crate) fn write(cursors: &IsarCursors, db: Db, id_key: &IdKey, object: &[u8]) -> Result<()> {
+    let mut cursor = cursors.get_cursor(db)?;
+    cursor.put(id_key.as_bytes(), &xxh3_64(object).to_le_bytes())
+}
+
+pub(crate) fn delete(cursors: &IsarCursors, db: Db, id_key: &IdKey) -> Result<()> {
+    let mut cursor = cursors.get_cursor(db)?;
+    if cursor.move_to(id_key.as_bytes())?.is_some() {
+        cursor.delete_current()?;
+    }
+    Ok(())
+}
+
+pub(crate) fn verify(cursors: &IsarCursors, db: Db, id_key: &IdKey, object: &[u8]) -> Result<()> {
+    let mut cursor = cursors.get_cursor(db)?;
+    let checksum = xxh3_64(object).to_le_bytes();
+    match cursor.move_to(id_key.as_bytes())? {
+        Some((_, stored)) if stored == checksum => Ok(()),
+        _ => Err(IsarError::ChecksumMismatch {
+            id: id_key.get_id(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::CursorCache;
+    use crate::mdbx::env::tests::get_env;
+
+    #[test]
+    fn test_verify() {
+        let env = get_env();
+        let txn = env.txn(true).unwrap();
+        let db = Db::open(&txn, Some("checksums"), true, false, false).unwrap();
+        let cursors = IsarCursors::new(&txn, CursorCache::default());
+        let id_key = IdKey::new(5);
+
+        write(&cursors, db, &id_key, b"object").unwrap();
+        assert!(verify(&cursors, db, &id_key, b"object").is_ok());
+        assert_eq!(
+            verify(&cursors, db, &id_key, b"objekt"),
+            Err(IsarError::ChecksumMismatch { id: 5 })
+        );
+
+        delete(&cursors, db, &id_key).unwrap();
+        assert_eq!(
+            verify(&cursors, db, &id_key, b"object"),
+            Err(IsarError::ChecksumMismatch { id: 5 })
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Collation {
+    Binary,
+    Root,
+    German,
+    Swedish,
+    Turkish,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Collation {
+    fn default() -> Self {
+        Collation::Binary
+    }
+}
+
+impl Collation {
+    pub fn is_binary(&self) -> bool {
+        *self == Collation::Binary
+    }
+
+    // Locale tables are only available with the collation feature.
+    pub fn is_supported(&self) -> bool {
+        self.is_binary() || cfg!(feature = "collation")
+    }
+
+    // Returns a key whose byte order matches the collation order. The key never contains a
+    // zero byte. Case is only compared if the collation is case sensitive.
+    pub fn sort_key(&self, value: &str, case_sensitive: bool) -> Vec<u8> {
+        #[cfg(feature = "collation")]
+        if !self.is_binary() {
+            return tables::sort_key(*self, value, case_sensitive);
+        }
+        let _ = case_sensitive;
+        value.as_bytes().to_vec()
+    }
+
+    pub fn compare(&self, s1: &str, s2: &str) -> Ordering {
+        self.compare_with_case(s1, s2, true)
+    }
+
+    // Matches the order of index keys with the same collation and case sensitivity.
+    pub fn compare_with_case(&self, s1: &str, s2: &str, case_sensitive: bool) -> Ordering {
+        if !self.is_binary() {
+            self.sort_key(s1, case_sensitive)
+                .cmp(&self.sort_key(s2, case_sensitive))
+        } else if case_sensitive {
+            s1.cmp(s2)
+        } else {
+            s1.to_lowercase().cmp(&s2.to_lowercase())
+        }
+    }
+}
+
+#[cfg(feature = "collation")]
+mod tables {
+    use super::Collation;
+    use unicode_normalization::UnicodeNormalization;
+
+    // Letters that sort as separate letters next to an anchor letter instead of as accented
+    // variants of their base letter.
+    const SWEDISH: &[(char, char, i32)] = &[
+        ('å', 'z', 1),
+        ('ä', 'z', 2),
+        ('æ', 'z', 2),
+        ('ö', 'z', 3),
+        ('ø', 'z', 3),
+    ];
+
+    const TURKISH: &[(char, char, i32)] = &[
+        ('ç', 'c', 1),
+        ('ğ', 'g', 1),
+        ('ı', 'i', -1),
+        ('ö', 'o', 1),
+        ('ş', 's', 1),
+        ('ü', 'u', 1),
+    ];
+
+    const LEVEL_SEPARATOR: u8 = 1;
+
+    fn tailoring(collation: Collation) -> &'static [(char, char, i32)] {
+        match collation {
+            Collation::Swedish => SWEDISH,
+            Collation::Turkish => TURKISH,
+            _ => &[],
+        }
+    }
+
+    fn to_lowercase(collation: Collation, c: char) -> String {
+        match (collation, c) {
+            (Collation::Turkish, 'I') => "ı".to_string(),
+            (Collation::Turkish, 'İ') => "i".to_string(),
+            _ => c.to_lowercase().collect(),
+        }
+    }
+
+    fn push_weight(key: &mut Vec<u8>, weight: u32) {
+        // Three digits in base 254 offset by two to keep zero and the separator free.
+        key.push((weight / (254 * 254) % 254) as u8 + 2);
+        key.push((weight / 254 % 254) as u8 + 2);
+        key.push((weight % 254) as u8 + 2);
+    }
+
+    pub(super) fn sort_key(collation: Collation, value: &str, case_sensitive: bool) -> Vec<u8> {
+        let tailoring = tailoring(collation);
+        let mut primary = vec![];
+        let mut secondary = vec![];
+        let mut tertiary = vec![];
+        for c in value.nfc() {
+            let lower = to_lowercase(collation, c);
+            let upper = lower.chars().ne(std::iter::once(c));
+            for lc in lower.nfc() {
+                if let Some((_, anchor, offset)) = tailoring.iter().find(|(t, _, _)| *t == lc) {
+                    primary.push((*anchor as i32 * 4 + offset) as u32);
+                    secondary.push(0);
+                    tertiary.push(upper as u32);
+                } else if lc == 'ß' {
+                    for _ in 0..2 {
+                        primary.push('s' as u32 * 4);
+                        secondary.push(1);
+                        tertiary.push(upper as u32);
+                    }
+                } else {
+                    let mut decomposed = Some(lc).into_iter().nfd();
+                    let base = decomposed.next().unwrap();
+                    let mark = decomposed.next().map_or(0, |m| m as u32 - 0x2ff);
+                    primary.push(base as u32 * 4);
+                    secondary.push(mark);
+                    tertiary.push(upper as u32);
+                }
+            }
+        }
+
+        let mut key = vec![];
+        for weight in primary {
+            push_weight(&mut key, weight);
+        }
+        key.push(LEVEL_SEPARATOR);
+        for weight in secondary {
+            push_weight(&mut key, weight);
+        }
+        if case_sensitive {
+            key.push(LEVEL_SEPARATOR);
+            for weight in tertiary {
+                push_weight(&mut key, weight);
+            }
+        }
+        key
+    }
+}
+
+#[cfg(all(test, feature = "collation"))]
+mod tests {
+    use super::*;
+
+    fn sorted(collation: Collation, words: &[&str]) -> Vec<String> {
+        let mut words = words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        words.sort_by(|a, b| collation.compare(a, b));
+        words
+    }
+
+    #[test]
+    fn test_swedish() {
+        let words = ["öl", "ål", "zebra", "äng", "apa"];
+        assert_eq!(
+            sorted(Collation::Swedish, &words),
+            vec!["apa", "zebra", "ål", "äng", "öl"]
+        );
+        assert_eq!(
+            sorted(Collation::German, &words),
+            vec!["ål", "äng", "apa", "öl", "zebra"]
+        );
+    }
+
+    #[test]
+    fn test_turkish() {
+        let words = ["inek", "ılık", "hava", "çay", "dere", "cam"];
+        assert_eq!(
+            sorted(Collation::Turkish, &words),
+            vec!["cam", "çay", "dere", "hava", "ılık", "inek"]
+        );
+        assert_eq!(
+            Collation::Turkish.sort_key("ILIK", false),
+            Collation::Turkish.sort_key("ılık", false)
+        );
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(
+            Collation::Root.sort_key("Äpfel", false),
+            Collation::Root.sort_key("äpfel", false)
+        );
+        assert_ne!(
+            Collation::Root.sort_key("Äpfel", true),
+            Collation::Root.sort_key("äpfel", true)
+        );
+        assert_eq!(Collation::Root.compare("a", "B"), Ordering::Less);
+        assert_eq!(Collation::Root.compare("b", "B"), Ordering::Less);
+        assert_eq!(Collation::Binary.compare("b", "B"), Ordering::Greater);
+        assert_eq!(
+            Collation::Binary.compare_with_case("b", "C", false),
+            Ordering::Less
+        );
+        assert_eq!(Collation::Root.compare("strasse", "Straße"), Ordering::Less);
+        assert_eq!(
+            Collation::Root.compare("Straße", "strasse z"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_no_zero_bytes() {
+        let key = Collation::Root.sort_key("\u{0}a\u{fe}Ää", true);
+        assert!(!key.contains(&0));
+    }
+}
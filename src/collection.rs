@@ -1,62 +1,184 @@
+use crate::aggregate::IsarAggregate;
+use crate::change_log::{self, ChangeOp};
+use crate::checksum;
 use crate::cursor::IsarCursors;
-use crate::error::{IsarError, Result};
+use crate::error::{illegal_arg, IsarError, Result};
 use crate::id_key::IdKey;
+use crate::import;
+use crate::index::composite_key_builder::CompositeKeyBuilder;
 use crate::index::index_key::IndexKey;
 use crate::index::IsarIndex;
+use crate::keys;
 use crate::link::IsarLink;
 use crate::mdbx::db::Db;
 use crate::mdbx::debug_dump_db;
+use crate::object::compression;
+use crate::object::csv_encode_decode::{CsvDecoder, CsvOptions};
 use crate::object::isar_object::{IsarObject, Property};
 use crate::object::json_encode_decode::JsonEncodeDecode;
 use crate::object::object_builder::ObjectBuilder;
+use crate::object::object_info::ObjectInfo;
+use crate::query::aggregation::AggregationResult;
 use crate::query::query_builder::QueryBuilder;
+use crate::query::spill::MemoryBudget;
+use crate::schema::link_schema::OnDelete;
 use crate::txn::IsarTxn;
 use crate::watch::change_set::ChangeSet;
+use byteorder::{ByteOrder, LittleEndian};
+use intmap::IntMap;
 use serde_json::Value;
-use std::cell::Cell;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+
+pub const IMPORT_PROGRESS_INTERVAL: usize = 1000;
 
 pub struct IsarCollection {
     pub name: String,
     pub properties: Vec<(String, Property)>,
-    props: Vec<Property>,
+    pub(crate) object_info: ObjectInfo,
+    compressed_properties: Vec<Property>,
 
     pub(crate) instance_id: u64,
     pub(crate) db: Db,
+    pub(crate) checksum_db: Option<Db>,
+    change_log_db: Option<Db>,
     pub(crate) indexes: Vec<(String, IsarIndex)>,
     pub(crate) links: Vec<(String, IsarLink)>, // links from this collection
+    aggregates: Vec<(String, IsarAggregate)>,
+
+    auto_increment: AtomicI64,
+    property_stats: Option<Mutex<Vec<PropertyStats>>>,
+    pub(crate) memory_budget: Option<MemoryBudget>,
+}
+
+// What put() does if the object has the same key in a unique index as an existing object.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OnConflict {
+    // Fails with UniqueViolated containing the id of the existing object.
+    Abort,
+    // Deletes the existing object.
+    Replace,
+    // Keeps the existing object and does not write the new object.
+    Ignore,
+    // Writes the new object with the id of the existing object, keeping its links.
+    Update,
+}
 
-    auto_increment: Cell<i64>,
+// State of a put_all() call. Entries of indexes that are not unique are written at the end.
+struct PutBatch {
+    ids: IntMap<()>,
+    last_id: Option<i64>,
+    index_entries: Vec<Vec<(Vec<u8>, i64)>>,
 }
 
-unsafe impl Send for IsarCollection {}
-unsafe impl Sync for IsarCollection {}
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct PropertyStats {
+    pub filter_count: usize,
+    pub sort_count: usize,
+}
+
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct IndexStats {
+    pub entries: u64,
+    pub size: u64,
+}
+
+// Sizes are the bytes of the database pages including unused space in the pages.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct CollectionStats {
+    pub object_count: u64,
+    pub object_size: u64,
+    // The objects, indexes, links and checksums of the collection. Backlinks are counted by the
+    // collection of the link.
+    pub size: u64,
+    pub indexes: Vec<(String, IndexStats)>,
+}
+
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct ChangeToken {
+    pub counter: u64,
+    pub last_modified: i64,
+}
+
+impl ChangeToken {
+    pub(crate) fn key(col_name: &str) -> Vec<u8> {
+        format!("_c_{}", col_name).into_bytes()
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        ChangeToken {
+            counter: LittleEndian::read_u64(bytes),
+            last_modified: LittleEndian::read_i64(&bytes[8..]),
+        }
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        LittleEndian::write_u64(&mut bytes, self.counter);
+        LittleEndian::write_i64(&mut bytes[8..], self.last_modified);
+        bytes
+    }
+}
 
 impl IsarCollection {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         db: Db,
+        checksum_db: Option<Db>,
+        change_log_db: Option<Db>,
         instance_id: u64,
         name: String,
         properties: Vec<(String, Property)>,
+        embedded: Vec<(Property, ObjectInfo)>,
+        compressed_properties: Vec<Property>,
         indexes: Vec<(String, IsarIndex)>,
         links: Vec<(String, IsarLink)>,
+        aggregates: Vec<(String, IsarAggregate)>,
+        property_stats: bool,
+        memory_budget: Option<MemoryBudget>,
     ) -> Self {
-        let props = properties.iter().map(|(_, p)| *p).collect();
+        let object_info = ObjectInfo::new(properties.clone(), embedded);
+        let property_stats = if property_stats {
+            Some(Mutex::new(vec![PropertyStats::default(); properties.len()]))
+        } else {
+            None
+        };
         IsarCollection {
             instance_id,
             db,
+            checksum_db,
+            change_log_db,
             name,
             properties,
-            props,
+            object_info,
+            compressed_properties,
             indexes,
             links,
-            auto_increment: Cell::new(0),
+            aggregates,
+            auto_increment: AtomicI64::new(0),
+            property_stats,
+            memory_budget,
         }
     }
 
+    // Stable id that can be used to prefix object keys of this collection.
+    pub fn get_collection_id(&self) -> u64 {
+        keys::collection_id(&self.name)
+    }
+
     pub fn new_object_builder(&self, buffer: Option<Vec<u8>>) -> ObjectBuilder {
-        ObjectBuilder::new(&self.props, buffer)
+        self.object_info.new_object_builder(buffer)
+    }
+
+    pub fn property_by_name(&self, name: &str) -> Option<Property> {
+        self.object_info.get_property(name)
+    }
+
+    // Properties of the objects stored in an Object or ObjectList property.
+    pub fn get_embedded(&self, property: Property) -> Option<&ObjectInfo> {
+        self.object_info.get_embedded(property)
     }
 
     pub fn new_query_builder(&self) -> QueryBuilder {
@@ -73,9 +195,7 @@ impl IsarCollection {
     }
 
     pub(crate) fn update_auto_increment(&self, id: i64) {
-        if id > self.auto_increment.get() {
-            self.auto_increment.set(id);
-        }
+        self.auto_increment.fetch_max(id, Ordering::SeqCst);
     }
 
     pub fn auto_increment(&self, _: &mut IsarTxn) -> Result<i64> {
@@ -83,27 +203,41 @@ impl IsarCollection {
     }
 
     pub(crate) fn auto_increment_internal(&self) -> Result<i64> {
-        let last = self.auto_increment.get();
-        if last < i64::MAX {
-            self.auto_increment.set(last + 1);
-            Ok(last + 1)
-        } else {
-            Err(IsarError::AutoIncrementOverflow {})
-        }
+        self.auto_increment
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                last.checked_add(1)
+            })
+            .map(|last| last + 1)
+            .map_err(|_| IsarError::AutoIncrementOverflow {})
     }
 
     pub(crate) fn get_runtime_id(&self) -> u64 {
         self.db.runtime_id()
     }
 
+    pub(crate) fn write<'txn, 'env, T, F>(&self, txn: &'txn mut IsarTxn<'env>, job: F) -> Result<T>
+    where
+        F: FnOnce(&IsarCursors<'txn, 'env>, Option<&mut ChangeSet<'_>>) -> Result<T>,
+    {
+        txn.register_collection_change(&self.name);
+        txn.write(self.instance_id, job)
+    }
+
+    pub fn change_token(&self, txn: &mut IsarTxn) -> Result<ChangeToken> {
+        txn.get_change_token(self.instance_id, &self.name)
+    }
+
     pub fn get<'txn>(&self, txn: &'txn mut IsarTxn, id: i64) -> Result<Option<IsarObject<'txn>>> {
         txn.read(self.instance_id, |cursors| {
             let mut cursor = cursors.get_cursor(self.db)?;
             let id_key = IdKey::new(id);
-            let object = cursor
-                .move_to(id_key.as_bytes())?
-                .map(|(_, v)| IsarObject::from_bytes(v));
-            Ok(object)
+            if let Some((_, bytes)) = cursor.move_to(id_key.as_bytes())? {
+                let object = cursors.read_object(bytes)?;
+                self.verify_checksum(cursors, &id_key, object.as_bytes())?;
+                Ok(Some(object))
+            } else {
+                Ok(None)
+            }
         })
     }
 
@@ -114,6 +248,11 @@ impl IsarCollection {
             .ok_or(IsarError::UnknownIndex {})
     }
 
+    pub fn new_index_key(&self, index_index: usize) -> Result<CompositeKeyBuilder<'_>> {
+        let index = self.get_index_by_index(index_index)?;
+        Ok(CompositeKeyBuilder::new(&index.properties))
+    }
+
     pub fn get_by_index<'txn>(
         &self,
         txn: &'txn mut IsarTxn,
@@ -130,7 +269,9 @@ impl IsarCollection {
                         .ok_or(IsarError::DbCorrupted {
                             message: "Invalid index entry".to_string(),
                         })?;
-                let result = (id_key.get_id(), IsarObject::from_bytes(bytes));
+                let object = cursors.read_object(bytes)?;
+                self.verify_checksum(cursors, &id_key, object.as_bytes())?;
+                let result = (id_key.get_id(), object);
                 Ok(Some(result))
             } else {
                 Ok(None)
@@ -138,28 +279,165 @@ impl IsarCollection {
         })
     }
 
+    // Returns the id of the object. With OnConflict::Ignore and OnConflict::Update this is the id
+    // of the existing object if there is a conflict.
     pub fn put(
         &self,
         txn: &mut IsarTxn,
         id: Option<i64>,
         object: IsarObject,
-        replace_on_conflict: bool,
+        on_conflict: OnConflict,
     ) -> Result<i64> {
-        txn.write(self.instance_id, |cursors, change_set| {
-            self.put_internal(cursors, change_set, id, object, replace_on_conflict)
+        self.write(txn, |cursors, change_set| {
+            self.put_internal(cursors, change_set, id, object, on_conflict, None)
         })
     }
 
+    // Puts the objects in the order of their ids which is much faster than separate put() calls
+    // for many objects. Objects without id are written last. Returns the ids in the order of the
+    // objects.
+    pub fn put_all(
+        &self,
+        txn: &mut IsarTxn,
+        objects: &[(Option<i64>, IsarObject)],
+        on_conflict: OnConflict,
+    ) -> Result<Vec<i64>> {
+        self.write(txn, |cursors, mut change_set| {
+            let mut order: Vec<usize> = (0..objects.len()).collect();
+            order.sort_by_key(|i| {
+                let id = objects[*i].0;
+                (id.is_none(), id)
+            });
+            let last_id = cursors
+                .get_cursor(self.db)?
+                .move_to_last()?
+                .map(|(key, _)| IdKey::from_bytes(key).get_id());
+            let mut batch = PutBatch {
+                ids: IntMap::new(),
+                last_id,
+                index_entries: vec![vec![]; self.indexes.len()],
+            };
+            let mut ids = vec![0; objects.len()];
+            for i in order {
+                let (id, object) = objects[i];
+                ids[i] = self.put_internal(
+                    cursors,
+                    change_set.as_deref_mut(),
+                    id,
+                    object,
+                    on_conflict,
+                    Some(&mut batch),
+                )?;
+            }
+            self.write_batch_index_entries(cursors, &mut batch)?;
+            Ok(ids)
+        })
+    }
+
+    // Appends objects with ascending ids that are greater than the ids of all existing objects
+    // to the end of the object and index dbs. This is the fastest way to import ordered data
+    // into an empty collection. Fails with UniqueViolated for conflicts in unique indexes.
+    pub fn put_all_sorted(&self, txn: &mut IsarTxn, objects: &[(i64, IsarObject)]) -> Result<()> {
+        self.write(txn, |cursors, mut change_set| {
+            let last_id = cursors
+                .get_cursor(self.db)?
+                .move_to_last()?
+                .map(|(key, _)| IdKey::from_bytes(key).get_id());
+            let mut previous_id = last_id;
+            for (id, _) in objects {
+                if matches!(previous_id, Some(previous_id) if previous_id >= *id) {
+                    return illegal_arg(
+                        "Ids have to be ascending and greater than the ids of existing objects.",
+                    );
+                }
+                previous_id = Some(*id);
+            }
+
+            let mut batch = PutBatch {
+                ids: IntMap::new(),
+                last_id,
+                index_entries: vec![vec![]; self.indexes.len()],
+            };
+            for (id, object) in objects {
+                self.put_internal(
+                    cursors,
+                    change_set.as_deref_mut(),
+                    Some(*id),
+                    *object,
+                    OnConflict::Abort,
+                    Some(&mut batch),
+                )?;
+            }
+            self.write_batch_index_entries(cursors, &mut batch)
+        })
+    }
+
+    fn write_batch_index_entries(&self, cursors: &IsarCursors, batch: &mut PutBatch) -> Result<()> {
+        for ((_, index), entries) in self.indexes.iter().zip(&mut batch.index_entries) {
+            index.create_sorted(cursors, entries)?;
+        }
+        Ok(())
+    }
+
+    // The index entries of an object of the batch have to be written before it is deleted.
+    fn prepare_delete(
+        &self,
+        cursors: &IsarCursors,
+        batch: Option<&mut PutBatch>,
+        id_key: &IdKey,
+    ) -> Result<()> {
+        if let Some(batch) = batch {
+            if batch.ids.contains_key(id_key.get_unsigned_id()) {
+                self.write_batch_index_entries(cursors, batch)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn find_unique_conflict(
+        &self,
+        cursors: &IsarCursors,
+        id: Option<i64>,
+        object: IsarObject,
+    ) -> Result<Option<i64>> {
+        for (_, index) in &self.indexes {
+            if let Some(conflict) = index.find_conflict(cursors, id, object)? {
+                return Ok(Some(conflict));
+            }
+        }
+        Ok(None)
+    }
+
     fn put_internal(
         &self,
         cursors: &IsarCursors,
         mut change_set: Option<&mut ChangeSet>,
-        id: Option<i64>,
+        mut id: Option<i64>,
         object: IsarObject,
-        replace_on_conflict: bool,
+        on_conflict: OnConflict,
+        mut batch: Option<&mut PutBatch>,
     ) -> Result<i64> {
+        // Conflicts are resolved before anything is written unless the existing objects are
+        // replaced.
+        if on_conflict != OnConflict::Replace {
+            if let Some(conflict) = self.find_unique_conflict(cursors, id, object)? {
+                match on_conflict {
+                    OnConflict::Ignore => return Ok(conflict),
+                    OnConflict::Update => {
+                        // The object cannot be merged if it conflicts with multiple objects.
+                        id = Some(conflict);
+                        if let Some(conflict) = self.find_unique_conflict(cursors, id, object)? {
+                            return Err(IsarError::UniqueViolated { id: conflict });
+                        }
+                    }
+                    _ => return Err(IsarError::UniqueViolated { id: conflict }),
+                }
+            }
+        }
+
         let (id, id_key) = if let Some(id) = id {
             let id_key = IdKey::new(id);
+            self.prepare_delete(cursors, batch.as_deref_mut(), &id_key)?;
             self.delete_internal(cursors, false, change_set.as_deref_mut(), &id_key)?;
             self.update_auto_increment(id);
             (id, id_key)
@@ -172,27 +450,57 @@ impl IsarCollection {
             return Err(IsarError::InvalidObject {});
         }*/
 
-        for (_, index) in &self.indexes {
+        for (i, (_, index)) in self.indexes.iter().enumerate() {
+            if let (false, Some(batch)) = (index.unique, batch.as_deref_mut()) {
+                index.collect_for_object(id, object, &mut batch.index_entries[i])?;
+                continue;
+            }
             index.create_for_object(cursors, &id_key, object, |id_key| {
-                if replace_on_conflict {
+                if on_conflict == OnConflict::Replace {
+                    self.prepare_delete(cursors, batch.as_deref_mut(), id_key)?;
                     self.delete_internal(cursors, true, change_set.as_deref_mut(), id_key)?;
                     Ok(true)
                 } else {
-                    Err(IsarError::UniqueViolated {})
+                    Err(IsarError::UniqueViolated {
+                        id: id_key.get_id(),
+                    })
                 }
             })?;
         }
 
+        let compressed = compression::compress(object, &self.compressed_properties);
+        let bytes = compressed.as_deref().unwrap_or_else(|| object.as_bytes());
         let mut cursor = cursors.get_cursor(self.db)?;
-        cursor.put(id_key.as_bytes(), object.as_bytes())?;
+        if let Some(batch) = batch {
+            // The objects of a batch are sorted so new objects are usually the last ones.
+            if !matches!(batch.last_id, Some(last_id) if last_id >= id) {
+                cursor.append(id_key.as_bytes(), bytes)?;
+                batch.last_id = Some(id);
+            } else {
+                cursor.put(id_key.as_bytes(), bytes)?;
+            }
+            batch.ids.insert(id_key.get_unsigned_id(), ());
+        } else {
+            cursor.put(id_key.as_bytes(), bytes)?;
+        }
+        cursors.record_writes(self.db, 1, 0);
+        for (_, aggregate) in &self.aggregates {
+            aggregate.update(cursors, &id_key, object, false)?;
+        }
+        if let Some(checksum_db) = self.checksum_db {
+            checksum::write(cursors, checksum_db, &id_key, object.as_bytes())?;
+        }
+        self.log_change(cursors, ChangeOp::Put, id)?;
         if let Some(change_set) = change_set {
             change_set.register_change(self.get_runtime_id(), Some(id), Some(object));
+            change_set.register_object_change(self.get_runtime_id(), id, object, false);
+            change_set.register_payload(self.get_runtime_id(), id, Some(object.as_bytes()));
         }
         Ok(id)
     }
 
     pub fn delete(&self, txn: &mut IsarTxn, id: i64) -> Result<bool> {
-        txn.write(self.instance_id, |cursors, change_set| {
+        self.write(txn, |cursors, change_set| {
             let id_key = IdKey::new(id);
             self.delete_internal(cursors, true, change_set, &id_key)
         })
@@ -205,7 +513,7 @@ impl IsarCollection {
         key: &IndexKey,
     ) -> Result<bool> {
         let index = self.get_index_by_index(index_index)?;
-        txn.write(self.instance_id, |cursors, change_set| {
+        self.write(txn, |cursors, change_set| {
             if let Some(id_key) = index.get_id(cursors, key)? {
                 self.delete_internal(cursors, true, change_set, &id_key)?;
                 Ok(true)
@@ -215,16 +523,81 @@ impl IsarCollection {
         })
     }
 
-    fn delete_internal(
+    pub(crate) fn delete_internal(
+        &self,
+        cursors: &IsarCursors,
+        delete_links: bool,
+        mut change_set: Option<&mut ChangeSet>,
+        id_key: &IdKey,
+    ) -> Result<bool> {
+        let mut cascade = vec![];
+        let deleted = self.delete_object(
+            cursors,
+            delete_links,
+            change_set.as_deref_mut(),
+            id_key,
+            &mut cascade,
+        )?;
+        // Cascades are not recursive because links may form long chains.
+        while let Some((col_id, id)) = cascade.pop() {
+            let col = cursors
+                .get_collection(col_id)
+                .ok_or(IsarError::DbCorrupted {
+                    message: "Link target collection not found.".to_string(),
+                })?;
+            let id_key = IdKey::new(id);
+            col.delete_object(
+                cursors,
+                true,
+                change_set.as_deref_mut(),
+                &id_key,
+                &mut cascade,
+            )?;
+        }
+        Ok(deleted)
+    }
+
+    // Adds the objects that have to be deleted because of cascading links to cascade.
+    fn delete_object(
         &self,
         cursors: &IsarCursors,
         delete_links: bool,
         change_set: Option<&mut ChangeSet>,
         id_key: &IdKey,
+        cascade: &mut Vec<(u64, i64)>,
     ) -> Result<bool> {
         let mut cursor = cursors.get_cursor(self.db)?;
         if let Some((_, object)) = cursor.move_to(id_key.as_bytes())? {
-            let object = IsarObject::from_bytes(object);
+            let object = cursors.read_object(object)?;
+            if delete_links {
+                for (_, link) in &self.links {
+                    let target_col_id = link.get_target_col_runtime_id();
+                    match link.on_delete() {
+                        OnDelete::Unlink => {}
+                        OnDelete::Cascade => {
+                            link.iter_ids(cursors, id_key, |_, target_key| {
+                                cascade.push((target_col_id, target_key.get_id()));
+                                Ok(true)
+                            })?;
+                        }
+                        OnDelete::Restrict => {
+                            let mut has_links = false;
+                            link.iter_ids(cursors, id_key, |_, _| {
+                                has_links = true;
+                                Ok(false)
+                            })?;
+                            if has_links {
+                                return Err(IsarError::DeleteRestricted {
+                                    id: id_key.get_id(),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            for (_, aggregate) in &self.aggregates {
+                aggregate.update(cursors, id_key, object, true)?;
+            }
             for (_, index) in &self.indexes {
                 index.delete_for_object(cursors, id_key, object)?;
             }
@@ -236,14 +609,31 @@ impl IsarCollection {
             if let Some(change_set) = change_set {
                 let id = id_key.get_id();
                 change_set.register_change(self.get_runtime_id(), Some(id), Some(object));
+                change_set.register_object_change(self.get_runtime_id(), id, object, true);
+                change_set.register_payload(self.get_runtime_id(), id, None);
             }
             cursor.delete_current()?;
+            if let Some(checksum_db) = self.checksum_db {
+                checksum::delete(cursors, checksum_db, id_key)?;
+            }
+            // Objects replaced by put_internal() are logged as puts.
+            if delete_links {
+                cursors.record_writes(self.db, 0, 1);
+                self.log_change(cursors, ChangeOp::Delete, id_key.get_id())?;
+            }
             Ok(true)
         } else {
             Ok(false)
         }
     }
 
+    fn log_change(&self, cursors: &IsarCursors, op: ChangeOp, id: i64) -> Result<()> {
+        if let Some(change_log_db) = self.change_log_db {
+            change_log::write(cursors, change_log_db, &self.name, op, id)?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_link(&self, link_index: usize) -> Result<IsarLink> {
         self.links
             .get(link_index)
@@ -253,6 +643,13 @@ impl IsarCollection {
             })
     }
 
+    pub(crate) fn get_link_aggregates(&self, link_db: Db) -> impl Iterator<Item = &IsarAggregate> {
+        self.aggregates
+            .iter()
+            .map(|(_, aggregate)| aggregate)
+            .filter(move |aggregate| aggregate.counts_link(link_db))
+    }
+
     pub fn link(
         &self,
         txn: &mut IsarTxn,
@@ -261,7 +658,7 @@ impl IsarCollection {
         target_id: i64,
     ) -> Result<bool> {
         let link = self.get_link(link_index)?;
-        txn.write(self.instance_id, |cursors, change_set| {
+        self.write(txn, |cursors, change_set| {
             self.register_link_change(change_set, link);
             let source_key = IdKey::new(id);
             let target_key = IdKey::new(target_id);
@@ -277,7 +674,7 @@ impl IsarCollection {
         target_id: i64,
     ) -> Result<bool> {
         let link = self.get_link(link_index)?;
-        txn.write(self.instance_id, |cursors, change_set| {
+        self.write(txn, |cursors, change_set| {
             self.register_link_change(change_set, link);
             let source_key = IdKey::new(id);
             let target_key = IdKey::new(target_id);
@@ -287,13 +684,107 @@ impl IsarCollection {
 
     pub fn unlink_all(&self, txn: &mut IsarTxn, link_index: usize, id: i64) -> Result<()> {
         let link = self.get_link(link_index)?;
-        txn.write(self.instance_id, |cursors, change_set| {
+        self.write(txn, |cursors, change_set| {
             self.register_link_change(change_set, link);
             let id_key = IdKey::new(id);
             link.delete_all_for_object(cursors, &id_key)
         })
     }
 
+    // Replaces all links of the object with links to the target ids.
+    pub fn replace_links(
+        &self,
+        txn: &mut IsarTxn,
+        link_index: usize,
+        id: i64,
+        target_ids: &[i64],
+    ) -> Result<()> {
+        let link = self.get_link(link_index)?;
+        self.write(txn, |cursors, change_set| {
+            self.register_link_change(change_set, link);
+            let source_key = IdKey::new(id);
+            link.delete_all_for_object(cursors, &source_key)?;
+            for target_id in target_ids {
+                link.create(cursors, &source_key, &IdKey::new(*target_id))?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn get_linked_ids(
+        &self,
+        txn: &mut IsarTxn,
+        link_index: usize,
+        id: i64,
+    ) -> Result<Vec<i64>> {
+        let link = self.get_link(link_index)?;
+        txn.read(self.instance_id, |cursors| {
+            let mut ids = vec![];
+            link.iter_ids(cursors, &IdKey::new(id), |_, id_key| {
+                ids.push(id_key.get_id());
+                Ok(true)
+            })?;
+            Ok(ids)
+        })
+    }
+
+    // Breadth-first traversal of a self-link starting at the root object. Every object is
+    // visited at most once, even if the links contain cycles.
+    pub fn traverse<'txn, F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        link_index: usize,
+        root_id: i64,
+        max_depth: usize,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, usize, IsarObject<'txn>) -> bool,
+    {
+        let link = self.get_link(link_index)?;
+        if link.get_target_col_runtime_id() != self.get_runtime_id() {
+            return illegal_arg("Only self-links can be traversed.");
+        }
+        txn.read(self.instance_id, |cursors| {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            let root_key = IdKey::new(root_id);
+            let root = if let Some((_, bytes)) = cursor.move_to(root_key.as_bytes())? {
+                let root = cursors.read_object(bytes)?;
+                self.verify_checksum(cursors, &root_key, root.as_bytes())?;
+                root
+            } else {
+                return Ok(());
+            };
+            if !callback(root_id, 0, root) {
+                return Ok(());
+            }
+
+            let mut visited = IntMap::new();
+            visited.insert(root_key.get_unsigned_id(), ());
+            let mut queue = VecDeque::new();
+            queue.push_back((root_id, 0));
+            let mut stopped = false;
+            while let Some((id, depth)) = queue.pop_front() {
+                if depth >= max_depth {
+                    continue;
+                }
+                link.iter(cursors, &IdKey::new(id), |id_key, object| {
+                    if !visited.insert(id_key.get_unsigned_id(), ()) {
+                        return Ok(true);
+                    }
+                    self.verify_checksum(cursors, &id_key, object.as_bytes())?;
+                    queue.push_back((id_key.get_id(), depth + 1));
+                    stopped = !callback(id_key.get_id(), depth + 1, object);
+                    Ok(!stopped)
+                })?;
+                if stopped {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
     pub fn clear(&self, txn: &mut IsarTxn) -> Result<()> {
         for (_, index) in &self.indexes {
             index.clear(txn)?;
@@ -302,8 +793,23 @@ impl IsarCollection {
             link.clear(txn)?;
         }
         txn.clear_db(self.db)?;
+        if let Some(checksum_db) = self.checksum_db {
+            txn.clear_db(checksum_db)?;
+        }
+        if self.change_log_db.is_some() || !self.aggregates.is_empty() || !self.links.is_empty() {
+            self.write(txn, |cursors, _| {
+                for (_, aggregate) in &self.aggregates {
+                    aggregate.clear(cursors)?;
+                }
+                for (_, link) in &self.links {
+                    link.clear_aggregates(cursors)?;
+                }
+                self.log_change(cursors, ChangeOp::Clear, 0)
+            })?;
+        }
         txn.register_all_changed(self.get_runtime_id())?;
-        self.auto_increment.set(i64::MIN);
+        txn.register_collection_change(&self.name);
+        self.auto_increment.store(i64::MIN, Ordering::SeqCst);
         Ok(())
     }
 
@@ -314,28 +820,118 @@ impl IsarCollection {
         json: Value,
         replace_on_conflict: bool,
     ) -> Result<()> {
-        txn.write(self.instance_id, |cursors, mut change_set| {
+        self.import_json_with_progress(txn, id_name, json, replace_on_conflict, |_| {})
+    }
+
+    // Calls progress with the number of imported objects after every
+    // IMPORT_PROGRESS_INTERVAL objects and once all objects have been imported.
+    pub fn import_json_with_progress<F>(
+        &self,
+        txn: &mut IsarTxn,
+        id_name: Option<&str>,
+        json: Value,
+        replace_on_conflict: bool,
+        mut progress: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize),
+    {
+        self.write(txn, |cursors, mut change_set| {
             let array = json.as_array().ok_or(IsarError::InvalidJson {})?;
             let mut ob_result_cache = None;
-            for value in array {
-                let id = if let Some(id_name) = id_name {
-                    if let Some(id) = value.get(id_name) {
-                        let id = id.as_i64().ok_or(IsarError::InvalidJson {})?;
-                        Some(id)
-                    } else {
-                        None
-                    }
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 && i % IMPORT_PROGRESS_INTERVAL == 0 {
+                    progress(i);
+                }
+                let id = import::get_import_id(value, id_name)?;
+                let ob = JsonEncodeDecode::decode(&self.object_info, value, ob_result_cache)?;
+                let object = ob.finish();
+                let on_conflict = if replace_on_conflict {
+                    OnConflict::Replace
                 } else {
-                    None
+                    OnConflict::Abort
                 };
-                let ob = JsonEncodeDecode::decode(self, value, ob_result_cache)?;
-                let object = ob.finish();
                 self.put_internal(
                     cursors,
                     change_set.as_deref_mut(),
                     id,
                     object,
-                    replace_on_conflict,
+                    on_conflict,
+                    None,
+                )?;
+                ob_result_cache = Some(ob.recycle());
+            }
+            progress(array.len());
+            Ok(())
+        })
+    }
+
+    // Like import_json_with_progress() but the JSON array is parsed and serialized on n_threads
+    // worker threads while the objects are written.
+    pub fn import_json_parallel<F>(
+        &self,
+        txn: &mut IsarTxn,
+        id_name: Option<&str>,
+        json: Vec<u8>,
+        replace_on_conflict: bool,
+        n_threads: usize,
+        mut progress: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize),
+    {
+        let on_conflict = if replace_on_conflict {
+            OnConflict::Replace
+        } else {
+            OnConflict::Abort
+        };
+        self.write(txn, |cursors, mut change_set| {
+            let mut count = 0;
+            import::decode_parallel(&self.object_info, id_name, json, n_threads, |id, object| {
+                if count > 0 && count % IMPORT_PROGRESS_INTERVAL == 0 {
+                    progress(count);
+                }
+                self.put_internal(
+                    cursors,
+                    change_set.as_deref_mut(),
+                    id,
+                    object,
+                    on_conflict,
+                    None,
+                )?;
+                count += 1;
+                Ok(())
+            })?;
+            progress(count);
+            Ok(())
+        })
+    }
+
+    // Reads the rows one by one so the file does not have to fit into memory.
+    pub fn import_csv<R: Read>(
+        &self,
+        txn: &mut IsarTxn,
+        id_name: Option<&str>,
+        csv: R,
+        options: &CsvOptions,
+        replace_on_conflict: bool,
+    ) -> Result<()> {
+        let on_conflict = if replace_on_conflict {
+            OnConflict::Replace
+        } else {
+            OnConflict::Abort
+        };
+        self.write(txn, |cursors, mut change_set| {
+            let mut decoder = CsvDecoder::new(&self.object_info, id_name, csv, options)?;
+            let mut ob_result_cache = None;
+            while let Some((id, ob)) = decoder.decode_next(&self.object_info, ob_result_cache)? {
+                self.put_internal(
+                    cursors,
+                    change_set.as_deref_mut(),
+                    id,
+                    ob.finish(),
+                    on_conflict,
+                    None,
                 )?;
                 ob_result_cache = Some(ob.recycle());
             }
@@ -350,30 +946,218 @@ impl IsarCollection {
         }
     }
 
-    pub(crate) fn fill_indexes(&self, indexes: &[usize], cursors: &IsarCursors) -> Result<()> {
+    // Adds up to batch_size objects starting at start_id to the index. Returns the id to continue
+    // with or None if all objects have been added.
+    pub(crate) fn fill_index(
+        &self,
+        index_name: &str,
+        start_id: i64,
+        batch_size: usize,
+        cursors: &IsarCursors,
+    ) -> Result<Option<i64>> {
+        let (_, index) = self.indexes.iter().find(|(n, _)| n == index_name).unwrap();
+        let mut count = 0;
+        let mut next_id = None;
         let mut cursor = cursors.get_cursor(self.db)?;
         cursor.iter_between(
-            &u64::MIN.to_le_bytes(),
-            &u64::MAX.to_le_bytes(),
+            IdKey::new(start_id).as_bytes(),
+            IdKey::new(i64::MAX).as_bytes(),
             false,
             false,
             true,
             |cursor, key, object| {
                 let id_key = IdKey::from_bytes(key);
-                let object = IsarObject::from_bytes(object);
-                for index_index in indexes {
-                    let (_, index) = self.indexes.get(*index_index).unwrap();
-                    index.create_for_object(cursors, &id_key, object, |id_key| {
-                        let deleted = self.delete_internal(cursors, true, None, id_key)?;
-                        if deleted {
-                            cursor.move_to_next()?; // todo find out why this is necessary
-                        }
-                        Ok(true)
-                    })?;
+                if count == batch_size {
+                    next_id = Some(id_key.get_id());
+                    return Ok(false);
                 }
+                count += 1;
+                // Objects read while deleting duplicates are not kept after the job.
+                unsafe {
+                    cursors.with_object(object, |object| {
+                        index.create_for_object(cursors, &id_key, object, |id_key| {
+                            let deleted = self.delete_internal(cursors, true, None, id_key)?;
+                            if deleted {
+                                cursor.move_to_next()?; // todo find out why this is necessary
+                            }
+                            Ok(true)
+                        })
+                    })
+                }?;
                 Ok(true)
             },
         )?;
+        Ok(next_id)
+    }
+
+    pub fn get_stats(&self, txn: &mut IsarTxn) -> Result<CollectionStats> {
+        txn.read(self.instance_id, |cursors| self.get_stats_internal(cursors))
+    }
+
+    pub(crate) fn get_stats_internal(&self, cursors: &IsarCursors) -> Result<CollectionStats> {
+        let (object_count, object_size) = cursors.stat(self.db)?;
+        let mut stats = CollectionStats {
+            object_count,
+            object_size,
+            size: object_size,
+            indexes: vec![],
+        };
+        for (name, index) in &self.indexes {
+            let (entries, size) = index.stat(cursors)?;
+            stats.size += size;
+            stats
+                .indexes
+                .push((name.clone(), IndexStats { entries, size }));
+        }
+        for (_, link) in &self.links {
+            if !link.is_backlink() {
+                stats.size += link.size(cursors)?;
+            }
+        }
+        if let Some(checksum_db) = self.checksum_db {
+            let (_, size) = cursors.stat(checksum_db)?;
+            stats.size += size;
+        }
+        Ok(stats)
+    }
+
+    pub fn property_stats(&self) -> Option<Vec<(String, PropertyStats)>> {
+        let stats = self.property_stats.as_ref()?.lock().unwrap();
+        let stats = self
+            .properties
+            .iter()
+            .zip(stats.iter())
+            .map(|((name, _), stats)| (name.clone(), *stats))
+            .collect();
+        Some(stats)
+    }
+
+    pub fn reset_property_stats(&self) {
+        if let Some(stats) = &self.property_stats {
+            for stats in stats.lock().unwrap().iter_mut() {
+                *stats = PropertyStats::default();
+            }
+        }
+    }
+
+    pub(crate) fn record_property_usage(&self, filter: &[Property], sort: &[Property]) {
+        if let Some(stats) = &self.property_stats {
+            let mut stats = stats.lock().unwrap();
+            for (i, (_, property)) in self.properties.iter().enumerate() {
+                if filter.contains(property) {
+                    stats[i].filter_count += 1;
+                }
+                if sort.contains(property) {
+                    stats[i].sort_count += 1;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn verify_checksum(
+        &self,
+        cursors: &IsarCursors,
+        id_key: &IdKey,
+        object: &[u8],
+    ) -> Result<()> {
+        if let Some(checksum_db) = self.checksum_db {
+            checksum::verify(cursors, checksum_db, id_key, object)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn verify_checksums(&self, cursors: &IsarCursors) -> Result<()> {
+        if let Some(checksum_db) = self.checksum_db {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, object| {
+                    let id_key = IdKey::from_bytes(key);
+                    // The job only reads the object itself.
+                    unsafe {
+                        cursors.with_object(object, |object| {
+                            checksum::verify(cursors, checksum_db, &id_key, object.as_bytes())
+                        })
+                    }?;
+                    Ok(true)
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn get_aggregate(&self, txn: &mut IsarTxn, name: &str) -> Result<AggregationResult> {
+        let aggregate = self.aggregates.iter().find(|(n, _)| n == name);
+        let aggregate = if let Some((_, aggregate)) = aggregate {
+            aggregate
+        } else {
+            return illegal_arg("Unknown aggregate.");
+        };
+        txn.read(self.instance_id, |cursors| aggregate.get_result(cursors))
+    }
+
+    // Computes the aggregates that have been added or changed from the existing objects and links.
+    pub(crate) fn init_aggregates(&self, cursors: &IsarCursors) -> Result<()> {
+        let mut added = vec![];
+        for (_, aggregate) in &self.aggregates {
+            if !aggregate.is_initialized(cursors)? {
+                aggregate.clear(cursors)?;
+                aggregate.init_link_count(cursors)?;
+                added.push(aggregate);
+            }
+        }
+        if added.is_empty() {
+            return Ok(());
+        }
+        let mut cursor = cursors.get_cursor(self.db)?;
+        cursor.iter_between(
+            &u64::MIN.to_le_bytes(),
+            &u64::MAX.to_le_bytes(),
+            false,
+            false,
+            true,
+            |_, key, object| {
+                // The aggregates do not keep the objects they read.
+                unsafe {
+                    cursors.with_object(object, |object| {
+                        for aggregate in &added {
+                            aggregate.update(cursors, &IdKey::from_bytes(key), object, false)?;
+                        }
+                        Ok(())
+                    })
+                }?;
+                Ok(true)
+            },
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn fill_checksums(&self, cursors: &IsarCursors) -> Result<()> {
+        if let Some(checksum_db) = self.checksum_db {
+            let mut cursor = cursors.get_cursor(self.db)?;
+            cursor.iter_between(
+                &u64::MIN.to_le_bytes(),
+                &u64::MAX.to_le_bytes(),
+                false,
+                false,
+                true,
+                |_, key, object| {
+                    let id_key = IdKey::from_bytes(key);
+                    // The job only reads the object itself.
+                    unsafe {
+                        cursors.with_object(object, |object| {
+                            checksum::write(cursors, checksum_db, &id_key, object.as_bytes())
+                        })
+                    }?;
+                    Ok(true)
+                },
+            )?;
+        }
         Ok(())
     }
 
@@ -0,0 +1,158 @@
+use crate::cursor::DEFAULT_CURSOR_CACHE_SIZE;
+use crate::error::{illegal_arg, Result};
+
+const MIN_PAGE_SIZE: usize = 256;
+const MAX_PAGE_SIZE: usize = 65536;
+
+// Which cursor is closed when a txn returns a cursor to its full cursor cache.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CursorEviction {
+    // The cursor of the db that was used least recently.
+    LeastRecentlyUsed,
+    // The returned cursor. Keeps the cursors of the dbs that were used first in the txn.
+    MostRecentlyUsed,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IsarConfig {
+    pub relaxed_durability: bool,
+    // Only one process may open the database. Readers of other processes don't have to be
+    // tracked in the shared lock file.
+    pub exclusive: bool,
+    // Size of the reader table. Every open read txn occupies a slot. Read txns are not bound
+    // to threads, so a single-process app only needs one slot per concurrent read txn.
+    pub max_readers: Option<u32>,
+    pub page_size: Option<usize>,
+    pub initial_size: Option<usize>,
+    pub growth_step: Option<usize>,
+    pub shrink_threshold: Option<usize>,
+    pub no_readahead: bool,
+    pub write_map: bool,
+    pub no_meminit: bool,
+    pub checksums: bool,
+    pub property_stats: bool,
+    pub index_build_batch_size: usize,
+    pub txn_size_warning: Option<usize>,
+    pub change_log: bool,
+    // Number of bytes a query may use to buffer results for sorting and distinct. Larger
+    // queries move the results to a temporary database in the instance directory.
+    pub query_memory_budget: Option<usize>,
+    // Number of unused cursors a txn keeps for later operations. Zero closes every cursor
+    // after each operation.
+    pub cursor_cache_size: usize,
+    pub cursor_eviction: CursorEviction,
+}
+
+impl Default for IsarConfig {
+    fn default() -> Self {
+        IsarConfig {
+            relaxed_durability: false,
+            exclusive: true,
+            max_readers: None,
+            page_size: None,
+            initial_size: None,
+            growth_step: None,
+            shrink_threshold: None,
+            no_readahead: false,
+            write_map: false,
+            no_meminit: true,
+            checksums: false,
+            property_stats: false,
+            index_build_batch_size: 10000,
+            txn_size_warning: None,
+            change_log: false,
+            query_memory_budget: None,
+            cursor_cache_size: DEFAULT_CURSOR_CACHE_SIZE,
+            cursor_eviction: CursorEviction::LeastRecentlyUsed,
+        }
+    }
+}
+
+impl IsarConfig {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(page_size) = self.page_size {
+            if !page_size.is_power_of_two() || !(MIN_PAGE_SIZE..=MAX_PAGE_SIZE).contains(&page_size)
+            {
+                return illegal_arg("Page size has to be a power of two between 256 and 65536.");
+            }
+        }
+        if self.max_readers == Some(0) {
+            return illegal_arg("Max readers has to be greater than zero.");
+        }
+        if self.index_build_batch_size == 0 {
+            return illegal_arg("Index build batch size has to be greater than zero.");
+        }
+        if self.query_memory_budget == Some(0) {
+            return illegal_arg("Query memory budget has to be greater than zero.");
+        }
+        if self.growth_step == Some(0) {
+            return illegal_arg("Growth step has to be greater than zero.");
+        }
+        if let (Some(growth_step), Some(shrink_threshold)) =
+            (self.growth_step, self.shrink_threshold)
+        {
+            if shrink_threshold < growth_step {
+                return illegal_arg("Shrink threshold must not be smaller than the growth step.");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(IsarConfig::default().validate().is_ok());
+
+        let config = IsarConfig {
+            page_size: Some(4096),
+            initial_size: Some(1 << 20),
+            growth_step: Some(1 << 20),
+            shrink_threshold: Some(4 << 20),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for page_size in &[0, 128, 1000, 131072] {
+            let config = IsarConfig {
+                page_size: Some(*page_size),
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+        }
+
+        let config = IsarConfig {
+            growth_step: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = IsarConfig {
+            max_readers: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = IsarConfig {
+            index_build_batch_size: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = IsarConfig {
+            query_memory_budget: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = IsarConfig {
+            growth_step: Some(4 << 20),
+            shrink_threshold: Some(1 << 20),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
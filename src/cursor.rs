@@ -1,38 +1,178 @@
+use crate::collection::IsarCollection;
+use crate::config::CursorEviction;
 use crate::error::Result;
 use crate::mdbx::cursor::{Cursor, UnboundCursor};
 use crate::mdbx::db::Db;
 use crate::mdbx::txn::Txn;
+use crate::object::compression;
+use crate::object::isar_object::IsarObject;
+use crate::write_profile::WriteProfile;
 use intmap::IntMap;
 use std::cell::RefCell;
 use std::ops::{Deref, DerefMut};
 
+pub(crate) const DEFAULT_CURSOR_CACHE_SIZE: usize = 16;
+
+// Unbound cursors that are kept for later operations of a txn. Every cursor remembers the db it
+// was bound to last because MDBX rebinds a cursor to the same db without any work.
+pub(crate) struct CursorCache {
+    size: usize,
+    eviction: CursorEviction,
+    // The most recently used cursor is last.
+    cursors: Vec<(u64, UnboundCursor)>,
+    pub opened: u64,
+    pub reused: u64,
+    pub evicted: u64,
+}
+
+impl CursorCache {
+    pub fn new(size: usize, eviction: CursorEviction) -> Self {
+        CursorCache {
+            size,
+            eviction,
+            cursors: vec![],
+            opened: 0,
+            reused: 0,
+            evicted: 0,
+        }
+    }
+
+    // Cursors of other dbs are taken least recently used first.
+    fn take(&mut self, db_id: u64) -> UnboundCursor {
+        let same_db = self.cursors.iter().rposition(|(id, _)| *id == db_id);
+        let oldest = if self.cursors.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        if let Some(index) = same_db.or(oldest) {
+            self.reused += 1;
+            self.cursors.remove(index).1
+        } else {
+            self.opened += 1;
+            UnboundCursor::new()
+        }
+    }
+
+    fn put(&mut self, db_id: u64, cursor: UnboundCursor) {
+        if self.cursors.len() >= self.size {
+            self.evicted += 1;
+            if self.size == 0 || self.eviction == CursorEviction::MostRecentlyUsed {
+                return;
+            }
+            self.cursors.remove(0);
+        }
+        self.cursors.push((db_id, cursor));
+    }
+
+    // Closes all cached cursors.
+    pub fn clear(&mut self) {
+        self.cursors.clear();
+    }
+}
+
+impl Default for CursorCache {
+    fn default() -> Self {
+        CursorCache::new(DEFAULT_CURSOR_CACHE_SIZE, CursorEviction::LeastRecentlyUsed)
+    }
+}
+
+// Decompressed objects that have been read with IsarCursors. They have to be kept as long as the
+// objects are used.
+pub(crate) type ObjectBuffers = Vec<Vec<u8>>;
+
 pub(crate) struct IsarCursors<'txn, 'env> {
     txn: &'txn Txn<'env>,
-    unbound_cursors: RefCell<Vec<UnboundCursor>>,
+    cache: RefCell<CursorCache>,
     cursors: RefCell<IntMap<Cursor<'txn>>>,
+    buffers: RefCell<ObjectBuffers>,
+    // The collections of the instance that links may cascade deletes to.
+    collections: &'txn [IsarCollection],
+    write_profile: Option<&'txn RefCell<WriteProfile>>,
 }
 
 impl<'txn, 'env> IsarCursors<'txn, 'env> {
-    pub fn new(
-        txn: &'txn Txn<'env>,
-        unbound_cursors: Vec<UnboundCursor>,
-    ) -> IsarCursors<'txn, 'env> {
+    pub fn new(txn: &'txn Txn<'env>, cache: CursorCache) -> IsarCursors<'txn, 'env> {
         IsarCursors {
             txn,
-            unbound_cursors: RefCell::new(unbound_cursors),
+            cache: RefCell::new(cache),
             cursors: RefCell::new(IntMap::new()),
+            buffers: RefCell::new(vec![]),
+            collections: &[],
+            write_profile: None,
+        }
+    }
+
+    pub fn with_collections(mut self, collections: &'txn [IsarCollection]) -> Self {
+        self.collections = collections;
+        self
+    }
+
+    pub fn with_write_profile(
+        mut self,
+        write_profile: Option<&'txn RefCell<WriteProfile>>,
+    ) -> Self {
+        self.write_profile = write_profile;
+        self
+    }
+
+    // Counts entries written to and deleted from the db if the writes of the txn are profiled.
+    pub fn record_writes(&self, db: Db, written: u64, deleted: u64) {
+        if let Some(write_profile) = self.write_profile {
+            write_profile.borrow_mut().record(db, written, deleted);
         }
     }
 
+    pub fn get_collection(&self, runtime_id: u64) -> Option<&'txn IsarCollection> {
+        self.collections
+            .iter()
+            .find(|c| c.get_runtime_id() == runtime_id)
+    }
+
+    // Every object of a collection db has to be read with this method because objects with
+    // compressed properties are stored in a different format.
+    pub fn read_object(&self, bytes: &'txn [u8]) -> Result<IsarObject<'txn>> {
+        if !compression::is_compressed(bytes) {
+            return Ok(IsarObject::from_bytes(bytes));
+        }
+        let object = compression::decompress(bytes)?;
+        let mut buffers = self.buffers.borrow_mut();
+        buffers.push(object);
+        let object = buffers.last().unwrap();
+        // The heap memory of a buffer does not move when the buffers grow and the buffers are
+        // kept by the txn until its next operation.
+        let bytes = unsafe { std::slice::from_raw_parts(object.as_ptr(), object.len()) };
+        Ok(IsarObject::from_bytes(bytes))
+    }
+
+    // The number of decompressed objects that are kept.
+    pub fn object_mark(&self) -> usize {
+        self.buffers.borrow().len()
+    }
+
+    // Releases the objects decompressed after the mark. They must not be used afterwards.
+    pub unsafe fn release_objects(&self, mark: usize) {
+        self.buffers.borrow_mut().truncate(mark);
+    }
+
+    // Reads an object that is released together with all objects read by the job when the job
+    // returns. Loops over many objects would otherwise keep every decompressed object. Objects
+    // read with read_object inside the job must not be used after it returns.
+    pub unsafe fn with_object<T, F>(&self, bytes: &'txn [u8], job: F) -> Result<T>
+    where
+        F: for<'o> FnOnce(IsarObject<'o>) -> Result<T>,
+    {
+        let mark = self.object_mark();
+        let result = self.read_object(bytes).and_then(job);
+        self.release_objects(mark);
+        result
+    }
+
     pub fn get_cursor<'a>(&'a self, db: Db) -> Result<IsarCursor<'a, 'txn, 'env>> {
         let cursor = if let Some(cursor) = self.cursors.borrow_mut().remove(db.runtime_id()) {
             cursor
         } else {
-            let unbound = self
-                .unbound_cursors
-                .borrow_mut()
-                .pop()
-                .unwrap_or_else(UnboundCursor::new);
+            let unbound = self.cache.borrow_mut().take(db.runtime_id());
             unbound.bind(self.txn, db)?
         };
 
@@ -43,12 +183,52 @@ impl<'txn, 'env> IsarCursors<'txn, 'env> {
         })
     }
 
-    pub fn close(self) -> Vec<UnboundCursor> {
-        let mut unbound_cursors = self.unbound_cursors.take();
-        for (_, cursor) in self.cursors.borrow_mut().drain() {
-            unbound_cursors.push(cursor.unbind())
+    // Estimates the number of entries between the keys including entries of the upper key.
+    // The range is narrowed to the first and last existing key because the estimation excludes
+    // the upper key and is inaccurate for keys after the last entry.
+    pub fn estimate_between(&self, db: Db, lower_key: &[u8], upper_key: &[u8]) -> Result<usize> {
+        let mut cursor = self.get_cursor(db)?;
+        let first_key = if let Some((key, _)) = cursor.move_to_gte(lower_key)? {
+            key
+        } else {
+            return Ok(0);
+        };
+        let last_key = match cursor.move_to_gte(upper_key)? {
+            Some((key, _)) if key == upper_key => Some(key),
+            Some(_) => cursor.move_to_prev_no_dup()?.map(|(key, _)| key),
+            None => cursor.move_to_last()?.map(|(key, _)| key),
+        };
+        let last_key = if let Some(last_key) = last_key {
+            last_key
+        } else {
+            return Ok(0);
+        };
+
+        let distance = db.estimate_range(self.txn, first_key, last_key)?;
+        if first_key == last_key {
+            Ok(distance as usize)
+        } else if distance <= 0 {
+            Ok(0)
+        } else {
+            let last_entries = db.estimate_range(self.txn, last_key, last_key)?;
+            Ok((distance + last_entries) as usize)
+        }
+    }
+
+    pub fn stat(&self, db: Db) -> Result<(u64, u64)> {
+        db.stat(self.txn)
+    }
+
+    pub fn used_bytes(&self) -> Result<u64> {
+        self.txn.used_bytes()
+    }
+
+    pub fn close(self) -> (CursorCache, ObjectBuffers) {
+        let mut cache = self.cache.into_inner();
+        for (db_id, cursor) in self.cursors.borrow_mut().drain() {
+            cache.put(db_id, cursor.unbind())
         }
-        unbound_cursors
+        (cache, self.buffers.into_inner())
     }
 }
 
@@ -78,11 +258,87 @@ impl<'a, 'txn, 'env> Drop for IsarCursor<'a, 'txn, 'env> {
         let cursors = &self.cursors.cursors;
         if !cursors.borrow().contains_key(self.db_id) {
             cursors.borrow_mut().insert(self.db_id, cursor);
-        } else if self.cursors.unbound_cursors.borrow().len() < 3 {
+        } else {
             self.cursors
-                .unbound_cursors
+                .cache
                 .borrow_mut()
-                .push(cursor.unbind());
+                .put(self.db_id, cursor.unbind());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mdbx::env::tests::get_env;
+    use crate::object::data_type::DataType;
+    use crate::object::isar_object::Property;
+    use crate::object::object_builder::ObjectBuilder;
+
+    fn cached_dbs(cache: &CursorCache) -> Vec<u64> {
+        cache.cursors.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn test_cursor_cache() {
+        let mut cache = CursorCache::new(2, CursorEviction::LeastRecentlyUsed);
+        for db_id in 1..=3 {
+            cache.put(db_id, UnboundCursor::new());
+        }
+        assert_eq!(cached_dbs(&cache), vec![2, 3]);
+        assert_eq!(cache.evicted, 1);
+
+        let cursor1 = cache.take(3);
+        assert_eq!(cached_dbs(&cache), vec![2]);
+        let cursor2 = cache.take(4);
+        let cursor3 = cache.take(5);
+        assert_eq!((cache.opened, cache.reused), (1, 2));
+        cache.put(3, cursor1);
+        cache.put(4, cursor2);
+        cache.put(5, cursor3);
+        assert_eq!(cached_dbs(&cache), vec![4, 5]);
+        assert_eq!(cache.evicted, 2);
+
+        let mut cache = CursorCache::new(2, CursorEviction::MostRecentlyUsed);
+        for db_id in 1..=3 {
+            cache.put(db_id, UnboundCursor::new());
+        }
+        assert_eq!(cached_dbs(&cache), vec![1, 2]);
+        assert_eq!(cache.evicted, 1);
+
+        let mut cache = CursorCache::new(0, CursorEviction::LeastRecentlyUsed);
+        cache.put(1, UnboundCursor::new());
+        assert!(cached_dbs(&cache).is_empty());
+        assert_eq!(cache.evicted, 1);
+    }
+
+    #[test]
+    fn test_with_object() {
+        let property = Property::new(DataType::String, 2);
+        let value = "compressed ".repeat(100);
+        let properties = [property];
+        let mut builder = ObjectBuilder::new(&properties, None);
+        builder.write_string(Some(&value));
+        let bytes = compression::compress(builder.finish(), &properties).unwrap();
+
+        let env = get_env();
+        let txn = env.txn(false).unwrap();
+        let cursors = IsarCursors::new(&txn, CursorCache::default());
+        let object = cursors.read_object(&bytes).unwrap();
+        assert_eq!(cursors.object_mark(), 1);
+
+        for _ in 0..3 {
+            let length = unsafe {
+                cursors.with_object(&bytes, |object| {
+                    let inner = cursors.read_object(&bytes)?;
+                    assert_eq!(inner.read_string(property), Some(value.as_str()));
+                    Ok(object.read_string(property).unwrap().len())
+                })
+            }
+            .unwrap();
+            assert_eq!(length, value.len());
         }
+        assert_eq!(cursors.object_mark(), 1);
+        assert_eq!(object.read_string(property), Some(value.as_str()));
     }
 }
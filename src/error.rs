@@ -1,8 +1,9 @@
+use crate::mdbx::error::is_transient_error;
 use snafu::Snafu;
 
 pub type Result<T> = std::result::Result<T, IsarError>;
 
-#[derive(Debug, Snafu, Eq, PartialEq)]
+#[derive(Clone, Debug, Snafu, Eq, PartialEq)]
 pub enum IsarError {
     #[snafu(display("Isar version of the file is too new or too old to be used."))]
     VersionError {},
@@ -18,8 +19,8 @@ pub enum IsarError {
     #[snafu(display("The database is full."))]
     DbFull {},
 
-    #[snafu(display("Unique index violated."))]
-    UniqueViolated {},
+    #[snafu(display("Unique index violated by the object with id {}.", id))]
+    UniqueViolated { id: i64 },
 
     #[snafu(display("Write transaction required."))]
     WriteTxnRequired {},
@@ -54,13 +55,83 @@ pub enum IsarError {
     #[snafu(display("SchemaMismatch: The schema of the existing instance does not match."))]
     SchemaMismatch {},
 
+    #[snafu(display(
+        "PathMismatch: An instance with the same name is already open in a different directory."
+    ))]
+    PathMismatch {},
+
+    #[snafu(display(
+        "ChecksumMismatch: The object with id {} does not match its checksum.",
+        id
+    ))]
+    ChecksumMismatch { id: i64 },
+
+    #[snafu(display("BackupError: {}", message))]
+    BackupError { message: String },
+
+    #[snafu(display("IoError: {}", message))]
+    IoError { message: String },
+
+    #[snafu(display("CsvError: {}", message))]
+    CsvError { message: String },
+
+    #[snafu(display("InspectorError: {}", message))]
+    InspectorError { message: String },
+
     #[snafu(display("InstanceMismatch: The transaction is from a different instance."))]
     InstanceMismatch {},
 
+    #[snafu(display("Timed out waiting for the write transaction."))]
+    TxnTimeout {},
+
+    #[snafu(display(
+        "TxnActive: This thread already has a write transaction. Finish it before starting another one."
+    ))]
+    TxnActive {},
+
+    #[snafu(display("WorkerStopped: The worker thread has stopped."))]
+    WorkerStopped {},
+
+    #[snafu(display(
+        "DeleteRestricted: The object with id {} has links that prevent its deletion.",
+        id
+    ))]
+    DeleteRestricted { id: i64 },
+
+    #[snafu(display(
+        "MapResized: The database was resized by another instance. Retry the transaction."
+    ))]
+    MapResized {},
+
+    #[snafu(display(
+        "DbBusy: The database is locked by another process. Retry later or close the other process."
+    ))]
+    DbBusy {},
+
+    #[snafu(display(
+        "ReadersFull: Too many read transactions are open. Finish unused transactions and retry."
+    ))]
+    ReadersFull {},
+
+    #[snafu(display(
+        "TxnFull: The transaction contains too many changes. Split it into smaller transactions."
+    ))]
+    TxnFull {},
+
     #[snafu(display("MdbxError ({}): {}", code, message))]
     MdbxError { code: i32, message: String },
 }
 
+impl IsarError {
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            IsarError::MapResized {} | IsarError::DbBusy {} | IsarError::ReadersFull {} => true,
+            IsarError::MdbxError { code, .. } => is_transient_error(*code),
+            _ => false,
+        }
+    }
+}
+
 pub fn illegal_arg<T>(msg: &str) -> Result<T> {
     Err(IsarError::IllegalArg {
         message: msg.to_string(),
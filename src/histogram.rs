@@ -0,0 +1,148 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Every power of two is split into 2^SUB_BUCKET_BITS buckets so recorded values are off by
+// less than 1 / 2^SUB_BUCKET_BITS.
+const SUB_BUCKET_BITS: u32 = 3;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+// Values up to 2^MAX_EXPONENT microseconds (about 13 days) are stored exactly enough, larger
+// values are counted in the last bucket.
+const MAX_EXPONENT: u32 = 40;
+const BUCKETS: usize = ((MAX_EXPONENT - SUB_BUCKET_BITS + 2) as u64 * SUB_BUCKETS) as usize;
+
+// Records durations in microseconds with a fixed relative precision like an HDR histogram.
+// Recording does not lock so it can be shared by all txns of an instance.
+pub(crate) struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+    sum: AtomicU64,
+    max: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        LatencyHistogram {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, duration: Duration) {
+        let micros = duration.as_micros().min(u64::MAX as u128) as u64;
+        self.buckets[bucket_index(micros)].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(micros, Ordering::Relaxed);
+        self.max.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: self
+                .buckets
+                .iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+            sum: self.sum.load(Ordering::Relaxed),
+            max: self.max.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn bucket_index(micros: u64) -> usize {
+    if micros < SUB_BUCKETS {
+        return micros as usize;
+    }
+    let exponent = (63 - micros.leading_zeros()).min(MAX_EXPONENT);
+    let sub_bucket =
+        (micros >> (exponent - SUB_BUCKET_BITS)).min(2 * SUB_BUCKETS - 1) - SUB_BUCKETS;
+    ((exponent - SUB_BUCKET_BITS + 1) as u64 * SUB_BUCKETS + sub_bucket) as usize
+}
+
+// The largest value that is counted in the bucket.
+fn bucket_upper_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let exponent = (index / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let sub_bucket = index % SUB_BUCKETS + SUB_BUCKETS;
+    ((sub_bucket + 1) << (exponent - SUB_BUCKET_BITS)) - 1
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistogramSnapshot {
+    buckets: Vec<u64>,
+    sum: u64,
+    max: u64,
+}
+
+impl HistogramSnapshot {
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    pub fn mean(&self) -> Duration {
+        self.sum
+            .checked_div(self.count())
+            .map(Duration::from_micros)
+            .unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max)
+    }
+
+    // The duration that the given fraction (0 to 1) of the recorded durations do not exceed.
+    // It is rounded up to the precision of the histogram but never exceeds the maximum.
+    pub fn percentile(&self, fraction: f64) -> Duration {
+        let count = self.count();
+        if count == 0 {
+            return Duration::default();
+        }
+        let rank = ((fraction.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= rank {
+                return Duration::from_micros(bucket_upper_bound(index).min(self.max));
+            }
+        }
+        self.max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_index() {
+        let mut last_index = 0;
+        for micros in 0..100_000 {
+            let index = bucket_index(micros);
+            assert!(index == last_index || index == last_index + 1);
+            assert!(bucket_upper_bound(index) >= micros);
+            assert!(bucket_upper_bound(index) - micros <= micros / SUB_BUCKETS);
+            last_index = index;
+        }
+        assert_eq!(bucket_index(u64::MAX), BUCKETS - 1);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.snapshot().percentile(0.5), Duration::default());
+        for micros in 1..=1000 {
+            histogram.record(Duration::from_micros(micros));
+        }
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count(), 1000);
+        assert_eq!(snapshot.mean(), Duration::from_micros(500));
+        assert_eq!(snapshot.max(), Duration::from_micros(1000));
+        assert_eq!(snapshot.percentile(1.0), Duration::from_micros(1000));
+        let p50 = snapshot.percentile(0.5).as_micros();
+        assert!((500..=500 + 500 / SUB_BUCKETS as u128).contains(&p50));
+        let p99 = snapshot.percentile(0.99).as_micros();
+        assert!((990..=1000).contains(&p99));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::keys::{oid_from_bytes, oid_to_bytes};
 use std::borrow::{Borrow, Cow};
 use std::convert::TryInto;
 
@@ -7,8 +8,7 @@ pub struct IdKey<'a> {
 
 impl<'a> IdKey<'a> {
     pub fn new(id: i64) -> Self {
-        let unsigned: u64 = unsafe { std::mem::transmute(id) };
-        let bytes = (unsigned ^ 1 << 63).to_le_bytes().to_vec();
+        let bytes = oid_to_bytes(id).to_vec();
         IdKey {
             bytes: Cow::Owned(bytes),
         }
@@ -25,9 +25,7 @@ impl<'a> IdKey<'a> {
     }
 
     pub fn get_id(&self) -> i64 {
-        let unsigned = self.get_unsigned_id();
-        let signed: i64 = unsafe { std::mem::transmute(unsigned) };
-        signed ^ 1 << 63
+        oid_from_bytes(self.as_bytes()).unwrap()
     }
 
     pub fn as_bytes(&self) -> &[u8] {
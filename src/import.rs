@@ -0,0 +1,196 @@
+use crate::error::{illegal_arg, IsarError, Result};
+use crate::object::isar_object::IsarObject;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::object::object_info::ObjectInfo;
+use crossbeam_channel::bounded;
+use intmap::IntMap;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const IMPORT_BATCH_SIZE: usize = 256;
+
+type DecodedBatch = Result<Vec<(Option<i64>, Vec<u8>)>>;
+
+pub(crate) fn get_import_id(value: &Value, id_name: Option<&str>) -> Result<Option<i64>> {
+    if let Some(id) = id_name.and_then(|id_name| value.get(id_name)) {
+        let id = id.as_i64().ok_or(IsarError::InvalidJson {})?;
+        Ok(Some(id))
+    } else {
+        Ok(None)
+    }
+}
+
+// Parses and serializes the objects of a JSON array on worker threads. The callback is called
+// for every object in the order of the array while the workers decode the following objects.
+pub(crate) fn decode_parallel<F>(
+    object_info: &ObjectInfo,
+    id_name: Option<&str>,
+    json: Vec<u8>,
+    n_threads: usize,
+    mut callback: F,
+) -> Result<()>
+where
+    F: FnMut(Option<i64>, IsarObject) -> Result<()>,
+{
+    if n_threads == 0 {
+        return illegal_arg("The import needs at least one thread.");
+    }
+    let elements = split_json_array(&json)?;
+    let batches: Arc<Vec<Vec<(usize, usize)>>> = Arc::new(
+        elements
+            .chunks(IMPORT_BATCH_SIZE)
+            .map(|batch| batch.to_vec())
+            .collect(),
+    );
+    let json = Arc::new(json);
+    let next_batch = Arc::new(AtomicUsize::new(0));
+
+    // Workers only take a new batch after sending the previous one so at most n_threads
+    // batches are decoded ahead of the writer.
+    let (sender, receiver) = bounded::<(usize, DecodedBatch)>(n_threads);
+    for _ in 0..n_threads {
+        let object_info = object_info.clone();
+        let id_name = id_name.map(|id_name| id_name.to_string());
+        let json = json.clone();
+        let batches = batches.clone();
+        let next_batch = next_batch.clone();
+        let sender = sender.clone();
+        thread::spawn(move || loop {
+            let i = next_batch.fetch_add(1, Ordering::SeqCst);
+            if i >= batches.len() {
+                break;
+            }
+            let batch = decode_batch(&object_info, id_name.as_deref(), &json, &batches[i]);
+            // The receiver is dropped if writing failed.
+            if sender.send((i, batch)).is_err() {
+                break;
+            }
+        });
+    }
+    drop(sender);
+
+    let mut decoded = IntMap::new();
+    for i in 0..batches.len() {
+        let batch = loop {
+            if let Some(batch) = decoded.remove(i as u64) {
+                break batch;
+            }
+            // Every batch is sent before its worker stops.
+            let (decoded_i, batch) = receiver.recv().unwrap();
+            decoded.insert(decoded_i as u64, batch);
+        };
+        for (id, bytes) in batch? {
+            callback(id, IsarObject::from_bytes(&bytes))?;
+        }
+    }
+    Ok(())
+}
+
+fn decode_batch(
+    object_info: &ObjectInfo,
+    id_name: Option<&str>,
+    json: &[u8],
+    elements: &[(usize, usize)],
+) -> DecodedBatch {
+    let mut objects = vec![];
+    let mut ob_result_cache = None;
+    for (start, end) in elements {
+        let value: Value =
+            serde_json::from_slice(&json[*start..*end]).map_err(|_| IsarError::InvalidJson {})?;
+        let id = get_import_id(&value, id_name)?;
+        let ob = JsonEncodeDecode::decode(object_info, &value, ob_result_cache)?;
+        objects.push((id, ob.finish().as_bytes().to_vec()));
+        ob_result_cache = Some(ob.recycle());
+    }
+    Ok(objects)
+}
+
+// Returns the byte ranges of the elements of a JSON array. The elements themselves are only
+// parsed by the workers.
+fn split_json_array(json: &[u8]) -> Result<Vec<(usize, usize)>> {
+    let mut elements = vec![];
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = None;
+    let mut after_comma = false;
+    let mut closed = false;
+    for (i, byte) in json.iter().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if *byte == b'\\' {
+                escaped = true;
+            } else if *byte == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        if closed || (depth == 0 && *byte != b'[') {
+            return Err(IsarError::InvalidJson {});
+        }
+        if depth == 1 && start.is_none() && *byte != b',' && *byte != b']' {
+            start = Some(i);
+            after_comma = false;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(start) = start.take() {
+                        elements.push((start, i));
+                    } else if after_comma {
+                        return Err(IsarError::InvalidJson {});
+                    }
+                    closed = true;
+                }
+            }
+            b',' if depth == 1 => {
+                let start = start.take().ok_or(IsarError::InvalidJson {})?;
+                elements.push((start, i));
+                after_comma = true;
+            }
+            _ => {}
+        }
+    }
+    if !closed {
+        return Err(IsarError::InvalidJson {});
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_json_array() {
+        let split = |json: &str| {
+            split_json_array(json.as_bytes()).map(|elements| {
+                elements
+                    .iter()
+                    .map(|(start, end)| json[*start..*end].trim().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(split(" [ ] "), Ok(vec![]));
+        assert_eq!(
+            split(r#"[{"a": [1, {"b": "],\"}"}]}, 2 ,"x"]"#),
+            Ok(vec![
+                r#"{"a": [1, {"b": "],\"}"}]}"#.to_string(),
+                "2".to_string(),
+                r#""x""#.to_string()
+            ])
+        );
+        for invalid in &["", "{}", "[1,]", "[,1]", "[1", "[1] 2"] {
+            assert_eq!(split(invalid), Err(IsarError::InvalidJson {}));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::{illegal_arg, Result};
+use crate::index::index_key::IndexKey;
+use crate::index::IndexProperty;
+use crate::object::data_type::DataType;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::schema::index_schema::IndexType;
+use serde_json::Value;
+
+// Builds keys for where clauses of an index. Every value has to match the type of the next
+// property of the index. Keys may contain only the first properties of a composite index.
+pub struct CompositeKeyBuilder<'a> {
+    properties: &'a [IndexProperty],
+    key: IndexKey,
+    added: usize,
+    unbounded: bool,
+}
+
+impl<'a> CompositeKeyBuilder<'a> {
+    pub(crate) fn new(properties: &'a [IndexProperty]) -> Self {
+        CompositeKeyBuilder {
+            properties,
+            key: IndexKey::new(),
+            added: 0,
+            unbounded: false,
+        }
+    }
+
+    fn next_property(&mut self, data_types: &[DataType]) -> Result<&'a IndexProperty> {
+        if self.unbounded {
+            return illegal_arg("No values can be added after an unbounded upper key.");
+        }
+        let index_property = if let Some(index_property) = self.properties.get(self.added) {
+            index_property
+        } else {
+            return illegal_arg("All properties of the index have already been added.");
+        };
+        let data_type = index_property.property.data_type;
+        let element_type = data_type.get_element_type();
+        let matches = if index_property.index_type == IndexType::Hash {
+            data_types.contains(&data_type)
+        } else {
+            data_types.contains(&element_type.unwrap_or(data_type))
+        };
+        if !matches {
+            return illegal_arg("The value does not match the type of the index property.");
+        }
+        self.added += 1;
+        Ok(index_property)
+    }
+
+    pub fn add_byte(&mut self, value: u8) -> Result<()> {
+        self.next_property(&[DataType::Byte])?;
+        self.key.add_byte(value);
+        Ok(())
+    }
+
+    pub fn add_int(&mut self, value: i32) -> Result<()> {
+        self.next_property(&[DataType::Int])?;
+        self.key.add_int(value);
+        Ok(())
+    }
+
+    pub fn add_long(&mut self, value: i64) -> Result<()> {
+        self.next_property(&[DataType::Long, DataType::DateTime])?;
+        self.key.add_long(value);
+        Ok(())
+    }
+
+    pub fn add_float(&mut self, value: f32) -> Result<()> {
+        let index_property = self.next_property(&[DataType::Float])?;
+        index_property.add_float_to_key(&mut self.key, value);
+        Ok(())
+    }
+
+    pub fn add_double(&mut self, value: f64) -> Result<()> {
+        let index_property = self.next_property(&[DataType::Double])?;
+        index_property.add_double_to_key(&mut self.key, value);
+        Ok(())
+    }
+
+    // Case sensitivity, normalization, collation, stemming and hashing of the index are applied.
+    pub fn add_string(&mut self, value: Option<&str>) -> Result<()> {
+        let index_property = self.next_property(&[DataType::String])?;
+        if index_property.index_type == IndexType::Value {
+            let value = value.map(|v| index_property.stem(v));
+            index_property.add_string_to_key(&mut self.key, value.as_deref());
+        } else {
+            self.key.add_hash(index_property.hash_string(value, 0));
+        }
+        Ok(())
+    }
+
+    // Converts the value to the type of the next property. Null is stored as the null value of
+    // the type.
+    pub fn add_json(&mut self, value: &Value) -> Result<()> {
+        let data_type = if let Some(index_property) = self.properties.get(self.added) {
+            index_property.property.data_type
+        } else {
+            return illegal_arg("All properties of the index have already been added.");
+        };
+        match data_type.get_element_type().unwrap_or(data_type) {
+            DataType::Byte => self.add_byte(JsonEncodeDecode::value_to_byte(value)?),
+            DataType::Int => self.add_int(JsonEncodeDecode::value_to_int(value)?),
+            DataType::Long => self.add_long(JsonEncodeDecode::value_to_long(value)?),
+            DataType::DateTime => self.add_long(JsonEncodeDecode::value_to_date_time(value)?),
+            DataType::Float => self.add_float(JsonEncodeDecode::value_to_float(value)?),
+            DataType::Double => self.add_double(JsonEncodeDecode::value_to_double(value)?),
+            DataType::String => self.add_string(JsonEncodeDecode::value_to_string(value)?),
+            _ => illegal_arg("The value does not match the type of the index property."),
+        }
+    }
+
+    // Makes the key greater than all keys starting with the values added so far. The key
+    // should be used as an inclusive upper bound.
+    pub fn add_upper_unbounded(&mut self) -> Result<()> {
+        if self.unbounded {
+            return illegal_arg("The key is already unbounded.");
+        }
+        for index_property in &self.properties[self.added..] {
+            let data_type = index_property.property.data_type;
+            // Strings start with a marker byte that is smaller than 0xFF. All other values
+            // have a fixed size.
+            let size = if index_property.index_type != IndexType::Value {
+                8
+            } else {
+                match data_type.get_element_type().unwrap_or(data_type) {
+                    DataType::Byte | DataType::String => 1,
+                    DataType::Int | DataType::Float => 4,
+                    _ => 8,
+                }
+            };
+            for _ in 0..size {
+                self.key.add_byte(u8::MAX);
+            }
+        }
+        self.unbounded = true;
+        Ok(())
+    }
+
+    pub fn finish(self) -> IndexKey {
+        self.key
+    }
+}
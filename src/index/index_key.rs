@@ -1,8 +1,8 @@
-use crate::index::IsarIndex;
+use crate::collation::Collation;
+use crate::index::key_encoding;
 use crate::mdbx::ByteKey;
 use std::borrow::Borrow;
 use std::cmp::Ordering;
-use xxhash_rust::xxh3::xxh3_64;
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct IndexKey {
@@ -15,76 +15,48 @@ impl IndexKey {
     }
 
     pub fn add_byte(&mut self, value: u8) {
-        self.bytes.push(value);
+        key_encoding::encode_byte(&mut self.bytes, value);
     }
 
     pub fn add_int(&mut self, value: i32) {
-        let unsigned: u32 = unsafe { std::mem::transmute(value) };
-        let bytes: [u8; 4] = (unsigned ^ 1 << 31).to_be_bytes();
-        self.bytes.extend_from_slice(&bytes);
+        key_encoding::encode_int(&mut self.bytes, value);
     }
 
     pub fn add_long(&mut self, value: i64) {
-        let unsigned: u64 = unsafe { std::mem::transmute(value) };
-        let bytes = (unsigned ^ 1 << 63).to_be_bytes().to_vec();
-        self.bytes.extend_from_slice(&bytes);
+        key_encoding::encode_long(&mut self.bytes, value);
     }
 
     pub fn add_float(&mut self, value: f32) {
-        let bytes: [u8; 4] = if !value.is_nan() {
-            let bits = if value.is_sign_positive() {
-                value.to_bits() + 2u32.pow(31)
-            } else {
-                !(-value).to_bits() - 2u32.pow(31)
-            };
-            bits.to_be_bytes()
-        } else {
-            [0; 4]
-        };
-        self.bytes.extend_from_slice(&bytes);
+        key_encoding::encode_float(&mut self.bytes, value);
     }
 
     pub fn add_double(&mut self, value: f64) {
-        let bytes: [u8; 8] = if !value.is_nan() {
-            let bits = if value.is_sign_positive() {
-                value.to_bits() + 2u64.pow(63)
-            } else {
-                !(-value).to_bits() - 2u64.pow(63)
-            };
-            bits.to_be_bytes()
-        } else {
-            [0; 8]
-        };
-        self.bytes.extend_from_slice(&bytes);
+        key_encoding::encode_double(&mut self.bytes, value);
     }
 
     pub fn add_string(&mut self, value: Option<&str>, case_sensitive: bool) {
-        if let Some(value) = value {
-            let value = if case_sensitive {
+        let value = value.map(|value| {
+            if case_sensitive {
                 value.to_string()
             } else {
                 value.to_lowercase()
-            };
-            let bytes = value.as_bytes();
-            self.bytes.push(1);
-            if bytes.len() >= IsarIndex::MAX_STRING_INDEX_SIZE {
-                self.bytes
-                    .extend_from_slice(&bytes[0..IsarIndex::MAX_STRING_INDEX_SIZE]);
-                self.bytes.push(0);
-                let hash = xxh3_64(bytes);
-                self.bytes.extend_from_slice(&u64::to_le_bytes(hash));
-            } else {
-                self.bytes.extend_from_slice(bytes);
-                self.bytes.push(0);
             }
-        } else {
-            self.bytes.push(0);
-        }
+        });
+        key_encoding::encode_string(&mut self.bytes, value.as_ref().map(|v| v.as_bytes()));
+    }
+
+    pub fn add_collated_string(
+        &mut self,
+        value: Option<&str>,
+        collation: Collation,
+        case_sensitive: bool,
+    ) {
+        let key = value.map(|value| collation.sort_key(value, case_sensitive));
+        key_encoding::encode_string(&mut self.bytes, key.as_deref());
     }
 
     pub fn add_hash(&mut self, value: u64) {
-        let bytes: [u8; 8] = value.to_be_bytes();
-        self.bytes.extend_from_slice(&bytes);
+        key_encoding::encode_hash(&mut self.bytes, value);
     }
 
     #[allow(clippy::len_without_is_empty)]
@@ -96,6 +68,10 @@ impl IndexKey {
         self.bytes.truncate(len);
     }
 
+    pub(crate) fn append(&mut self, other: &IndexKey) {
+        self.bytes.extend_from_slice(&other.bytes);
+    }
+
     pub(crate) fn increase(&mut self) -> bool {
         let mut increased = false;
         for i in (0..self.bytes.len()).rev() {
@@ -19,36 +19,51 @@ impl<'a> IndexKeyBuilder<'a> {
         object: IsarObject,
         mut callback: impl FnMut(&IndexKey) -> Result<bool>,
     ) -> Result<bool> {
-        let first = self.properties.first().unwrap();
-        if first.property.data_type.get_element_type().is_none()
-            || first.index_type == IndexType::Hash
-        {
-            let key = self.create_primitive_key(object);
+        let multi_entry = self.properties.iter().position(|p| p.is_multi_entry());
+        if let Some(position) = multi_entry {
+            // Every element gets its own key combined with the other properties.
+            let prefix = Self::create_primitive_key(&self.properties[..position], object);
+            let suffix = Self::create_primitive_key(&self.properties[position + 1..], object);
+            let mut emit = |key: &mut IndexKey| {
+                key.append(&suffix);
+                callback(key)
+            };
+            let index_property = &self.properties[position];
+            if index_property.splitter.is_some() {
+                Self::create_split_keys(index_property, object, &prefix, &mut emit)
+            } else {
+                Self::create_list_keys(index_property, object, &prefix, &mut emit)
+            }
+        } else {
+            let key = Self::create_primitive_key(self.properties, object);
             callback(&key)?;
             Ok(true)
-        } else {
-            assert_eq!(self.properties.len(), 1);
-            Self::create_list_keys(first, object, &mut callback)
         }
     }
 
-    fn create_primitive_key(&self, object: IsarObject) -> IndexKey {
+    fn create_primitive_key(properties: &[IndexProperty], object: IsarObject) -> IndexKey {
         let mut key = IndexKey::new();
-        for index_property in self.properties {
+        for index_property in properties {
             let property = index_property.property;
 
             if index_property.index_type == IndexType::Hash {
-                let hash = object.hash_property(property, index_property.case_sensitive, 0);
+                let hash = index_property.hash_property(object);
                 key.add_hash(hash);
             } else {
                 match property.data_type {
                     DataType::Byte => key.add_byte(object.read_byte(property)),
                     DataType::Int => key.add_int(object.read_int(property)),
-                    DataType::Float => key.add_float(object.read_float(property)),
+                    DataType::Float => {
+                        index_property.add_float_to_key(&mut key, object.read_float(property))
+                    }
                     DataType::Long => key.add_long(object.read_long(property)),
-                    DataType::Double => key.add_double(object.read_double(property)),
+                    DataType::DateTime => key.add_long(object.read_date_time(property)),
+                    DataType::GeoPoint => key.add_hash(object.read_geo_point(property).geohash()),
+                    DataType::Double => {
+                        index_property.add_double_to_key(&mut key, object.read_double(property))
+                    }
                     DataType::String => {
-                        key.add_string(object.read_string(property), index_property.case_sensitive)
+                        index_property.add_string_to_key(&mut key, object.read_string(property))
                     }
                     _ => unreachable!(),
                 }
@@ -57,12 +72,36 @@ impl<'a> IndexKeyBuilder<'a> {
         key
     }
 
+    fn create_split_keys(
+        index_property: &IndexProperty,
+        object: IsarObject,
+        prefix: &IndexKey,
+        mut callback: impl FnMut(&mut IndexKey) -> Result<bool>,
+    ) -> Result<bool> {
+        // Parts that produce the same key must only be added once.
+        let mut keys: Vec<IndexKey> = vec![];
+        if let Some(value) = object.read_string(index_property.property) {
+            for (_, part) in index_property.split_string(value) {
+                let mut key = prefix.clone();
+                index_property.add_string_to_key(&mut key, Some(&part));
+                if !keys.contains(&key) {
+                    keys.push(key.clone());
+                    if !callback(&mut key)? {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+
     fn create_list_keys(
         index_property: &IndexProperty,
         object: IsarObject,
-        mut callback: impl FnMut(&IndexKey) -> Result<bool>,
+        prefix: &IndexKey,
+        mut callback: impl FnMut(&mut IndexKey) -> Result<bool>,
     ) -> Result<bool> {
-        let mut key = IndexKey::new();
+        let mut key = prefix.clone();
         let property = index_property.property;
         if object.is_null(property) {
             return Ok(true);
@@ -70,59 +109,68 @@ impl<'a> IndexKeyBuilder<'a> {
         match property.data_type {
             DataType::ByteList => {
                 for value in object.read_byte_list(property).unwrap() {
-                    key.truncate(0);
+                    key.truncate(prefix.len());
                     key.add_byte(*value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
             }
             DataType::IntList => {
                 for value in object.read_int_list(property).unwrap() {
-                    key.truncate(0);
+                    key.truncate(prefix.len());
                     key.add_int(value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
             }
             DataType::LongList => {
                 for value in object.read_long_list(property).unwrap() {
-                    key.truncate(0);
+                    key.truncate(prefix.len());
+                    key.add_long(value);
+                    if !callback(&mut key)? {
+                        return Ok(false);
+                    }
+                }
+            }
+            DataType::DateTimeList => {
+                for value in object.read_date_time_list(property).unwrap() {
+                    key.truncate(prefix.len());
                     key.add_long(value);
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
             }
             DataType::FloatList => {
                 for value in object.read_float_list(property).unwrap() {
-                    key.truncate(0);
-                    key.add_float(value);
-                    if !callback(&key)? {
+                    key.truncate(prefix.len());
+                    index_property.add_float_to_key(&mut key, value);
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
             }
             DataType::DoubleList => {
                 for value in object.read_double_list(property).unwrap() {
-                    key.truncate(0);
-                    key.add_double(value);
-                    if !callback(&key)? {
+                    key.truncate(prefix.len());
+                    index_property.add_double_to_key(&mut key, value);
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
             }
             DataType::StringList => {
                 for value in object.read_string_list(property).unwrap() {
-                    key.truncate(0);
+                    key.truncate(prefix.len());
                     if index_property.index_type == IndexType::HashElements {
-                        let hash = IsarObject::hash_string(value, index_property.case_sensitive, 0);
+                        let hash = index_property.hash_string(value, 0);
                         key.add_hash(hash);
                     } else {
-                        key.add_string(value, index_property.case_sensitive);
+                        index_property.add_string_to_key(&mut key, value);
                     }
-                    if !callback(&key)? {
+                    if !callback(&mut key)? {
                         return Ok(false);
                     }
                 }
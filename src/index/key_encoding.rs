@@ -0,0 +1,216 @@
+//! Encoding of index key components.
+//!
+//! Every value is encoded so that comparing the encoded bytes gives the same order as comparing
+//! the values. Composite keys are the concatenation of their encoded components. The decode
+//! functions return the value and the remaining bytes so composite keys can be read component by
+//! component.
+
+use crate::index::IsarIndex;
+use std::convert::TryInto;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Maximum number of string bytes stored in a key.
+pub const MAX_STRING_SIZE: usize = IsarIndex::MAX_STRING_INDEX_SIZE;
+
+/// Bytes are stored as they are.
+pub fn encode_byte(buffer: &mut Vec<u8>, value: u8) {
+    buffer.push(value);
+}
+
+pub fn decode_byte(bytes: &[u8]) -> Option<(u8, &[u8])> {
+    let (value, rest) = bytes.split_first()?;
+    Some((*value, rest))
+}
+
+/// Big endian with the sign bit flipped so negative values sort first.
+pub fn encode_int(buffer: &mut Vec<u8>, value: i32) {
+    let unsigned = value as u32;
+    buffer.extend_from_slice(&(unsigned ^ 1 << 31).to_be_bytes());
+}
+
+pub fn decode_int(bytes: &[u8]) -> Option<(i32, &[u8])> {
+    let (value, rest) = split(bytes, 4)?;
+    let unsigned = u32::from_be_bytes(value.try_into().unwrap());
+    Some(((unsigned ^ 1 << 31) as i32, rest))
+}
+
+/// Big endian with the sign bit flipped so negative values sort first.
+pub fn encode_long(buffer: &mut Vec<u8>, value: i64) {
+    let unsigned = value as u64;
+    buffer.extend_from_slice(&(unsigned ^ 1 << 63).to_be_bytes());
+}
+
+pub fn decode_long(bytes: &[u8]) -> Option<(i64, &[u8])> {
+    let (value, rest) = split(bytes, 8)?;
+    let unsigned = u64::from_be_bytes(value.try_into().unwrap());
+    Some(((unsigned ^ 1 << 63) as i64, rest))
+}
+
+/// Positive values have the sign bit set, negative values have all bits inverted. NaN is encoded
+/// as zero and sorts before negative infinity.
+pub fn encode_float(buffer: &mut Vec<u8>, value: f32) {
+    let bits = if value.is_nan() {
+        0
+    } else if value.is_sign_positive() {
+        value.to_bits() + 2u32.pow(31)
+    } else {
+        !(-value).to_bits() - 2u32.pow(31)
+    };
+    buffer.extend_from_slice(&bits.to_be_bytes());
+}
+
+pub fn decode_float(bytes: &[u8]) -> Option<(f32, &[u8])> {
+    let (value, rest) = split(bytes, 4)?;
+    let bits = u32::from_be_bytes(value.try_into().unwrap());
+    let value = if bits >= 2u32.pow(31) {
+        f32::from_bits(bits - 2u32.pow(31))
+    } else if bits == 0 {
+        f32::NAN
+    } else {
+        -f32::from_bits(!(bits + 2u32.pow(31)))
+    };
+    Some((value, rest))
+}
+
+/// Same as [encode_float] with 64 bits.
+pub fn encode_double(buffer: &mut Vec<u8>, value: f64) {
+    let bits = if value.is_nan() {
+        0
+    } else if value.is_sign_positive() {
+        value.to_bits() + 2u64.pow(63)
+    } else {
+        !(-value).to_bits() - 2u64.pow(63)
+    };
+    buffer.extend_from_slice(&bits.to_be_bytes());
+}
+
+pub fn decode_double(bytes: &[u8]) -> Option<(f64, &[u8])> {
+    let (value, rest) = split(bytes, 8)?;
+    let bits = u64::from_be_bytes(value.try_into().unwrap());
+    let value = if bits >= 2u64.pow(63) {
+        f64::from_bits(bits - 2u64.pow(63))
+    } else if bits == 0 {
+        f64::NAN
+    } else {
+        -f64::from_bits(!(bits + 2u64.pow(63)))
+    };
+    Some((value, rest))
+}
+
+/// Null is encoded as a single zero byte. Other values are prefixed with a one byte and
+/// terminated by a zero byte. Values of at least [MAX_STRING_SIZE] bytes are truncated and
+/// followed by a little endian xxh3 hash of the full value.
+///
+/// The bytes must not contain zero bytes. Case folding or collation have to be applied before.
+pub fn encode_string(buffer: &mut Vec<u8>, value: Option<&[u8]>) {
+    if let Some(value) = value {
+        buffer.push(1);
+        if value.len() >= MAX_STRING_SIZE {
+            buffer.extend_from_slice(&value[0..MAX_STRING_SIZE]);
+            buffer.push(0);
+            let hash = xxh3_64(value);
+            buffer.extend_from_slice(&u64::to_le_bytes(hash));
+        } else {
+            buffer.extend_from_slice(value);
+            buffer.push(0);
+        }
+    } else {
+        buffer.push(0);
+    }
+}
+
+/// Returns the stored bytes of the string. Truncated values only contain the first
+/// [MAX_STRING_SIZE] bytes.
+pub fn decode_string(bytes: &[u8]) -> Option<(Option<&[u8]>, &[u8])> {
+    let (marker, rest) = bytes.split_first()?;
+    if *marker == 0 {
+        return Some((None, rest));
+    }
+    let end = rest.iter().position(|b| *b == 0)?;
+    let value = &rest[..end];
+    let mut rest = &rest[end + 1..];
+    if value.len() == MAX_STRING_SIZE {
+        rest = split(rest, 8)?.1;
+    }
+    Some((Some(value), rest))
+}
+
+/// Hashes are stored big endian.
+pub fn encode_hash(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn decode_hash(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let (value, rest) = split(bytes, 8)?;
+    Some((u64::from_be_bytes(value.try_into().unwrap()), rest))
+}
+
+fn split(bytes: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+    if bytes.len() >= len {
+        Some(bytes.split_at(len))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_numbers() {
+        let mut buffer = vec![];
+        encode_byte(&mut buffer, 7);
+        encode_int(&mut buffer, -12);
+        encode_long(&mut buffer, i64::MIN);
+        encode_float(&mut buffer, -1.5);
+        encode_double(&mut buffer, f64::INFINITY);
+        encode_hash(&mut buffer, 42);
+
+        let (byte, rest) = decode_byte(&buffer).unwrap();
+        let (int, rest) = decode_int(rest).unwrap();
+        let (long, rest) = decode_long(rest).unwrap();
+        let (float, rest) = decode_float(rest).unwrap();
+        let (double, rest) = decode_double(rest).unwrap();
+        let (hash, rest) = decode_hash(rest).unwrap();
+        assert_eq!(
+            (byte, int, long, float, double, hash),
+            (7, -12, i64::MIN, -1.5, f64::INFINITY, 42)
+        );
+        assert!(rest.is_empty());
+        assert!(decode_long(&[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn test_roundtrip_floats() {
+        for value in &[f32::NEG_INFINITY, f32::MIN, -0.0, 0.0, 3.25, f32::MAX] {
+            let mut buffer = vec![];
+            encode_float(&mut buffer, *value);
+            let (decoded, _) = decode_float(&buffer).unwrap();
+            assert_eq!(decoded.to_bits(), value.to_bits());
+        }
+        let mut buffer = vec![];
+        encode_double(&mut buffer, f64::NAN);
+        assert!(decode_double(&buffer).unwrap().0.is_nan());
+    }
+
+    #[test]
+    fn test_roundtrip_string() {
+        let long = vec![b'a'; MAX_STRING_SIZE + 5];
+        let mut buffer = vec![];
+        encode_string(&mut buffer, None);
+        encode_string(&mut buffer, Some(b"hello"));
+        encode_string(&mut buffer, Some(&long));
+        encode_int(&mut buffer, 5);
+
+        let (none, rest) = decode_string(&buffer).unwrap();
+        let (hello, rest) = decode_string(rest).unwrap();
+        let (truncated, rest) = decode_string(rest).unwrap();
+        let (int, rest) = decode_int(rest).unwrap();
+        assert_eq!(none, None);
+        assert_eq!(hello, Some(&b"hello"[..]));
+        assert_eq!(truncated, Some(&long[..MAX_STRING_SIZE]));
+        assert_eq!(int, 5);
+        assert!(rest.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::collation::Collation;
 use crate::cursor::IsarCursors;
 use crate::error::Result;
 use crate::id_key::IdKey;
@@ -5,33 +6,138 @@ use crate::index::index_key::IndexKey;
 use crate::index::index_key_builder::IndexKeyBuilder;
 use crate::mdbx::db::Db;
 use crate::mdbx::debug_dump_db;
+use crate::object::data_type::DataType;
 use crate::object::isar_object::{IsarObject, Property};
-use crate::schema::index_schema::IndexType;
+use crate::schema::index_schema::{IndexType, StringSplitter};
+use crate::stemmer::Stemmer;
 use crate::txn::IsarTxn;
+use caseless::default_case_fold_str;
+use std::borrow::Cow;
 use std::collections::HashSet;
+use unicode_normalization::UnicodeNormalization;
 
+pub mod composite_key_builder;
 pub mod index_key;
 pub(crate) mod index_key_builder;
+pub mod key_encoding;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct IndexProperty {
     pub property: Property,
     pub index_type: IndexType,
     pub case_sensitive: bool,
+    pub normalize: bool,
+    pub collation: Collation,
+    pub decimals: Option<i8>,
+    pub splitter: Option<StringSplitter>,
+    // Case folded and normalized.
+    pub stopwords: Vec<String>,
+    pub stemmer: Option<Stemmer>,
 }
 
 impl IndexProperty {
-    pub(crate) fn new(property: Property, index_type: IndexType, case_sensitive: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        property: Property,
+        index_type: IndexType,
+        case_sensitive: bool,
+        normalize: bool,
+        collation: Collation,
+        decimals: Option<i8>,
+        splitter: Option<StringSplitter>,
+        stopwords: &[String],
+        stemmer: Option<Stemmer>,
+    ) -> Self {
         IndexProperty {
             property,
             index_type,
             case_sensitive,
+            normalize,
+            collation,
+            decimals,
+            splitter,
+            stopwords: stopwords
+                .iter()
+                .map(|w| normalize_string(w, false))
+                .collect(),
+            stemmer,
+        }
+    }
+
+    // Whether the index entries of this property are ordered like the values when they are
+    // sorted with the binary collation.
+    pub(crate) fn has_value_order(&self) -> bool {
+        if self.index_type != IndexType::Value
+            || self.decimals.is_some()
+            || self.splitter.is_some()
+            || self.stemmer.is_some()
+        {
+            return false;
+        }
+        match self.property.data_type {
+            DataType::Byte
+            | DataType::Int
+            | DataType::Float
+            | DataType::Long
+            | DataType::Double
+            | DataType::DateTime => true,
+            DataType::String => {
+                self.case_sensitive && !self.normalize && self.collation.is_binary()
+            }
+            _ => false,
+        }
+    }
+
+    // Returns the parts of the string that get their own index entry together with their
+    // stemmed form. Stopwords are skipped.
+    pub(crate) fn split_string<'a>(&self, value: &'a str) -> Vec<(&'a str, Cow<'a, str>)> {
+        let splitter = if let Some(splitter) = self.splitter {
+            splitter
+        } else {
+            return vec![(value, Cow::Borrowed(value))];
+        };
+        splitter
+            .split(value)
+            .into_iter()
+            .filter(|part| {
+                self.stopwords.is_empty()
+                    || !self.stopwords.contains(&normalize_string(part, false))
+            })
+            .map(|part| (part, self.stem(part)))
+            .collect()
+    }
+
+    // Case insensitive indexes stem the case folded word because stemmers expect lowercase
+    // input.
+    pub(crate) fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        match self.stemmer {
+            Some(stemmer) if self.case_sensitive => Cow::Owned(stemmer.stem(word)),
+            Some(stemmer) => Cow::Owned(stemmer.stem(&normalize_string(word, false))),
+            None => Cow::Borrowed(word),
+        }
+    }
+
+    pub(crate) fn add_float_to_key(&self, key: &mut IndexKey, value: f32) {
+        if let Some(decimals) = self.decimals {
+            key.add_float(round_float(value, decimals));
+        } else {
+            key.add_float(value);
+        }
+    }
+
+    pub(crate) fn add_double_to_key(&self, key: &mut IndexKey, value: f64) {
+        if let Some(decimals) = self.decimals {
+            key.add_double(round_double(value, decimals));
+        } else {
+            key.add_double(value);
         }
     }
 
     pub fn get_string_with_case(&self, object: IsarObject) -> Option<String> {
         object.read_string(self.property).map(|str| {
-            if self.case_sensitive {
+            if self.normalize {
+                normalize_string(str, self.case_sensitive)
+            } else if self.case_sensitive {
                 str.to_string()
             } else {
                 str.to_lowercase()
@@ -39,25 +145,114 @@ impl IndexProperty {
         })
     }
 
+    pub(crate) fn add_string_to_key(&self, key: &mut IndexKey, value: Option<&str>) {
+        if !self.collation.is_binary() {
+            key.add_collated_string(value, self.collation, self.case_sensitive);
+        } else if self.normalize {
+            let value = value.map(|v| normalize_string(v, self.case_sensitive));
+            key.add_string(value.as_deref(), true);
+        } else {
+            key.add_string(value, self.case_sensitive);
+        }
+    }
+
+    pub(crate) fn hash_string(&self, value: Option<&str>, seed: u64) -> u64 {
+        if self.normalize {
+            let value = value.map(|v| normalize_string(v, self.case_sensitive));
+            IsarObject::hash_string(value.as_deref(), true, seed)
+        } else {
+            IsarObject::hash_string(value, self.case_sensitive, seed)
+        }
+    }
+
+    pub(crate) fn hash_property(&self, object: IsarObject) -> u64 {
+        if !self.normalize {
+            return object.hash_property(self.property, self.case_sensitive, 0);
+        }
+        match self.property.data_type {
+            DataType::String => self.hash_string(object.read_string(self.property), 0),
+            DataType::StringList => {
+                if let Some(list) = object.read_string_list(self.property) {
+                    list.into_iter()
+                        .fold(0, |hash, value| self.hash_string(value, hash))
+                } else {
+                    0
+                }
+            }
+            _ => object.hash_property(self.property, self.case_sensitive, 0),
+        }
+    }
+
     fn is_multi_entry(&self) -> bool {
-        self.property.data_type.get_element_type().is_some() && self.index_type != IndexType::Hash
+        (self.property.data_type.get_element_type().is_some() || self.splitter.is_some())
+            && self.index_type != IndexType::Hash
+    }
+}
+
+// Applies NFC normalization and, for case insensitive values, full unicode case folding so
+// that for example "Straße" and "STRASSE" produce the same key.
+pub fn normalize_string(value: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        value.nfc().collect()
+    } else {
+        default_case_fold_str(&value.nfc().collect::<String>())
+            .nfc()
+            .collect()
+    }
+}
+
+// Rounds to the given number of decimal places. Negative values round to tens, hundreds and so
+// on. Zero is always positive so that -0.0 and 0.0 produce the same key.
+pub fn round_float(value: f32, decimals: i8) -> f32 {
+    if !value.is_finite() {
+        return value;
+    }
+    let factor = 10f32.powi(decimals.abs() as i32);
+    let rounded = if decimals >= 0 {
+        (value * factor).round() / factor
+    } else {
+        (value / factor).round() * factor
+    };
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+pub fn round_double(value: f64, decimals: i8) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    let factor = 10f64.powi(decimals.abs() as i32);
+    let rounded = if decimals >= 0 {
+        (value * factor).round() / factor
+    } else {
+        (value / factor).round() * factor
+    };
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
     }
 }
 
 #[derive(Clone, Eq, PartialEq)]
 pub(crate) struct IsarIndex {
+    pub name: String,
     pub properties: Vec<IndexProperty>,
     pub unique: bool,
     pub multi_entry: bool,
-    db: Db,
+    pub db: Db,
 }
 
 impl IsarIndex {
     pub const MAX_STRING_INDEX_SIZE: usize = 1024;
 
-    pub fn new(db: Db, properties: Vec<IndexProperty>, unique: bool) -> Self {
-        let multi_entry = properties.first().unwrap().is_multi_entry();
+    pub fn new(name: &str, db: Db, properties: Vec<IndexProperty>, unique: bool) -> Self {
+        let multi_entry = properties.iter().any(|p| p.is_multi_entry());
         IsarIndex {
+            name: name.to_string(),
             properties,
             unique,
             multi_entry,
@@ -85,11 +280,89 @@ impl IsarIndex {
                 }
             }
             cursor.put(key.as_bytes(), id_key.as_bytes())?;
+            cursors.record_writes(self.db, 1, 0);
+            Ok(true)
+        })?;
+        Ok(())
+    }
+
+    // Collects the entries of the object instead of writing them so they can be written in key
+    // order by create_sorted().
+    pub fn collect_for_object(
+        &self,
+        id: i64,
+        object: IsarObject,
+        entries: &mut Vec<(Vec<u8>, i64)>,
+    ) -> Result<()> {
+        let key_builder = IndexKeyBuilder::new(&self.properties);
+        key_builder.create_keys(object, |key| {
+            entries.push((key.as_bytes().to_vec(), id));
             Ok(true)
         })?;
         Ok(())
     }
 
+    // Writing entries in key order touches every page only once. Entries after the last entry
+    // of the index are appended without searching for their position.
+    pub fn create_sorted(
+        &self,
+        cursors: &IsarCursors,
+        entries: &mut Vec<(Vec<u8>, i64)>,
+    ) -> Result<()> {
+        // The ids of a key are sorted by their bytes.
+        let mut sorted: Vec<(Vec<u8>, Vec<u8>)> = entries
+            .drain(..)
+            .map(|(key, id)| (key, IdKey::new(id).as_bytes().to_vec()))
+            .collect();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        let mut cursor = cursors.get_cursor(self.db)?;
+        let last_entry = cursor
+            .move_to_last()?
+            .map(|(key, id)| (key.to_vec(), id.to_vec()));
+        let first_appended = if let Some(last_entry) = last_entry {
+            sorted.partition_point(|entry| entry <= &last_entry)
+        } else {
+            0
+        };
+        for (i, (key, id)) in sorted.iter().enumerate() {
+            if i < first_appended {
+                cursor.put(key, id)?;
+            } else {
+                cursor.append_dup(key, id)?;
+            }
+        }
+        cursors.record_writes(self.db, sorted.len() as u64, 0);
+        Ok(())
+    }
+
+    // Returns the id of an object other than id that has the same key in this unique index.
+    pub(crate) fn find_conflict(
+        &self,
+        cursors: &IsarCursors,
+        id: Option<i64>,
+        object: IsarObject,
+    ) -> Result<Option<i64>> {
+        let mut conflict = None;
+        if !self.unique {
+            return Ok(conflict);
+        }
+        let mut cursor = cursors.get_cursor(self.db)?;
+        let key_builder = IndexKeyBuilder::new(&self.properties);
+        key_builder.create_keys(object, |key| {
+            if let Some((_, existing_key)) = cursor.move_to(key.as_bytes())? {
+                let existing_id = IdKey::from_bytes(existing_key).get_id();
+                if Some(existing_id) != id {
+                    conflict = Some(existing_id);
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        })?;
+        Ok(conflict)
+    }
+
     pub fn delete_for_object(
         &self,
         cursors: &IsarCursors,
@@ -106,6 +379,7 @@ impl IsarIndex {
             };
             if entry.is_some() {
                 cursor.delete_current()?;
+                cursors.record_writes(self.db, 0, 1);
             }
             Ok(true)
         })?;
@@ -132,6 +406,15 @@ impl IsarIndex {
         )
     }
 
+    pub fn estimate_between(
+        &self,
+        cursors: &IsarCursors,
+        lower_key: &IndexKey,
+        upper_key: &IndexKey,
+    ) -> Result<usize> {
+        cursors.estimate_between(self.db, lower_key.as_bytes(), upper_key.as_bytes())
+    }
+
     pub fn get_id<'txn, 'env>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
@@ -145,6 +428,21 @@ impl IsarIndex {
         Ok(result)
     }
 
+    pub fn contains(&self, cursors: &IsarCursors, key: &IndexKey, id_key: &IdKey) -> Result<bool> {
+        let mut cursor = cursors.get_cursor(self.db)?;
+        if self.unique {
+            let entry = cursor.move_to(key.as_bytes())?;
+            Ok(matches!(entry, Some((_, id)) if id == id_key.as_bytes()))
+        } else {
+            let entry = cursor.move_to_key_val(key.as_bytes(), id_key.as_bytes())?;
+            Ok(entry.is_some())
+        }
+    }
+
+    pub fn stat(&self, cursors: &IsarCursors) -> Result<(u64, u64)> {
+        cursors.stat(self.db)
+    }
+
     pub fn clear(&self, txn: &mut IsarTxn) -> Result<()> {
         txn.clear_db(self.db)
     }
@@ -0,0 +1,291 @@
+use crate::error::{illegal_arg, IsarError, Result};
+use crate::instance::IsarInstance;
+use crate::query::query_builder::QueryBuilder;
+use crate::txn::IsarTxn;
+use crate::watch::WatchHandle;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Debug server for database inspector UIs. Clients send requests as JSON text messages over a
+// WebSocket connection:
+//
+// {"id": 1, "method": "listCollections"}
+// {"id": 2, "method": "query", "collection": "users", "query": {"filter": ...}}
+// {"id": 3, "method": "put", "collection": "users", "objects": [{"id": 1, "name": "a"}]}
+// {"id": 4, "method": "delete", "collection": "users", "ids": [1, 2]}
+// {"id": 5, "method": "watch", "collection": "users"}
+//
+// Every request is answered with {"id": 1, "result": ...} or {"id": 1, "error": "..."}. Queries
+// use the format of `QueryBuilder::from_json`. Watched collections send
+// {"event": "change", "collection": "users"} after every change.
+//
+// The inspector keeps a reference to the instance, so the instance cannot be closed before the
+// inspector has been dropped.
+pub struct Inspector {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Inspector {
+    pub(crate) fn start<A: ToSocketAddrs>(
+        instance: &Arc<IsarInstance>,
+        addr: A,
+    ) -> Result<Inspector> {
+        let listener = TcpListener::bind(addr).map_err(|e| inspector_error(&e.to_string()))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| inspector_error(&e.to_string()))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| inspector_error(&e.to_string()))?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let instance = instance.clone();
+            let stopped = stopped.clone();
+            thread::spawn(move || accept_connections(instance, listener, stopped))
+        };
+        Ok(Inspector {
+            addr,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    // Useful to find the port when binding to port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+// Closes all connections and stops the server.
+impl Drop for Inspector {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn accept_connections(
+    instance: Arc<IsarInstance>,
+    listener: TcpListener,
+    stopped: Arc<AtomicBool>,
+) {
+    let mut connections = vec![];
+    while !stopped.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let instance = instance.clone();
+                let stopped = stopped.clone();
+                connections.push(thread::spawn(move || {
+                    serve_connection(&instance, stream, &stopped)
+                }));
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(_) => break,
+        }
+    }
+    for connection in connections {
+        let _ = connection.join();
+    }
+}
+
+fn serve_connection(instance: &IsarInstance, stream: TcpStream, stopped: &AtomicBool) {
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    let mut socket = if let Ok(socket) = tungstenite::accept(stream) {
+        socket
+    } else {
+        return;
+    };
+    // Reads time out regularly to send change events and to notice when the server stops.
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+
+    let (sender, receiver) = unbounded();
+    let mut watchers = vec![];
+    while !stopped.load(Ordering::SeqCst) {
+        if !send_events(&mut socket, &receiver) {
+            break;
+        }
+        match socket.read_message() {
+            Ok(Message::Text(text)) => {
+                let response = handle_request(instance, &text, &sender, &mut watchers);
+                if socket
+                    .write_message(Message::Text(response.to_string()))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => break,
+        }
+    }
+    let _ = socket.close(None);
+}
+
+fn send_events(socket: &mut WebSocket<TcpStream>, receiver: &Receiver<String>) -> bool {
+    receiver.try_iter().all(|collection| {
+        let event = json!({"event": "change", "collection": collection});
+        socket
+            .write_message(Message::Text(event.to_string()))
+            .is_ok()
+    })
+}
+
+fn handle_request(
+    instance: &IsarInstance,
+    text: &str,
+    sender: &Sender<String>,
+    watchers: &mut Vec<WatchHandle>,
+) -> Value {
+    let request: Value = if let Ok(request) = serde_json::from_str(text) {
+        request
+    } else {
+        return json!({"id": null, "error": IsarError::InvalidJson {}.to_string()});
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    match execute_request(instance, &request, sender, watchers) {
+        Ok(result) => json!({"id": id, "result": result}),
+        Err(e) => json!({"id": id, "error": e.to_string()}),
+    }
+}
+
+fn execute_request(
+    instance: &IsarInstance,
+    request: &Value,
+    sender: &Sender<String>,
+    watchers: &mut Vec<WatchHandle>,
+) -> Result<Value> {
+    let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
+    if method == "listCollections" {
+        return list_collections(instance);
+    }
+
+    let name = request
+        .get("collection")
+        .and_then(|c| c.as_str())
+        .unwrap_or("");
+    let collection = instance
+        .collection_by_name(name)
+        .ok_or_else(|| inspector_error(&format!("Unknown collection \"{}\".", name)))?;
+    match method {
+        "query" => {
+            let empty = json!({});
+            let query = request.get("query").unwrap_or(&empty);
+            let query = QueryBuilder::from_json(collection, query)?.build();
+            let mut txn = instance.begin_txn(false, true)?;
+            let result = query.export_json(&mut txn, collection, Some("id"), true, false);
+            txn.abort();
+            result
+        }
+        "put" => {
+            let objects = request.get("objects").cloned().unwrap_or(Value::Null);
+            write(instance, |txn| {
+                collection.import_json(txn, Some("id"), objects, true)?;
+                Ok(Value::Null)
+            })
+        }
+        "delete" => {
+            let ids = request
+                .get("ids")
+                .and_then(|ids| ids.as_array())
+                .ok_or(IsarError::InvalidJson {})?;
+            write(instance, |txn| {
+                let mut count = 0;
+                for id in ids {
+                    let id = id.as_i64().ok_or(IsarError::InvalidJson {})?;
+                    if collection.delete(txn, id)? {
+                        count += 1;
+                    }
+                }
+                Ok(json!(count))
+            })
+        }
+        "watch" => {
+            let sender = sender.clone();
+            let name = collection.name.clone();
+            let handle = instance.watch_collection(
+                collection,
+                Box::new(move || {
+                    let _ = sender.send(name.clone());
+                }),
+            );
+            watchers.push(handle);
+            Ok(Value::Null)
+        }
+        _ => illegal_arg("Unknown inspector method."),
+    }
+}
+
+fn list_collections(instance: &IsarInstance) -> Result<Value> {
+    let mut txn = instance.begin_txn(false, true)?;
+    let mut collections = vec![];
+    for collection in &instance.collections {
+        let count = collection.new_query_builder().build().count(&mut txn);
+        let count = match count {
+            Ok(count) => count,
+            Err(e) => {
+                txn.abort();
+                return Err(e);
+            }
+        };
+        let properties: Vec<Value> = collection
+            .properties
+            .iter()
+            .map(|(name, p)| json!({"name": name, "type": p.data_type}))
+            .collect();
+        collections.push(json!({
+            "name": collection.name,
+            "properties": properties,
+            "count": count,
+        }));
+    }
+    txn.abort();
+    Ok(Value::Array(collections))
+}
+
+fn write<F>(instance: &IsarInstance, job: F) -> Result<Value>
+where
+    F: FnOnce(&mut IsarTxn) -> Result<Value>,
+{
+    let mut txn = instance.begin_txn(true, false)?;
+    match job(&mut txn) {
+        Ok(result) => {
+            txn.commit()?;
+            Ok(result)
+        }
+        Err(e) => {
+            txn.abort();
+            Err(e)
+        }
+    }
+}
+
+fn inspector_error(message: &str) -> IsarError {
+    IsarError::InspectorError {
+        message: message.to_string(),
+    }
+}
@@ -1,98 +1,220 @@
-use crate::collection::IsarCollection;
+use crate::backup;
+use crate::change_log::{self, ApplyPolicy, ApplySummary};
+use crate::collection::{CollectionStats, IsarCollection};
+use crate::config::{CursorEviction, IsarConfig};
+use crate::cursor::CursorCache;
 use crate::error::*;
-use crate::mdbx::env::Env;
+use crate::histogram::{HistogramSnapshot, LatencyHistogram};
+use crate::id_key::IdKey;
+#[cfg(feature = "inspector")]
+use crate::inspector::Inspector;
+use crate::mdbx::db::Db;
+use crate::mdbx::env::{Env, DATA_FILE_NAME};
+use crate::query::search::{self, SearchOptions, SearchResult};
+use crate::query::spill::{MemoryBudget, SPILL_DIR_NAME};
 use crate::query::Query;
-use crate::schema::schema_manager::SchemaManger;
+use crate::query_pool::QueryPool;
+use crate::schema::schema_manager::{build_index, SchemaManger};
 use crate::schema::Schema;
-use crate::txn::IsarTxn;
+use crate::txn::{IsarTxn, RetryPolicy, TxnPriority};
+use crate::txn_queue::TxnQueue;
 use crate::watch::change_set::ChangeSet;
 use crate::watch::isar_watchers::{IsarWatchers, WatcherModifier};
-use crate::watch::watcher::WatcherCallback;
+use crate::watch::lifecycle::{
+    add_lifecycle_watcher, notify_lifecycle, remove_lifecycle_watcher, LifecycleCallback,
+    LifecycleEvent,
+};
+use crate::watch::log::{add_log_watcher, remove_log_watcher, LogCallback};
+use crate::watch::pause::PauseState;
+use crate::watch::watcher::{
+    ChangesWatcherCallback, PayloadWatcherCallback, WatcherCallback, WatcherErrorCallback,
+};
 use crate::watch::WatchHandle;
+use crate::write_batch::WriteBatch;
+use crate::write_queue::WriteQueue;
 use crossbeam_channel::{unbounded, Sender};
 use intmap::IntMap;
 use once_cell::sync::Lazy;
 use rand::random;
-use std::fs::{create_dir_all, remove_dir_all};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, canonicalize, create_dir_all, remove_dir_all, File};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::mem;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex, RwLock};
+#[cfg(feature = "inspector")]
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
 use xxhash_rust::xxh3::xxh3_64;
 
 static INSTANCES: Lazy<RwLock<IntMap<Arc<IsarInstance>>>> =
     Lazy::new(|| RwLock::new(IntMap::new()));
 
+// The ids of the instances that are being opened. Instances are opened without holding the
+// INSTANCES lock so lifecycle callbacks can use other instances.
+static OPENING: Lazy<(Mutex<HashSet<u64>>, Condvar)> =
+    Lazy::new(|| (Mutex::new(HashSet::new()), Condvar::new()));
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstanceStats {
+    // The used bytes of the database file.
+    pub size: u64,
+    pub collections: Vec<(String, CollectionStats)>,
+    // Durations of all write txn commits since the instance was opened.
+    pub commit_latency: HistogramSnapshot,
+}
+
 pub struct IsarInstance {
     pub name: String,
-    pub dir: String,
+    pub dir: PathBuf,
     pub collections: Vec<IsarCollection>,
+    collection_names: HashMap<String, usize>,
     pub(crate) instance_id: u64,
     pub(crate) schema_hash: u64,
 
     env: Env,
+    info_db: Db,
+    change_log_db: Option<Db>,
+    txn_size_warning: Option<u64>,
+    cursor_cache_size: usize,
+    cursor_eviction: CursorEviction,
+    commit_latency: LatencyHistogram,
+    txn_queue: TxnQueue,
     watchers: Mutex<IsarWatchers>,
     watcher_modifier_sender: Sender<WatcherModifier>,
 }
 
 impl IsarInstance {
-    pub fn open(
+    pub fn open<P: AsRef<Path>>(
         name: &str,
-        dir: &str,
+        dir: P,
         relaxed_durability: bool,
         schema: Schema,
     ) -> Result<Arc<Self>> {
-        let mut lock = INSTANCES.write().unwrap();
+        let config = IsarConfig {
+            relaxed_durability,
+            ..Default::default()
+        };
+        Self::open_with_config(name, dir, schema, config)
+    }
+
+    pub fn open_with_config<P: AsRef<Path>>(
+        name: &str,
+        dir: P,
+        schema: Schema,
+        config: IsarConfig,
+    ) -> Result<Arc<Self>> {
+        config.validate()?;
         let instance_id = xxh3_64(name.as_bytes());
-        if let Some(instance) = lock.get(instance_id) {
-            if instance.schema_hash == schema.get_hash() {
+        let (opening, opened) = &*OPENING;
+        let mut opening_lock = opening.lock().unwrap();
+        while opening_lock.contains(&instance_id) {
+            opening_lock = opened.wait(opening_lock).unwrap();
+        }
+        if let Some(instance) = INSTANCES.read().unwrap().get(instance_id) {
+            return if !Self::is_same_dir(&instance.dir, dir.as_ref()) {
+                Err(IsarError::PathMismatch {})
+            } else if instance.schema_hash == schema.get_hash() {
                 Ok(instance.clone())
             } else {
                 Err(IsarError::SchemaMismatch {})
-            }
-        } else {
-            let new_instance =
-                Self::open_internal(name, dir, instance_id, relaxed_durability, schema)?;
-            let new_instance = Arc::new(new_instance);
+            };
+        }
+        opening_lock.insert(instance_id);
+        mem::drop(opening_lock);
+
+        let result = Self::open_internal(name, dir.as_ref(), instance_id, &config, schema);
+        let result = result.map(Arc::new);
+
+        let mut opening_lock = opening.lock().unwrap();
+        if let Ok(new_instance) = &result {
+            let mut lock = INSTANCES.write().unwrap();
             lock.insert(instance_id, new_instance.clone());
-            Ok(new_instance)
+        }
+        opening_lock.remove(&instance_id);
+        mem::drop(opening_lock);
+        opened.notify_all();
+
+        let new_instance = result?;
+        notify_lifecycle(name, LifecycleEvent::Opened);
+        Ok(new_instance)
+    }
+
+    fn is_same_dir(dir1: &Path, dir2: &Path) -> bool {
+        if dir1 == dir2 {
+            return true;
+        }
+        match (canonicalize(dir1), canonicalize(dir2)) {
+            (Ok(dir1), Ok(dir2)) => dir1 == dir2,
+            _ => false,
         }
     }
 
     fn open_internal(
         name: &str,
-        dir: &str,
+        dir: &Path,
         instance_id: u64,
-        relaxed_durability: bool,
+        config: &IsarConfig,
         mut schema: Schema,
     ) -> Result<Self> {
         let schema_hash = schema.get_hash();
 
-        let mut path_buf = PathBuf::from(dir);
-        path_buf.push(name);
-        let path = path_buf.as_path().to_str().unwrap();
-        if create_dir_all(path).is_err() {
+        let path = dir.join(name);
+        if create_dir_all(&path).is_err() {
             return Err(IsarError::PathError {});
         }
 
-        let db_count = schema.count_dbs() as u64 + 3;
-        let env = Env::create(path, db_count, relaxed_durability)
+        let db_count = (schema.count_dbs() + schema.collections.len()) as u64 + 4;
+        let env = Env::create(&path, db_count, config)
             .map_err(|e| IsarError::EnvError { error: Box::new(e) })?;
 
-        let txn = env.txn(true)?;
-        let collections = {
-            let mut manager = SchemaManger::create(instance_id, &txn)?;
-            manager.perform_migration(&mut schema)?;
-            manager.open_collections(&schema)?
-        };
-        txn.commit()?;
+        MemoryBudget::clear_dir(&path);
+        backup::TempDir::clear(&path);
+        let memory_budget = config
+            .query_memory_budget
+            .map(|limit| MemoryBudget::new(limit, path.join(SPILL_DIR_NAME)));
+
+        let mut migrating = false;
+        let result = Self::migrate(
+            name,
+            &env,
+            instance_id,
+            config,
+            &mut schema,
+            memory_budget,
+            &mut migrating,
+        );
+        if migrating {
+            let event = if result.is_ok() {
+                LifecycleEvent::MigrationFinished
+            } else {
+                LifecycleEvent::MigrationFailed
+            };
+            notify_lifecycle(name, event);
+        }
+        let (collections, info_db, change_log_db) = result?;
+        let collection_names: HashMap<String, usize> = collections
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.name.clone(), i))
+            .collect();
 
         let (tx, rx) = unbounded();
 
         Ok(IsarInstance {
             env,
+            info_db,
+            change_log_db,
+            txn_size_warning: config.txn_size_warning.map(|s| s as u64),
+            cursor_cache_size: config.cursor_cache_size,
+            cursor_eviction: config.cursor_eviction,
+            commit_latency: LatencyHistogram::new(),
+            txn_queue: TxnQueue::new(),
             name: name.to_string(),
-            dir: dir.to_string(),
+            dir: dir.to_path_buf(),
             collections,
+            collection_names,
             instance_id,
             schema_hash,
             watchers: Mutex::new(IsarWatchers::new(rx)),
@@ -100,12 +222,81 @@ impl IsarInstance {
         })
     }
 
+    // Migrates the database to the schema and builds new indexes. The migration events are only
+    // emitted if the schema or config changed since the last open.
+    fn migrate(
+        name: &str,
+        env: &Env,
+        instance_id: u64,
+        config: &IsarConfig,
+        schema: &mut Schema,
+        memory_budget: Option<MemoryBudget>,
+        migrating: &mut bool,
+    ) -> Result<(Vec<IsarCollection>, Db, Option<Db>)> {
+        let txn = env.txn(true)?;
+        let (collections, info_db, change_log_db, pending_indexes) = {
+            let mut manager = SchemaManger::create(instance_id, &txn, config)?;
+            if manager.needs_migration(schema)? {
+                *migrating = true;
+                notify_lifecycle(name, LifecycleEvent::MigrationStarted);
+            }
+            manager.perform_migration(schema)?;
+            let collections = manager.open_collections(schema, memory_budget)?;
+            let pending_indexes = manager.get_pending_index_builds(schema)?;
+            let change_log_db = manager.get_change_log_db();
+            (
+                collections,
+                manager.get_info_db(),
+                change_log_db,
+                pending_indexes,
+            )
+        };
+        txn.commit()?;
+        for (col_name, index_name) in pending_indexes {
+            let col = collections.iter().find(|c| c.name == col_name).unwrap();
+            build_index(
+                env,
+                info_db,
+                &collections,
+                col,
+                &index_name,
+                config.index_build_batch_size,
+            )?;
+        }
+        Ok((collections, info_db, change_log_db))
+    }
+
     pub fn get_instance(name: &str) -> Option<Arc<Self>> {
         let instance_id = xxh3_64(name.as_bytes());
         INSTANCES.read().unwrap().get(instance_id).cloned()
     }
 
+    pub fn collection_by_name(&self, name: &str) -> Option<&IsarCollection> {
+        let index = *self.collection_names.get(name)?;
+        self.collections.get(index)
+    }
+
     pub fn begin_txn(&self, write: bool, silent: bool) -> Result<IsarTxn> {
+        self.try_begin_txn(write, silent, TxnPriority::Interactive, None)
+    }
+
+    // Write transactions wait in a queue until the previous write transaction has finished. If
+    // the timeout passes first, TxnTimeout is returned. A thread that already has a write
+    // transaction gets TxnActive instead of waiting for itself. Read transactions start
+    // immediately.
+    pub fn try_begin_txn(
+        &self,
+        write: bool,
+        silent: bool,
+        priority: TxnPriority,
+        timeout: Option<Duration>,
+    ) -> Result<IsarTxn> {
+        let write_ticket = if write {
+            Some(self.txn_queue.acquire(priority, timeout)?)
+        } else {
+            None
+        };
+
         let change_set = if write && !silent {
             let mut watchers_lock = self.watchers.lock().unwrap();
             watchers_lock.sync();
@@ -116,16 +307,296 @@ impl IsarInstance {
         };
 
         let txn = self.env.txn(write)?;
-        IsarTxn::new(self.instance_id, txn, write, change_set)
+        let size_warning = self.txn_size_warning.map(|size| (self.name.as_str(), size));
+        IsarTxn::new(
+            self.instance_id,
+            &self.env,
+            &self.collections,
+            txn,
+            self.info_db,
+            write,
+            change_set,
+            CursorCache::new(self.cursor_cache_size, self.cursor_eviction),
+            size_warning,
+            &self.commit_latency,
+            write_ticket,
+        )
+    }
+
+    // Runs the job in a new transaction and commits it. If the job or the commit fails with a
+    // retryable error, the transaction is aborted and the job runs again after a backoff.
+    pub fn txn_with_retry<T, F>(&self, write: bool, policy: &RetryPolicy, mut job: F) -> Result<T>
+    where
+        F: FnMut(&mut IsarTxn) -> Result<T>,
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let result = self
+                .begin_txn(write, false)
+                .and_then(|mut txn| match job(&mut txn) {
+                    Ok(result) => {
+                        if write {
+                            txn.commit()?;
+                        } else {
+                            txn.abort();
+                        }
+                        Ok(result)
+                    }
+                    Err(e) => {
+                        txn.abort();
+                        Err(e)
+                    }
+                });
+            match result {
+                Err(e) if e.is_retryable() && attempt < policy.max_attempts => {
+                    thread::sleep(policy.backoff(attempt));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub fn query_pool(self: &Arc<Self>, n_threads: usize) -> Result<QueryPool> {
+        QueryPool::new(self, n_threads)
+    }
+
+    // Starts a debug server for inspector UIs. The server stops when the inspector is dropped.
+    #[cfg(feature = "inspector")]
+    pub fn start_inspector<A: ToSocketAddrs>(self: &Arc<Self>, addr: A) -> Result<Inspector> {
+        Inspector::start(self, addr)
+    }
+
+    // Creates a compacted copy of the database. Other transactions can continue during the copy.
+    pub fn copy_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.env.copy_to_file(path.as_ref())
+    }
+
+    // Like copy_to_file() but the copy is meant to replace the database, so lifecycle watchers
+    // are notified about the compaction.
+    pub fn compact_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.copy_to_file(path)?;
+        notify_lifecycle(&self.name, LifecycleEvent::Compacted);
+        Ok(())
+    }
+
+    // Checks the stored checksums of all objects. Instances without checksums always pass.
+    pub fn verify(&self) -> Result<()> {
+        let mut txn = self.begin_txn(false, true)?;
+        let result = txn.read(self.instance_id, |cursors| {
+            for collection in &self.collections {
+                collection.verify_checksums(cursors)?;
+            }
+            Ok(())
+        });
+        txn.abort();
+        let event = if result.is_ok() {
+            LifecycleEvent::VerificationPassed
+        } else {
+            LifecycleEvent::VerificationFailed
+        };
+        notify_lifecycle(&self.name, event);
+        result
+    }
+
+    // Writes all changes after since_seq as JSON lines and returns the sequence number of the
+    // last change. Requires the change log to be enabled in the config.
+    pub fn export_changes_json<W: Write>(&self, since_seq: u64, writer: &mut W) -> Result<u64> {
+        let change_log_db = self.change_log_db.ok_or(IsarError::IllegalArg {
+            message: "The change log is not enabled.".to_string(),
+        })?;
+        let mut txn = self.begin_txn(false, true)?;
+        let result = txn.read(self.instance_id, |cursors| {
+            change_log::export_json(cursors, change_log_db, &self.collections, since_seq, writer)
+        });
+        txn.abort();
+        result
+    }
+
+    // Applies changes exported with export_changes_json() in a single transaction.
+    pub fn apply_changes<R: Read>(&self, reader: R, policy: ApplyPolicy) -> Result<ApplySummary> {
+        let mut txn = self.begin_txn(true, false)?;
+        let result = change_log::apply_json(
+            &mut txn,
+            self.instance_id,
+            &self.collections,
+            reader,
+            policy,
+        );
+        match result {
+            Ok(summary) => {
+                txn.commit()?;
+                Ok(summary)
+            }
+            Err(e) => {
+                txn.abort();
+                Err(e)
+            }
+        }
+    }
+
+    pub fn export_encrypted<P: AsRef<Path>>(&self, path: P, key: &[u8; 32]) -> Result<()> {
+        let tmp_dir = backup::TempDir::create(&self.dir.join(&self.name))?;
+        let tmp_path = tmp_dir.path().join(DATA_FILE_NAME);
+        self.env.copy_to_file(&tmp_path)?;
+        let data = File::open(&tmp_path).map_err(|_| IsarError::PathError {})?;
+        let backup = File::create(&path).map_err(|_| IsarError::PathError {})?;
+        let result = backup::encrypt(BufReader::new(data), BufWriter::new(backup), key);
+        if result.is_err() {
+            let _ = fs::remove_file(path);
+        }
+        result
     }
 
-    fn new_watcher(&self, start: WatcherModifier, stop: WatcherModifier) -> WatchHandle {
+    // Replaces the database of the instance with a copy created by copy_to_file() and opens it.
+    pub fn open_from_backup<P: AsRef<Path>, D: AsRef<Path>>(
+        path: P,
+        name: &str,
+        dir: D,
+        schema: Schema,
+        config: IsarConfig,
+    ) -> Result<Arc<Self>> {
+        if Self::get_instance(name).is_some() {
+            return illegal_arg("Cannot restore an instance that is currently open.");
+        }
+        let instance_path = dir.as_ref().join(name);
+        if create_dir_all(&instance_path).is_err() {
+            return Err(IsarError::PathError {});
+        }
+        fs::copy(path, instance_path.join(DATA_FILE_NAME)).map_err(|_| IsarError::PathError {})?;
+        Self::open_with_config(name, dir, schema, config)
+    }
+
+    pub fn restore_encrypted<P: AsRef<Path>, D: AsRef<Path>>(
+        path: P,
+        key: &[u8; 32],
+        name: &str,
+        dir: D,
+    ) -> Result<()> {
+        if Self::get_instance(name).is_some() {
+            return illegal_arg("Cannot restore an instance that is currently open.");
+        }
+        let instance_path = dir.as_ref().join(name);
+        if create_dir_all(&instance_path).is_err() {
+            return Err(IsarError::PathError {});
+        }
+        // The backup is restored next to the database first so a backup that turns out to be
+        // invalid does not leave a partial database behind.
+        let tmp_dir = backup::TempDir::create(&instance_path)?;
+        let tmp_path = tmp_dir.path().join(DATA_FILE_NAME);
+        let backup = File::open(path).map_err(|_| IsarError::PathError {})?;
+        let data = File::create(&tmp_path).map_err(|_| IsarError::PathError {})?;
+        backup::decrypt(BufReader::new(backup), BufWriter::new(data), key)?;
+        fs::rename(&tmp_path, instance_path.join(DATA_FILE_NAME))
+            .map_err(|_| IsarError::PathError {})
+    }
+
+    // Lifecycle events are reported for all instances. The callback runs on the thread that
+    // performs the operation without holding any locks, but it must not open the instance whose
+    // migration is being reported.
+    pub fn watch_lifecycle(callback: LifecycleCallback) -> WatchHandle {
+        let watcher_id = random();
+        let (callback, pause_state) = PauseState::wrap_lifecycle(callback);
+        add_lifecycle_watcher(watcher_id, callback);
+        WatchHandle::new(
+            Box::new(move || remove_lifecycle_watcher(watcher_id)),
+            pause_state,
+        )
+    }
+
+    // Log messages are reported for all instances, for example when a txn exceeds the size
+    // warning of the config.
+    pub fn watch_log(callback: LogCallback) -> WatchHandle {
+        let watcher_id = random();
+        let (callback, pause_state) = PauseState::wrap_log(callback);
+        add_log_watcher(watcher_id, callback);
+        WatchHandle::new(
+            Box::new(move || remove_log_watcher(watcher_id)),
+            pause_state,
+        )
+    }
+
+    // Deletes all objects of the collection that are not referenced by the given backlink.
+    // Returns the number of deleted objects.
+    pub fn delete_orphans(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        backlink_index: usize,
+    ) -> Result<usize> {
+        if collection.instance_id != self.instance_id {
+            return illegal_arg("The collection belongs to a different instance.");
+        }
+        let backlink = collection.get_link(backlink_index)?;
+        if !backlink.is_backlink() {
+            return illegal_arg("The link is not a backlink.");
+        }
+        collection.write(txn, |cursors, mut change_set| {
+            let mut linked_ids = IntMap::new();
+            backlink.iter_linked_ids(cursors, |id_key| {
+                linked_ids.insert(id_key.get_unsigned_id(), ());
+                Ok(true)
+            })?;
+
+            let mut orphans = vec![];
+            let mut cursor = cursors.get_cursor(collection.db)?;
+            cursor.iter_between(
+                IdKey::new(i64::MIN).as_bytes(),
+                IdKey::new(i64::MAX).as_bytes(),
+                false,
+                false,
+                true,
+                |_, id_key, _| {
+                    let id_key = IdKey::from_bytes(id_key);
+                    if !linked_ids.contains_key(id_key.get_unsigned_id()) {
+                        orphans.push(id_key.get_id());
+                    }
+                    Ok(true)
+                },
+            )?;
+
+            for id in &orphans {
+                let id_key = IdKey::new(*id);
+                collection.delete_internal(cursors, true, change_set.as_deref_mut(), &id_key)?;
+            }
+            Ok(orphans.len())
+        })
+    }
+
+    pub fn write_batch(&self) -> WriteBatch<'_> {
+        WriteBatch::new(self)
+    }
+
+    // Searches the words of the text in the string properties of the collections.
+    pub fn search(
+        &self,
+        txn: &mut IsarTxn,
+        text: &str,
+        collections: &[&IsarCollection],
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        search::search(txn, self.instance_id, text, collections, options)
+    }
+
+    pub fn write_queue(self: &Arc<Self>, flush_interval: Duration) -> WriteQueue {
+        WriteQueue::new(self, flush_interval)
+    }
+
+    fn new_watcher(
+        &self,
+        start: WatcherModifier,
+        stop: WatcherModifier,
+        pause_state: Arc<PauseState>,
+    ) -> WatchHandle {
         self.watcher_modifier_sender.try_send(start).unwrap();
 
         let sender = self.watcher_modifier_sender.clone();
-        WatchHandle::new(Box::new(move || {
-            let _ = sender.try_send(stop);
-        }))
+        WatchHandle::new(
+            Box::new(move || {
+                let _ = sender.try_send(stop);
+            }),
+            pause_state,
+        )
     }
 
     pub fn watch_collection(
@@ -133,6 +604,7 @@ impl IsarInstance {
         collection: &IsarCollection,
         callback: WatcherCallback,
     ) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap(callback);
         let watcher_id = random();
         let col_id = collection.get_runtime_id();
         self.new_watcher(
@@ -143,6 +615,7 @@ impl IsarInstance {
             Box::new(move |iw| {
                 iw.get_col_watchers(col_id).remove_watcher(watcher_id);
             }),
+            pause_state,
         )
     }
 
@@ -152,6 +625,7 @@ impl IsarInstance {
         oid: i64,
         callback: WatcherCallback,
     ) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap(callback);
         let watcher_id = random();
         let col_id = collection.get_runtime_id();
         self.new_watcher(
@@ -163,6 +637,57 @@ impl IsarInstance {
                 iw.get_col_watchers(col_id)
                     .remove_object_watcher(oid, watcher_id);
             }),
+            pause_state,
+        )
+    }
+
+    pub fn watch_object_with_payload(
+        &self,
+        collection: &IsarCollection,
+        oid: i64,
+        max_payload_size: usize,
+        callback: PayloadWatcherCallback,
+    ) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap_payload(callback);
+        let watcher_id = random();
+        let col_id = collection.get_runtime_id();
+        self.new_watcher(
+            Box::new(move |iw| {
+                iw.get_col_watchers(col_id).add_payload_watcher(
+                    watcher_id,
+                    oid,
+                    max_payload_size,
+                    callback,
+                );
+            }),
+            Box::new(move |iw| {
+                iw.get_col_watchers(col_id)
+                    .remove_payload_watcher(oid, watcher_id);
+            }),
+            pause_state,
+        )
+    }
+
+    pub fn watch_id_range(
+        &self,
+        collection: &IsarCollection,
+        lower: i64,
+        upper: i64,
+        callback: WatcherCallback,
+    ) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap(callback);
+        let watcher_id = random();
+        let col_id = collection.get_runtime_id();
+        self.new_watcher(
+            Box::new(move |iw| {
+                iw.get_col_watchers(col_id)
+                    .add_id_range_watcher(watcher_id, lower, upper, callback);
+            }),
+            Box::new(move |iw| {
+                iw.get_col_watchers(col_id)
+                    .remove_id_range_watcher(watcher_id);
+            }),
+            pause_state,
         )
     }
 
@@ -172,6 +697,7 @@ impl IsarInstance {
         query: Query,
         callback: WatcherCallback,
     ) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap(callback);
         let watcher_id = random();
         let col_id = collection.get_runtime_id();
         self.new_watcher(
@@ -182,9 +708,102 @@ impl IsarInstance {
             Box::new(move |iw| {
                 iw.get_col_watchers(col_id).remove_query_watcher(watcher_id);
             }),
+            pause_state,
         )
     }
 
+    pub fn watch_collection_with_changes(
+        &self,
+        collection: &IsarCollection,
+        callback: ChangesWatcherCallback,
+    ) -> WatchHandle {
+        self.watch_changes(collection, None, callback)
+    }
+
+    pub fn watch_query_with_changes(
+        &self,
+        collection: &IsarCollection,
+        query: Query,
+        callback: ChangesWatcherCallback,
+    ) -> WatchHandle {
+        self.watch_changes(collection, Some(query), callback)
+    }
+
+    fn watch_changes(
+        &self,
+        collection: &IsarCollection,
+        query: Option<Query>,
+        callback: ChangesWatcherCallback,
+    ) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap_changes(callback);
+        let watcher_id = random();
+        let col_id = collection.get_runtime_id();
+        self.new_watcher(
+            Box::new(move |iw| {
+                iw.get_col_watchers(col_id)
+                    .add_changes_watcher(watcher_id, query, callback);
+            }),
+            Box::new(move |iw| {
+                iw.get_col_watchers(col_id)
+                    .remove_changes_watcher(watcher_id);
+            }),
+            pause_state,
+        )
+    }
+
+    // Receives the panic messages of watcher callbacks of this instance.
+    pub fn watch_errors(&self, callback: WatcherErrorCallback) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap_error(callback);
+        let watcher_id = random();
+        self.new_watcher(
+            Box::new(move |iw| {
+                iw.add_error_watcher(watcher_id, callback);
+            }),
+            Box::new(move |iw| {
+                iw.remove_error_watcher(watcher_id);
+            }),
+            pause_state,
+        )
+    }
+
+    pub fn watch_fill_ratio(&self, threshold: f64, callback: WatcherCallback) -> WatchHandle {
+        let (callback, pause_state) = PauseState::wrap(callback);
+        let watcher_id = random();
+        self.new_watcher(
+            Box::new(move |iw| {
+                iw.add_fill_ratio_watcher(watcher_id, threshold, callback);
+            }),
+            Box::new(move |iw| {
+                iw.remove_fill_ratio_watcher(watcher_id);
+            }),
+            pause_state,
+        )
+    }
+
+    // The stats of all collections in a single snapshot.
+    pub fn get_stats(&self) -> Result<InstanceStats> {
+        let mut txn = self.begin_txn(false, true)?;
+        let result = txn.read(self.instance_id, |cursors| {
+            let mut collections = vec![];
+            for collection in &self.collections {
+                let stats = collection.get_stats_internal(cursors)?;
+                collections.push((collection.name.clone(), stats));
+            }
+            Ok(InstanceStats {
+                size: cursors.used_bytes()?,
+                collections,
+                commit_latency: self.commit_latency.snapshot(),
+            })
+        });
+        txn.abort();
+        result
+    }
+
+    pub fn fill_ratio(&self) -> Result<f64> {
+        let txn = self.env.txn(false)?;
+        txn.fill_ratio()
+    }
+
     fn close_internal(self: Arc<Self>, delete_from_disk: bool) -> bool {
         // Check whether all other references are gone
         if Arc::strong_count(&self) == 2 {
@@ -192,13 +811,15 @@ impl IsarInstance {
             // Check again to make sure there are no new references
             if Arc::strong_count(&self) == 2 {
                 lock.remove(self.instance_id);
+                mem::drop(lock);
 
+                let name = self.name.clone();
+                let path = self.dir.join(&self.name);
+                mem::drop(self);
                 if delete_from_disk {
-                    let mut path_buf = PathBuf::from(&self.dir);
-                    path_buf.push(&self.name);
-                    mem::drop(self);
-                    let _ = remove_dir_all(path_buf);
+                    let _ = remove_dir_all(path);
                 }
+                notify_lifecycle(&name, LifecycleEvent::Closed);
                 return true;
             }
         }
@@ -0,0 +1,68 @@
+//! Stable encoding of object ids as they are stored in mdbx.
+//!
+//! Every collection uses its own database so object keys do not contain the collection. Embedders
+//! that need a single key space, for example sync layers, can prefix the object key with the id
+//! of its collection.
+
+use std::convert::TryInto;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Little endian with the sign bit flipped so negative ids sort first as integer keys.
+pub fn oid_to_bytes(oid: i64) -> [u8; 8] {
+    let unsigned = oid as u64;
+    (unsigned ^ 1 << 63).to_le_bytes()
+}
+
+/// Returns `None` if the bytes are not a valid object key.
+pub fn oid_from_bytes(bytes: &[u8]) -> Option<i64> {
+    let bytes: [u8; 8] = bytes.try_into().ok()?;
+    let unsigned = u64::from_le_bytes(bytes);
+    Some((unsigned ^ 1 << 63) as i64)
+}
+
+/// The id of a collection only depends on its name.
+pub fn collection_id(collection_name: &str) -> u64 {
+    xxh3_64(collection_name.as_bytes())
+}
+
+/// The big endian collection id followed by the object key.
+pub fn prefixed_oid_to_bytes(collection_id: u64, oid: i64) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    bytes[..8].copy_from_slice(&collection_id.to_be_bytes());
+    bytes[8..].copy_from_slice(&oid_to_bytes(oid));
+    bytes
+}
+
+/// Returns the collection id and object id of a prefixed key.
+pub fn prefixed_oid_from_bytes(bytes: &[u8]) -> Option<(u64, i64)> {
+    if bytes.len() != 16 {
+        return None;
+    }
+    let collection_id = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+    let oid = oid_from_bytes(&bytes[8..])?;
+    Some((collection_id, oid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oid_bytes() {
+        for oid in &[i64::MIN, -1, 0, 1, i64::MAX] {
+            assert_eq!(oid_from_bytes(&oid_to_bytes(*oid)), Some(*oid));
+        }
+        assert_eq!(oid_to_bytes(i64::MIN), [0; 8]);
+        assert_eq!(oid_from_bytes(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_prefixed_oid_bytes() {
+        let col_id = collection_id("users");
+        let bytes = prefixed_oid_to_bytes(col_id, -5);
+        assert_eq!(prefixed_oid_from_bytes(&bytes), Some((col_id, -5)));
+        assert_eq!(&bytes[8..], &oid_to_bytes(-5));
+        assert_eq!(prefixed_oid_from_bytes(&bytes[1..]), None);
+        assert_ne!(collection_id("users"), collection_id("posts"));
+    }
+}
@@ -3,19 +3,36 @@
 #[cfg(not(target_endian = "little"))]
 compile_error!("Only little endian systems are supported.");
 
+mod aggregate;
+mod backup;
+pub mod change_log;
+mod checksum;
+pub mod collation;
 pub mod collection;
+pub mod config;
 mod cursor;
 pub mod error;
+pub mod histogram;
 pub mod id_key;
+mod import;
 pub mod index;
+#[cfg(feature = "inspector")]
+pub mod inspector;
 pub mod instance;
+pub mod keys;
 mod link;
 mod mdbx;
 pub mod object;
 pub mod query;
+pub mod query_pool;
 pub mod schema;
+pub mod stemmer;
 pub mod txn;
+mod txn_queue;
 pub mod verify;
 pub mod watch;
+pub mod write_batch;
+pub mod write_profile;
+pub mod write_queue;
 
 // todo check missing property in isarobject
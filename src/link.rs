@@ -1,3 +1,4 @@
+use crate::collection::IsarCollection;
 use crate::cursor::IsarCursors;
 use crate::error::IsarError::DbCorrupted;
 use crate::error::{IsarError, Result};
@@ -6,6 +7,7 @@ use crate::mdbx::cursor::Cursor;
 use crate::mdbx::db::Db;
 use crate::mdbx::debug_dump_db;
 use crate::object::isar_object::IsarObject;
+use crate::schema::link_schema::OnDelete;
 use crate::txn::IsarTxn;
 use std::collections::HashSet;
 
@@ -15,6 +17,8 @@ pub(crate) struct IsarLink {
     bl_db: Db,
     source_db: Db,
     target_db: Db,
+    backlink: bool,
+    on_delete: OnDelete,
 }
 
 impl IsarLink {
@@ -24,9 +28,37 @@ impl IsarLink {
             bl_db,
             source_db,
             target_db,
+            backlink: false,
+            on_delete: OnDelete::Unlink,
         }
     }
 
+    pub fn with_on_delete(mut self, on_delete: OnDelete) -> IsarLink {
+        self.on_delete = on_delete;
+        self
+    }
+
+    // The same link viewed from the target collection.
+    pub fn to_backlink(self) -> IsarLink {
+        IsarLink {
+            db: self.bl_db,
+            bl_db: self.db,
+            source_db: self.target_db,
+            target_db: self.source_db,
+            backlink: !self.backlink,
+            on_delete: OnDelete::Unlink,
+        }
+    }
+
+    pub fn is_backlink(&self) -> bool {
+        self.backlink
+    }
+
+    // Backlinks are always unlinked.
+    pub fn on_delete(&self) -> OnDelete {
+        self.on_delete
+    }
+
     pub fn get_target_col_runtime_id(&self) -> u64 {
         self.target_db.runtime_id()
     }
@@ -58,7 +90,84 @@ impl IsarLink {
         let mut target_cursor = cursors.get_cursor(self.target_db)?;
         self.iter_ids(cursors, id_key, |_, link_target_key| {
             if let Some((id, object)) = target_cursor.move_to(link_target_key.as_bytes())? {
-                callback(IdKey::from_bytes(id), IsarObject::from_bytes(object))
+                callback(IdKey::from_bytes(id), cursors.read_object(object)?)
+            } else {
+                Err(IsarError::DbCorrupted {
+                    message: "Target object does not exist".to_string(),
+                })
+            }
+        })
+    }
+
+    pub fn estimate_between(
+        &self,
+        cursors: &IsarCursors,
+        lower_key: &IdKey,
+        upper_key: &IdKey,
+    ) -> Result<usize> {
+        cursors.estimate_between(self.db, lower_key.as_bytes(), upper_key.as_bytes())
+    }
+
+    pub fn iter_ids_between<F>(
+        &self,
+        cursors: &IsarCursors,
+        lower_key: &IdKey,
+        upper_key: &IdKey,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(IdKey, IdKey) -> Result<bool>,
+    {
+        let mut cursor = cursors.get_cursor(self.db)?;
+        cursor.iter_between(
+            lower_key.as_bytes(),
+            upper_key.as_bytes(),
+            true,
+            false,
+            true,
+            |_, source_key, target_key| {
+                callback(IdKey::from_bytes(source_key), IdKey::from_bytes(target_key))
+            },
+        )
+    }
+
+    pub fn iter_between<'txn, 'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        lower_key: &IdKey,
+        upper_key: &IdKey,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        let mut target_cursor = cursors.get_cursor(self.target_db)?;
+        self.iter_ids_between(cursors, lower_key, upper_key, |_, link_target_key| {
+            if let Some((id, object)) = target_cursor.move_to(link_target_key.as_bytes())? {
+                callback(IdKey::from_bytes(id), cursors.read_object(object)?)
+            } else {
+                Err(IsarError::DbCorrupted {
+                    message: "Target object does not exist".to_string(),
+                })
+            }
+        })
+    }
+
+    // Iterates all links in the order of their source ids together with the target objects.
+    pub fn iter_all<'txn, 'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(IdKey, IsarObject<'txn>) -> Result<bool>,
+    {
+        let mut target_cursor = cursors.get_cursor(self.target_db)?;
+        let lower_key = IdKey::new(i64::MIN);
+        let upper_key = IdKey::new(i64::MAX);
+        self.iter_ids_between(cursors, &lower_key, &upper_key, |source_key, target_key| {
+            if let Some((_, object)) = target_cursor.move_to(target_key.as_bytes())? {
+                callback(source_key, cursors.read_object(object)?)
             } else {
                 Err(IsarError::DbCorrupted {
                     message: "Target object does not exist".to_string(),
@@ -67,6 +176,22 @@ impl IsarLink {
         })
     }
 
+    // Iterates every id that has at least one link once.
+    pub fn iter_linked_ids<F>(&self, cursors: &IsarCursors, mut callback: F) -> Result<bool>
+    where
+        F: FnMut(IdKey) -> Result<bool>,
+    {
+        let mut cursor = cursors.get_cursor(self.db)?;
+        cursor.iter_between(
+            IdKey::new(i64::MIN).as_bytes(),
+            IdKey::new(i64::MAX).as_bytes(),
+            true,
+            true,
+            true,
+            |_, id_key, _| callback(IdKey::from_bytes(id_key)),
+        )
+    }
+
     pub fn create(
         &self,
         cursors: &IsarCursors,
@@ -82,10 +207,19 @@ impl IsarLink {
         }
 
         let mut link_cursor = cursors.get_cursor(self.db)?;
+        let exists = link_cursor
+            .move_to_key_val(source_key.as_bytes(), target_key.as_bytes())?
+            .is_some();
+        if exists {
+            return Ok(true);
+        }
         link_cursor.put(source_key.as_bytes(), target_key.as_bytes())?;
 
         let mut backlink_cursor = cursors.get_cursor(self.bl_db)?;
         backlink_cursor.put(target_key.as_bytes(), source_key.as_bytes())?;
+        cursors.record_writes(self.db, 1, 0);
+        cursors.record_writes(self.bl_db, 1, 0);
+        self.update_aggregates(cursors, 1)?;
 
         Ok(true)
     }
@@ -109,6 +243,9 @@ impl IsarLink {
             if backlink_exists {
                 link_cursor.delete_current()?;
                 backlink_cursor.delete_current()?;
+                cursors.record_writes(self.db, 0, 1);
+                cursors.record_writes(self.bl_db, 0, 1);
+                self.update_aggregates(cursors, -1)?;
                 Ok(true)
             } else {
                 Err(DbCorrupted {
@@ -122,6 +259,7 @@ impl IsarLink {
 
     pub fn delete_all_for_object(&self, cursors: &IsarCursors, id_key: &IdKey) -> Result<()> {
         let mut backlink_cursor = cursors.get_cursor(self.bl_db)?;
+        let mut deleted = 0;
         self.iter_ids(cursors, id_key, |cursor, link_target_key| {
             let exists = backlink_cursor
                 .move_to_key_val(link_target_key.as_bytes(), id_key.as_bytes())?
@@ -129,6 +267,9 @@ impl IsarLink {
             if exists {
                 cursor.delete_current()?;
                 backlink_cursor.delete_current()?;
+                cursors.record_writes(self.db, 0, 1);
+                cursors.record_writes(self.bl_db, 0, 1);
+                deleted += 1;
                 Ok(true)
             } else {
                 Err(DbCorrupted {
@@ -136,9 +277,54 @@ impl IsarLink {
                 })
             }
         })?;
+        self.update_aggregates(cursors, -deleted)
+    }
+
+    // Link aggregates belong to the collection of the link, even if the links are changed through
+    // the backlink.
+    fn get_aggregate_owner<'txn>(
+        &self,
+        cursors: &IsarCursors<'txn, '_>,
+    ) -> Option<(&'txn IsarCollection, Db)> {
+        let (col_id, link_db) = if self.backlink {
+            (self.target_db.runtime_id(), self.bl_db)
+        } else {
+            (self.source_db.runtime_id(), self.db)
+        };
+        cursors.get_collection(col_id).map(|col| (col, link_db))
+    }
+
+    fn update_aggregates(&self, cursors: &IsarCursors, change: i64) -> Result<()> {
+        if let Some((col, link_db)) = self.get_aggregate_owner(cursors) {
+            for aggregate in col.get_link_aggregates(link_db) {
+                aggregate.update_link_count(cursors, change)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Resets the link aggregates after the link has been cleared.
+    pub fn clear_aggregates(&self, cursors: &IsarCursors) -> Result<()> {
+        if let Some((col, link_db)) = self.get_aggregate_owner(cursors) {
+            for aggregate in col.get_link_aggregates(link_db) {
+                aggregate.clear(cursors)?;
+            }
+        }
         Ok(())
     }
 
+    // The dbs of both directions of the link.
+    pub fn get_dbs(&self) -> [Db; 2] {
+        [self.db, self.bl_db]
+    }
+
+    // The bytes of both directions of the link.
+    pub fn size(&self, cursors: &IsarCursors) -> Result<u64> {
+        let (_, size) = cursors.stat(self.db)?;
+        let (_, bl_size) = cursors.stat(self.bl_db)?;
+        Ok(size + bl_size)
+    }
+
     pub fn clear(&self, txn: &mut IsarTxn) -> Result<()> {
         txn.clear_db(self.db)?;
         txn.clear_db(self.bl_db)
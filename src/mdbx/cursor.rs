@@ -1,10 +1,11 @@
 use crate::error::Result;
 use crate::mdbx::db::Db;
-use crate::mdbx::txn::Txn;
-use crate::mdbx::{from_mdb_val, mdbx_result, to_mdb_val, ByteKey, KeyVal, EMPTY_KEY, EMPTY_VAL};
+use crate::mdbx::error::{mdbx_result, with_db_context};
+use crate::mdbx::txn::{Txn, WriteCounter};
+use crate::mdbx::{from_mdb_val, to_mdb_val, ByteKey, KeyVal, EMPTY_KEY, EMPTY_VAL};
 use core::ptr;
+use libc::c_int;
 use std::cmp::Ordering;
-use std::marker::PhantomData;
 
 pub struct UnboundCursor {
     cursor: *mut ffi::MDBX_cursor,
@@ -19,12 +20,14 @@ impl UnboundCursor {
 
     pub fn bind<'txn>(self, txn: &'txn Txn, db: Db) -> Result<Cursor<'txn>> {
         unsafe {
-            mdbx_result(ffi::mdbx_cursor_bind(txn.txn, self.cursor, db.dbi))?;
+            mdbx_result(ffi::mdbx_cursor_bind(txn.txn, self.cursor, db.dbi))
+                .map_err(|e| with_db_context(e, db.dbi, None))?;
         }
 
         Ok(Cursor {
             cursor: self,
-            _marker: PhantomData::default(),
+            dbi: db.dbi,
+            write_counter: &txn.write_counter,
         })
     }
 }
@@ -37,7 +40,8 @@ impl Drop for UnboundCursor {
 
 pub struct Cursor<'txn> {
     cursor: UnboundCursor,
-    _marker: PhantomData<&'txn ()>,
+    dbi: ffi::MDBX_dbi,
+    write_counter: &'txn WriteCounter,
 }
 
 impl<'txn> Cursor<'txn> {
@@ -45,14 +49,18 @@ impl<'txn> Cursor<'txn> {
         self.cursor
     }
 
+    fn result(&self, err_code: c_int, key: Option<&[u8]>) -> Result<()> {
+        mdbx_result(err_code).map_err(|e| with_db_context(e, self.dbi, key))
+    }
+
     #[allow(clippy::try_err)]
     fn op_get(
         &mut self,
         op: ffi::MDBX_cursor_op,
-        key: Option<&[u8]>,
+        key_bytes: Option<&[u8]>,
         val: Option<&[u8]>,
     ) -> Result<Option<KeyVal<'txn>>> {
-        let mut key = key.map_or(EMPTY_KEY, |key| unsafe { to_mdb_val(key) });
+        let mut key = key_bytes.map_or(EMPTY_KEY, |key| unsafe { to_mdb_val(key) });
         let mut data = val.map_or(EMPTY_VAL, |val| unsafe { to_mdb_val(val) });
 
         let result = unsafe { ffi::mdbx_cursor_get(self.cursor.cursor, &mut key, &mut data, op) };
@@ -65,7 +73,7 @@ impl<'txn> Cursor<'txn> {
             }
             ffi::MDBX_NOTFOUND | ffi::MDBX_ENODATA => Ok(None),
             e => {
-                mdbx_result(e)?;
+                self.result(e, key_bytes)?;
                 unreachable!();
             }
         }
@@ -95,6 +103,10 @@ impl<'txn> Cursor<'txn> {
         self.op_get(ffi::MDBX_cursor_op::MDBX_PREV_NODUP, None, None)
     }
 
+    pub fn move_to_first(&mut self) -> Result<Option<KeyVal<'txn>>> {
+        self.op_get(ffi::MDBX_cursor_op::MDBX_FIRST, None, None)
+    }
+
     pub fn move_to_next(&mut self) -> Result<Option<KeyVal<'txn>>> {
         self.op_get(ffi::MDBX_cursor_op::MDBX_NEXT, None, None)
     }
@@ -104,18 +116,43 @@ impl<'txn> Cursor<'txn> {
     }
 
     pub fn put(&mut self, key: &[u8], data: &[u8]) -> Result<()> {
+        self.put_with_flags(key, data, 0)
+    }
+
+    /// The key has to be greater than all keys of the database
+    pub fn append(&mut self, key: &[u8], data: &[u8]) -> Result<()> {
+        self.put_with_flags(key, data, ffi::MDBX_APPEND)
+    }
+
+    /// The key has to be greater than or equal to all keys of the database and the data greater
+    /// than all values of an equal key
+    pub fn append_dup(&mut self, key: &[u8], data: &[u8]) -> Result<()> {
+        self.put_with_flags(key, data, ffi::MDBX_APPEND | ffi::MDBX_APPENDDUP)
+    }
+
+    fn put_with_flags(
+        &mut self,
+        key: &[u8],
+        data: &[u8],
+        flags: ffi::MDBX_put_flags_t,
+    ) -> Result<()> {
         unsafe {
-            let key = to_mdb_val(key);
+            let key_val = to_mdb_val(key);
             let mut data = to_mdb_val(data);
             #[allow(clippy::useless_conversion)]
-            mdbx_result(ffi::mdbx_cursor_put(self.cursor.cursor, &key, &mut data, 0))?;
+            let result =
+                ffi::mdbx_cursor_put(self.cursor.cursor, &key_val, &mut data, flags.into());
+            self.result(result, Some(key))?;
         }
+        self.write_counter.record(key.len() + data.len());
         Ok(())
     }
 
     /// Requires the cursor to have a valid position
     pub fn delete_current(&mut self) -> Result<()> {
-        unsafe { mdbx_result(ffi::mdbx_cursor_del(self.cursor.cursor, 0))? };
+        let result = unsafe { ffi::mdbx_cursor_del(self.cursor.cursor, 0) };
+        self.result(result, None)?;
+        self.write_counter.record(0);
 
         Ok(())
     }
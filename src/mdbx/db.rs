@@ -1,8 +1,9 @@
 use crate::error::Result;
-use crate::mdbx::mdbx_result;
+use crate::mdbx::error::{mdbx_result, with_db_context};
+use crate::mdbx::to_mdb_val;
 use crate::mdbx::txn::Txn;
 use std::ffi::CString;
-use std::ptr;
+use std::{mem, ptr};
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Db {
@@ -48,13 +49,53 @@ impl Db {
         Ok(Self { dbi, dup })
     }
 
+    // Estimates the number of entries from the lower key up to, but excluding, the upper key.
+    // The entries of a single key are counted exactly if both keys are equal. The result is
+    // negative if the upper key is smaller than the lower key.
+    pub fn estimate_range(&self, txn: &Txn, lower_key: &[u8], upper_key: &[u8]) -> Result<isize> {
+        let mut items: ffi::ptrdiff_t = 0;
+        unsafe {
+            let mut lower = to_mdb_val(lower_key);
+            let mut upper = to_mdb_val(upper_key);
+            mdbx_result(ffi::mdbx_estimate_range(
+                txn.txn,
+                self.dbi,
+                &mut lower,
+                ptr::null_mut(),
+                &mut upper,
+                ptr::null_mut(),
+                &mut items,
+            ))
+            .map_err(|e| with_db_context(e, self.dbi, Some(lower_key)))?;
+        }
+        Ok(items as isize)
+    }
+
+    // Returns the number of entries and the bytes of all pages used by the db.
+    pub fn stat(&self, txn: &Txn) -> Result<(u64, u64)> {
+        let mut stat: ffi::MDBX_stat = unsafe { mem::zeroed() };
+        unsafe {
+            mdbx_result(ffi::mdbx_dbi_stat(
+                txn.txn,
+                self.dbi,
+                &mut stat,
+                mem::size_of::<ffi::MDBX_stat>(),
+            ))
+            .map_err(|e| with_db_context(e, self.dbi, None))?
+        };
+        let pages = stat.ms_branch_pages + stat.ms_leaf_pages + stat.ms_overflow_pages;
+        Ok((stat.ms_entries, pages * stat.ms_psize as u64))
+    }
+
     pub fn clear(&self, txn: &Txn) -> Result<()> {
-        unsafe { mdbx_result(ffi::mdbx_drop(txn.txn, self.dbi, false)) }?;
+        unsafe { mdbx_result(ffi::mdbx_drop(txn.txn, self.dbi, false)) }
+            .map_err(|e| with_db_context(e, self.dbi, None))?;
         Ok(())
     }
 
     pub fn drop(self, txn: &Txn) -> Result<()> {
-        unsafe { mdbx_result(ffi::mdbx_drop(txn.txn, self.dbi, true)) }?;
+        unsafe { mdbx_result(ffi::mdbx_drop(txn.txn, self.dbi, true)) }
+            .map_err(|e| with_db_context(e, self.dbi, None))?;
         Ok(())
     }
 }
@@ -1,8 +1,13 @@
+use crate::config::IsarConfig;
 use crate::error::{IsarError, Result};
-use crate::mdbx::mdbx_result;
+use crate::mdbx::error::mdbx_result;
 use crate::mdbx::txn::Txn;
 use core::ptr;
+use libc::c_int;
+#[cfg(not(windows))]
 use std::ffi::CString;
+use std::path::Path;
+use std::thread;
 
 pub struct Env {
     env: *mut ffi::MDBX_env,
@@ -13,9 +18,11 @@ unsafe impl Send for Env {}
 
 const MB: isize = 1 << 20;
 
+pub(crate) const DATA_FILE_NAME: &str = "mdbx.dat";
+
 impl Env {
-    pub fn create(path: &str, max_dbs: u64, relaxed_durability: bool) -> Result<Env> {
-        let path = CString::new(path.as_bytes()).unwrap();
+    pub fn create(path: &Path, max_dbs: u64, config: &IsarConfig) -> Result<Env> {
+        let path = encode_path(path)?;
         let mut env: *mut ffi::MDBX_env = ptr::null_mut();
         unsafe {
             mdbx_result(ffi::mdbx_env_create(&mut env))?;
@@ -24,26 +31,50 @@ impl Env {
                 ffi::MDBX_option_t::MDBX_opt_max_db,
                 max_dbs,
             ))?;
+            if let Some(max_readers) = config.max_readers {
+                mdbx_result(ffi::mdbx_env_set_option(
+                    env,
+                    ffi::MDBX_option_t::MDBX_opt_max_readers,
+                    max_readers as u64,
+                ))?;
+            }
 
-            let mut flags =
-                ffi::MDBX_NOTLS | ffi::MDBX_EXCLUSIVE | ffi::MDBX_NOMEMINIT | ffi::MDBX_COALESCE;
-            if relaxed_durability {
+            let mut flags = ffi::MDBX_NOTLS | ffi::MDBX_COALESCE;
+            if config.exclusive {
+                flags |= ffi::MDBX_EXCLUSIVE;
+            }
+            if config.relaxed_durability {
                 flags |= ffi::MDBX_NOMETASYNC;
             }
+            if config.no_readahead {
+                flags |= ffi::MDBX_NORDAHEAD;
+            }
+            if config.write_map {
+                flags |= ffi::MDBX_WRITEMAP;
+            }
+            if config.no_meminit {
+                flags |= ffi::MDBX_NOMEMINIT;
+            }
 
+            let initial_size = config.initial_size.map_or(0, |s| s as isize);
+            let page_size = config.page_size.map_or(-1, |s| s as isize);
             let mut err_code = 0;
             for i in 1..10 {
+                let growth_step = config.growth_step.map_or((5 * MB) / i, |s| s as isize);
+                let shrink_threshold = config
+                    .shrink_threshold
+                    .map_or((20 * MB) / i, |s| s as isize);
                 mdbx_result(ffi::mdbx_env_set_geometry(
                     env,
                     MB,
-                    0,
+                    initial_size,
                     (2000 * MB) / i,
-                    (5 * MB) / i,
-                    (20 * MB) / i,
-                    -1,
+                    growth_step,
+                    shrink_threshold,
+                    page_size,
                 ))?;
 
-                err_code = ffi::mdbx_env_open(env, path.as_ptr(), flags, 0o600);
+                err_code = env_open(env, &path, flags);
                 if err_code == ffi::MDBX_SUCCESS {
                     break;
                 }
@@ -60,6 +91,34 @@ impl Env {
         }
     }
 
+    // Opens an environment for temporary data that is never synced. mdbx writes the dirty pages
+    // of a write txn to the file once there are more than max_dirty_pages.
+    pub fn create_temp(path: &Path, page_size: usize, max_dirty_pages: usize) -> Result<Env> {
+        let path = encode_path(path)?;
+        let mut env: *mut ffi::MDBX_env = ptr::null_mut();
+        unsafe {
+            mdbx_result(ffi::mdbx_env_create(&mut env))?;
+            let env = Env { env };
+            mdbx_result(ffi::mdbx_env_set_option(
+                env.env,
+                ffi::MDBX_option_t::MDBX_opt_txn_dp_limit,
+                max_dirty_pages as u64,
+            ))?;
+            mdbx_result(ffi::mdbx_env_set_geometry(
+                env.env,
+                -1,
+                -1,
+                1000 * MB,
+                -1,
+                -1,
+                page_size as isize,
+            ))?;
+            let flags = ffi::MDBX_NOTLS | ffi::MDBX_EXCLUSIVE | ffi::MDBX_UTTERLY_NOSYNC;
+            mdbx_result(env_open(env.env, &path, flags))?;
+            Ok(env)
+        }
+    }
+
     pub fn txn(&self, write: bool) -> Result<Txn> {
         let flags = if write { 0 } else { ffi::MDBX_RDONLY };
         let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
@@ -74,6 +133,130 @@ impl Env {
         }
         Ok(Txn::new(txn))
     }
+
+    // Child txns can be committed into or aborted without affecting their parent. They are not
+    // supported with MDBX_WRITEMAP.
+    pub fn nested_txn<'env>(&'env self, parent: &Txn<'env>) -> Result<Txn<'env>> {
+        let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+        unsafe {
+            mdbx_result(ffi::mdbx_txn_begin_ex(
+                self.env,
+                parent.txn,
+                0,
+                &mut txn,
+                ptr::null_mut(),
+            ))?;
+        }
+        Ok(Txn::new(txn))
+    }
+
+    pub fn copy_to_file(&self, path: &Path) -> Result<()> {
+        let path = encode_path(path)?;
+        unsafe { mdbx_result(env_copy(self.env, &path, ffi::MDBX_CP_COMPACT)) }
+    }
+
+    // mdbx does not allow a thread to begin a read txn while it owns the write txn. Thanks to
+    // MDBX_NOTLS the read txn can be started on a helper thread and used from any thread.
+    pub fn detached_read_txn(&self) -> Result<Txn> {
+        let env = self.env as usize;
+        let (result, txn) = thread::spawn(move || {
+            let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+            let result = unsafe {
+                ffi::mdbx_txn_begin_ex(
+                    env as *mut ffi::MDBX_env,
+                    ptr::null_mut(),
+                    ffi::MDBX_RDONLY,
+                    &mut txn,
+                    ptr::null_mut(),
+                )
+            };
+            (result, txn as usize)
+        })
+        .join()
+        .unwrap();
+        mdbx_result(result)?;
+        Ok(Txn::new(txn as *mut ffi::MDBX_txn))
+    }
+}
+
+// Windows paths are passed to mdbx as UTF-16 so they do not have to be valid in the ANSI code
+// page.
+#[cfg(windows)]
+type EncodedPath = Vec<u16>;
+
+#[cfg(not(windows))]
+type EncodedPath = CString;
+
+#[cfg(unix)]
+fn encode_path(path: &Path) -> Result<EncodedPath> {
+    use std::os::unix::ffi::OsStrExt;
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| IsarError::PathError {})
+}
+
+#[cfg(windows)]
+fn encode_path(path: &Path) -> Result<EncodedPath> {
+    use std::os::windows::ffi::OsStrExt;
+    let path: Vec<u16> = path.as_os_str().encode_wide().collect();
+    let unc_prefix: Vec<u16> = r"\\?\UNC\".encode_utf16().collect();
+    let verbatim_prefix: Vec<u16> = r"\\?\".encode_utf16().collect();
+    // mdbx does not understand verbatim paths like the ones returned by canonicalize()
+    let mut path: Vec<u16> = if let Some(unc_path) = path.strip_prefix(unc_prefix.as_slice()) {
+        r"\\"
+            .encode_utf16()
+            .chain(unc_path.iter().copied())
+            .collect()
+    } else {
+        path.strip_prefix(verbatim_prefix.as_slice())
+            .unwrap_or(&path)
+            .to_vec()
+    };
+    if path.contains(&0) {
+        return Err(IsarError::PathError {});
+    }
+    path.push(0);
+    Ok(path)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn encode_path(path: &Path) -> Result<EncodedPath> {
+    let path = path.to_str().ok_or(IsarError::PathError {})?;
+    CString::new(path).map_err(|_| IsarError::PathError {})
+}
+
+#[cfg(windows)]
+unsafe fn env_open(
+    env: *mut ffi::MDBX_env,
+    path: &EncodedPath,
+    flags: ffi::MDBX_env_flags_t,
+) -> c_int {
+    ffi::mdbx_env_openW(env, path.as_ptr(), flags, 0o600)
+}
+
+#[cfg(not(windows))]
+unsafe fn env_open(
+    env: *mut ffi::MDBX_env,
+    path: &EncodedPath,
+    flags: ffi::MDBX_env_flags_t,
+) -> c_int {
+    ffi::mdbx_env_open(env, path.as_ptr(), flags, 0o600)
+}
+
+#[cfg(windows)]
+unsafe fn env_copy(
+    env: *mut ffi::MDBX_env,
+    path: &EncodedPath,
+    flags: ffi::MDBX_copy_flags_t,
+) -> c_int {
+    ffi::mdbx_env_copyW(env, path.as_ptr(), flags)
+}
+
+#[cfg(not(windows))]
+unsafe fn env_copy(
+    env: *mut ffi::MDBX_env,
+    path: &EncodedPath,
+    flags: ffi::MDBX_copy_flags_t,
+) -> c_int {
+    ffi::mdbx_env_copy(env, path.as_ptr(), flags)
 }
 
 impl Drop for Env {
@@ -97,10 +280,76 @@ pub mod tests {
         get_env();
     }
 
+    #[test]
+    fn test_create_with_geometry() {
+        let mut dir = std::env::temp_dir();
+        let r: u64 = rand::random();
+        dir.push(&r.to_string());
+        let config = IsarConfig {
+            page_size: Some(8192),
+            initial_size: Some(2 * MB as usize),
+            growth_step: Some(MB as usize),
+            shrink_threshold: Some(4 * MB as usize),
+            ..Default::default()
+        };
+        let env = Env::create(&dir, 50, &config).unwrap();
+        let txn = env.txn(true).unwrap();
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_create_with_flags() {
+        let mut dir = std::env::temp_dir();
+        let r: u64 = rand::random();
+        dir.push(&r.to_string());
+        let config = IsarConfig {
+            no_readahead: true,
+            write_map: true,
+            no_meminit: false,
+            ..Default::default()
+        };
+        let env = Env::create(&dir, 50, &config).unwrap();
+        let txn = env.txn(true).unwrap();
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_create_not_exclusive() {
+        let mut dir = std::env::temp_dir();
+        let r: u64 = rand::random();
+        dir.push(&r.to_string());
+        let config = IsarConfig {
+            exclusive: false,
+            max_readers: Some(4),
+            ..Default::default()
+        };
+        let env = Env::create(&dir, 50, &config).unwrap();
+        let txn = env.txn(true).unwrap();
+        txn.commit().unwrap();
+        let txn = env.txn(false).unwrap();
+        txn.abort();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut dir = std::env::temp_dir();
+        let r: u64 = rand::random();
+        dir.push(OsStr::from_bytes(&[b'i', 0xff, b's', 0xfe]));
+        dir.push(&r.to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        let env = Env::create(&dir, 50, &IsarConfig::default()).unwrap();
+        let txn = env.txn(true).unwrap();
+        txn.commit().unwrap();
+    }
+
     pub fn get_env() -> Env {
         let mut dir = std::env::temp_dir();
         let r: u64 = rand::random();
         dir.push(&r.to_string());
-        Env::create(dir.to_str().unwrap(), 50, false).unwrap()
+        Env::create(&dir, 50, &IsarConfig::default()).unwrap()
     }
 }
@@ -0,0 +1,157 @@
+use crate::error::{IsarError, Result};
+use libc::c_int;
+use std::ffi::CStr;
+
+// Longer keys are shortened in error messages.
+const MAX_CONTEXT_KEY_LEN: usize = 32;
+
+#[inline]
+pub fn mdbx_result(err_code: c_int) -> Result<()> {
+    match err_code {
+        ffi::MDBX_SUCCESS | ffi::MDBX_RESULT_TRUE => Ok(()),
+        other => Err(mdbx_error(other)),
+    }
+}
+
+// Errors the user can react to get their own variant. All other errors keep their code and get
+// a hint how to resolve them.
+pub(crate) fn mdbx_error(err_code: c_int) -> IsarError {
+    match err_code {
+        ffi::MDBX_MAP_FULL => IsarError::DbFull {},
+        ffi::MDBX_MAP_RESIZED => IsarError::MapResized {},
+        ffi::MDBX_BUSY => IsarError::DbBusy {},
+        ffi::MDBX_READERS_FULL => IsarError::ReadersFull {},
+        ffi::MDBX_TXN_FULL | ffi::MDBX_CURSOR_FULL => IsarError::TxnFull {},
+        ffi::MDBX_VERSION_MISMATCH => IsarError::VersionError {},
+        ffi::MDBX_ENOFILE => IsarError::PathError {},
+        ffi::MDBX_BAD_VALSIZE | ffi::MDBX_TOO_LARGE => IsarError::IllegalArg {
+            message: "The key or value is too large".to_string(),
+        },
+        ffi::MDBX_CORRUPTED
+        | ffi::MDBX_PAGE_NOTFOUND
+        | ffi::MDBX_INVALID
+        | ffi::MDBX_PANIC
+        | ffi::MDBX_WANNA_RECOVERY
+        | ffi::MDBX_EBADSIGN => IsarError::DbCorrupted {
+            message: format!(
+                "{}. Restore a backup or delete the instance.",
+                error_message(err_code)
+            ),
+        },
+        other => {
+            let message = error_message(other);
+            IsarError::MdbxError {
+                code: other,
+                message: match hint(other) {
+                    Some(hint) => format!("{}. {}", message, hint),
+                    None => message,
+                },
+            }
+        }
+    }
+}
+
+fn hint(err_code: c_int) -> Option<&'static str> {
+    let hint = match err_code {
+        ffi::MDBX_BAD_RSLOT | ffi::MDBX_THREAD_MISMATCH | ffi::MDBX_TXN_OVERLAPPING => {
+            "Transactions have to be used on the thread that started them."
+        }
+        ffi::MDBX_BAD_TXN => "The transaction failed before. Abort it and start a new one.",
+        ffi::MDBX_DBS_FULL => "The schema has too many collections and indexes.",
+        ffi::MDBX_INCOMPATIBLE | ffi::MDBX_BAD_DBI => {
+            "The database was changed by another instance. Close and reopen the instance."
+        }
+        ffi::MDBX_EPERM | libc::EACCES | libc::EROFS => "Make sure the directory is writable.",
+        libc::ENOMEM => "Use a smaller initial size or close other instances.",
+        libc::EIO | libc::ENOSPC => "Make sure the device has enough free space.",
+        _ => return None,
+    };
+    Some(hint)
+}
+
+fn error_message(err_code: c_int) -> String {
+    unsafe {
+        let err_raw = ffi::mdbx_strerror(err_code);
+        CStr::from_ptr(err_raw).to_string_lossy().to_string()
+    }
+}
+
+// Adds the db and key of a failed operation to errors that are not already specific to it.
+pub(crate) fn with_db_context(
+    error: IsarError,
+    dbi: ffi::MDBX_dbi,
+    key: Option<&[u8]>,
+) -> IsarError {
+    let mut context = format!("(db {}", dbi);
+    if let Some(key) = key {
+        context.push_str(", key ");
+        for byte in key.iter().take(MAX_CONTEXT_KEY_LEN) {
+            context.push_str(&format!("{:02x}", byte));
+        }
+        if key.len() > MAX_CONTEXT_KEY_LEN {
+            context.push_str("..");
+        }
+    }
+    context.push(')');
+    match error {
+        IsarError::MdbxError { code, message } => IsarError::MdbxError {
+            code,
+            message: format!("{} {}", message, context),
+        },
+        IsarError::DbCorrupted { message } => IsarError::DbCorrupted {
+            message: format!("{} {}", message, context),
+        },
+        IsarError::IllegalArg { message } => IsarError::IllegalArg {
+            message: format!("{} {}", message, context),
+        },
+        other => other,
+    }
+}
+
+// Errors caused by concurrent access or a pending resize that may succeed when retried.
+pub(crate) fn is_transient_error(err_code: c_int) -> bool {
+    matches!(
+        err_code,
+        ffi::MDBX_BUSY | ffi::MDBX_MAP_RESIZED | ffi::MDBX_READERS_FULL | ffi::MDBX_BAD_RSLOT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mdbx_error() {
+        assert_eq!(mdbx_result(ffi::MDBX_SUCCESS), Ok(()));
+        assert_eq!(mdbx_error(ffi::MDBX_MAP_RESIZED), IsarError::MapResized {});
+        assert!(mdbx_error(ffi::MDBX_BUSY).is_retryable());
+        assert!(matches!(
+            mdbx_error(ffi::MDBX_CORRUPTED),
+            IsarError::DbCorrupted { .. }
+        ));
+
+        let error = with_db_context(mdbx_error(ffi::MDBX_BAD_TXN), 3, Some(&[1, 255]));
+        if let IsarError::MdbxError { code, message } = &error {
+            assert_eq!(*code, ffi::MDBX_BAD_TXN);
+            assert!(message.contains("Abort it and start a new one."));
+            assert!(message.ends_with(" (db 3, key 01ff)"));
+        } else {
+            panic!("unexpected error {:?}", error);
+        }
+        assert!(!error.is_retryable());
+
+        let long_key = [0; 40];
+        let error = with_db_context(mdbx_error(ffi::MDBX_BAD_VALSIZE), 1, Some(&long_key));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "IllegalArg: The key or value is too large (db 1, key {}..).",
+                "00".repeat(32)
+            )
+        );
+        assert_eq!(
+            with_db_context(IsarError::DbFull {}, 1, None),
+            IsarError::DbFull {}
+        );
+    }
+}
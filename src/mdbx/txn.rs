@@ -1,10 +1,43 @@
 use crate::error::Result;
-use crate::mdbx::mdbx_result;
+use crate::mdbx::error::mdbx_result;
 use core::ptr;
+use std::cell::Cell;
 use std::marker::PhantomData;
+use std::mem;
+use std::time::Instant;
+
+// Counts the writes performed by the cursors of a txn.
+#[derive(Default)]
+pub(crate) struct WriteCounter {
+    operations: Cell<u64>,
+    bytes: Cell<u64>,
+}
+
+impl WriteCounter {
+    pub fn record(&self, bytes: usize) {
+        self.operations.set(self.operations.get() + 1);
+        self.bytes.set(self.bytes.get() + bytes as u64);
+    }
+
+    pub fn get_operations(&self) -> u64 {
+        self.operations.get()
+    }
+
+    pub fn get_bytes(&self) -> u64 {
+        self.bytes.get()
+    }
+
+    pub fn add(&self, other: &WriteCounter) {
+        self.operations
+            .set(self.operations.get() + other.get_operations());
+        self.bytes.set(self.bytes.get() + other.get_bytes());
+    }
+}
 
 pub struct Txn<'env> {
     pub(crate) txn: *mut ffi::MDBX_txn,
+    pub(crate) write_counter: WriteCounter,
+    started: Instant,
     _marker: PhantomData<&'env ()>,
 }
 
@@ -12,10 +45,16 @@ impl<'env> Txn<'env> {
     pub(crate) fn new(txn: *mut ffi::MDBX_txn) -> Self {
         Txn {
             txn,
+            write_counter: WriteCounter::default(),
+            started: Instant::now(),
             _marker: PhantomData::default(),
         }
     }
 
+    pub fn get_started(&self) -> Instant {
+        self.started
+    }
+
     pub fn commit(mut self) -> Result<()> {
         let result = unsafe { mdbx_result(ffi::mdbx_txn_commit_ex(self.txn, ptr::null_mut())) };
         self.txn = ptr::null_mut();
@@ -24,6 +63,44 @@ impl<'env> Txn<'env> {
     }
 
     pub fn abort(self) {}
+
+    // Moves a read txn to the latest snapshot.
+    pub fn renew(&mut self) -> Result<()> {
+        unsafe { mdbx_result(ffi::mdbx_txn_renew(self.txn))? };
+        self.started = Instant::now();
+        Ok(())
+    }
+
+    pub fn dirty_bytes(&self) -> Result<u64> {
+        let mut info: ffi::MDBX_txn_info = unsafe { mem::zeroed() };
+        unsafe { mdbx_result(ffi::mdbx_txn_info(self.txn, &mut info, false))? };
+        Ok(info.txn_space_dirty)
+    }
+
+    pub fn page_size(&self) -> Result<u64> {
+        let mut stat: ffi::MDBX_stat = unsafe { mem::zeroed() };
+        unsafe {
+            mdbx_result(ffi::mdbx_env_stat_ex(
+                ptr::null(),
+                self.txn,
+                &mut stat,
+                mem::size_of::<ffi::MDBX_stat>(),
+            ))?
+        };
+        Ok(stat.ms_psize as u64)
+    }
+
+    pub fn used_bytes(&self) -> Result<u64> {
+        let mut info: ffi::MDBX_txn_info = unsafe { mem::zeroed() };
+        unsafe { mdbx_result(ffi::mdbx_txn_info(self.txn, &mut info, false))? };
+        Ok(info.txn_space_used)
+    }
+
+    pub fn fill_ratio(&self) -> Result<f64> {
+        let mut info: ffi::MDBX_txn_info = unsafe { mem::zeroed() };
+        unsafe { mdbx_result(ffi::mdbx_txn_info(self.txn, &mut info, false))? };
+        Ok(info.txn_space_used as f64 / info.txn_space_limit_hard as f64)
+    }
 }
 
 impl<'a> Drop for Txn<'a> {
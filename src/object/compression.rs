@@ -0,0 +1,158 @@
+use crate::error::{IsarError, Result};
+use crate::object::isar_object::{IsarObject, Property};
+use byteorder::{ByteOrder, LittleEndian};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+// Set in the static size of objects that contain compressed values.
+const COMPRESSED_FLAG: u16 = 0x8000;
+
+// Smaller values are not worth the compression overhead.
+const MIN_COMPRESSED_SIZE: usize = 64;
+
+// Original offset and length and the compressed length of a value.
+const TABLE_ENTRY_SIZE: usize = 12;
+
+pub(crate) fn is_compressed(bytes: &[u8]) -> bool {
+    LittleEndian::read_u16(bytes) & COMPRESSED_FLAG != 0
+}
+
+// Replaces the values of the properties with their deflated bytes. The static section is kept
+// and a table of the compressed values is appended so the original object can be restored
+// exactly. Returns None if no value got smaller.
+pub(crate) fn compress(object: IsarObject, properties: &[Property]) -> Option<Vec<u8>> {
+    let bytes = object.as_bytes();
+    let static_size = LittleEndian::read_u16(bytes);
+    if static_size & COMPRESSED_FLAG != 0 {
+        return None;
+    }
+
+    let mut values = vec![];
+    for property in properties {
+        if !object.contains_property(*property) {
+            continue;
+        }
+        let offset = LittleEndian::read_u32(&bytes[property.offset..]) as usize;
+        let length = LittleEndian::read_u32(&bytes[property.offset + 4..]) as usize;
+        if offset != 0 && length >= MIN_COMPRESSED_SIZE {
+            values.push((offset, length));
+        }
+    }
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+
+    let mut compressed = bytes[..static_size as usize].to_vec();
+    LittleEndian::write_u16(&mut compressed, static_size | COMPRESSED_FLAG);
+    let mut table = vec![];
+    let mut position = static_size as usize;
+    for (offset, length) in values {
+        if offset < position || offset + length > bytes.len() {
+            continue;
+        }
+        let value = &bytes[offset..offset + length];
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        let deflated = encoder
+            .write_all(value)
+            .and_then(|_| encoder.finish())
+            .ok()?;
+        if deflated.len() + TABLE_ENTRY_SIZE >= length {
+            continue;
+        }
+        compressed.extend_from_slice(&bytes[position..offset]);
+        compressed.extend_from_slice(&deflated);
+        table.push((offset as u32, length as u32, deflated.len() as u32));
+        position = offset + length;
+    }
+    if table.is_empty() {
+        return None;
+    }
+    compressed.extend_from_slice(&bytes[position..]);
+    for (offset, length, deflated_length) in &table {
+        compressed.extend_from_slice(&offset.to_le_bytes());
+        compressed.extend_from_slice(&length.to_le_bytes());
+        compressed.extend_from_slice(&deflated_length.to_le_bytes());
+    }
+    compressed.extend_from_slice(&(table.len() as u32).to_le_bytes());
+    Some(compressed)
+}
+
+// Restores the object that was passed to compress().
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Vec<u8>> {
+    let static_size = (LittleEndian::read_u16(bytes) & !COMPRESSED_FLAG) as usize;
+    let table_end = bytes.len().checked_sub(4).ok_or_else(corrupted)?;
+    let count = LittleEndian::read_u32(&bytes[table_end..]) as usize;
+    let table_start = count
+        .checked_mul(TABLE_ENTRY_SIZE)
+        .and_then(|size| table_end.checked_sub(size))
+        .filter(|start| *start >= static_size)
+        .ok_or_else(corrupted)?;
+
+    let mut object = bytes[..static_size].to_vec();
+    LittleEndian::write_u16(&mut object, static_size as u16);
+    let mut position = static_size;
+    for entry in bytes[table_start..table_end].chunks(TABLE_ENTRY_SIZE) {
+        let offset = LittleEndian::read_u32(entry) as usize;
+        let length = LittleEndian::read_u32(&entry[4..]) as usize;
+        let deflated_length = LittleEndian::read_u32(&entry[8..]) as usize;
+        let uncompressed = offset.checked_sub(object.len()).ok_or_else(corrupted)?;
+        let deflated_start = position + uncompressed;
+        let deflated_end = deflated_start + deflated_length;
+        if deflated_end > table_start {
+            return Err(corrupted());
+        }
+        object.extend_from_slice(&bytes[position..deflated_start]);
+        DeflateDecoder::new(&bytes[deflated_start..deflated_end])
+            .read_to_end(&mut object)
+            .map_err(|_| corrupted())?;
+        if object.len() != offset + length {
+            return Err(corrupted());
+        }
+        position = deflated_end;
+    }
+    object.extend_from_slice(&bytes[position..table_start]);
+    Ok(object)
+}
+
+fn corrupted() -> IsarError {
+    IsarError::DbCorrupted {
+        message: "Compressed object is invalid.".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::data_type::DataType;
+    use crate::object::object_builder::ObjectBuilder;
+
+    #[test]
+    fn test_compress() {
+        let properties = vec![
+            Property::new(DataType::String, 2),
+            Property::new(DataType::Int, 10),
+            Property::new(DataType::String, 14),
+            Property::new(DataType::ByteList, 22),
+        ];
+        let long = "markdown ".repeat(100);
+        let mut builder = ObjectBuilder::new(&properties, None);
+        builder.write_string(Some(&long));
+        builder.write_int(42);
+        builder.write_string(Some("short"));
+        builder.write_byte_list(Some(&[7; 200]));
+        let object = builder.finish();
+
+        let compressed = [properties[0], properties[2], properties[3]];
+        let bytes = compress(object, &compressed).unwrap();
+        assert!(is_compressed(&bytes));
+        assert!(!is_compressed(object.as_bytes()));
+        assert!(bytes.len() < object.as_bytes().len() / 2);
+        assert_eq!(decompress(&bytes).unwrap(), object.as_bytes());
+
+        assert_eq!(compress(object, &[properties[2]]), None);
+        assert!(decompress(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
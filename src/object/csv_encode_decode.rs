@@ -0,0 +1,245 @@
+use crate::error::{IsarError, Result};
+use crate::object::data_type::DataType;
+use crate::object::isar_object::IsarObject;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::object::object_builder::ObjectBuilder;
+use crate::object::object_info::ObjectInfo;
+use csv::{Position, ReaderBuilder, StringRecord, Writer, WriterBuilder};
+use serde_json::{Map, Value};
+use std::io::{Read, Write};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    // Whether the first row contains the property names. Without it the columns are the id (if
+    // an id name is provided) followed by all properties in the order of the schema.
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            header: true,
+        }
+    }
+}
+
+// Null values are written as \N so they can be told apart from empty strings. Strings that start
+// with a backslash are escaped with another backslash.
+const NULL_FIELD: &str = "\\N";
+
+#[derive(Copy, Clone)]
+enum Column {
+    Id,
+    Property(usize),
+}
+
+// Maps the columns of a CSV file to the properties of a collection. Empty fields are empty strings
+// for string properties and null otherwise. Lists, embedded objects and geo points are stored as
+// JSON.
+pub(crate) struct CsvDecoder<R: Read> {
+    reader: csv::Reader<R>,
+    columns: Vec<Column>,
+    record: StringRecord,
+}
+
+impl<R: Read> CsvDecoder<R> {
+    pub fn new(
+        object_info: &ObjectInfo,
+        id_name: Option<&str>,
+        csv: R,
+        options: &CsvOptions,
+    ) -> Result<Self> {
+        let mut reader = ReaderBuilder::new()
+            .delimiter(options.delimiter)
+            .has_headers(options.header)
+            .from_reader(csv);
+        let columns = if options.header {
+            let header = reader.headers().map_err(csv_error)?;
+            let mut columns = vec![];
+            for name in header {
+                let column = if Some(name) == id_name {
+                    Column::Id
+                } else if let Some(index) =
+                    object_info.properties.iter().position(|(n, _)| n == name)
+                {
+                    Column::Property(index)
+                } else {
+                    return Err(IsarError::CsvError {
+                        message: format!("Unknown column \"{}\".", name),
+                    });
+                };
+                columns.push(column);
+            }
+            columns
+        } else {
+            let properties = (0..object_info.properties.len()).map(Column::Property);
+            if id_name.is_some() {
+                Some(Column::Id).into_iter().chain(properties).collect()
+            } else {
+                properties.collect()
+            }
+        };
+        Ok(CsvDecoder {
+            reader,
+            columns,
+            record: StringRecord::new(),
+        })
+    }
+
+    // Reads the next row. Returns None once all rows have been read.
+    pub fn decode_next<'a>(
+        &mut self,
+        object_info: &'a ObjectInfo,
+        buffer: Option<Vec<u8>>,
+    ) -> Result<Option<(Option<i64>, ObjectBuilder<'a>)>> {
+        if !self
+            .reader
+            .read_record(&mut self.record)
+            .map_err(csv_error)?
+        {
+            return Ok(None);
+        }
+        let line = self.record.position().map_or(0, Position::line);
+        let invalid = || IsarError::CsvError {
+            message: format!("Invalid value in line {}.", line),
+        };
+
+        if self.record.len() != self.columns.len() {
+            return Err(invalid());
+        }
+
+        let mut id = None;
+        let mut map = Map::new();
+        for (column, field) in self.columns.iter().zip(self.record.iter()) {
+            match column {
+                Column::Id if !field.is_empty() => {
+                    id = Some(field.parse().map_err(|_| invalid())?);
+                }
+                Column::Id => {}
+                Column::Property(index) => {
+                    let (name, property) = &object_info.properties[*index];
+                    let value = field_to_value(field, property.data_type).ok_or_else(invalid)?;
+                    map.insert(name.clone(), value);
+                }
+            }
+        }
+        let ob = JsonEncodeDecode::decode(object_info, &Value::Object(map), buffer)
+            .map_err(|_| invalid())?;
+        Ok(Some((id, ob)))
+    }
+}
+
+fn field_to_value(field: &str, data_type: DataType) -> Option<Value> {
+    if field == NULL_FIELD {
+        return Some(Value::Null);
+    } else if data_type == DataType::String {
+        let value = match field.strip_prefix('\\') {
+            Some(unescaped) if unescaped.starts_with('\\') => unescaped,
+            _ => field,
+        };
+        return Some(Value::from(value));
+    } else if field.is_empty() {
+        return Some(Value::Null);
+    }
+    let value = match data_type {
+        DataType::Byte => match field {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::from(field.parse::<u8>().ok()?),
+        },
+        DataType::Int | DataType::Long => Value::from(field.parse::<i64>().ok()?),
+        DataType::Float | DataType::Double => Value::from(field.parse::<f64>().ok()?),
+        DataType::DateTime => match field.parse::<i64>() {
+            Ok(micros) => Value::from(micros),
+            Err(_) => Value::from(field),
+        },
+        _ => serde_json::from_str(field).ok()?,
+    };
+    Some(value)
+}
+
+pub(crate) struct CsvEncoder<W: Write> {
+    writer: Writer<W>,
+    primitive_null: bool,
+    byte_as_bool: bool,
+    record: Vec<String>,
+}
+
+impl<W: Write> CsvEncoder<W> {
+    pub fn new(
+        object_info: &ObjectInfo,
+        id_name: Option<&str>,
+        csv: W,
+        options: &CsvOptions,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Result<Self> {
+        let mut writer = WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .from_writer(csv);
+        if options.header {
+            let properties = object_info.properties.iter().map(|(name, _)| name.as_str());
+            writer
+                .write_record(id_name.into_iter().chain(properties))
+                .map_err(csv_error)?;
+        }
+        Ok(CsvEncoder {
+            writer,
+            primitive_null,
+            byte_as_bool,
+            record: vec![],
+        })
+    }
+
+    pub fn encode(
+        &mut self,
+        object_info: &ObjectInfo,
+        id: Option<i64>,
+        object: IsarObject,
+    ) -> Result<()> {
+        self.record.clear();
+        if let Some(id) = id {
+            self.record.push(id.to_string());
+        }
+        for (_, property) in &object_info.properties {
+            let value = JsonEncodeDecode::encode_property(
+                object_info,
+                object,
+                *property,
+                self.primitive_null,
+                self.byte_as_bool,
+            );
+            let field = match value {
+                Value::Null => NULL_FIELD.to_string(),
+                // Formatted as f32 so the value is not extended with the digits of an f64.
+                Value::Number(_) if property.data_type == DataType::Float => {
+                    object.read_float(*property).to_string()
+                }
+                Value::Number(number) => number.to_string(),
+                Value::Bool(value) => value.to_string(),
+                Value::String(value) if value.starts_with('\\') => format!("\\{}", value),
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+            self.record.push(field);
+        }
+        self.writer.write_record(&self.record).map_err(csv_error)
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.flush().map_err(|_| IsarError::CsvError {
+            message: "Could not write the CSV.".to_string(),
+        })
+    }
+}
+
+fn csv_error(error: csv::Error) -> IsarError {
+    let message = match error.position() {
+        Some(position) => format!("Invalid CSV in line {}.", position.line()),
+        None if error.is_io_error() => "Could not read or write the CSV.".to_string(),
+        None => "Invalid CSV.".to_string(),
+    };
+    IsarError::CsvError { message }
+}
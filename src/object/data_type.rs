@@ -8,21 +8,35 @@ pub enum DataType {
     Float,
     Long,
     Double,
+    // Microseconds since the Unix epoch in UTC.
+    DateTime,
+    // Latitude and longitude in degrees.
+    GeoPoint,
     String,
+    // An embedded object with its own properties.
+    Object,
     #[serde(alias = "BoolList")]
     ByteList,
     IntList,
     FloatList,
     LongList,
     DoubleList,
+    DateTimeList,
     StringList,
+    ObjectList,
 }
 
 impl DataType {
     pub fn is_static(&self) -> bool {
         matches!(
             &self,
-            DataType::Int | DataType::Long | DataType::Float | DataType::Double | DataType::Byte
+            DataType::Int
+                | DataType::Long
+                | DataType::Float
+                | DataType::Double
+                | DataType::Byte
+                | DataType::DateTime
+                | DataType::GeoPoint
         )
     }
 
@@ -34,6 +48,7 @@ impl DataType {
         match *self {
             DataType::Byte => 1,
             DataType::Int | DataType::Float => 4,
+            DataType::GeoPoint => 16,
             _ => 8,
         }
     }
@@ -49,7 +64,9 @@ impl DataType {
             DataType::FloatList => Some(DataType::Float),
             DataType::LongList => Some(DataType::Long),
             DataType::DoubleList => Some(DataType::Double),
+            DataType::DateTimeList => Some(DataType::DateTime),
             DataType::StringList => Some(DataType::String),
+            DataType::ObjectList => Some(DataType::Object),
             _ => None,
         }
     }
@@ -0,0 +1,193 @@
+// DateTime values are stored as microseconds since the Unix epoch in UTC. JSON uses RFC 3339
+// strings.
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// Days since the Unix epoch of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_offset = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_offset + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_offset = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_offset + 2) / 5 + 1;
+    let month = if month_offset < 10 {
+        month_offset + 3
+    } else {
+        month_offset - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn format_date_time(micros: i64) -> String {
+    let seconds = micros.div_euclid(MICROS_PER_SECOND);
+    let fraction = micros.rem_euclid(MICROS_PER_SECOND);
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let mut result = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    );
+    if fraction != 0 {
+        result.push_str(&format!(".{:06}", fraction));
+    }
+    result.push('Z');
+    result
+}
+
+fn parse_number(value: &str, digits: usize) -> Option<i64> {
+    if value.len() == digits && value.bytes().all(|b| b.is_ascii_digit()) {
+        value.parse().ok()
+    } else {
+        None
+    }
+}
+
+// Parses "YYYY-MM-DD" optionally followed by "THH:MM:SS", a fraction and a "Z" or "+HH:MM"
+// offset. Values without offset are UTC.
+pub fn parse_date_time(value: &str) -> Option<i64> {
+    let (date, time) = if value.len() > 10 {
+        if !matches!(value.as_bytes()[10], b'T' | b't' | b' ') {
+            return None;
+        }
+        (value.get(..10)?, value.get(11..)?)
+    } else {
+        (value, "")
+    };
+
+    let year = parse_number(date.get(0..4)?, 4)?;
+    let month = parse_number(date.get(5..7)?, 2)?;
+    let day = parse_number(date.get(8..10)?, 2)?;
+    if date.as_bytes()[4] != b'-' || date.as_bytes()[7] != b'-' {
+        return None;
+    }
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if day < 1 || day > month_days {
+        return None;
+    }
+
+    let mut seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+    let mut fraction = 0;
+    if !time.is_empty() {
+        let hour = parse_number(time.get(0..2)?, 2)?;
+        let minute = parse_number(time.get(3..5)?, 2)?;
+        let second = parse_number(time.get(6..8)?, 2)?;
+        if time.as_bytes()[2] != b':' || time.as_bytes()[5] != b':' {
+            return None;
+        }
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        seconds += hour * 3600 + minute * 60 + second;
+
+        let mut rest = &time[8..];
+        if let Some(digits) = rest.strip_prefix('.') {
+            let end = digits
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(digits.len());
+            if end == 0 {
+                return None;
+            }
+            let micros = format!("{:0<6}", &digits[..end.min(6)]);
+            fraction = micros.parse::<i64>().ok()?;
+            rest = &digits[end..];
+        }
+
+        match rest {
+            "" | "Z" | "z" => {}
+            _ => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let offset_hour = parse_number(rest.get(1..3)?, 2)?;
+                let offset_minute = parse_number(rest.get(4..6)?, 2)?;
+                if rest.len() != 6 || rest.as_bytes()[3] != b':' {
+                    return None;
+                }
+                seconds -= sign * (offset_hour * 3600 + offset_minute * 60);
+            }
+        }
+    }
+
+    seconds
+        .checked_mul(MICROS_PER_SECOND)?
+        .checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_date_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_date_time(-1), "1969-12-31T23:59:59.999999Z");
+        assert_eq!(
+            format_date_time(951_827_696_789_000),
+            "2000-02-29T12:34:56.789000Z"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_date_time("1970-01-01"), Some(0));
+        assert_eq!(parse_date_time("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_date_time("2000-02-29T12:34:56.789Z"),
+            Some(951_827_696_789_000)
+        );
+        assert_eq!(
+            parse_date_time("2000-02-29T14:34:56.789+02:00"),
+            Some(951_827_696_789_000)
+        );
+        assert_eq!(parse_date_time("1969-12-31T23:59:59.9999999"), Some(-1));
+        assert_eq!(parse_date_time("2001-02-29"), None);
+        assert_eq!(parse_date_time("2000-01-01T24:00:00Z"), None);
+        assert_eq!(parse_date_time("2000-01-01T00:00:00+0100"), None);
+        assert_eq!(parse_date_time("today"), None);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let values = [
+            -62_135_596_800_000_000,
+            -86_400_000_001,
+            0,
+            1,
+            253_402_300_799_999_999,
+        ];
+        for micros in &values {
+            let formatted = format_date_time(*micros);
+            assert_eq!(parse_date_time(&formatted), Some(*micros));
+        }
+    }
+}
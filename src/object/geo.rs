@@ -0,0 +1,253 @@
+use crate::error::{illegal_arg, Result};
+use std::f64::consts::PI;
+
+// Mean radius of the earth in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+// Areas are covered by at most this many geohash cells per bounding box.
+const MAX_COVERING_CELLS: u64 = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    pub fn new(lat: f64, lon: f64) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return illegal_arg(
+                "Latitude has to be between -90 and 90 and longitude between -180 and 180.",
+            );
+        }
+        Ok(GeoPoint { lat, lon })
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.lat.is_nan() || self.lon.is_nan()
+    }
+
+    // Great-circle distance in meters.
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.lon - self.lon).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+    }
+
+    // Interleaves the bits of the quantized longitude and latitude so that nearby points
+    // usually share a long prefix.
+    pub(crate) fn geohash(&self) -> u64 {
+        interleave(quantize(self.lon, 180.0), quantize(self.lat, 90.0))
+    }
+}
+
+// Maps -max..=max to 0..=u32::MAX.
+fn quantize(value: f64, max: f64) -> u64 {
+    ((value + max) / (2.0 * max) * 4_294_967_296.0).min(u32::MAX as f64) as u32 as u64
+}
+
+fn spread(value: u64) -> u64 {
+    let mut value = value & 0xFFFF_FFFF;
+    value = (value | value << 16) & 0x0000_FFFF_0000_FFFF;
+    value = (value | value << 8) & 0x00FF_00FF_00FF_00FF;
+    value = (value | value << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | value << 2) & 0x3333_3333_3333_3333;
+    (value | value << 1) & 0x5555_5555_5555_5555
+}
+
+fn interleave(lon: u64, lat: u64) -> u64 {
+    spread(lon) << 1 | spread(lat)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Area {
+    Box {
+        south: f64,
+        west: f64,
+        north: f64,
+        east: f64,
+    },
+    Radius {
+        center: GeoPoint,
+        meters: f64,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GeoArea(Area);
+
+impl GeoArea {
+    // Boxes with a west edge east of the east edge cross the antimeridian.
+    pub fn bounding_box(south: f64, west: f64, north: f64, east: f64) -> Result<Self> {
+        GeoPoint::new(south, west)?;
+        GeoPoint::new(north, east)?;
+        if south > north {
+            return illegal_arg("The south edge of the box has to be below the north edge.");
+        }
+        Ok(GeoArea(Area::Box {
+            south,
+            west,
+            north,
+            east,
+        }))
+    }
+
+    pub fn radius(lat: f64, lon: f64, meters: f64) -> Result<Self> {
+        let center = GeoPoint::new(lat, lon)?;
+        if meters.is_nan() || meters < 0.0 {
+            return illegal_arg("The radius must not be negative.");
+        }
+        Ok(GeoArea(Area::Radius { center, meters }))
+    }
+
+    pub fn contains(&self, point: GeoPoint) -> bool {
+        if point.is_null() {
+            return false;
+        }
+        match self.0 {
+            Area::Box {
+                south,
+                west,
+                north,
+                east,
+            } => {
+                let lon_matches = if west <= east {
+                    point.lon >= west && point.lon <= east
+                } else {
+                    point.lon >= west || point.lon <= east
+                };
+                lon_matches && point.lat >= south && point.lat <= north
+            }
+            Area::Radius { center, meters } => center.distance(&point) <= meters,
+        }
+    }
+
+    // Boxes as (south, west, north, east) that contain the area and don't cross the
+    // antimeridian.
+    fn bounding_boxes(&self) -> Vec<(f64, f64, f64, f64)> {
+        match self.0 {
+            Area::Box {
+                south,
+                west,
+                north,
+                east,
+            } => {
+                if west <= east {
+                    vec![(south, west, north, east)]
+                } else {
+                    vec![(south, west, north, 180.0), (south, -180.0, north, east)]
+                }
+            }
+            Area::Radius { center, meters } => {
+                let angle = meters / EARTH_RADIUS;
+                let south = center.lat - angle.to_degrees();
+                let north = center.lat + angle.to_degrees();
+                let ratio = angle.sin() / center.lat.to_radians().cos();
+                if south <= -90.0 || north >= 90.0 || angle >= PI || ratio >= 1.0 {
+                    return vec![(south.max(-90.0), -180.0, north.min(90.0), 180.0)];
+                }
+                let dlon = ratio.asin().to_degrees();
+                let (west, east) = (center.lon - dlon, center.lon + dlon);
+                if west < -180.0 {
+                    vec![
+                        (south, west + 360.0, north, 180.0),
+                        (south, -180.0, north, east),
+                    ]
+                } else if east > 180.0 {
+                    vec![
+                        (south, west, north, 180.0),
+                        (south, -180.0, north, east - 360.0),
+                    ]
+                } else {
+                    vec![(south, west, north, east)]
+                }
+            }
+        }
+    }
+
+    // Sorted, non-overlapping geohash ranges that contain every point of the area. They may
+    // also contain points outside of the area.
+    pub(crate) fn covering_ranges(&self) -> Vec<(u64, u64)> {
+        let mut ranges = vec![];
+        for (south, west, north, east) in self.bounding_boxes() {
+            let (x1, x2) = (quantize(west, 180.0), quantize(east, 180.0));
+            let (y1, y2) = (quantize(south, 90.0), quantize(north, 90.0));
+            let cells = |level: u32| {
+                let shift = 32 - level;
+                ((x2 >> shift) - (x1 >> shift) + 1) * ((y2 >> shift) - (y1 >> shift) + 1)
+            };
+            // The finest level at which a few cells cover the box.
+            let mut level = 0;
+            while level < 32 && cells(level + 1) <= MAX_COVERING_CELLS {
+                level += 1;
+            }
+            let shift = 32 - level;
+            for x in (x1 >> shift)..=(x2 >> shift) {
+                for y in (y1 >> shift)..=(y2 >> shift) {
+                    let lower = interleave(x, y).checked_shl(2 * shift).unwrap_or(0);
+                    let upper = lower | u64::MAX.checked_shr(2 * level).unwrap_or(0);
+                    ranges.push((lower, upper));
+                }
+            }
+        }
+
+        ranges.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = vec![];
+        for (lower, upper) in ranges {
+            if let Some(last) = merged.last_mut() {
+                if lower <= last.1.saturating_add(1) {
+                    last.1 = last.1.max(upper);
+                    continue;
+                }
+            }
+            merged.push((lower, upper));
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let berlin = GeoPoint::new(52.5200, 13.4050).unwrap();
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        let distance = berlin.distance(&paris);
+        assert!((distance - 877_500.0).abs() < 1_000.0);
+        assert_eq!(berlin.distance(&berlin), 0.0);
+    }
+
+    #[test]
+    fn test_covering_ranges_contain_area() {
+        let areas = [
+            GeoArea::bounding_box(10.0, 20.0, 10.5, 20.5).unwrap(),
+            GeoArea::bounding_box(-10.0, 170.0, 10.0, -170.0).unwrap(),
+            GeoArea::radius(52.52, 13.405, 5_000.0).unwrap(),
+            GeoArea::radius(0.0, 179.99, 10_000.0).unwrap(),
+            GeoArea::radius(89.99, 0.0, 10_000.0).unwrap(),
+        ];
+        for area in &areas {
+            let ranges = area.covering_ranges();
+            let mut contained = 0;
+            for (south, west, north, east) in area.bounding_boxes() {
+                for i in 0..=50 {
+                    for j in 0..=50 {
+                        let point = GeoPoint {
+                            lat: south + (north - south) * i as f64 / 50.0,
+                            lon: west + (east - west) * j as f64 / 50.0,
+                        };
+                        if area.contains(point) {
+                            contained += 1;
+                            let hash = point.geohash();
+                            assert!(ranges.iter().any(|(l, u)| hash >= *l && hash <= *u));
+                        }
+                    }
+                }
+            }
+            assert!(contained > 0);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::object::data_type::DataType;
+use crate::object::geo::GeoPoint;
 use crate::object::object_builder::ObjectBuilder;
 use byteorder::{ByteOrder, LittleEndian};
 use num_traits::Float;
@@ -17,6 +18,19 @@ impl Property {
     }
 }
 
+// Value of a scalar property. Null values are represented by the null value of the type.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PropertyValue<'a> {
+    Byte(u8),
+    Int(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    DateTime(i64),
+    GeoPoint(GeoPoint),
+    String(Option<&'a str>),
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct IsarObject<'a> {
     bytes: &'a [u8],
@@ -31,6 +45,11 @@ impl<'a> IsarObject<'a> {
     pub const NULL_LONG: i64 = i64::MIN;
     pub const NULL_FLOAT: f32 = f32::NAN;
     pub const NULL_DOUBLE: f64 = f64::NAN;
+    pub const NULL_DATE_TIME: i64 = i64::MIN;
+    pub const NULL_GEO_POINT: GeoPoint = GeoPoint {
+        lat: f64::NAN,
+        lon: f64::NAN,
+    };
 
     pub fn from_bytes(bytes: &'a [u8]) -> Self {
         let static_size = LittleEndian::read_u16(bytes) as usize;
@@ -58,6 +77,8 @@ impl<'a> IsarObject<'a> {
             DataType::Long => self.read_long(property) == Self::NULL_LONG,
             DataType::Float => self.read_float(property).is_nan(),
             DataType::Double => self.read_double(property).is_nan(),
+            DataType::DateTime => self.read_date_time(property) == Self::NULL_DATE_TIME,
+            DataType::GeoPoint => self.read_geo_point(property).is_null(),
             _ => self.get_offset_length(property.offset, false).is_none(),
         }
     }
@@ -95,6 +116,15 @@ impl<'a> IsarObject<'a> {
 
     pub fn read_long(&self, property: Property) -> i64 {
         assert_eq!(property.data_type, DataType::Long);
+        self.read_i64(property)
+    }
+
+    pub fn read_date_time(&self, property: Property) -> i64 {
+        assert_eq!(property.data_type, DataType::DateTime);
+        self.read_i64(property)
+    }
+
+    fn read_i64(&self, property: Property) -> i64 {
         if self.contains_property(property) {
             LittleEndian::read_i64(&self.bytes[property.offset..])
         } else {
@@ -111,6 +141,18 @@ impl<'a> IsarObject<'a> {
         }
     }
 
+    pub fn read_geo_point(&self, property: Property) -> GeoPoint {
+        assert_eq!(property.data_type, DataType::GeoPoint);
+        if self.contains_property(property) {
+            GeoPoint {
+                lat: LittleEndian::read_f64(&self.bytes[property.offset..]),
+                lon: LittleEndian::read_f64(&self.bytes[property.offset + 8..]),
+            }
+        } else {
+            Self::NULL_GEO_POINT
+        }
+    }
+
     fn get_offset_length(&self, offset: usize, dynamic_offset: bool) -> Option<(usize, usize)> {
         if dynamic_offset || self.contains_offset(offset) {
             let list_offset = LittleEndian::read_u32(&self.bytes[offset..]) as usize;
@@ -122,17 +164,38 @@ impl<'a> IsarObject<'a> {
         None
     }
 
+    pub fn read_length(&self, property: Property) -> Option<usize> {
+        assert!(property.data_type.is_dynamic());
+        let (_, length) = self.get_offset_length(property.offset, false)?;
+        Some(length)
+    }
+
     fn read_string_at(&self, offset: usize, dynamic_offset: bool) -> Option<&'a str> {
         let (offset, length) = self.get_offset_length(offset, dynamic_offset)?;
         let str = unsafe { std::str::from_utf8_unchecked(&self.bytes[offset..offset + length]) };
         Some(str)
     }
 
-    pub fn read_string(&'a self, property: Property) -> Option<&'a str> {
+    pub fn read_string(&self, property: Property) -> Option<&'a str> {
         assert_eq!(property.data_type, DataType::String);
         self.read_string_at(property.offset, false)
     }
 
+    // Only scalar properties that are not objects can be read.
+    pub(crate) fn read_value(&self, property: Property) -> PropertyValue<'a> {
+        match property.data_type {
+            DataType::Byte => PropertyValue::Byte(self.read_byte(property)),
+            DataType::Int => PropertyValue::Int(self.read_int(property)),
+            DataType::Float => PropertyValue::Float(self.read_float(property)),
+            DataType::Long => PropertyValue::Long(self.read_long(property)),
+            DataType::Double => PropertyValue::Double(self.read_double(property)),
+            DataType::DateTime => PropertyValue::DateTime(self.read_date_time(property)),
+            DataType::GeoPoint => PropertyValue::GeoPoint(self.read_geo_point(property)),
+            DataType::String => PropertyValue::String(self.read_string(property)),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn read_byte_list(&self, property: Property) -> Option<&'a [u8]> {
         assert_eq!(property.data_type, DataType::ByteList);
         let (offset, length) = self.get_offset_length(property.offset, false)?;
@@ -163,6 +226,15 @@ impl<'a> IsarObject<'a> {
 
     pub fn read_long_list(&self, property: Property) -> Option<Vec<i64>> {
         assert_eq!(property.data_type, DataType::LongList);
+        self.read_i64_list(property)
+    }
+
+    pub fn read_date_time_list(&self, property: Property) -> Option<Vec<i64>> {
+        assert_eq!(property.data_type, DataType::DateTimeList);
+        self.read_i64_list(property)
+    }
+
+    fn read_i64_list(&self, property: Property) -> Option<Vec<i64>> {
         let (offset, length) = self.get_offset_length(property.offset, false)?;
         let list = (offset..offset + length * 8)
             .step_by(8)
@@ -194,13 +266,42 @@ impl<'a> IsarObject<'a> {
         Some(list)
     }
 
+    // Embedded objects contain their own static size so they can be read like any other object.
+    pub fn read_object(&self, property: Property) -> Option<IsarObject<'a>> {
+        assert_eq!(property.data_type, DataType::Object);
+        self.read_object_at(property.offset, false)
+    }
+
+    fn read_object_at(&self, offset: usize, dynamic_offset: bool) -> Option<IsarObject<'a>> {
+        let (offset, length) = self.get_offset_length(offset, dynamic_offset)?;
+        Some(IsarObject::from_bytes(&self.bytes[offset..offset + length]))
+    }
+
+    pub fn read_object_list(&self, property: Property) -> Option<Vec<Option<IsarObject<'a>>>> {
+        assert_eq!(property.data_type, DataType::ObjectList);
+        let (offset, length) = self.get_offset_length(property.offset, false)?;
+        let list = (offset..offset + length * 8)
+            .step_by(8)
+            .map(|offset| self.read_object_at(offset, true))
+            .collect();
+        Some(list)
+    }
+
     pub fn hash_property(&self, property: Property, case_sensitive: bool, seed: u64) -> u64 {
         match property.data_type {
             DataType::Byte => xxh3_64_with_seed(&[self.read_byte(property)], seed),
             DataType::Int => xxh3_64_with_seed(&self.read_int(property).to_le_bytes(), seed),
             DataType::Float => xxh3_64_with_seed(&self.read_float(property).to_le_bytes(), seed),
             DataType::Long => xxh3_64_with_seed(&self.read_long(property).to_le_bytes(), seed),
+            DataType::DateTime => {
+                xxh3_64_with_seed(&self.read_date_time(property).to_le_bytes(), seed)
+            }
             DataType::Double => xxh3_64_with_seed(&self.read_double(property).to_le_bytes(), seed),
+            DataType::GeoPoint => {
+                let point = self.read_geo_point(property);
+                let hash = xxh3_64_with_seed(&point.lat.to_le_bytes(), seed);
+                xxh3_64_with_seed(&point.lon.to_le_bytes(), hash)
+            }
             DataType::String => Self::hash_string(self.read_string(property), case_sensitive, seed),
             _ => {
                 if let Some((offset, length)) = self.get_offset_length(property.offset, false) {
@@ -211,7 +312,7 @@ impl<'a> IsarObject<'a> {
                         DataType::IntList | DataType::FloatList => {
                             xxh3_64_with_seed(&self.bytes[offset..offset + length * 4], seed)
                         }
-                        DataType::LongList | DataType::DoubleList => {
+                        DataType::LongList | DataType::DoubleList | DataType::DateTimeList => {
                             xxh3_64_with_seed(&self.bytes[offset..offset + length * 8], seed)
                         }
                         DataType::StringList => Self::hash_string_list(
@@ -219,6 +320,16 @@ impl<'a> IsarObject<'a> {
                             case_sensitive,
                             seed,
                         ),
+                        DataType::Object => {
+                            xxh3_64_with_seed(&self.bytes[offset..offset + length], seed)
+                        }
+                        DataType::ObjectList => self
+                            .read_object_list(property)
+                            .unwrap()
+                            .into_iter()
+                            .fold(seed, |hash, object| {
+                                object.map_or(hash, |o| xxh3_64_with_seed(o.as_bytes(), hash))
+                            }),
                         _ => panic!(),
                     }
                 } else {
@@ -292,11 +403,23 @@ impl<'a> IsarObject<'a> {
                 compare_float(f1, f2)
             }
             DataType::Long => self.read_long(property).cmp(&other.read_long(property)),
+            DataType::DateTime => self
+                .read_date_time(property)
+                .cmp(&other.read_date_time(property)),
             DataType::Double => {
                 let f1 = self.read_double(property);
                 let f2 = other.read_double(property);
                 compare_float(f1, f2)
             }
+            DataType::GeoPoint => {
+                let p1 = self.read_geo_point(property);
+                let p2 = other.read_geo_point(property);
+                if p1.lat != p2.lat {
+                    compare_float(p1.lat, p2.lat)
+                } else {
+                    compare_float(p1.lon, p2.lon)
+                }
+            }
             DataType::String => {
                 let s1 = self.read_string(property);
                 let s2 = other.read_string(property);
@@ -335,8 +458,20 @@ mod tests {
     #[test]
     fn test_read_non_contained_property() {
         let data_types = vec![
-            Byte, Int, Float, Long, Double, String, ByteList, IntList, FloatList, LongList,
-            DoubleList, StringList,
+            Byte,
+            Int,
+            Float,
+            Long,
+            Double,
+            DateTime,
+            String,
+            ByteList,
+            IntList,
+            FloatList,
+            LongList,
+            DoubleList,
+            DateTimeList,
+            StringList,
         ];
         for data_type in data_types {
             builder!(_b, p, data_type);
@@ -346,6 +481,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_length() {
+        builder!(b, p, String);
+        b.write_null();
+        assert_eq!(b.finish().read_length(p), None);
+
+        builder!(b, p, String);
+        b.write_string(Some("hello"));
+        assert_eq!(b.finish().read_length(p), Some(5));
+
+        builder!(b, p, LongList);
+        b.write_long_list(Some(&[]));
+        assert_eq!(b.finish().read_length(p), Some(0));
+
+        builder!(b, p, StringList);
+        b.write_string_list(Some(&[Some("a"), None]));
+        assert_eq!(b.finish().read_length(p), Some(2));
+    }
+
     #[test]
     fn test_read_byte() {
         builder!(b, p, Byte);
@@ -398,6 +552,19 @@ mod tests {
         assert!(!b.finish().is_null(p));
     }
 
+    #[test]
+    fn test_read_date_time() {
+        builder!(b, p, DateTime);
+        b.write_null();
+        assert_eq!(b.finish().read_date_time(p), IsarObject::NULL_DATE_TIME);
+        assert!(b.finish().is_null(p));
+
+        builder!(b, p, DateTime);
+        b.write_date_time(-1);
+        assert_eq!(b.finish().read_date_time(p), -1);
+        assert!(!b.finish().is_null(p));
+    }
+
     #[test]
     fn test_read_double() {
         builder!(b, p, Double);
@@ -501,6 +668,22 @@ mod tests {
         assert!(!b.finish().is_null(p));
     }
 
+    #[test]
+    fn test_read_date_time_list() {
+        builder!(b, p, DateTimeList);
+        b.write_null();
+        assert_eq!(b.finish().read_date_time_list(p), None);
+        assert!(b.finish().is_null(p));
+
+        builder!(b, p, DateTimeList);
+        b.write_date_time_list(Some(&[1, IsarObject::NULL_DATE_TIME]));
+        assert_eq!(
+            b.finish().read_date_time_list(p),
+            Some(vec![1, IsarObject::NULL_DATE_TIME])
+        );
+        assert!(!b.finish().is_null(p));
+    }
+
     #[test]
     fn test_read_double_list() {
         builder!(b, p, DoubleList);
@@ -539,4 +722,39 @@ mod tests {
         assert_eq!(b.finish().read_string_list(p), Some(vec![]));
         assert!(!b.finish().is_null(p));
     }
+
+    #[test]
+    fn test_read_object() {
+        builder!(inner, inner_p, Int);
+        inner.write_int(123);
+        let inner = inner.finish();
+
+        builder!(b, p, Object);
+        b.write_null();
+        assert_eq!(b.finish().read_object(p), None);
+        assert!(b.finish().is_null(p));
+
+        builder!(b, p, Object);
+        b.write_object(Some(inner));
+        let object = b.finish().read_object(p).unwrap();
+        assert_eq!(object, inner);
+        assert_eq!(object.read_int(inner_p), 123);
+    }
+
+    #[test]
+    fn test_read_object_list() {
+        builder!(inner, inner_p, String);
+        inner.write_string(Some("hello"));
+        let inner = inner.finish();
+
+        builder!(b, p, ObjectList);
+        b.write_null();
+        assert_eq!(b.finish().read_object_list(p), None);
+
+        builder!(b, p, ObjectList);
+        b.write_object_list(Some(&[Some(inner), None]));
+        let list = b.finish().read_object_list(p).unwrap();
+        assert_eq!(list, vec![Some(inner), None]);
+        assert_eq!(list[0].unwrap().read_string(inner_p), Some("hello"));
+    }
 }
@@ -1,63 +1,101 @@
-use crate::collection::IsarCollection;
 use crate::error::{IsarError, Result};
 use crate::object::data_type::DataType;
-use crate::object::isar_object::IsarObject;
+use crate::object::date_time::{format_date_time, parse_date_time};
+use crate::object::geo::GeoPoint;
+use crate::object::isar_object::{IsarObject, Property};
 use crate::object::object_builder::ObjectBuilder;
+use crate::object::object_info::ObjectInfo;
 use serde_json::{json, Map, Value};
 
 pub(crate) struct JsonEncodeDecode {}
 
 impl<'a> JsonEncodeDecode {
     pub fn encode(
-        collection: &IsarCollection,
+        object_info: &ObjectInfo,
         object: IsarObject,
         primitive_null: bool,
         byte_as_bool: bool,
     ) -> Map<String, Value> {
         let mut object_map = Map::new();
 
-        for (property_name, property) in &collection.properties {
+        for (property_name, property) in &object_info.properties {
             let property = *property;
             let value =
-                if primitive_null && object.is_null(property) {
-                    Value::Null
-                } else {
-                    match property.data_type {
-                        DataType::Byte => {
-                            if byte_as_bool {
-                                json!(object.read_bool(property))
-                            } else {
-                                json!(object.read_byte(property))
-                            }
-                        }
-                        DataType::Int => json!(object.read_int(property)),
-                        DataType::Float => json!(object.read_float(property)),
-                        DataType::Long => json!(object.read_long(property)),
-                        DataType::Double => json!(object.read_double(property)),
-                        DataType::String => json!(object.read_string(property)),
-                        DataType::ByteList => json!(object.read_byte_list(property)),
-                        DataType::IntList => json!(object.read_int_list(property)),
-                        DataType::FloatList => json!(object.read_float_list(property)),
-                        DataType::LongList => json!(object.read_long_list(property)),
-                        DataType::DoubleList => json!(object.read_double_list(property)),
-                        DataType::StringList => json!(object.read_string_list(property)),
-                    }
-                };
+                Self::encode_property(object_info, object, property, primitive_null, byte_as_bool);
             object_map.insert(property_name.clone(), value);
         }
 
         object_map
     }
 
+    pub(crate) fn encode_property(
+        object_info: &ObjectInfo,
+        object: IsarObject,
+        property: Property,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Value {
+        if primitive_null && object.is_null(property) {
+            Value::Null
+        } else {
+            match property.data_type {
+                DataType::Byte => {
+                    if byte_as_bool {
+                        json!(object.read_bool(property))
+                    } else {
+                        json!(object.read_byte(property))
+                    }
+                }
+                DataType::Int => json!(object.read_int(property)),
+                DataType::Float => json!(object.read_float(property)),
+                DataType::Long => json!(object.read_long(property)),
+                DataType::Double => json!(object.read_double(property)),
+                DataType::DateTime => Self::date_time_to_value(object.read_date_time(property)),
+                DataType::GeoPoint => Self::geo_point_to_value(object.read_geo_point(property)),
+                DataType::String => json!(object.read_string(property)),
+                DataType::ByteList => json!(object.read_byte_list(property)),
+                DataType::IntList => json!(object.read_int_list(property)),
+                DataType::FloatList => json!(object.read_float_list(property)),
+                DataType::LongList => json!(object.read_long_list(property)),
+                DataType::DoubleList => json!(object.read_double_list(property)),
+                DataType::DateTimeList => {
+                    json!(object.read_date_time_list(property).map(|list| list
+                        .into_iter()
+                        .map(Self::date_time_to_value)
+                        .collect::<Vec<_>>()))
+                }
+                DataType::StringList => json!(object.read_string_list(property)),
+                DataType::Object => {
+                    let embedded = object_info.get_embedded(property).unwrap();
+                    json!(object.read_object(property).map(|object| {
+                        Self::encode(embedded, object, primitive_null, byte_as_bool)
+                    }))
+                }
+                DataType::ObjectList => {
+                    let embedded = object_info.get_embedded(property).unwrap();
+                    json!(object.read_object_list(property).map(|list| list
+                        .into_iter()
+                        .map(|object| object.map(|object| Self::encode(
+                            embedded,
+                            object,
+                            primitive_null,
+                            byte_as_bool
+                        )))
+                        .collect::<Vec<_>>()))
+                }
+            }
+        }
+    }
+
     pub fn decode(
-        collection: &'a IsarCollection,
+        object_info: &'a ObjectInfo,
         json: &Value,
         buffer: Option<Vec<u8>>,
     ) -> Result<ObjectBuilder<'a>> {
-        let mut ob = collection.new_object_builder(buffer);
+        let mut ob = object_info.new_object_builder(buffer);
         let object = json.as_object().ok_or(IsarError::InvalidJson {})?;
 
-        for (property_name, property) in &collection.properties {
+        for (property_name, property) in &object_info.properties {
             if let Some(value) = object.get(property_name) {
                 match property.data_type {
                     DataType::Byte => ob.write_byte(Self::value_to_byte(value)?),
@@ -65,6 +103,8 @@ impl<'a> JsonEncodeDecode {
                     DataType::Float => ob.write_float(Self::value_to_float(value)?),
                     DataType::Long => ob.write_long(Self::value_to_long(value)?),
                     DataType::Double => ob.write_double(Self::value_to_double(value)?),
+                    DataType::DateTime => ob.write_date_time(Self::value_to_date_time(value)?),
+                    DataType::GeoPoint => ob.write_geo_point(Self::value_to_geo_point(value)?),
                     DataType::String => ob.write_string(Self::value_to_string(value)?),
                     DataType::ByteList => {
                         let list = Self::value_to_array(value, Self::value_to_byte)?;
@@ -86,6 +126,10 @@ impl<'a> JsonEncodeDecode {
                         let list = Self::value_to_array(value, Self::value_to_double)?;
                         ob.write_double_list(list.as_deref());
                     }
+                    DataType::DateTimeList => {
+                        let list = Self::value_to_array(value, Self::value_to_date_time)?;
+                        ob.write_date_time_list(list.as_deref());
+                    }
                     DataType::StringList => {
                         if value.is_null() {
                             ob.write_string_list(None);
@@ -97,6 +141,37 @@ impl<'a> JsonEncodeDecode {
                             return Err(IsarError::InvalidJson {});
                         }
                     }
+                    DataType::Object => {
+                        let embedded = object_info.get_embedded(*property).unwrap();
+                        if value.is_null() {
+                            ob.write_object(None);
+                        } else {
+                            let embedded_ob = Self::decode(embedded, value, None)?;
+                            ob.write_object(Some(embedded_ob.finish()));
+                        }
+                    }
+                    DataType::ObjectList => {
+                        let embedded = object_info.get_embedded(*property).unwrap();
+                        if value.is_null() {
+                            ob.write_object_list(None);
+                        } else if let Some(value) = value.as_array() {
+                            let mut builders = vec![];
+                            for element in value {
+                                if element.is_null() {
+                                    builders.push(None);
+                                } else {
+                                    builders.push(Some(Self::decode(embedded, element, None)?));
+                                }
+                            }
+                            let list: Vec<Option<IsarObject>> = builders
+                                .iter()
+                                .map(|ob| ob.as_ref().map(|ob| ob.finish()))
+                                .collect();
+                            ob.write_object_list(Some(list.as_slice()));
+                        } else {
+                            return Err(IsarError::InvalidJson {});
+                        }
+                    }
                 }
             } else {
                 ob.write_null();
@@ -106,7 +181,7 @@ impl<'a> JsonEncodeDecode {
         Ok(ob)
     }
 
-    fn value_to_byte(value: &Value) -> Result<u8> {
+    pub(crate) fn value_to_byte(value: &Value) -> Result<u8> {
         if value.is_null() {
             return Ok(IsarObject::NULL_BYTE);
         } else if let Some(value) = value.as_i64() {
@@ -124,7 +199,7 @@ impl<'a> JsonEncodeDecode {
         Err(IsarError::InvalidJson {})
     }
 
-    fn value_to_int(value: &Value) -> Result<i32> {
+    pub(crate) fn value_to_int(value: &Value) -> Result<i32> {
         if value.is_null() {
             return Ok(IsarObject::NULL_INT);
         } else if let Some(value) = value.as_i64() {
@@ -135,7 +210,7 @@ impl<'a> JsonEncodeDecode {
         Err(IsarError::InvalidJson {})
     }
 
-    fn value_to_float(value: &Value) -> Result<f32> {
+    pub(crate) fn value_to_float(value: &Value) -> Result<f32> {
         if value.is_null() {
             return Ok(IsarObject::NULL_FLOAT);
         } else if let Some(value) = value.as_f64() {
@@ -146,7 +221,7 @@ impl<'a> JsonEncodeDecode {
         Err(IsarError::InvalidJson {})
     }
 
-    fn value_to_long(value: &Value) -> Result<i64> {
+    pub(crate) fn value_to_long(value: &Value) -> Result<i64> {
         if value.is_null() {
             Ok(IsarObject::NULL_LONG)
         } else if let Some(value) = value.as_i64() {
@@ -156,7 +231,7 @@ impl<'a> JsonEncodeDecode {
         }
     }
 
-    fn value_to_double(value: &Value) -> Result<f64> {
+    pub(crate) fn value_to_double(value: &Value) -> Result<f64> {
         if value.is_null() {
             Ok(IsarObject::NULL_DOUBLE)
         } else if let Some(value) = value.as_f64() {
@@ -166,7 +241,50 @@ impl<'a> JsonEncodeDecode {
         }
     }
 
-    fn value_to_string(value: &Value) -> Result<Option<&str>> {
+    fn date_time_to_value(value: i64) -> Value {
+        if value == IsarObject::NULL_DATE_TIME {
+            Value::Null
+        } else {
+            json!(format_date_time(value))
+        }
+    }
+
+    // Accepts RFC 3339 strings and microseconds since the Unix epoch.
+    pub(crate) fn value_to_date_time(value: &Value) -> Result<i64> {
+        if value.is_null() {
+            Ok(IsarObject::NULL_DATE_TIME)
+        } else if let Some(value) = value.as_i64() {
+            Ok(value)
+        } else if let Some(value) = value.as_str().and_then(parse_date_time) {
+            Ok(value)
+        } else {
+            Err(IsarError::InvalidJson {})
+        }
+    }
+
+    fn geo_point_to_value(point: GeoPoint) -> Value {
+        if point.is_null() {
+            Value::Null
+        } else {
+            json!({"lat": point.lat, "lon": point.lon})
+        }
+    }
+
+    // Accepts objects like {"lat": 52.52, "lon": 13.405}.
+    fn value_to_geo_point(value: &Value) -> Result<GeoPoint> {
+        if value.is_null() {
+            return Ok(IsarObject::NULL_GEO_POINT);
+        }
+        let lat = value.get("lat").and_then(|lat| lat.as_f64());
+        let lon = value.get("lon").and_then(|lon| lon.as_f64());
+        if let (Some(lat), Some(lon)) = (lat, lon) {
+            GeoPoint::new(lat, lon).map_err(|_| IsarError::InvalidJson {})
+        } else {
+            Err(IsarError::InvalidJson {})
+        }
+    }
+
+    pub(crate) fn value_to_string(value: &Value) -> Result<Option<&str>> {
         if value.is_null() {
             Ok(None)
         } else if let Some(value) = value.as_str() {
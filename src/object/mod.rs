@@ -1,4 +1,9 @@
+pub(crate) mod compression;
+pub mod csv_encode_decode;
 pub mod data_type;
+pub mod date_time;
+pub mod geo;
 pub mod isar_object;
 pub mod json_encode_decode;
 pub mod object_builder;
+pub mod object_info;
@@ -1,4 +1,5 @@
 use crate::object::data_type::DataType;
+use crate::object::geo::GeoPoint;
 use crate::object::isar_object::IsarObject;
 use crate::object::isar_object::Property;
 use std::slice::from_raw_parts;
@@ -52,13 +53,18 @@ impl<'a> ObjectBuilder<'a> {
             DataType::Float => self.write_float(IsarObject::NULL_FLOAT),
             DataType::Long => self.write_long(IsarObject::NULL_LONG),
             DataType::Double => self.write_double(IsarObject::NULL_DOUBLE),
+            DataType::DateTime => self.write_date_time(IsarObject::NULL_DATE_TIME),
+            DataType::GeoPoint => self.write_geo_point(IsarObject::NULL_GEO_POINT),
             DataType::String => self.write_string(None),
             DataType::ByteList => self.write_byte_list(None),
             DataType::IntList => self.write_int_list(None),
             DataType::FloatList => self.write_float_list(None),
             DataType::LongList => self.write_long_list(None),
             DataType::DoubleList => self.write_double_list(None),
+            DataType::DateTimeList => self.write_date_time_list(None),
             DataType::StringList => self.write_string_list(None),
+            DataType::Object => self.write_object(None),
+            DataType::ObjectList => self.write_object_list(None),
         }
     }
 
@@ -101,6 +107,19 @@ impl<'a> ObjectBuilder<'a> {
         self.write_at(property.offset, &value.to_le_bytes());
     }
 
+    pub fn write_date_time(&mut self, value: i64) {
+        let property = self.next_property(false);
+        assert_eq!(property.data_type, DataType::DateTime);
+        self.write_at(property.offset, &value.to_le_bytes());
+    }
+
+    pub fn write_geo_point(&mut self, value: GeoPoint) {
+        let property = self.next_property(false);
+        assert_eq!(property.data_type, DataType::GeoPoint);
+        self.write_at(property.offset, &value.lat.to_le_bytes());
+        self.write_at(property.offset + 8, &value.lon.to_le_bytes());
+    }
+
     pub fn write_string(&mut self, value: Option<&str>) {
         let property = self.next_property(false);
         assert_eq!(property.data_type, DataType::String);
@@ -137,6 +156,12 @@ impl<'a> ObjectBuilder<'a> {
         self.write_list(property.offset, value);
     }
 
+    pub fn write_date_time_list(&mut self, value: Option<&[i64]>) {
+        let property = self.next_property(false);
+        assert_eq!(property.data_type, DataType::DateTimeList);
+        self.write_list(property.offset, value);
+    }
+
     pub fn write_string_list(&mut self, value: Option<&[Option<&str>]>) {
         let property = self.next_property(false);
         assert_eq!(property.data_type, DataType::StringList);
@@ -155,6 +180,29 @@ impl<'a> ObjectBuilder<'a> {
         }
     }
 
+    pub fn write_object(&mut self, value: Option<IsarObject>) {
+        let property = self.next_property(false);
+        assert_eq!(property.data_type, DataType::Object);
+        self.write_list(property.offset, value.as_ref().map(|o| o.as_bytes()));
+    }
+
+    pub fn write_object_list(&mut self, value: Option<&[Option<IsarObject>]>) {
+        let property = self.next_property(false);
+        assert_eq!(property.data_type, DataType::ObjectList);
+        if let Some(value) = value {
+            self.write_at(property.offset, &(self.dynamic_offset as u32).to_le_bytes());
+            self.write_at(property.offset + 4, &(value.len() as u32).to_le_bytes());
+            let mut offset_list_offset = self.dynamic_offset;
+            self.dynamic_offset += value.len() * 8;
+            for object in value {
+                self.write_list(offset_list_offset, object.as_ref().map(|o| o.as_bytes()));
+                offset_list_offset += 8;
+            }
+        } else {
+            self.write_at(property.offset, &0u64.to_le_bytes());
+        }
+    }
+
     fn write_list<T>(&mut self, offset: usize, list: Option<&[T]>) {
         if let Some(list) = list {
             self.write_at(offset, &(self.dynamic_offset as u32).to_le_bytes());
@@ -230,8 +278,23 @@ mod tests {
         bytes.extend_from_slice(&IsarObject::NULL_DOUBLE.to_le_bytes());
         assert_eq!(b.finish().as_bytes(), &bytes);
 
+        builder!(b, DateTime);
+        b.write_null();
+        let mut bytes = vec![18, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+        bytes.extend_from_slice(&IsarObject::NULL_DATE_TIME.to_le_bytes());
+        assert_eq!(b.finish().as_bytes(), &bytes);
+
         let list_types = vec![
-            String, ByteList, IntList, FloatList, LongList, DoubleList, StringList,
+            String,
+            ByteList,
+            IntList,
+            FloatList,
+            LongList,
+            DoubleList,
+            DateTimeList,
+            StringList,
+            Object,
+            ObjectList,
         ];
 
         for list_type in list_types {
@@ -337,6 +400,22 @@ mod tests {
         b.write_long(123123);
     }
 
+    #[test]
+    pub fn test_write_date_time() {
+        builder!(b, DateTime);
+        b.write_date_time(-123123);
+        let mut bytes = vec![18, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+        bytes.extend_from_slice(&(-123123i64).to_le_bytes());
+        assert_eq!(b.finish().as_bytes(), &bytes)
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_write_date_time_wrong_type() {
+        builder!(b, Long);
+        b.write_date_time(123123);
+    }
+
     #[test]
     pub fn test_write_double() {
         builder!(b, Double);
@@ -0,0 +1,44 @@
+use crate::object::isar_object::Property;
+use crate::object::object_builder::ObjectBuilder;
+use std::collections::HashMap;
+
+// Properties of a collection or an embedded object. Object and ObjectList properties have
+// their own ObjectInfo.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectInfo {
+    pub properties: Vec<(String, Property)>,
+    props: Vec<Property>,
+    property_names: HashMap<String, Property>,
+    embedded: Vec<(Property, ObjectInfo)>,
+}
+
+impl ObjectInfo {
+    pub(crate) fn new(
+        properties: Vec<(String, Property)>,
+        embedded: Vec<(Property, ObjectInfo)>,
+    ) -> Self {
+        let props = properties.iter().map(|(_, p)| *p).collect();
+        let property_names = properties.iter().cloned().collect();
+        ObjectInfo {
+            properties,
+            props,
+            property_names,
+            embedded,
+        }
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<Property> {
+        self.property_names.get(name).copied()
+    }
+
+    pub fn get_embedded(&self, property: Property) -> Option<&ObjectInfo> {
+        self.embedded
+            .iter()
+            .find(|(p, _)| *p == property)
+            .map(|(_, info)| info)
+    }
+
+    pub fn new_object_builder(&self, buffer: Option<Vec<u8>>) -> ObjectBuilder<'_> {
+        ObjectBuilder::new(&self.props, buffer)
+    }
+}
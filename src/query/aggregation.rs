@@ -0,0 +1,174 @@
+use crate::collection::IsarCollection;
+use crate::error::{illegal_arg, Result};
+use crate::id_key::IdKey;
+use crate::object::data_type::DataType;
+use crate::object::isar_object::{IsarObject, Property};
+use crate::query::Query;
+use crate::txn::IsarTxn;
+use intmap::IntMap;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AggregationResult {
+    Long(i64),
+    Double(f64),
+    Null,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AggregationOp {
+    Min,
+    Max,
+    Sum,
+    Average,
+}
+
+// Null values are skipped. Min, max and average of no values are null, the sum is zero.
+// Integer sums saturate instead of overflowing.
+#[derive(Copy, Clone)]
+struct Aggregator {
+    property: Property,
+    op: AggregationOp,
+    is_long: bool,
+    count: usize,
+    long_value: i64,
+    double_value: f64,
+}
+
+impl Aggregator {
+    fn new(property: Property, op: AggregationOp) -> Result<Self> {
+        let is_long = match property.data_type {
+            DataType::Int | DataType::Long => true,
+            DataType::DateTime if op == AggregationOp::Min || op == AggregationOp::Max => true,
+            DataType::Float | DataType::Double => false,
+            _ => return illegal_arg("Property does not support this aggregation."),
+        };
+        let (long_value, double_value) = match op {
+            AggregationOp::Min => (i64::MAX, f64::INFINITY),
+            AggregationOp::Max => (i64::MIN, f64::NEG_INFINITY),
+            _ => (0, 0.0),
+        };
+        Ok(Aggregator {
+            property,
+            op,
+            is_long,
+            count: 0,
+            long_value,
+            double_value,
+        })
+    }
+
+    fn add(&mut self, object: IsarObject) {
+        let property = self.property;
+        if object.is_null(property) {
+            return;
+        }
+        self.count += 1;
+        if self.is_long {
+            let value = match property.data_type {
+                DataType::Int => object.read_int(property) as i64,
+                DataType::Long => object.read_long(property),
+                _ => object.read_date_time(property),
+            };
+            self.long_value = match self.op {
+                AggregationOp::Min => self.long_value.min(value),
+                AggregationOp::Max => self.long_value.max(value),
+                _ => self.long_value.saturating_add(value),
+            };
+        } else {
+            let value = if property.data_type == DataType::Float {
+                object.read_float(property) as f64
+            } else {
+                object.read_double(property)
+            };
+            self.double_value = match self.op {
+                AggregationOp::Min => self.double_value.min(value),
+                AggregationOp::Max => self.double_value.max(value),
+                _ => self.double_value + value,
+            };
+        }
+    }
+
+    fn result(&self) -> AggregationResult {
+        let count = self.count;
+        match self.op {
+            _ if count == 0 && self.op != AggregationOp::Sum => AggregationResult::Null,
+            AggregationOp::Average if self.is_long => {
+                AggregationResult::Double(self.long_value as f64 / count as f64)
+            }
+            AggregationOp::Average => AggregationResult::Double(self.double_value / count as f64),
+            _ if self.is_long => AggregationResult::Long(self.long_value),
+            _ => AggregationResult::Double(self.double_value),
+        }
+    }
+}
+
+impl Query {
+    pub fn min(&self, txn: &mut IsarTxn, property: Property) -> Result<AggregationResult> {
+        self.aggregate(txn, property, AggregationOp::Min)
+    }
+
+    pub fn max(&self, txn: &mut IsarTxn, property: Property) -> Result<AggregationResult> {
+        self.aggregate(txn, property, AggregationOp::Max)
+    }
+
+    pub fn sum(&self, txn: &mut IsarTxn, property: Property) -> Result<AggregationResult> {
+        self.aggregate(txn, property, AggregationOp::Sum)
+    }
+
+    pub fn average(&self, txn: &mut IsarTxn, property: Property) -> Result<AggregationResult> {
+        self.aggregate(txn, property, AggregationOp::Average)
+    }
+
+    fn aggregate(
+        &self,
+        txn: &mut IsarTxn,
+        property: Property,
+        op: AggregationOp,
+    ) -> Result<AggregationResult> {
+        let mut aggregator = Aggregator::new(property, op)?;
+        txn.read(self.instance_id, |cursors| {
+            self.stream_internal(cursors, |_, object| {
+                aggregator.add(object);
+                Ok(true)
+            })
+        })?;
+        Ok(aggregator.result())
+    }
+
+    // Aggregates a property of the objects linked to each result of the query. The results are
+    // returned in query order together with their ids. The link db is read in a single pass.
+    pub fn aggregate_linked(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        link_index: usize,
+        op: AggregationOp,
+        property: Property,
+    ) -> Result<Vec<(i64, AggregationResult)>> {
+        let link = collection.get_link(link_index)?;
+        let empty = Aggregator::new(property, op)?;
+        let ids = self.find_ids(txn)?;
+        let mut aggregators = IntMap::new();
+        for id in &ids {
+            aggregators.insert(IdKey::new(*id).get_unsigned_id(), empty);
+        }
+        txn.read(self.instance_id, |cursors| {
+            let mark = cursors.object_mark();
+            link.iter_all(cursors, |source_key, object| {
+                if let Some(aggregator) = aggregators.get_mut(source_key.get_unsigned_id()) {
+                    aggregator.add(object);
+                }
+                unsafe { cursors.release_objects(mark) };
+                Ok(true)
+            })
+        })?;
+        let results = ids
+            .into_iter()
+            .map(|id| {
+                let aggregator = aggregators.get(IdKey::new(id).get_unsigned_id()).unwrap();
+                (id, aggregator.result())
+            })
+            .collect();
+        Ok(results)
+    }
+}
@@ -2,13 +2,19 @@ use crate::collection::IsarCollection;
 use crate::cursor::IsarCursors;
 use crate::error::{illegal_arg, Result};
 use crate::id_key::IdKey;
+use crate::index::index_key::IndexKey;
+use crate::index::IsarIndex;
 use crate::link::IsarLink;
 use crate::object::data_type::DataType;
+use crate::object::geo::GeoArea;
 use crate::object::isar_object::{IsarObject, Property};
 use crate::query::fast_wild_match::fast_wild_match;
 use enum_dispatch::enum_dispatch;
 use itertools::Itertools;
 use paste::paste;
+use serde_json::{json, Value};
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
 #[macro_export]
 macro_rules! primitive_create {
@@ -68,6 +74,77 @@ macro_rules! string_filter_create {
     };
 }
 
+macro_rules! next_float {
+    ($value:expr, $type:ty, $up:expr) => {{
+        let value: $type = $value;
+        if value.is_nan() || (value.is_infinite() && value.is_sign_positive() == $up) {
+            value
+        } else if value == 0.0 {
+            let min = <$type>::from_bits(1);
+            if $up {
+                min
+            } else {
+                -min
+            }
+        } else if (value > 0.0) == $up {
+            <$type>::from_bits(value.to_bits() + 1)
+        } else {
+            <$type>::from_bits(value.to_bits() - 1)
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! primitive_all_create {
+    ($data_type:ident, $property:expr, $lower:expr, $upper:expr) => {
+        paste! {
+            if $property.data_type == DataType::[<$data_type List>] {
+                Ok(Filter(
+                    FilterCond::[<All $data_type Between>]([<All $data_type BetweenCond>] {
+                        $property,
+                        $lower,
+                        $upper,
+                    })
+                ))
+            } else {
+                illegal_arg("Property does not support this filter.")
+            }
+        }
+    };
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct FilterAnalysis {
+    evaluations: usize,
+    matches: usize,
+    duration: Duration,
+    children: Vec<FilterAnalysis>,
+}
+
+// A part of a string that matches a word of a text_contains filter. Start and end are byte
+// offsets into the UTF-8 string of the property.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TextMatch {
+    pub property: Property,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FilterValue {
+    Long(i64),
+    Double(f64),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Comparison {
+    Equal,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
 #[derive(Clone)]
 pub struct Filter(FilterCond);
 
@@ -97,6 +174,34 @@ impl Filter {
         primitive_create!(Double, property, lower, upper)
     }
 
+    pub fn date_time(property: Property, lower: i64, upper: i64) -> Result<Filter> {
+        primitive_create!(DateTime, property, lower, upper)
+    }
+
+    pub fn all_byte_between(property: Property, lower: u8, upper: u8) -> Result<Filter> {
+        primitive_all_create!(Byte, property, lower, upper)
+    }
+
+    pub fn all_int_between(property: Property, lower: i32, upper: i32) -> Result<Filter> {
+        primitive_all_create!(Int, property, lower, upper)
+    }
+
+    pub fn all_long_between(property: Property, lower: i64, upper: i64) -> Result<Filter> {
+        primitive_all_create!(Long, property, lower, upper)
+    }
+
+    pub fn all_float_between(property: Property, lower: f32, upper: f32) -> Result<Filter> {
+        primitive_all_create!(Float, property, lower, upper)
+    }
+
+    pub fn all_double_between(property: Property, lower: f64, upper: f64) -> Result<Filter> {
+        primitive_all_create!(Double, property, lower, upper)
+    }
+
+    pub fn all_date_time_between(property: Property, lower: i64, upper: i64) -> Result<Filter> {
+        primitive_all_create!(DateTime, property, lower, upper)
+    }
+
     pub fn string_to_bytes(str: Option<&str>, case_sensitive: bool) -> Option<Vec<u8>> {
         if case_sensitive {
             str.map(|s| s.as_bytes().to_vec())
@@ -145,6 +250,25 @@ impl Filter {
         Ok(Filter(filter_cond))
     }
 
+    pub fn all_string_between(
+        property: Property,
+        lower: Option<&str>,
+        upper: Option<&str>,
+        case_sensitive: bool,
+    ) -> Result<Filter> {
+        if property.data_type == DataType::StringList {
+            let filter_cond = FilterCond::AllStringBetween(AllStringBetweenCond {
+                property,
+                lower: Self::string_to_bytes(lower, case_sensitive),
+                upper: Self::string_to_bytes(upper, case_sensitive),
+                case_sensitive,
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
     pub fn string_starts_with(
         property: Property,
         value: &str,
@@ -173,6 +297,86 @@ impl Filter {
         string_filter_create!(Matches, property, value, case_sensitive)
     }
 
+    pub fn byte_list_equals(property: Property, value: &[u8]) -> Result<Filter> {
+        if property.data_type == DataType::ByteList {
+            let filter_cond = FilterCond::ByteListEquals(ByteListEqualsCond {
+                property,
+                value: value.to_vec(),
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn byte_list_starts_with(property: Property, value: &[u8]) -> Result<Filter> {
+        if property.data_type == DataType::ByteList {
+            let filter_cond = FilterCond::ByteListStartsWith(ByteListStartsWithCond {
+                property,
+                value: value.to_vec(),
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn string_is_empty(property: Property) -> Result<Filter> {
+        let filter_cond = if property.data_type == DataType::String {
+            FilterCond::StringIsEmpty(StringIsEmptyCond { property })
+        } else if property.data_type == DataType::StringList {
+            FilterCond::AnyStringIsEmpty(AnyStringIsEmptyCond { property })
+        } else {
+            return illegal_arg("Property does not support this filter.");
+        };
+        Ok(Filter(filter_cond))
+    }
+
+    pub fn string_length_between(property: Property, lower: usize, upper: usize) -> Result<Filter> {
+        let filter_cond = if property.data_type == DataType::String {
+            FilterCond::StringLengthBetween(StringLengthBetweenCond {
+                property,
+                lower,
+                upper,
+            })
+        } else if property.data_type == DataType::StringList {
+            FilterCond::AnyStringLengthBetween(AnyStringLengthBetweenCond {
+                property,
+                lower,
+                upper,
+            })
+        } else {
+            return illegal_arg("Property does not support this filter.");
+        };
+        Ok(Filter(filter_cond))
+    }
+
+    pub fn list_is_empty(property: Property) -> Result<Filter> {
+        if !property.data_type.is_scalar() {
+            let filter_cond = FilterCond::ListIsEmpty(ListIsEmptyCond { property });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn any_null_element(property: Property) -> Result<Filter> {
+        if !property.data_type.is_scalar() {
+            let filter_cond = FilterCond::AnyNullElement(AnyNullElementCond { property });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
+    pub fn no_null_elements(property: Property) -> Result<Filter> {
+        let any_null = Self::any_null_element(property)?;
+        Ok(Self::and(vec![
+            Self::is_not_null(property),
+            Self::not(any_null),
+        ]))
+    }
+
     pub fn null(property: Property) -> Filter {
         let filter_cond = FilterCond::Null(NullCond { property });
         Filter(filter_cond)
@@ -203,11 +407,307 @@ impl Filter {
         Filter(filter_cond)
     }
 
+    // Matches objects that contain all the words. The property needs a single property index
+    // that splits the string so the words can be looked up in the index.
+    pub fn text_contains(
+        collection: &IsarCollection,
+        property: Property,
+        words: &[&str],
+    ) -> Result<Filter> {
+        let index = collection.indexes.iter().find(|(_, index)| {
+            index.properties.len() == 1
+                && index.properties[0].property == property
+                && index.properties[0].splitter.is_some()
+        });
+        let index = if let Some((_, index)) = index {
+            index.clone()
+        } else {
+            return illegal_arg("Property does not have a text index.");
+        };
+        let index_property = &index.properties[0];
+        let mut keys: Vec<IndexKey> = vec![];
+        for (_, part) in words
+            .iter()
+            .flat_map(|word| index_property.split_string(word))
+        {
+            let mut key = IndexKey::new();
+            index_property.add_string_to_key(&mut key, Some(&part));
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        let filter_cond = FilterCond::TextContains(TextContainsCond {
+            property,
+            index,
+            keys,
+        });
+        Ok(Filter(filter_cond))
+    }
+
+    // Matches objects whose GeoPoint lies within the area. Null points never match.
+    pub fn geo_within(property: Property, area: GeoArea) -> Result<Filter> {
+        if property.data_type != DataType::GeoPoint {
+            return illegal_arg("Property does not support this filter.");
+        }
+        let filter_cond = FilterCond::GeoWithin(GeoWithinCond { property, area });
+        Ok(Filter(filter_cond))
+    }
+
+    pub fn geo_within_radius(
+        property: Property,
+        lat: f64,
+        lon: f64,
+        meters: f64,
+    ) -> Result<Filter> {
+        Self::geo_within(property, GeoArea::radius(lat, lon, meters)?)
+    }
+
+    pub fn geo_within_box(
+        property: Property,
+        south: f64,
+        west: f64,
+        north: f64,
+        east: f64,
+    ) -> Result<Filter> {
+        Self::geo_within(property, GeoArea::bounding_box(south, west, north, east)?)
+    }
+
+    // Matches objects whose embedded object matches the filter. For ObjectList properties at
+    // least one element has to match.
+    pub fn embedded(property: Property, filter: Filter) -> Result<Filter> {
+        if property.data_type == DataType::Object || property.data_type == DataType::ObjectList {
+            let filter_cond = FilterCond::Embedded(EmbeddedCond {
+                property,
+                filter: Box::new(filter.0),
+            });
+            Ok(Filter(filter_cond))
+        } else {
+            illegal_arg("Property does not support this filter.")
+        }
+    }
+
     pub fn link(collection: &IsarCollection, link_index: usize, filter: Filter) -> Result<Filter> {
         let filter_cond = LinkCond::filter(collection, link_index, filter.0)?;
         Ok(Filter(filter_cond))
     }
 
+    // Only checks the property itself, so empty lists match.
+    pub fn is_not_null(property: Property) -> Filter {
+        Self::not(Self::null(property))
+    }
+
+    pub fn eq(property: Property, value: FilterValue) -> Result<Filter> {
+        Self::compare(property, value, Comparison::Equal)
+    }
+
+    pub fn gt(property: Property, value: FilterValue) -> Result<Filter> {
+        Self::compare(property, value, Comparison::Greater)
+    }
+
+    pub fn gte(property: Property, value: FilterValue) -> Result<Filter> {
+        Self::compare(property, value, Comparison::GreaterOrEqual)
+    }
+
+    pub fn lt(property: Property, value: FilterValue) -> Result<Filter> {
+        Self::compare(property, value, Comparison::Less)
+    }
+
+    pub fn lte(property: Property, value: FilterValue) -> Result<Filter> {
+        Self::compare(property, value, Comparison::LessOrEqual)
+    }
+
+    // Comparisons on lists match if at least one element satisfies them, so null and empty
+    // lists never match.
+    fn compare(property: Property, value: FilterValue, cmp: Comparison) -> Result<Filter> {
+        let data_type = property
+            .data_type
+            .get_element_type()
+            .unwrap_or(property.data_type);
+        match (data_type, value) {
+            (
+                DataType::Byte | DataType::Int | DataType::Long | DataType::DateTime,
+                FilterValue::Long(value),
+            ) => Self::compare_long(property, value, cmp),
+            (DataType::Float | DataType::Double, FilterValue::Long(value)) => {
+                Self::compare_double(property, value as f64, cmp)
+            }
+            (DataType::Float | DataType::Double, FilterValue::Double(value)) => {
+                Self::compare_double(property, value, cmp)
+            }
+            _ => illegal_arg("Property does not support this filter."),
+        }
+    }
+
+    fn compare_long(property: Property, value: i64, cmp: Comparison) -> Result<Filter> {
+        let bounds = match cmp {
+            Comparison::Equal => Some((value, value)),
+            Comparison::Greater => value.checked_add(1).map(|lower| (lower, i64::MAX)),
+            Comparison::GreaterOrEqual => Some((value, i64::MAX)),
+            Comparison::Less => value.checked_sub(1).map(|upper| (i64::MIN, upper)),
+            Comparison::LessOrEqual => Some((i64::MIN, value)),
+        };
+        let (lower, upper) = if let Some(bounds) = bounds {
+            bounds
+        } else {
+            return Ok(Self::stat(false));
+        };
+
+        let (min, max) = match property.data_type {
+            DataType::Byte | DataType::ByteList => (u8::MIN as i64, u8::MAX as i64),
+            DataType::Int | DataType::IntList => (i32::MIN as i64, i32::MAX as i64),
+            _ => (i64::MIN, i64::MAX),
+        };
+        if lower > max || upper < min || lower > upper {
+            return Ok(Self::stat(false));
+        }
+        let lower = lower.max(min);
+        let upper = upper.min(max);
+        match property.data_type {
+            DataType::Byte | DataType::ByteList => Self::byte(property, lower as u8, upper as u8),
+            DataType::Int | DataType::IntList => Self::int(property, lower as i32, upper as i32),
+            DataType::DateTime | DataType::DateTimeList => Self::date_time(property, lower, upper),
+            _ => Self::long(property, lower, upper),
+        }
+    }
+
+    // Float conditions exclude both bounds, treat a NaN lower bound as "include null" and a
+    // positive infinite upper bound as unbounded. Null is NaN and sorts before all values.
+    fn compare_double(property: Property, value: f64, cmp: Comparison) -> Result<Filter> {
+        let single = matches!(property.data_type, DataType::Float | DataType::FloatList);
+        let list = property.data_type.get_element_type().is_some();
+        let value = if single { value as f32 as f64 } else { value };
+        let next_up = |value: f64| {
+            if single {
+                next_float!(value as f32, f32, true) as f64
+            } else {
+                next_float!(value, f64, true)
+            }
+        };
+        let next_down = |value: f64| {
+            if single {
+                next_float!(value as f32, f32, false) as f64
+            } else {
+                next_float!(value, f64, false)
+            }
+        };
+        let between = |lower: f64, upper: f64| {
+            if single {
+                Self::float(property, lower as f32, upper as f32)
+            } else {
+                Self::double(property, lower, upper)
+            }
+        };
+        // Matches values outside of the bounds. For lists at least one element has to be
+        // outside, which a negated any condition would not guarantee.
+        let outside = |lower: f64, upper: f64| {
+            if !list {
+                Ok(Self::not(between(lower, upper)?))
+            } else {
+                let all = if single {
+                    Self::all_float_between(property, lower as f32, upper as f32)?
+                } else {
+                    Self::all_double_between(property, lower, upper)?
+                };
+                Ok(Self::and(vec![Self::is_not_null(property), Self::not(all)]))
+            }
+        };
+
+        if value.is_nan() {
+            return match cmp {
+                Comparison::Equal | Comparison::LessOrEqual => between(f64::NAN, f64::NAN),
+                Comparison::Greater => outside(f64::NAN, f64::NAN),
+                Comparison::GreaterOrEqual if !list => Ok(Self::stat(true)),
+                Comparison::GreaterOrEqual => outside(f64::INFINITY, f64::INFINITY),
+                Comparison::Less => Ok(Self::stat(false)),
+            };
+        }
+
+        match cmp {
+            Comparison::Equal => {
+                // Both bounds have to be checked separately, for lists they may be satisfied
+                // by different elements.
+                if value == f64::NEG_INFINITY || next_up(value) == f64::INFINITY {
+                    let gte = Self::compare_double(property, value, Comparison::GreaterOrEqual)?;
+                    let lte = Self::compare_double(property, value, Comparison::LessOrEqual)?;
+                    Ok(Self::and(vec![gte, lte]))
+                } else {
+                    between(next_down(value), next_up(value))
+                }
+            }
+            Comparison::Greater => {
+                if value == f64::INFINITY {
+                    Ok(Self::stat(false))
+                } else {
+                    between(value, f64::INFINITY)
+                }
+            }
+            Comparison::GreaterOrEqual => {
+                if value == f64::NEG_INFINITY {
+                    Self::compare_double(property, f64::NAN, Comparison::Greater)
+                } else {
+                    between(next_down(value), f64::INFINITY)
+                }
+            }
+            Comparison::Less => {
+                if value == f64::INFINITY {
+                    outside(next_down(value), f64::INFINITY)
+                } else {
+                    between(f64::NAN, value)
+                }
+            }
+            Comparison::LessOrEqual => {
+                if value == f64::INFINITY {
+                    Self::compare_double(property, f64::NAN, Comparison::GreaterOrEqual)
+                } else if next_up(value) == f64::INFINITY {
+                    outside(value, f64::INFINITY)
+                } else {
+                    between(f64::NAN, next_up(value))
+                }
+            }
+        }
+    }
+
+    pub(crate) fn normalize(self) -> Filter {
+        Filter(self.0.normalize())
+    }
+
+    // Matches of all text_contains filters that are not negated, ordered by property and offset.
+    pub(crate) fn get_text_matches(&self, object: IsarObject) -> Vec<TextMatch> {
+        let mut matches = vec![];
+        self.0.collect_text_matches(object, &mut matches);
+        matches.sort_by_key(|m| (m.property.offset, m.start));
+        matches.dedup();
+        matches
+    }
+
+    pub(crate) fn get_properties(&self) -> Vec<Property> {
+        let mut properties = vec![];
+        self.0.collect_properties(&mut properties);
+        properties
+    }
+
+    pub(crate) fn evaluate_analyzed(
+        &self,
+        id: &IdKey,
+        object: IsarObject,
+        cursors: Option<&IsarCursors>,
+        analysis: &mut FilterAnalysis,
+    ) -> Result<bool> {
+        self.0.evaluate_analyzed(id, object, cursors, analysis)
+    }
+
+    pub(crate) fn describe(&self, analysis: &FilterAnalysis) -> Value {
+        self.0.describe(analysis)
+    }
+
+    pub(crate) fn get_static(&self) -> Option<bool> {
+        if let FilterCond::Static(StaticCond { value }) = self.0 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn evaluate(
         &self,
         id: &IdKey,
@@ -227,33 +727,337 @@ enum FilterCond {
     LongBetween(LongBetweenCond),
     FloatBetween(FloatBetweenCond),
     DoubleBetween(DoubleBetweenCond),
+    DateTimeBetween(DateTimeBetweenCond),
+    GeoWithin(GeoWithinCond),
 
     StringBetween(StringBetweenCond),
     StringStartsWith(StringStartsWithCond),
     StringEndsWith(StringEndsWithCond),
     StringContains(StringContainsCond),
     StringMatches(StringMatchesCond),
+    StringIsEmpty(StringIsEmptyCond),
+    StringLengthBetween(StringLengthBetweenCond),
+    TextContains(TextContainsCond),
+
+    ByteListEquals(ByteListEqualsCond),
+    ByteListStartsWith(ByteListStartsWithCond),
 
     AnyByteBetween(AnyByteBetweenCond),
     AnyIntBetween(AnyIntBetweenCond),
     AnyLongBetween(AnyLongBetweenCond),
     AnyFloatBetween(AnyFloatBetweenCond),
     AnyDoubleBetween(AnyDoubleBetweenCond),
+    AnyDateTimeBetween(AnyDateTimeBetweenCond),
 
     AnyStringBetween(AnyStringBetweenCond),
     AnyStringStartsWith(AnyStringStartsWithCond),
     AnyStringEndsWith(AnyStringEndsWithCond),
     AnyStringContains(AnyStringContainsCond),
     AnyStringMatches(AnyStringMatchesCond),
+    AnyStringIsEmpty(AnyStringIsEmptyCond),
+    AnyStringLengthBetween(AnyStringLengthBetweenCond),
+
+    AllByteBetween(AllByteBetweenCond),
+    AllIntBetween(AllIntBetweenCond),
+    AllLongBetween(AllLongBetweenCond),
+    AllFloatBetween(AllFloatBetweenCond),
+    AllDoubleBetween(AllDoubleBetweenCond),
+    AllDateTimeBetween(AllDateTimeBetweenCond),
+    AllStringBetween(AllStringBetweenCond),
+
+    ListIsEmpty(ListIsEmptyCond),
+    AnyNullElement(AnyNullElementCond),
 
     Null(NullCond),
     And(AndCond),
     Or(OrCond),
     Not(NotCond),
     Static(StaticCond),
+    Embedded(EmbeddedCond),
     Link(LinkCond),
 }
 
+impl FilterCond {
+    // Pushes negations down to the leaves using De Morgan's laws so that the top of the
+    // filter tree consists of positive And / Or conditions. Static conditions and
+    // contradictory bounds are folded on the way.
+    fn normalize(self) -> FilterCond {
+        match self {
+            FilterCond::Not(NotCond { filter }) => filter.negate(),
+            FilterCond::And(AndCond { filters }) => {
+                Self::fold_and(filters.into_iter().map(|f| f.normalize()))
+            }
+            FilterCond::Or(OrCond { filters }) => {
+                Self::fold_or(filters.into_iter().map(|f| f.normalize()))
+            }
+            FilterCond::Embedded(EmbeddedCond { property, filter }) => match filter.normalize() {
+                FilterCond::Static(StaticCond { value: false }) => Self::stat(false),
+                filter => FilterCond::Embedded(EmbeddedCond {
+                    property,
+                    filter: Box::new(filter),
+                }),
+            },
+            FilterCond::Link(LinkCond { link, filter }) => match filter.normalize() {
+                FilterCond::Static(StaticCond { value: false }) => Self::stat(false),
+                filter => FilterCond::Link(LinkCond {
+                    link,
+                    filter: Box::new(filter),
+                }),
+            },
+            filter if filter.is_contradiction() => Self::stat(false),
+            filter => filter,
+        }
+    }
+
+    fn negate(self) -> FilterCond {
+        match self {
+            FilterCond::Not(NotCond { filter }) => filter.normalize(),
+            FilterCond::And(AndCond { filters }) => {
+                Self::fold_or(filters.into_iter().map(|f| f.negate()))
+            }
+            FilterCond::Or(OrCond { filters }) => {
+                Self::fold_and(filters.into_iter().map(|f| f.negate()))
+            }
+            filter => match filter.normalize() {
+                FilterCond::Static(StaticCond { value }) => Self::stat(!value),
+                filter => FilterCond::Not(NotCond {
+                    filter: Box::new(filter),
+                }),
+            },
+        }
+    }
+
+    fn stat(value: bool) -> FilterCond {
+        FilterCond::Static(StaticCond { value })
+    }
+
+    fn fold_and(filters: impl Iterator<Item = FilterCond>) -> FilterCond {
+        let mut folded = vec![];
+        for filter in filters {
+            match filter {
+                FilterCond::Static(StaticCond { value: true }) => {}
+                FilterCond::Static(StaticCond { value: false }) => return Self::stat(false),
+                FilterCond::And(AndCond { filters }) => folded.extend(filters),
+                filter => folded.push(filter),
+            }
+        }
+        match folded.len() {
+            0 => Self::stat(true),
+            1 => folded.pop().unwrap(),
+            _ => FilterCond::And(AndCond { filters: folded }),
+        }
+    }
+
+    fn fold_or(filters: impl Iterator<Item = FilterCond>) -> FilterCond {
+        let mut folded = vec![];
+        for filter in filters {
+            match filter {
+                FilterCond::Static(StaticCond { value: false }) => {}
+                FilterCond::Static(StaticCond { value: true }) => return Self::stat(true),
+                FilterCond::Or(OrCond { filters }) => folded.extend(filters),
+                filter => folded.push(filter),
+            }
+        }
+        match folded.len() {
+            0 => Self::stat(false),
+            1 => folded.pop().unwrap(),
+            _ => FilterCond::Or(OrCond { filters: folded }),
+        }
+    }
+
+    fn leaf(&self) -> Option<(&'static str, Property)> {
+        macro_rules! leaf {
+            ($($variant:ident),*) => {
+                match self {
+                    $(FilterCond::$variant(cond) => Some((stringify!($variant), cond.property)),)*
+                    _ => None,
+                }
+            };
+        }
+        leaf!(
+            ByteBetween,
+            IntBetween,
+            LongBetween,
+            FloatBetween,
+            DoubleBetween,
+            DateTimeBetween,
+            GeoWithin,
+            StringBetween,
+            StringStartsWith,
+            StringEndsWith,
+            StringContains,
+            StringMatches,
+            StringIsEmpty,
+            StringLengthBetween,
+            TextContains,
+            ByteListEquals,
+            ByteListStartsWith,
+            AnyByteBetween,
+            AnyIntBetween,
+            AnyLongBetween,
+            AnyFloatBetween,
+            AnyDoubleBetween,
+            AnyDateTimeBetween,
+            AnyStringBetween,
+            AnyStringStartsWith,
+            AnyStringEndsWith,
+            AnyStringContains,
+            AnyStringMatches,
+            AnyStringIsEmpty,
+            AnyStringLengthBetween,
+            AllByteBetween,
+            AllIntBetween,
+            AllLongBetween,
+            AllFloatBetween,
+            AllDoubleBetween,
+            AllDateTimeBetween,
+            AllStringBetween,
+            ListIsEmpty,
+            AnyNullElement,
+            Null
+        )
+    }
+
+    // Properties of linked collections are not collected.
+    fn collect_properties(&self, properties: &mut Vec<Property>) {
+        match self {
+            FilterCond::And(AndCond { filters }) | FilterCond::Or(OrCond { filters }) => {
+                for filter in filters {
+                    filter.collect_properties(properties);
+                }
+            }
+            FilterCond::Not(NotCond { filter }) => filter.collect_properties(properties),
+            FilterCond::Embedded(EmbeddedCond { property, .. }) => properties.push(*property),
+            _ => {
+                if let Some((_, property)) = self.leaf() {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    fn collect_text_matches(&self, object: IsarObject, matches: &mut Vec<TextMatch>) {
+        match self {
+            FilterCond::And(AndCond { filters }) | FilterCond::Or(OrCond { filters }) => {
+                for filter in filters {
+                    filter.collect_text_matches(object, matches);
+                }
+            }
+            FilterCond::TextContains(cond) => cond.collect_matches(object, matches),
+            _ => {}
+        }
+    }
+
+    fn evaluate_analyzed(
+        &self,
+        id: &IdKey,
+        object: IsarObject,
+        cursors: Option<&IsarCursors>,
+        analysis: &mut FilterAnalysis,
+    ) -> Result<bool> {
+        let start = Instant::now();
+        let result = match self {
+            FilterCond::And(AndCond { filters }) | FilterCond::Or(OrCond { filters }) => {
+                let is_and = matches!(self, FilterCond::And(_));
+                analysis
+                    .children
+                    .resize_with(filters.len(), FilterAnalysis::default);
+                let mut result = is_and;
+                for (filter, child) in filters.iter().zip(analysis.children.iter_mut()) {
+                    if filter.evaluate_analyzed(id, object, cursors, child)? != is_and {
+                        result = !is_and;
+                        break;
+                    }
+                }
+                result
+            }
+            FilterCond::Not(NotCond { filter }) => {
+                analysis.children.resize_with(1, FilterAnalysis::default);
+                !filter.evaluate_analyzed(id, object, cursors, &mut analysis.children[0])?
+            }
+            _ => self.evaluate(id, object, cursors)?,
+        };
+        analysis.evaluations += 1;
+        if result {
+            analysis.matches += 1;
+        }
+        analysis.duration += start.elapsed();
+        Ok(result)
+    }
+
+    fn describe(&self, analysis: &FilterAnalysis) -> Value {
+        let (name, property) = match self {
+            FilterCond::IdBetween(_) => ("IdBetween", None),
+            FilterCond::And(_) => ("And", None),
+            FilterCond::Or(_) => ("Or", None),
+            FilterCond::Not(_) => ("Not", None),
+            FilterCond::Static(_) => ("Static", None),
+            FilterCond::Embedded(EmbeddedCond { property, .. }) => {
+                ("Embedded", Some(property.offset))
+            }
+            FilterCond::Link(_) => ("Link", None),
+            _ => {
+                let (name, property) = self.leaf().unwrap();
+                (name, Some(property.offset))
+            }
+        };
+        let children = match self {
+            FilterCond::And(AndCond { filters }) | FilterCond::Or(OrCond { filters }) => filters
+                .iter()
+                .zip(&analysis.children)
+                .map(|(filter, child)| filter.describe(child))
+                .collect(),
+            FilterCond::Not(NotCond { filter }) => analysis
+                .children
+                .first()
+                .map(|child| vec![filter.describe(child)])
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+        json!({
+            "type": name,
+            "property_offset": property,
+            "evaluations": analysis.evaluations,
+            "matches": analysis.matches,
+            "time_us": analysis.duration.as_micros() as u64,
+            "children": children,
+        })
+    }
+
+    fn is_contradiction(&self) -> bool {
+        fn float_contradiction<T: num_traits::Float>(lower: T, upper: T) -> bool {
+            !lower.is_nan() && lower >= upper && upper != T::infinity()
+        }
+        fn string_contradiction(lower: &Option<Vec<u8>>, upper: &Option<Vec<u8>>) -> bool {
+            match (lower, upper) {
+                (Some(lower), Some(upper)) => lower > upper,
+                (Some(_), None) => true,
+                _ => false,
+            }
+        }
+        match self {
+            FilterCond::IdBetween(c) => c.lower > c.upper,
+            FilterCond::ByteBetween(c) => c.lower > c.upper,
+            FilterCond::IntBetween(c) => c.lower > c.upper,
+            FilterCond::LongBetween(c) => c.lower > c.upper,
+            FilterCond::FloatBetween(c) => float_contradiction(c.lower, c.upper),
+            FilterCond::DoubleBetween(c) => float_contradiction(c.lower, c.upper),
+            FilterCond::DateTimeBetween(c) => c.lower > c.upper,
+            FilterCond::StringBetween(c) => string_contradiction(&c.lower, &c.upper),
+            FilterCond::StringLengthBetween(c) => c.lower > c.upper,
+            FilterCond::AnyByteBetween(c) => c.lower > c.upper,
+            FilterCond::AnyIntBetween(c) => c.lower > c.upper,
+            FilterCond::AnyLongBetween(c) => c.lower > c.upper,
+            FilterCond::AnyFloatBetween(c) => float_contradiction(c.lower, c.upper),
+            FilterCond::AnyDoubleBetween(c) => float_contradiction(c.lower, c.upper),
+            FilterCond::AnyDateTimeBetween(c) => c.lower > c.upper,
+            FilterCond::AnyStringBetween(c) => string_contradiction(&c.lower, &c.upper),
+            FilterCond::AnyStringLengthBetween(c) => c.lower > c.upper,
+            _ => false,
+        }
+    }
+}
+
 #[enum_dispatch(FilterCond)]
 trait Condition {
     fn evaluate(
@@ -312,6 +1116,8 @@ filter_between_struct!(IntBetweenCond, Int, i32);
 primitive_filter_between!(IntBetweenCond, read_int);
 filter_between_struct!(LongBetweenCond, Long, i64);
 primitive_filter_between!(LongBetweenCond, read_long);
+filter_between_struct!(DateTimeBetweenCond, DateTime, i64);
+primitive_filter_between!(DateTimeBetweenCond, read_date_time);
 
 #[macro_export]
 macro_rules! primitive_filter_between_list {
@@ -357,6 +1163,56 @@ filter_between_struct!(AnyIntBetweenCond, Int, i32);
 primitive_filter_between_list!(AnyIntBetweenCond, read_int_list);
 filter_between_struct!(AnyLongBetweenCond, Long, i64);
 primitive_filter_between_list!(AnyLongBetweenCond, read_long_list);
+filter_between_struct!(AnyDateTimeBetweenCond, DateTime, i64);
+primitive_filter_between_list!(AnyDateTimeBetweenCond, read_date_time_list);
+
+#[macro_export]
+macro_rules! primitive_filter_between_all {
+    ($name:ident, $prop_accessor:ident) => {
+        impl Condition for $name {
+            fn evaluate(
+                &self,
+                _id: &IdKey,
+                object: IsarObject,
+                _: Option<&IsarCursors>,
+            ) -> Result<bool> {
+                let vals = object.$prop_accessor(self.property);
+                if let Some(vals) = vals {
+                    for val in vals {
+                        if self.lower > val || self.upper < val {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    };
+}
+
+filter_between_struct!(AllByteBetweenCond, Byte, u8);
+
+impl Condition for AllByteBetweenCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let vals = object.read_byte_list(self.property);
+        if let Some(vals) = vals {
+            Ok(vals
+                .iter()
+                .all(|val| self.lower <= *val && self.upper >= *val))
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+filter_between_struct!(AllIntBetweenCond, Int, i32);
+primitive_filter_between_all!(AllIntBetweenCond, read_int_list);
+filter_between_struct!(AllLongBetweenCond, Long, i64);
+primitive_filter_between_all!(AllLongBetweenCond, read_long_list);
+filter_between_struct!(AllDateTimeBetweenCond, DateTime, i64);
+primitive_filter_between_all!(AllDateTimeBetweenCond, read_date_time_list);
 
 #[macro_export]
 macro_rules! float_filter_between {
@@ -404,6 +1260,32 @@ float_filter_between_list!(AnyFloatBetweenCond, read_float_list);
 filter_between_struct!(AnyDoubleBetweenCond, Double, f64);
 float_filter_between_list!(AnyDoubleBetweenCond, read_double_list);
 
+#[macro_export]
+macro_rules! float_filter_between_all {
+    ($name:ident, $prop_accessor:ident) => {
+        impl Condition for $name {
+            fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+                let vals = object.$prop_accessor(self.property);
+                if let Some(vals) = vals {
+                    for val in vals {
+                        if !float_filter_between!(eval val, self.lower, self.upper) {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    };
+}
+
+filter_between_struct!(AllFloatBetweenCond, Float, f32);
+float_filter_between_all!(AllFloatBetweenCond, read_float_list);
+filter_between_struct!(AllDoubleBetweenCond, Double, f64);
+float_filter_between_all!(AllDoubleBetweenCond, read_double_list);
+
 #[derive(Clone)]
 struct StringBetweenCond {
     property: Property,
@@ -487,6 +1369,33 @@ impl Condition for AnyStringBetweenCond {
     }
 }
 
+#[derive(Clone)]
+struct AllStringBetweenCond {
+    property: Property,
+    lower: Option<Vec<u8>>,
+    upper: Option<Vec<u8>>,
+    case_sensitive: bool,
+}
+
+impl Condition for AllStringBetweenCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let list = object.read_string_list(self.property);
+        if let Some(list) = list {
+            let result = list.into_iter().all(|value| {
+                string_between(
+                    value,
+                    self.lower.as_deref(),
+                    self.upper.as_deref(),
+                    self.case_sensitive,
+                )
+            });
+            Ok(result)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! string_filter_struct {
     ($name:ident) => {
@@ -567,6 +1476,135 @@ string_filter!(StringEndsWith);
 string_filter!(StringContains);
 string_filter!(StringMatches);
 
+#[derive(Clone)]
+struct StringIsEmptyCond {
+    property: Property,
+}
+
+impl Condition for StringIsEmptyCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        Ok(object.read_length(self.property) == Some(0))
+    }
+}
+
+#[derive(Clone)]
+struct AnyStringIsEmptyCond {
+    property: Property,
+}
+
+impl Condition for AnyStringIsEmptyCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let list = object.read_string_list(self.property);
+        if let Some(list) = list {
+            Ok(list.contains(&Some("")))
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+// String lengths are compared in UTF-8 bytes as stored in the object.
+filter_between_struct!(StringLengthBetweenCond, String, usize);
+
+impl Condition for StringLengthBetweenCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let length = object.read_length(self.property);
+        Ok(matches!(length, Some(length) if self.lower <= length && self.upper >= length))
+    }
+}
+
+filter_between_struct!(AnyStringLengthBetweenCond, String, usize);
+
+impl Condition for AnyStringLengthBetweenCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let list = object.read_string_list(self.property);
+        if let Some(list) = list {
+            for value in list.into_iter().flatten() {
+                if self.lower <= value.len() && self.upper >= value.len() {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[derive(Clone)]
+struct ListIsEmptyCond {
+    property: Property,
+}
+
+impl Condition for ListIsEmptyCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        Ok(object.read_length(self.property) == Some(0))
+    }
+}
+
+#[derive(Clone)]
+struct AnyNullElementCond {
+    property: Property,
+}
+
+impl Condition for AnyNullElementCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let property = self.property;
+        let any_null = match property.data_type {
+            DataType::ByteList => object
+                .read_byte_list(property)
+                .map(|list| list.contains(&IsarObject::NULL_BYTE)),
+            DataType::IntList => object
+                .read_int_list(property)
+                .map(|list| list.contains(&IsarObject::NULL_INT)),
+            DataType::LongList => object
+                .read_long_list(property)
+                .map(|list| list.contains(&IsarObject::NULL_LONG)),
+            DataType::DateTimeList => object
+                .read_date_time_list(property)
+                .map(|list| list.contains(&IsarObject::NULL_DATE_TIME)),
+            DataType::FloatList => object
+                .read_float_list(property)
+                .map(|list| list.iter().any(|value| value.is_nan())),
+            DataType::DoubleList => object
+                .read_double_list(property)
+                .map(|list| list.iter().any(|value| value.is_nan())),
+            DataType::StringList => object
+                .read_string_list(property)
+                .map(|list| list.contains(&None)),
+            DataType::ObjectList => object
+                .read_object_list(property)
+                .map(|list| list.iter().any(|object| object.is_none())),
+            _ => None,
+        };
+        Ok(any_null.unwrap_or(false))
+    }
+}
+
+#[derive(Clone)]
+struct ByteListEqualsCond {
+    property: Property,
+    value: Vec<u8>,
+}
+
+impl Condition for ByteListEqualsCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let bytes = object.read_byte_list(self.property);
+        Ok(bytes == Some(self.value.as_slice()))
+    }
+}
+
+#[derive(Clone)]
+struct ByteListStartsWithCond {
+    property: Property,
+    value: Vec<u8>,
+}
+
+impl Condition for ByteListStartsWithCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        let bytes = object.read_byte_list(self.property);
+        Ok(matches!(bytes, Some(bytes) if bytes.starts_with(&self.value)))
+    }
+}
+
 #[derive(Clone)]
 struct NullCond {
     property: Property,
@@ -652,6 +1690,107 @@ impl Condition for StaticCond {
     }
 }
 
+#[derive(Clone)]
+struct TextContainsCond {
+    property: Property,
+    index: IsarIndex,
+    keys: Vec<IndexKey>,
+}
+
+impl Condition for TextContainsCond {
+    fn evaluate(
+        &self,
+        id: &IdKey,
+        object: IsarObject,
+        cursors: Option<&IsarCursors>,
+    ) -> Result<bool> {
+        if let Some(cursors) = cursors {
+            for key in &self.keys {
+                if !self.index.contains(cursors, key, id)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        } else {
+            // Without cursors the words of the object are compared to the searched words.
+            let object_keys = self.object_keys(object);
+            Ok(self
+                .keys
+                .iter()
+                .all(|key| object_keys.iter().any(|(_, k)| k == key)))
+        }
+    }
+}
+
+impl TextContainsCond {
+    // The byte range of every part of the string together with its index key.
+    fn object_keys(&self, object: IsarObject) -> Vec<(Range<usize>, IndexKey)> {
+        let index_property = &self.index.properties[0];
+        let value = if let Some(value) = object.read_string(self.property) {
+            value
+        } else {
+            return vec![];
+        };
+        index_property
+            .split_string(value)
+            .into_iter()
+            .map(|(part, stemmed)| {
+                let start = part.as_ptr() as usize - value.as_ptr() as usize;
+                let mut key = IndexKey::new();
+                index_property.add_string_to_key(&mut key, Some(&stemmed));
+                (start..start + part.len(), key)
+            })
+            .collect()
+    }
+
+    fn collect_matches(&self, object: IsarObject, matches: &mut Vec<TextMatch>) {
+        for (range, key) in self.object_keys(object) {
+            if self.keys.contains(&key) {
+                matches.push(TextMatch {
+                    property: self.property,
+                    start: range.start,
+                    end: range.end,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct GeoWithinCond {
+    property: Property,
+    area: GeoArea,
+}
+
+impl Condition for GeoWithinCond {
+    fn evaluate(&self, _id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        Ok(self.area.contains(object.read_geo_point(self.property)))
+    }
+}
+
+#[derive(Clone)]
+struct EmbeddedCond {
+    property: Property,
+    filter: Box<FilterCond>,
+}
+
+impl Condition for EmbeddedCond {
+    fn evaluate(&self, id: &IdKey, object: IsarObject, _: Option<&IsarCursors>) -> Result<bool> {
+        if self.property.data_type == DataType::Object {
+            if let Some(embedded) = object.read_object(self.property) {
+                return self.filter.evaluate(id, embedded, None);
+            }
+        } else if let Some(list) = object.read_object_list(self.property) {
+            for embedded in list.into_iter().flatten() {
+                if self.filter.evaluate(id, embedded, None)? {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
 #[derive(Clone)]
 struct LinkCond {
     link: IsarLink,
@@ -692,3 +1831,107 @@ impl LinkCond {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not(filter: Filter) -> Filter {
+        Filter::not(filter)
+    }
+
+    #[test]
+    fn test_normalize_double_negation() {
+        let filter = not(not(Filter::id(1, 2).unwrap())).normalize();
+        assert!(matches!(filter.0, FilterCond::IdBetween(_)));
+
+        let filter = not(not(not(Filter::id(1, 2).unwrap()))).normalize();
+        assert!(matches!(filter.0, FilterCond::Not(_)));
+    }
+
+    #[test]
+    fn test_normalize_de_morgan() {
+        let filter = not(Filter::or(vec![
+            Filter::id(1, 2).unwrap(),
+            not(Filter::id(3, 4).unwrap()),
+        ]))
+        .normalize();
+        if let FilterCond::And(AndCond { filters }) = filter.0 {
+            assert!(matches!(filters[0], FilterCond::Not(_)));
+            assert!(matches!(filters[1], FilterCond::IdBetween(_)));
+        } else {
+            panic!("Expected And");
+        }
+
+        let filter = not(Filter::and(vec![
+            not(Filter::id(1, 2).unwrap()),
+            not(Filter::id(3, 4).unwrap()),
+        ]))
+        .normalize();
+        if let FilterCond::Or(OrCond { filters }) = filter.0 {
+            assert!(matches!(filters[0], FilterCond::IdBetween(_)));
+            assert!(matches!(filters[1], FilterCond::IdBetween(_)));
+        } else {
+            panic!("Expected Or");
+        }
+    }
+
+    #[test]
+    fn test_normalize_static() {
+        let id = || Filter::id(1, 2).unwrap();
+
+        let filter = Filter::and(vec![Filter::stat(true), id()]).normalize();
+        assert!(matches!(filter.0, FilterCond::IdBetween(_)));
+
+        let filter = Filter::and(vec![Filter::stat(false), id()]).normalize();
+        assert_eq!(filter.get_static(), Some(false));
+
+        let filter = Filter::or(vec![Filter::stat(true), id()]).normalize();
+        assert_eq!(filter.get_static(), Some(true));
+
+        let filter = Filter::or(vec![Filter::stat(false), id()]).normalize();
+        assert!(matches!(filter.0, FilterCond::IdBetween(_)));
+
+        let filter = Filter::and(vec![]).normalize();
+        assert_eq!(filter.get_static(), Some(true));
+
+        let filter = not(Filter::or(vec![])).normalize();
+        assert_eq!(filter.get_static(), Some(true));
+
+        let filter = Filter::and(vec![id(), Filter::and(vec![id(), id()])]).normalize();
+        assert!(matches!(filter.0, FilterCond::And(AndCond { ref filters }) if filters.len() == 3));
+    }
+
+    #[test]
+    fn test_normalize_contradiction() {
+        let long = Property::new(DataType::Long, 2);
+        let double = Property::new(DataType::Double, 2);
+        let string = Property::new(DataType::String, 2);
+
+        let contradictions = vec![
+            Filter::id(2, 1).unwrap(),
+            Filter::long(long, 5, 4).unwrap(),
+            Filter::double(double, 2.0, 1.0).unwrap(),
+            Filter::double(double, 1.0, 1.0).unwrap(),
+            Filter::string(string, Some("b"), Some("a"), true).unwrap(),
+            Filter::string(string, Some("a"), None, true).unwrap(),
+            Filter::and(vec![Filter::id(1, 2).unwrap(), Filter::id(2, 1).unwrap()]),
+        ];
+        for filter in contradictions {
+            assert_eq!(filter.normalize().get_static(), Some(false));
+        }
+
+        let valid = vec![
+            Filter::id(1, 1).unwrap(),
+            Filter::double(double, f64::NAN, 1.0).unwrap(),
+            Filter::double(double, 1.0, f64::INFINITY).unwrap(),
+            Filter::string(string, None, None, true).unwrap(),
+        ];
+        for filter in valid {
+            assert_eq!(filter.normalize().get_static(), None);
+        }
+
+        let filter = not(Filter::id(2, 1).unwrap()).normalize();
+        assert_eq!(filter.get_static(), Some(true));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::cursor::IsarCursors;
+use crate::error::Result;
+use crate::id_key::IdKey;
+use crate::index::index_key::IndexKey;
+use crate::index::IsarIndex;
+use crate::mdbx::db::Db;
+use crate::object::geo::GeoArea;
+use crate::object::isar_object::IsarObject;
+use crate::query::index_where_clause::IndexWhereClause;
+use crate::query::Sort;
+use intmap::IntMap;
+
+// Looks up the geohash ranges that cover the area in a GeoPoint index. Points of the ranges
+// that are outside of the area are skipped.
+#[derive(Clone)]
+pub(crate) struct GeoWhereClause {
+    index: IsarIndex,
+    area: GeoArea,
+    ranges: Vec<IndexWhereClause>,
+    sort: Sort,
+}
+
+impl GeoWhereClause {
+    pub fn new(db: Db, index: IsarIndex, area: GeoArea) -> Result<Self> {
+        let mut ranges = vec![];
+        for (lower, upper) in area.covering_ranges() {
+            let mut lower_key = IndexKey::new();
+            lower_key.add_hash(lower);
+            let mut upper_key = IndexKey::new();
+            upper_key.add_hash(upper);
+            let range = IndexWhereClause::new(
+                db,
+                index.clone(),
+                lower_key,
+                upper_key,
+                false,
+                Sort::Ascending,
+            )?;
+            ranges.push(range);
+        }
+        Ok(GeoWhereClause {
+            index,
+            area,
+            ranges,
+            sort: Sort::Ascending,
+        })
+    }
+
+    pub fn set_sort(&mut self, sort: Sort) {
+        if sort != self.sort {
+            self.ranges.reverse();
+            self.sort = sort;
+        }
+        for range in &mut self.ranges {
+            range.set_sort(sort);
+        }
+    }
+
+    pub fn object_matches(&self, object: IsarObject) -> bool {
+        let property = self.index.properties[0].property;
+        self.area.contains(object.read_geo_point(property))
+    }
+
+    pub fn estimate(&self, cursors: &IsarCursors) -> Result<usize> {
+        let mut entries = 0;
+        for range in &self.ranges {
+            entries += range.estimate(cursors)?;
+        }
+        Ok(entries)
+    }
+
+    pub fn index(&self) -> &IsarIndex {
+        &self.index
+    }
+
+    fn iter_matching<'txn, 'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        index_entries: &mut usize,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        for range in &self.ranges {
+            let result = range.iter(cursors, None, index_entries, &mut 0, |id_key, object| {
+                if self.object_matches(object) {
+                    callback(id_key, object)
+                } else {
+                    Ok(true)
+                }
+            })?;
+            if !result {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn iter_ids<'txn, 'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>) -> Result<bool>,
+    {
+        self.iter_matching(cursors, &mut 0, |id_key, _| callback(id_key))
+    }
+
+    pub fn iter<'txn, 'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        mut result_ids: Option<&mut IntMap<()>>,
+        index_entries: &mut usize,
+        offset: &mut usize,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        self.iter_matching(cursors, index_entries, |id_key, object| {
+            if let Some(result_ids) = result_ids.as_deref_mut() {
+                if !result_ids.insert(id_key.get_unsigned_id(), ()) {
+                    return Ok(true);
+                }
+            }
+            if *offset > 0 {
+                *offset -= 1;
+                return Ok(true);
+            }
+            callback(id_key, object)
+        })
+    }
+}
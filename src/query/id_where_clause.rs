@@ -24,10 +24,18 @@ impl IdWhereClause {
         }
     }
 
+    pub(crate) fn set_sort(&mut self, sort: Sort) {
+        self.sort = sort;
+    }
+
     pub fn is_empty(&self) -> bool {
         self.upper < self.lower
     }
 
+    pub(crate) fn is_full_range(&self) -> bool {
+        self.lower == i64::MIN && self.upper == i64::MAX
+    }
+
     pub(crate) fn id_matches(&self, oid: i64) -> bool {
         self.lower <= oid && self.upper >= oid
     }
@@ -57,12 +65,39 @@ impl IdWhereClause {
                         return Ok(true);
                     }
                 }
-                let object = IsarObject::from_bytes(object);
+                let object = cursors.read_object(object)?;
                 callback(id_key, object)
             },
         )
     }
 
+    pub(crate) fn estimate(&self, cursors: &IsarCursors) -> Result<usize> {
+        let lower_key = IdKey::new(self.lower);
+        let upper_key = IdKey::new(self.upper);
+        cursors.estimate_between(self.db, lower_key.as_bytes(), upper_key.as_bytes())
+    }
+
+    pub(crate) fn iter_ids<'txn, 'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(&IdKey) -> Result<bool>,
+    {
+        let lower_key = IdKey::new(self.lower);
+        let upper_key = IdKey::new(self.upper);
+        let mut cursor = cursors.get_cursor(self.db)?;
+        cursor.iter_between(
+            lower_key.as_bytes(),
+            upper_key.as_bytes(),
+            false,
+            false,
+            self.sort == Sort::Ascending,
+            |_, id_key, _| callback(&IdKey::from_bytes(id_key)),
+        )
+    }
+
     pub(crate) fn is_overlapping(&self, other: &Self) -> bool {
         (self.lower <= other.lower && self.upper >= other.upper)
             || (other.lower <= self.lower && other.upper >= self.upper)
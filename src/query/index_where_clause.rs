@@ -38,6 +38,14 @@ impl IndexWhereClause {
         })
     }
 
+    pub fn sort(&self) -> Sort {
+        self.sort
+    }
+
+    pub fn set_sort(&mut self, sort: Sort) {
+        self.sort = sort;
+    }
+
     pub fn object_matches(&self, object: IsarObject) -> bool {
         let mut key_matches = false;
         let key_builder = IndexKeyBuilder::new(&self.index.properties);
@@ -50,6 +58,15 @@ impl IndexWhereClause {
         key_matches
     }
 
+    pub fn estimate(&self, cursors: &IsarCursors) -> Result<usize> {
+        self.index
+            .estimate_between(cursors, &self.lower_key, &self.upper_key)
+    }
+
+    pub fn index(&self) -> &IsarIndex {
+        &self.index
+    }
+
     pub fn iter_ids<'txn, 'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
@@ -72,6 +89,8 @@ impl IndexWhereClause {
         &self,
         cursors: &IsarCursors<'txn, 'env>,
         mut result_ids: Option<&mut IntMap<()>>,
+        index_entries: &mut usize,
+        offset: &mut usize,
         mut callback: F,
     ) -> Result<bool>
     where
@@ -79,17 +98,23 @@ impl IndexWhereClause {
     {
         let mut data_cursor = cursors.get_cursor(self.db)?;
         self.iter_ids(cursors, |id_key| {
+            *index_entries += 1;
             if let Some(result_ids) = result_ids.as_deref_mut() {
                 if !result_ids.insert(id_key.get_unsigned_id(), ()) {
                     return Ok(true);
                 }
             }
+            // Skipped objects don't have to be read.
+            if *offset > 0 {
+                *offset -= 1;
+                return Ok(true);
+            }
 
             let entry = data_cursor.move_to(id_key.as_bytes())?;
             let (_, object) = entry.ok_or(IsarError::DbCorrupted {
                 message: "Could not find object specified in index.".to_string(),
             })?;
-            let object = IsarObject::from_bytes(object);
+            let object = cursors.read_object(object)?;
 
             callback(id_key, object)
         })
@@ -0,0 +1,111 @@
+use crate::collection::IsarCollection;
+use crate::error::{illegal_arg, Result};
+use crate::object::isar_object::IsarObject;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::txn::IsarTxn;
+use serde_json::Value;
+
+// Appends the object to a JSON array that has been started with '['.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_element(
+    buffer: &mut Vec<u8>,
+    first: bool,
+    collection: &IsarCollection,
+    id_name: Option<&str>,
+    id: i64,
+    object: IsarObject,
+    primitive_null: bool,
+    byte_as_bool: bool,
+) {
+    let mut json = JsonEncodeDecode::encode(
+        &collection.object_info,
+        object,
+        primitive_null,
+        byte_as_bool,
+    );
+    if let Some(id_name) = id_name {
+        json.insert(id_name.to_string(), Value::from(id));
+    }
+    if !first {
+        buffer.push(b',');
+    }
+    serde_json::to_writer(buffer, &json).unwrap();
+}
+
+// A JSON export that is read in chunks. Only the ids of the results are kept in memory and the
+// objects are serialized when a chunk is read. Objects deleted in the meantime are skipped.
+pub struct JsonExport {
+    ids: Vec<i64>,
+    next: usize,
+    opened: bool,
+    // Whether an element has been written.
+    started: bool,
+    finished: bool,
+    id_name: Option<String>,
+    primitive_null: bool,
+    byte_as_bool: bool,
+}
+
+impl JsonExport {
+    pub(crate) fn new(
+        ids: Vec<i64>,
+        id_name: Option<&str>,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Self {
+        JsonExport {
+            ids,
+            next: 0,
+            opened: false,
+            started: false,
+            finished: false,
+            id_name: id_name.map(|id_name| id_name.to_string()),
+            primitive_null,
+            byte_as_bool,
+        }
+    }
+
+    // Returns the next part of the JSON array or None once the whole array has been returned.
+    // Chunks are at least chunk_size bytes long except for the last one. A chunk size of 0 is
+    // rejected because the export would never make progress.
+    pub fn next_chunk(
+        &mut self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        chunk_size: usize,
+    ) -> Result<Option<Vec<u8>>> {
+        if chunk_size == 0 {
+            return illegal_arg("The chunk size must be greater than 0.");
+        }
+        if self.finished {
+            return Ok(None);
+        }
+        let mut chunk = vec![];
+        if !self.opened {
+            chunk.push(b'[');
+            self.opened = true;
+        }
+        while chunk.len() < chunk_size && self.next < self.ids.len() {
+            let id = self.ids[self.next];
+            self.next += 1;
+            if let Some(object) = collection.get(txn, id)? {
+                write_element(
+                    &mut chunk,
+                    !self.started,
+                    collection,
+                    self.id_name.as_deref(),
+                    id,
+                    object,
+                    self.primitive_null,
+                    self.byte_as_bool,
+                );
+                self.started = true;
+            }
+        }
+        if self.next == self.ids.len() {
+            chunk.push(b']');
+            self.finished = true;
+        }
+        Ok(Some(chunk))
+    }
+}
@@ -8,12 +8,31 @@ use intmap::IntMap;
 #[derive(Clone)]
 pub(crate) struct LinkWhereClause {
     link: IsarLink,
-    id: i64,
+    lower: i64,
+    upper: i64,
 }
 
 impl LinkWhereClause {
-    pub fn new(link: IsarLink, id: i64) -> Result<Self> {
-        Ok(LinkWhereClause { link, id })
+    pub fn new(link: IsarLink, lower: i64, upper: i64) -> Result<Self> {
+        Ok(LinkWhereClause { link, lower, upper })
+    }
+
+    pub fn iter_ids<F>(&self, cursors: &IsarCursors, mut callback: F) -> Result<bool>
+    where
+        F: FnMut(&IdKey) -> Result<bool>,
+    {
+        if self.lower == self.upper {
+            let id_key = IdKey::new(self.lower);
+            self.link
+                .iter_ids(cursors, &id_key, |_, id_key| callback(&id_key))
+        } else {
+            let lower_key = IdKey::new(self.lower);
+            let upper_key = IdKey::new(self.upper);
+            self.link
+                .iter_ids_between(cursors, &lower_key, &upper_key, |_, id_key| {
+                    callback(&id_key)
+                })
+        }
     }
 
     pub fn iter<'txn, 'env, F>(
@@ -25,14 +44,33 @@ impl LinkWhereClause {
     where
         F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
     {
-        let id_key = IdKey::new(self.id);
-        self.link.iter(cursors, &id_key, |id_key, object| {
+        let callback = |id_key: IdKey<'txn>, object| {
             if let Some(result_ids) = result_ids.as_deref_mut() {
                 if !result_ids.insert(id_key.get_unsigned_id(), ()) {
                     return Ok(true);
                 }
             }
             callback(id_key, object)
-        })
+        };
+        if self.lower == self.upper {
+            let id_key = IdKey::new(self.lower);
+            self.link.iter(cursors, &id_key, callback)
+        } else {
+            let lower_key = IdKey::new(self.lower);
+            let upper_key = IdKey::new(self.upper);
+            self.link
+                .iter_between(cursors, &lower_key, &upper_key, callback)
+        }
+    }
+
+    pub fn estimate(&self, cursors: &IsarCursors) -> Result<usize> {
+        let lower_key = IdKey::new(self.lower);
+        let upper_key = IdKey::new(self.upper);
+        self.link.estimate_between(cursors, &lower_key, &upper_key)
+    }
+
+    // Multiple sources may link to the same target.
+    pub(crate) fn has_duplicates(&self) -> bool {
+        self.lower != self.upper
     }
 }
@@ -1,23 +1,44 @@
 use intmap::IntMap;
 use serde_json::{json, Value};
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Write;
+use std::mem;
+use std::time::{Duration, Instant};
 
+use crate::checksum;
+use crate::collation::Collation;
 use crate::collection::IsarCollection;
 use crate::cursor::IsarCursors;
-use crate::error::Result;
+use crate::error::{illegal_arg, IsarError, Result};
 use crate::id_key::IdKey;
-use crate::object::isar_object::{IsarObject, Property};
+use crate::index::key_encoding::MAX_STRING_SIZE;
+use crate::mdbx::db::Db;
+use crate::object::csv_encode_decode::{CsvEncoder, CsvOptions};
+use crate::object::data_type::DataType;
+use crate::object::isar_object::{IsarObject, Property, PropertyValue};
 use crate::object::json_encode_decode::JsonEncodeDecode;
-use crate::query::filter::Filter;
+use crate::query::filter::{Filter, FilterAnalysis, TextMatch};
+use crate::query::json_export::{write_element, JsonExport};
+use crate::query::query_plan::{QueryPlan, WhereClausePlan};
+use crate::query::spill::{LazySpillEnv, MemoryBudget, SortBuffer, SpillMap};
 use crate::query::where_clause::WhereClause;
 use crate::txn::IsarTxn;
 
+pub mod aggregation;
 mod fast_wild_match;
 pub mod filter;
+mod geo_where_clause;
 mod id_where_clause;
 mod index_where_clause;
+pub mod json_export;
 mod link_where_clause;
+pub mod multi_query;
 pub mod query_builder;
+pub(crate) mod query_json;
+pub mod query_plan;
+pub mod search;
+pub(crate) mod spill;
 mod where_clause;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -26,44 +47,91 @@ pub enum Sort {
     Descending,
 }
 
+// Which object of a group of objects with equal distinct properties is returned. The order is
+// the sort order of the query or the where clause order if the query is not sorted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DistinctKeep {
+    First,
+    Last,
+}
+
 pub enum Case {
     Sensitive,
     Insensitive,
 }
 
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct QueryStats {
+    pub scanned: usize,
+    pub filter_evaluations: usize,
+    pub index_entries: usize,
+    pub duration: Duration,
+}
+
+// Collects what the execution of a query did. The analysis is only collected by
+// explain_analyze() because every filter node is timed separately.
+#[derive(Default)]
+pub(crate) struct QueryCollector {
+    stats: QueryStats,
+    analysis: Option<QueryAnalysis>,
+}
+
+#[derive(Default)]
+struct QueryAnalysis {
+    where_clauses: Vec<Value>,
+    filter: FilterAnalysis,
+    sort_duration: Duration,
+}
+
 #[derive(Clone)]
 pub struct Query {
     instance_id: u64,
+    db: Db,
+    checksum_db: Option<Db>,
     where_clauses: Vec<WhereClause>,
     where_clauses_dup: bool,
     filter: Option<Filter>,
-    sort: Vec<(Property, Sort)>,
+    sort: Vec<(Property, Sort, Collation, bool)>,
+    // The number of leading sort properties that the where clause already returns in order.
+    index_sorted: usize,
     distinct: Vec<(Property, bool)>,
+    distinct_keep: DistinctKeep,
     offset: usize,
     limit: usize,
+    memory_budget: Option<MemoryBudget>,
 }
 
 impl<'txn> Query {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         instance_id: u64,
+        db: Db,
+        checksum_db: Option<Db>,
         where_clauses: Vec<WhereClause>,
         filter: Option<Filter>,
-        sort: Vec<(Property, Sort)>,
+        sort: Vec<(Property, Sort, Collation, bool)>,
         distinct: Vec<(Property, bool)>,
+        distinct_keep: DistinctKeep,
         offset: usize,
         limit: usize,
+        memory_budget: Option<MemoryBudget>,
     ) -> Self {
         let where_clauses_dup = Self::check_where_clauses_duplicates(&where_clauses);
+        let index_sorted = Self::count_index_sorted(&where_clauses, &sort);
         Query {
             instance_id,
+            db,
+            checksum_db,
             where_clauses,
             where_clauses_dup,
             filter,
             sort,
+            index_sorted,
             distinct,
+            distinct_keep,
             offset,
             limit,
+            memory_budget,
         }
     }
 
@@ -81,9 +149,38 @@ impl<'txn> Query {
         false
     }
 
+    fn count_index_sorted(
+        where_clauses: &[WhereClause],
+        sort: &[(Property, Sort, Collation, bool)],
+    ) -> usize {
+        if let [WhereClause::Index(wc)] = where_clauses {
+            if !wc.has_duplicates() {
+                return wc
+                    .index()
+                    .properties
+                    .iter()
+                    .zip(sort)
+                    .take_while(|(index_property, (p, sort, collation, case_sensitive))| {
+                        index_property.property == *p
+                            && *sort == wc.sort()
+                            && collation.is_binary()
+                            && *case_sensitive
+                            && index_property.has_value_order()
+                    })
+                    .count();
+            }
+        }
+        0
+    }
+
+    // If release_objects is set, the objects are released after the callback so it must not keep
+    // them.
     pub(crate) fn execute_raw<'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
+        collector: Option<&mut QueryCollector>,
+        mut offset: usize,
+        release_objects: bool,
         mut callback: F,
     ) -> Result<()>
     where
@@ -98,16 +195,67 @@ impl<'txn> Query {
         let static_filter = Filter::stat(true);
         let filter = self.filter.as_ref().unwrap_or(&static_filter);
 
+        let mut collector = collector;
+        let mut analysis = collector
+            .as_deref_mut()
+            .and_then(|collector| collector.analysis.as_mut());
+        let mut scanned = 0;
+        let mut index_entries = 0;
+        let mark = cursors.object_mark();
         for where_clause in &self.where_clauses {
-            let result = where_clause.iter(cursors, result_ids.as_mut(), |id_key, object| {
-                if filter.evaluate(&id_key, object, Some(cursors))? {
-                    callback(id_key, object)
-                } else {
-                    Ok(true)
-                }
-            })?;
+            let start = Instant::now();
+            let (wc_scanned, wc_index_entries) = (scanned, index_entries);
+            let mut matches = 0;
+            let result = where_clause.iter(
+                cursors,
+                result_ids.as_mut(),
+                &mut index_entries,
+                &mut offset,
+                |id_key, object| {
+                    scanned += 1;
+                    if let Some(checksum_db) = self.checksum_db {
+                        checksum::verify(cursors, checksum_db, &id_key, object.as_bytes())?;
+                    }
+                    let matched = match (&mut analysis, &self.filter) {
+                        (Some(analysis), Some(filter)) => filter.evaluate_analyzed(
+                            &id_key,
+                            object,
+                            Some(cursors),
+                            &mut analysis.filter,
+                        )?,
+                        _ => filter.evaluate(&id_key, object, Some(cursors))?,
+                    };
+                    let result = if matched {
+                        matches += 1;
+                        callback(id_key, object)
+                    } else {
+                        Ok(true)
+                    };
+                    if release_objects {
+                        unsafe { cursors.release_objects(mark) };
+                    }
+                    result
+                },
+            )?;
+            if let Some(analysis) = &mut analysis {
+                analysis.where_clauses.push(json!({
+                    "type": where_clause.get_type(),
+                    "index_entries": index_entries - wc_index_entries,
+                    "rows": scanned - wc_scanned,
+                    "matches": matches,
+                    "time_us": start.elapsed().as_micros() as u64,
+                }));
+            }
             if !result {
-                return Ok(());
+                break;
+            }
+        }
+
+        if let Some(collector) = collector {
+            collector.stats.scanned += scanned;
+            collector.stats.index_entries += index_entries;
+            if self.filter.is_some() {
+                collector.stats.filter_evaluations += scanned;
             }
         }
 
@@ -117,21 +265,45 @@ impl<'txn> Query {
     fn execute_unsorted<'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
+        collector: Option<&mut QueryCollector>,
+        release_objects: bool,
         callback: F,
     ) -> Result<()>
     where
         F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
     {
         if !self.distinct.is_empty() {
-            let callback = self.add_distinct_unsorted(callback);
-            let callback = self.add_offset_limit_unsorted(callback);
-            self.execute_raw(cursors, callback)
+            // The offset and limit only count distinct objects.
+            let spill_env = LazySpillEnv::new();
+            let callback = self.add_offset_limit_unsorted(self.offset, callback);
+            let callback = self.add_distinct_unsorted(&spill_env, callback);
+            self.execute_raw(cursors, collector, 0, release_objects, callback)
+        } else if self.filter.is_none() && self.checksum_db.is_none() {
+            // Every object is a result so the where clauses can skip the offset without reading
+            // the skipped objects. Objects with checksums are read so skipped objects are verified
+            // like they are with a filter.
+            let callback = self.add_offset_limit_unsorted(0, callback);
+            self.execute_raw(cursors, collector, self.offset, release_objects, callback)
         } else {
-            let callback = self.add_offset_limit_unsorted(callback);
-            self.execute_raw(cursors, callback)
+            let callback = self.add_offset_limit_unsorted(self.offset, callback);
+            self.execute_raw(cursors, collector, 0, release_objects, callback)
         }
     }
 
+    // Keeping the last object requires all results, so the query is executed like a sorted query.
+    fn requires_sorting(&self) -> bool {
+        !self.sort.is_empty()
+            || (!self.distinct.is_empty() && self.distinct_keep == DistinctKeep::Last)
+    }
+
+    // Sorted queries are streamed if the where clause returns the objects ordered by the first
+    // sort property. Only objects that are equal in the index sorted properties are buffered.
+    fn requires_buffering(&self) -> bool {
+        self.requires_sorting()
+            && (self.index_sorted == 0
+                || (!self.distinct.is_empty() && self.distinct_keep == DistinctKeep::Last))
+    }
+
     fn hash_properties(object: IsarObject, properties: &[(Property, bool)]) -> u64 {
         let mut hash = 0;
         for (property, case_sensitive) in properties {
@@ -140,18 +312,20 @@ impl<'txn> Query {
         hash
     }
 
-    fn add_distinct_unsorted<F>(
+    fn add_distinct_unsorted<'s, F>(
         &self,
+        spill_env: &'s LazySpillEnv,
         mut callback: F,
-    ) -> impl FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>
+    ) -> impl FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool> + 's
     where
-        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool> + 's,
     {
         let properties = self.distinct.clone();
-        let mut hashes = IntMap::new();
+        let mut hashes = SpillMap::new(self.memory_budget.clone(), spill_env);
         move |id_key, object| {
             let hash = Self::hash_properties(object, &properties);
-            if hashes.insert(hash, ()) {
+            if hashes.get(hash)?.is_none() {
+                hashes.insert(hash, 0)?;
                 callback(id_key, object)
             } else {
                 Ok(true)
@@ -161,12 +335,12 @@ impl<'txn> Query {
 
     fn add_offset_limit_unsorted<F>(
         &self,
+        offset: usize,
         mut callback: F,
     ) -> impl FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>
     where
         F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
     {
-        let offset = self.offset;
         let max_count = self.limit.saturating_add(offset);
         let mut count = 0;
         move |id_key, value| {
@@ -179,50 +353,243 @@ impl<'txn> Query {
         }
     }
 
-    fn execute_sorted<'env>(
+    fn execute_sorted<'env, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
-    ) -> Result<Vec<(IdKey<'txn>, IsarObject<'txn>)>> {
-        let mut results = vec![];
-        self.execute_raw(cursors, |id_key, object| {
-            results.push((id_key, object));
-            Ok(true)
-        })?;
+        collector: Option<&mut QueryCollector>,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        let mut collector = collector;
+        let spill_env = LazySpillEnv::new();
+        let mut buffer = SortBuffer::new(self, &spill_env);
+        self.execute_raw(
+            cursors,
+            collector.as_deref_mut(),
+            0,
+            false,
+            |id_key, object| {
+                buffer.push(id_key, object)?;
+                Ok(true)
+            },
+        )?;
 
-        results.sort_unstable_by(|(_, o1), (_, o2)| {
-            for (p, sort) in &self.sort {
-                let ord = o1.compare_property(o2, *p);
-                if ord != Ordering::Equal {
-                    return if *sort == Sort::Ascending {
-                        ord
+        let start = Instant::now();
+        if !buffer.is_spilled() {
+            let results = self.sort_results(buffer.into_results());
+            for (id, object) in self.add_offset_limit_sorted(results) {
+                if !callback(id, object)? {
+                    break;
+                }
+            }
+        } else {
+            let mut callback = self.add_offset_limit_unsorted(self.offset, callback);
+            if self.distinct.is_empty() {
+                buffer.merge(cursors, &mut callback)?;
+            } else if self.distinct_keep == DistinctKeep::First {
+                let distinct_env = LazySpillEnv::new();
+                let mut callback = self.add_distinct_unsorted(&distinct_env, callback);
+                buffer.merge(cursors, &mut callback)?;
+            } else {
+                // The first pass finds the position of the last object of every distinct group.
+                let distinct_env = LazySpillEnv::new();
+                let mut last_positions = SpillMap::new(self.memory_budget.clone(), &distinct_env);
+                let mut position = 0;
+                buffer.merge(cursors, &mut |_, object| {
+                    let hash = Self::hash_properties(object, &self.distinct);
+                    last_positions.insert(hash, position)?;
+                    position += 1;
+                    Ok(true)
+                })?;
+                let mut position = 0;
+                buffer.merge(cursors, &mut |id_key, object| {
+                    let hash = Self::hash_properties(object, &self.distinct);
+                    let is_last = last_positions.get(hash)? == Some(position);
+                    position += 1;
+                    if is_last {
+                        callback(id_key, object)
                     } else {
-                        ord.reverse()
-                    };
+                        Ok(true)
+                    }
+                })?;
+            }
+        }
+        Self::add_sort_duration(collector, start.elapsed());
+        Ok(())
+    }
+
+    fn sort_results(
+        &self,
+        mut results: Vec<(IdKey<'txn>, IsarObject<'txn>)>,
+    ) -> Vec<(IdKey<'txn>, IsarObject<'txn>)> {
+        if self.sort.is_empty() {
+            return self.add_distinct_sorted(results);
+        }
+        // A stable sort keeps the where clause order of equal objects so distinct keeps the same
+        // object every time.
+        results.sort_by(|(_, o1), (_, o2)| self.compare_sorted(*o1, *o2));
+
+        if !self.distinct.is_empty() {
+            self.add_distinct_sorted(results)
+        } else {
+            results
+        }
+    }
+
+    fn compare_sorted(&self, o1: IsarObject, o2: IsarObject) -> Ordering {
+        for (p, sort, collation, case_sensitive) in &self.sort {
+            let ord = if collation.is_binary() && *case_sensitive {
+                o1.compare_property(&o2, *p)
+            } else {
+                match (o1.read_string(*p), o2.read_string(*p)) {
+                    (Some(s1), Some(s2)) => collation.compare_with_case(s1, s2, *case_sensitive),
+                    (s1, s2) => s1.is_some().cmp(&s2.is_some()),
                 }
+            };
+            if ord != Ordering::Equal {
+                return if *sort == Sort::Ascending {
+                    ord
+                } else {
+                    ord.reverse()
+                };
             }
-            Ordering::Equal
-        });
+        }
+        Ordering::Equal
+    }
 
+    fn execute_index_sorted<'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        collector: Option<&mut QueryCollector>,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        let callback = self.add_offset_limit_unsorted(self.offset, callback);
         if !self.distinct.is_empty() {
-            Ok(self.add_distinct_sorted(results))
+            let spill_env = LazySpillEnv::new();
+            let callback = self.add_distinct_unsorted(&spill_env, callback);
+            self.execute_sorted_groups(cursors, collector, callback)
         } else {
-            Ok(results)
+            self.execute_sorted_groups(cursors, collector, callback)
         }
     }
 
+    // Collects the objects that are equal in the index sorted properties and sorts them by the
+    // remaining sort properties before passing them on.
+    fn execute_sorted_groups<'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        collector: Option<&mut QueryCollector>,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        let mut collector = collector;
+        let spill_env = LazySpillEnv::new();
+        let mut group = SortBuffer::new(self, &spill_env);
+        let mut first = None;
+        let mut finished = false;
+        let mut sort_duration = Duration::default();
+        self.execute_raw(
+            cursors,
+            collector.as_deref_mut(),
+            0,
+            false,
+            |id_key, object| {
+                if matches!(first, Some(first) if !self.is_same_sort_group(first, object)) {
+                    let start = Instant::now();
+                    let full_group = mem::replace(&mut group, SortBuffer::new(self, &spill_env));
+                    let more = full_group.finish(cursors, &mut callback)?;
+                    sort_duration += start.elapsed();
+                    if !more {
+                        finished = true;
+                        return Ok(false);
+                    }
+                    first = None;
+                }
+                first.get_or_insert(object);
+                group.push(id_key, object)?;
+                Ok(true)
+            },
+        )?;
+        if !finished {
+            let start = Instant::now();
+            group.finish(cursors, &mut callback)?;
+            sort_duration += start.elapsed();
+        }
+        Self::add_sort_duration(collector, sort_duration);
+        Ok(())
+    }
+
+    // The sort duration includes the time of the callback that receives the sorted objects.
+    fn add_sort_duration(collector: Option<&mut QueryCollector>, duration: Duration) {
+        if let Some(analysis) = collector.and_then(|collector| collector.analysis.as_mut()) {
+            analysis.sort_duration += duration;
+        }
+    }
+
+    fn is_same_sort_group(&self, o1: IsarObject, o2: IsarObject) -> bool {
+        fn key_bytes(value: Option<&str>) -> Option<&[u8]> {
+            value.map(|value| &value.as_bytes()[..value.len().min(MAX_STRING_SIZE)])
+        }
+        for (p, _, _, _) in self.sort.iter().take(self.index_sorted) {
+            let same = match p.data_type {
+                DataType::Float => {
+                    let (f1, f2) = (o1.read_float(*p), o2.read_float(*p));
+                    f1 == f2 || (f1.is_nan() && f2.is_nan())
+                }
+                DataType::Double => {
+                    let (f1, f2) = (o1.read_double(*p), o2.read_double(*p));
+                    f1 == f2 || (f1.is_nan() && f2.is_nan())
+                }
+                DataType::String => {
+                    let (s1, s2) = (o1.read_string(*p), o2.read_string(*p));
+                    let same = key_bytes(s1) == key_bytes(s2);
+                    // Truncated strings are ordered by their hash in the index so neither they
+                    // nor the following properties are in order.
+                    if same && matches!(s1, Some(s) if s.len() >= MAX_STRING_SIZE) {
+                        return true;
+                    }
+                    same
+                }
+                _ => o1.compare_property(&o2, *p) == Ordering::Equal,
+            };
+            if !same {
+                return false;
+            }
+        }
+        true
+    }
+
     fn add_distinct_sorted(
         &self,
         results: Vec<(IdKey<'txn>, IsarObject<'txn>)>,
     ) -> Vec<(IdKey<'txn>, IsarObject<'txn>)> {
         let properties = self.distinct.clone();
         let mut hashes = IntMap::new();
-        results
-            .into_iter()
-            .filter(|(_, object)| {
-                let hash = Self::hash_properties(*object, &properties);
-                hashes.insert(hash, ())
-            })
-            .collect()
+        let mut is_first = |object: IsarObject| {
+            let hash = Self::hash_properties(object, &properties);
+            hashes.insert(hash, ())
+        };
+        if self.distinct_keep == DistinctKeep::First {
+            results
+                .into_iter()
+                .filter(|(_, object)| is_first(*object))
+                .collect()
+        } else {
+            let mut results = results
+                .into_iter()
+                .rev()
+                .filter(|(_, object)| is_first(*object))
+                .collect::<Vec<_>>();
+            results.reverse();
+            results
+        }
     }
 
     fn add_offset_limit_sorted(
@@ -253,36 +620,194 @@ impl<'txn> Query {
         &self,
         cursors: &IsarCursors<'txn, 'env>,
         skip_sorting: bool,
-        mut callback: F,
+        collector: Option<&mut QueryCollector>,
+        callback: F,
     ) -> Result<()>
     where
         F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
     {
-        if self.sort.is_empty() || skip_sorting {
-            self.execute_unsorted(cursors, callback)?;
+        if self.limit == 0 {
+            return Ok(());
+        } else if !self.requires_sorting() || skip_sorting {
+            self.execute_unsorted(cursors, collector, false, callback)?;
+        } else if !self.requires_buffering() {
+            self.execute_index_sorted(cursors, collector, callback)?;
         } else {
-            let results = self.execute_sorted(cursors)?;
-            let results_iter = self.add_offset_limit_sorted(results);
-            for (id, object) in results_iter {
-                if !callback(id, object)? {
-                    break;
-                }
-            }
+            self.execute_sorted(cursors, collector, callback)?;
         }
         Ok(())
     }
 
+    // Like find_while_internal() for callbacks that do not keep the objects. Unless the results
+    // have to be buffered for sorting, decompressed objects are released after every object.
+    pub(crate) fn stream_internal<'env, F>(
+        &self,
+        cursors: &IsarCursors<'txn, 'env>,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: for<'o> FnMut(IdKey<'txn>, IsarObject<'o>) -> Result<bool>,
+    {
+        let callback = |id_key, object| callback(id_key, object);
+        if self.limit == 0 {
+            Ok(())
+        } else if !self.requires_sorting() {
+            self.execute_unsorted(cursors, None, true, callback)
+        } else {
+            self.find_while_internal(cursors, false, None, callback)
+        }
+    }
+
     pub fn find_while<F>(&self, txn: &'txn mut IsarTxn, mut callback: F) -> Result<()>
     where
         F: FnMut(i64, IsarObject<'txn>) -> bool,
     {
         txn.read(self.instance_id, |cursors| {
-            self.find_while_internal(cursors, false, |id_key, object| {
+            self.find_while_internal(cursors, false, None, |id_key, object| {
+                Ok(callback(id_key.get_id(), object))
+            })
+        })
+    }
+
+    // Like find_while but also returns the parts of the strings that match the words of the
+    // text_contains filters of the query so they can be highlighted.
+    pub fn find_while_highlighted<F>(&self, txn: &'txn mut IsarTxn, mut callback: F) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject<'txn>, &[TextMatch]) -> bool,
+    {
+        txn.read(self.instance_id, |cursors| {
+            self.find_while_internal(cursors, false, None, |id_key, object| {
+                let matches = self
+                    .filter
+                    .as_ref()
+                    .map(|filter| filter.get_text_matches(object))
+                    .unwrap_or_default();
+                Ok(callback(id_key.get_id(), object, &matches))
+            })
+        })
+    }
+
+    // Like find_while but only the value of a single scalar property is read.
+    pub fn find_property_while<F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        property: Property,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(i64, PropertyValue<'txn>) -> bool,
+    {
+        if !property.data_type.is_scalar() || property.data_type == DataType::Object {
+            return illegal_arg("Only scalar properties can be queried.");
+        }
+        self.find_while(txn, |id, object| callback(id, object.read_value(property)))
+    }
+
+    pub fn find_while_committed<F>(&self, txn: &'txn mut IsarTxn, mut callback: F) -> Result<()>
+    where
+        F: FnMut(i64, IsarObject<'txn>) -> bool,
+    {
+        txn.read_committed(self.instance_id, |cursors| {
+            self.find_while_internal(cursors, false, None, |id_key, object| {
                 Ok(callback(id_key.get_id(), object))
             })
         })
     }
 
+    pub fn find_while_with_stats<F>(
+        &self,
+        txn: &'txn mut IsarTxn,
+        mut callback: F,
+    ) -> Result<QueryStats>
+    where
+        F: FnMut(i64, IsarObject<'txn>) -> bool,
+    {
+        let start = Instant::now();
+        let mut collector = QueryCollector::default();
+        txn.read(self.instance_id, |cursors| {
+            self.find_while_internal(cursors, false, Some(&mut collector), |id_key, object| {
+                Ok(callback(id_key.get_id(), object))
+            })
+        })?;
+        let mut stats = collector.stats;
+        stats.duration = start.elapsed();
+        Ok(stats)
+    }
+
+    fn delete_while_internal<F>(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        collector: Option<&mut QueryCollector>,
+        mut callback: F,
+    ) -> Result<u32>
+    where
+        F: FnMut(i64, IsarObject) -> bool,
+    {
+        collection.write(txn, |cursors, mut change_set| {
+            let mut ids_to_delete = vec![];
+            self.find_while_internal(cursors, false, collector, |id_key, object| {
+                let id = id_key.get_id();
+                if callback(id, object) {
+                    ids_to_delete.push(id);
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            })?;
+            let mut count = 0;
+            for id in ids_to_delete {
+                let id_key = IdKey::new(id);
+                if collection.delete_internal(cursors, true, change_set.as_deref_mut(), &id_key)? {
+                    count += 1;
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    pub fn delete_while<F>(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        callback: F,
+    ) -> Result<u32>
+    where
+        F: FnMut(i64, IsarObject) -> bool,
+    {
+        self.delete_while_internal(txn, collection, None, callback)
+    }
+
+    pub fn delete(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        limit: usize,
+    ) -> Result<u32> {
+        let mut count = 0;
+        self.delete_while(txn, collection, |_, _| {
+            count += 1;
+            count <= limit
+        })
+    }
+
+    pub fn delete_while_with_stats<F>(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        callback: F,
+    ) -> Result<(u32, QueryStats)>
+    where
+        F: FnMut(i64, IsarObject) -> bool,
+    {
+        let start = Instant::now();
+        let mut collector = QueryCollector::default();
+        let count = self.delete_while_internal(txn, collection, Some(&mut collector), callback)?;
+        let mut stats = collector.stats;
+        stats.duration = start.elapsed();
+        Ok((count, stats))
+    }
+
     pub fn find_all_vec(&self, txn: &'txn mut IsarTxn) -> Result<Vec<(i64, IsarObject<'txn>)>> {
         let mut results = vec![];
         self.find_while(txn, |id, object| {
@@ -292,15 +817,123 @@ impl<'txn> Query {
         Ok(results)
     }
 
+    pub fn find_ids(&self, txn: &mut IsarTxn) -> Result<Vec<i64>> {
+        let mut ids = vec![];
+        if self.limit == 0 {
+            return Ok(ids);
+        } else if self.filter.is_some() || !self.sort.is_empty() || !self.distinct.is_empty() {
+            txn.read(self.instance_id, |cursors| {
+                self.stream_internal(cursors, |id_key, _| {
+                    ids.push(id_key.get_id());
+                    Ok(true)
+                })
+            })?;
+            return Ok(ids);
+        }
+
+        txn.read(self.instance_id, |cursors| {
+            let mut result_ids = if self.where_clauses_dup {
+                Some(IntMap::new())
+            } else {
+                None
+            };
+            let max_count = self.limit.saturating_add(self.offset);
+            let mut count = 0;
+            for where_clause in &self.where_clauses {
+                let result = where_clause.iter_ids(cursors, result_ids.as_mut(), |id| {
+                    count += 1;
+                    if count > max_count {
+                        return Ok(false);
+                    }
+                    if count > self.offset {
+                        ids.push(id);
+                    }
+                    Ok(true)
+                })?;
+                if !result {
+                    break;
+                }
+            }
+            Ok(())
+        })?;
+        Ok(ids)
+    }
+
     pub fn count(&self, txn: &mut IsarTxn) -> Result<u32> {
         let mut counter = 0;
-        self.find_while(txn, |_, _| {
-            counter += 1;
-            true
+        txn.read(self.instance_id, |cursors| {
+            self.stream_internal(cursors, |_, _| {
+                counter += 1;
+                Ok(true)
+            })
         })?;
         Ok(counter)
     }
 
+    // Describes the where clauses and how the results are processed without running the query.
+    pub fn explain(&self, txn: &mut IsarTxn) -> Result<QueryPlan> {
+        let mut where_clauses = vec![];
+        txn.read(self.instance_id, |cursors| {
+            for where_clause in &self.where_clauses {
+                where_clauses.push(WhereClausePlan {
+                    where_type: where_clause.get_type(),
+                    index: where_clause.get_index_name().map(|name| name.to_string()),
+                    estimated_entries: where_clause.estimate(cursors)?,
+                });
+            }
+            Ok(())
+        })?;
+        Ok(QueryPlan {
+            uses_index: where_clauses.iter().any(|wc| wc.index.is_some()),
+            full_scan: self.where_clauses.iter().any(|wc| wc.is_full_scan()),
+            filter: self.filter.is_some(),
+            buffered: self.requires_buffering(),
+            estimated_entries: where_clauses.iter().map(|wc| wc.estimated_entries).sum(),
+            where_clauses,
+        })
+    }
+
+    // Runs the query and reports what each where clause and filter node did. The time spent
+    // in the filter is included in the time of the where clauses.
+    pub fn explain_analyze(&self, txn: &mut IsarTxn) -> Result<Value> {
+        let start = Instant::now();
+        let mut collector = QueryCollector {
+            analysis: Some(QueryAnalysis::default()),
+            ..Default::default()
+        };
+        let mut results = 0;
+        txn.read(self.instance_id, |cursors| {
+            self.find_while_internal(cursors, false, Some(&mut collector), |_, _| {
+                results += 1;
+                Ok(true)
+            })
+        })?;
+
+        let analysis = collector.analysis.unwrap();
+        let filter = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.describe(&analysis.filter));
+        let sort = if self.requires_sorting() {
+            json!({
+                "properties": self.sort.len(),
+                "time_us": analysis.sort_duration.as_micros() as u64,
+            })
+        } else {
+            Value::Null
+        };
+        Ok(json!({
+            "where_clauses": analysis.where_clauses,
+            "filter": filter,
+            "sort": sort,
+            "distinct": self.distinct.len(),
+            "offset": self.offset,
+            "limit": self.limit,
+            "results": results,
+            "time_us": start.elapsed().as_micros() as u64,
+        }))
+    }
+
     pub fn export_json(
         &self,
         txn: &mut IsarTxn,
@@ -310,15 +943,189 @@ impl<'txn> Query {
         byte_as_bool: bool,
     ) -> Result<Value> {
         let mut items = vec![];
-        self.find_while(txn, |id, object| {
+        txn.read(self.instance_id, |cursors| {
+            self.stream_internal(cursors, |id_key, object| {
+                let mut json = JsonEncodeDecode::encode(
+                    &collection.object_info,
+                    object,
+                    primitive_null,
+                    byte_as_bool,
+                );
+                if let Some(id_name) = id_name {
+                    json.insert(id_name.to_string(), Value::from(id_key.get_id()));
+                }
+                items.push(json);
+                Ok(true)
+            })
+        })?;
+        Ok(json!(items))
+    }
+
+    // Like export_json() but the objects are written one at a time instead of building the
+    // whole array in memory.
+    pub fn export_json_stream<W: Write>(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        id_name: Option<&str>,
+        writer: &mut W,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Result<()> {
+        let write = |writer: &mut W, bytes: &[u8]| {
+            writer.write_all(bytes).map_err(|_| IsarError::IoError {
+                message: "Could not write the JSON.".to_string(),
+            })
+        };
+        write(writer, b"[")?;
+        let mut buffer = vec![];
+        let mut first = true;
+        txn.read(self.instance_id, |cursors| {
+            self.stream_internal(cursors, |id_key, object| {
+                buffer.clear();
+                write_element(
+                    &mut buffer,
+                    first,
+                    collection,
+                    id_name,
+                    id_key.get_id(),
+                    object,
+                    primitive_null,
+                    byte_as_bool,
+                );
+                first = false;
+                write(writer, &buffer)?;
+                Ok(true)
+            })
+        })?;
+        write(writer, b"]")
+    }
+
+    // Runs the query and returns an export that can be read in chunks, for example across FFI
+    // calls.
+    pub fn export_json_chunked(
+        &self,
+        txn: &mut IsarTxn,
+        id_name: Option<&str>,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Result<JsonExport> {
+        let ids = self.find_ids(txn)?;
+        Ok(JsonExport::new(ids, id_name, primitive_null, byte_as_bool))
+    }
+
+    // Writes the results while the query runs instead of collecting them first.
+    #[allow(clippy::too_many_arguments)]
+    pub fn export_csv<W: Write>(
+        &self,
+        txn: &mut IsarTxn,
+        collection: &IsarCollection,
+        id_name: Option<&str>,
+        csv: W,
+        options: &CsvOptions,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Result<()> {
+        let object_info = &collection.object_info;
+        let mut encoder = CsvEncoder::new(
+            object_info,
+            id_name,
+            csv,
+            options,
+            primitive_null,
+            byte_as_bool,
+        )?;
+        txn.read(self.instance_id, |cursors| {
+            self.stream_internal(cursors, |id_key, object| {
+                let id = id_name.map(|_| id_key.get_id());
+                encoder.encode(object_info, id, object)?;
+                Ok(true)
+            })
+        })?;
+        encoder.finish()
+    }
+
+    // Exports the query results and all objects reachable through the given links (source
+    // collection and link index) up to max_depth hops, grouped by collection name.
+    #[allow(clippy::too_many_arguments)]
+    pub fn export_json_cascade(
+        &self,
+        txn: &mut IsarTxn,
+        collections: &[IsarCollection],
+        collection: &IsarCollection,
+        links: &[(&IsarCollection, usize)],
+        max_depth: usize,
+        id_name: Option<&str>,
+        primitive_null: bool,
+        byte_as_bool: bool,
+    ) -> Result<Value> {
+        let links = links
+            .iter()
+            .map(|(col, link_index)| Ok((col.get_runtime_id(), col.get_link(*link_index)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut items: HashMap<u64, Vec<Value>> = HashMap::new();
+        let mut visited: HashMap<u64, IntMap<()>> = HashMap::new();
+        let mut export = |col: &IsarCollection, id: i64, object: IsarObject| {
+            let col_visited = visited
+                .entry(col.get_runtime_id())
+                .or_insert_with(IntMap::new);
+            if !col_visited.insert(IdKey::new(id).get_unsigned_id(), ()) {
+                return false;
+            }
             let mut json =
-                JsonEncodeDecode::encode(collection, object, primitive_null, byte_as_bool);
+                JsonEncodeDecode::encode(&col.object_info, object, primitive_null, byte_as_bool);
             if let Some(id_name) = id_name {
                 json.insert(id_name.to_string(), Value::from(id));
             }
-            items.push(json);
+            items
+                .entry(col.get_runtime_id())
+                .or_default()
+                .push(Value::from(json));
             true
+        };
+
+        txn.read(self.instance_id, |cursors| {
+            let mut frontier = vec![];
+            self.stream_internal(cursors, |id_key, object| {
+                if export(collection, id_key.get_id(), object) {
+                    frontier.push((collection.get_runtime_id(), id_key.get_id()));
+                }
+                Ok(true)
+            })?;
+
+            for _ in 0..max_depth {
+                let mut next_frontier = vec![];
+                for (col_id, id) in frontier {
+                    for (source_col_id, link) in &links {
+                        if *source_col_id != col_id {
+                            continue;
+                        }
+                        let target_col_id = link.get_target_col_runtime_id();
+                        let target_col = collections
+                            .iter()
+                            .find(|c| c.get_runtime_id() == target_col_id)
+                            .unwrap();
+                        link.iter(cursors, &IdKey::new(id), |id_key, object| {
+                            target_col.verify_checksum(cursors, &id_key, object.as_bytes())?;
+                            if export(target_col, id_key.get_id(), object) {
+                                next_frontier.push((target_col_id, id_key.get_id()));
+                            }
+                            Ok(true)
+                        })?;
+                    }
+                }
+                frontier = next_frontier;
+            }
+            Ok(())
         })?;
-        Ok(json!(items))
+
+        let mut result = serde_json::Map::new();
+        for col in collections {
+            if let Some(col_items) = items.remove(&col.get_runtime_id()) {
+                result.insert(col.name.clone(), Value::from(col_items));
+            }
+        }
+        Ok(Value::from(result))
     }
 }
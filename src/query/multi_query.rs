@@ -0,0 +1,95 @@
+use crate::collection::IsarCollection;
+use crate::error::{illegal_arg, Result};
+use crate::object::data_type::DataType;
+use crate::object::isar_object::IsarObject;
+use crate::query::Query;
+use crate::txn::IsarTxn;
+use std::collections::HashSet;
+
+// Executes multiple queries, possibly on different collections, as one query. This is useful
+// to search multiple collections at once.
+pub struct MultiQuery<'a> {
+    queries: Vec<(&'a IsarCollection, Query)>,
+    shared_properties: Vec<(String, DataType)>,
+}
+
+impl<'a> MultiQuery<'a> {
+    // All queries have to belong to the same instance and their collections have to share at
+    // least one property with the same name and type.
+    pub fn union(queries: Vec<(&'a IsarCollection, Query)>) -> Result<MultiQuery<'a>> {
+        let (first, _) = if let Some(first) = queries.first() {
+            first
+        } else {
+            return illegal_arg("A union requires at least one query.");
+        };
+        let same_instance = queries.iter().all(|(col, query)| {
+            col.instance_id == first.instance_id && query.instance_id == first.instance_id
+        });
+        if !same_instance {
+            return illegal_arg("The queries of a union have to belong to the same instance.");
+        }
+
+        let shared_properties: Vec<(String, DataType)> = first
+            .properties
+            .iter()
+            .filter(|(name, p)| {
+                queries.iter().all(|(col, _)| {
+                    col.properties
+                        .iter()
+                        .any(|(n, p2)| n == name && p2.data_type == p.data_type)
+                })
+            })
+            .map(|(name, p)| (name.clone(), p.data_type))
+            .collect();
+        if shared_properties.is_empty() {
+            return illegal_arg("The collections of a union do not share any properties.");
+        }
+
+        Ok(MultiQuery {
+            queries,
+            shared_properties,
+        })
+    }
+
+    // The properties all collections have. Their offsets differ between the collections so they
+    // have to be looked up in the collection of each result.
+    pub fn get_shared_properties(&self) -> &[(String, DataType)] {
+        &self.shared_properties
+    }
+
+    // Calls the callback with the collection id, the object id and the object of every result.
+    // The results of each query are returned in the order of the queries. Objects matched by
+    // multiple queries of the same collection are only returned once.
+    pub fn find_while<F>(&self, txn: &mut IsarTxn, mut callback: F) -> Result<()>
+    where
+        F: FnMut(u64, i64, IsarObject) -> bool,
+    {
+        let mut returned = HashSet::new();
+        for (col, query) in &self.queries {
+            let col_id = col.get_collection_id();
+            let col_queries = self.queries.iter().filter(|(c, _)| c.name == col.name);
+            let check_duplicates = col_queries.count() > 1;
+            let mut stopped = false;
+            query.find_while(txn, |id, object| {
+                if check_duplicates && !returned.insert((col_id, id)) {
+                    return true;
+                }
+                stopped = !callback(col_id, id, object);
+                !stopped
+            })?;
+            if stopped {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn count(&self, txn: &mut IsarTxn) -> Result<u32> {
+        let mut count = 0;
+        self.find_while(txn, |_, _, _| {
+            count += 1;
+            true
+        })?;
+        Ok(count)
+    }
+}
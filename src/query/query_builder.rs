@@ -1,20 +1,27 @@
 use super::index_where_clause::IndexWhereClause;
+use crate::collation::Collation;
 use crate::collection::IsarCollection;
 use crate::error::{illegal_arg, Result};
 use crate::index::index_key::IndexKey;
+use crate::object::data_type::DataType;
+use crate::object::geo::GeoArea;
 use crate::object::isar_object::Property;
 use crate::query::filter::Filter;
+use crate::query::geo_where_clause::GeoWhereClause;
 use crate::query::id_where_clause::IdWhereClause;
 use crate::query::link_where_clause::LinkWhereClause;
 use crate::query::where_clause::WhereClause;
-use crate::query::{Query, Sort};
+use crate::query::{DistinctKeep, Query, Sort};
+use itertools::Itertools;
 
 pub struct QueryBuilder<'a> {
     collection: &'a IsarCollection,
     where_clauses: Option<Vec<WhereClause>>,
     filter: Option<Filter>,
-    sort: Vec<(Property, Sort)>,
+    direction: Option<Sort>,
+    sort: Vec<(Property, Sort, Collation, bool)>,
     distinct: Vec<(Property, bool)>,
+    distinct_keep: DistinctKeep,
     offset: usize,
     limit: usize,
 }
@@ -25,8 +32,10 @@ impl<'a> QueryBuilder<'a> {
             collection,
             where_clauses: None,
             filter: None,
+            direction: None,
             sort: vec![],
             distinct: vec![],
+            distinct_keep: DistinctKeep::First,
             offset: 0,
             limit: usize::MAX,
         }
@@ -92,8 +101,32 @@ impl<'a> QueryBuilder<'a> {
         Ok(())
     }
 
+    // Finds the objects whose point lies within the area using a GeoPoint index.
+    pub fn add_geo_where_clause(&mut self, index_index: usize, area: GeoArea) -> Result<()> {
+        let index = self.collection.get_index_by_index(index_index)?;
+        if index.properties[0].property.data_type != DataType::GeoPoint {
+            return illegal_arg("Geo where clauses require a GeoPoint index.");
+        }
+        let wc = GeoWhereClause::new(self.collection.db, index.clone(), area)?;
+        self.init_where_clauses();
+        self.where_clauses
+            .as_mut()
+            .unwrap()
+            .push(WhereClause::Geo(wc));
+        Ok(())
+    }
+
     pub fn add_link_where_clause(&mut self, link_index: usize, id: i64) -> Result<()> {
-        self.add_link_where_clause_internal(self.collection, link_index, id)
+        self.add_link_where_clause_internal(self.collection, link_index, id, id)
+    }
+
+    pub fn add_link_range_where_clause(
+        &mut self,
+        link_index: usize,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        self.add_link_where_clause_internal(self.collection, link_index, start, end)
     }
 
     pub fn add_backlink_where_clause(
@@ -102,18 +135,37 @@ impl<'a> QueryBuilder<'a> {
         link_index: usize,
         id: i64,
     ) -> Result<()> {
-        self.add_link_where_clause_internal(col, link_index, id)
+        self.add_link_where_clause_internal(col, link_index, id, id)
+    }
+
+    pub fn add_backlink_range_where_clause(
+        &mut self,
+        col: &IsarCollection,
+        link_index: usize,
+        start: i64,
+        end: i64,
+    ) -> Result<()> {
+        self.add_link_where_clause_internal(col, link_index, start, end)
     }
 
     fn add_link_where_clause_internal(
         &mut self,
         col: &IsarCollection,
         link_index: usize,
-        id: i64,
+        start: i64,
+        end: i64,
     ) -> Result<()> {
+        if self.direction.is_some() {
+            return illegal_arg("Link where clauses do not support a direction.");
+        }
         let link = col.get_link(link_index)?;
         self.init_where_clauses();
-        let wc = LinkWhereClause::new(link, id)?;
+        let (lower, upper) = if start > end {
+            (end, start)
+        } else {
+            (start, end)
+        };
+        let wc = LinkWhereClause::new(link, lower, upper)?;
         self.where_clauses
             .as_mut()
             .unwrap()
@@ -125,19 +177,61 @@ impl<'a> QueryBuilder<'a> {
         self.filter = Some(filter);
     }
 
+    // Iterates the where clauses and their results in the given direction. Link where clauses
+    // return the links in the order they are stored and cannot be combined with a direction.
+    pub fn set_direction(&mut self, direction: Sort) -> Result<()> {
+        let has_link = self
+            .where_clauses
+            .iter()
+            .flatten()
+            .any(WhereClause::is_link);
+        if has_link {
+            return illegal_arg("Link where clauses do not support a direction.");
+        }
+        self.direction = Some(direction);
+        Ok(())
+    }
+
     pub fn add_sort(&mut self, property: Property, sort: Sort) -> Result<()> {
         if property.data_type.is_scalar() {
-            self.sort.push((property, sort));
+            self.sort.push((property, sort, Collation::Binary, true));
             Ok(())
         } else {
             illegal_arg("Only scalar types may be used for sorting.")
         }
     }
 
+    // Strings are compared like keys of an index with the same collation and case sensitivity.
+    pub fn add_collated_sort(
+        &mut self,
+        property: Property,
+        sort: Sort,
+        collation: Collation,
+        case_sensitive: bool,
+    ) -> Result<()> {
+        if property.data_type != DataType::String {
+            illegal_arg("Only String properties may be sorted using a collation.")
+        } else if !collation.is_supported() {
+            illegal_arg("Collations are not supported by this build.")
+        } else {
+            self.sort.push((property, sort, collation, case_sensitive));
+            Ok(())
+        }
+    }
+
+    // Multiple distinct properties are combined: objects are only duplicates if all of their
+    // distinct properties are equal.
     pub fn add_distinct(&mut self, property: Property, case_sensitive: bool) {
         self.distinct.push((property, case_sensitive));
     }
 
+    pub fn set_distinct_keep(&mut self, keep: DistinctKeep) {
+        self.distinct_keep = keep;
+    }
+
+    // Offset and limit are applied last, after the filter, distinct and sort. They use saturating
+    // arithmetic so usize::MAX can be used for "no limit" with any offset. A limit of 0 returns
+    // no results.
     pub fn set_offset(&mut self, offset: usize) {
         self.offset = offset;
     }
@@ -150,14 +244,44 @@ impl<'a> QueryBuilder<'a> {
         if self.where_clauses.is_none() {
             self.add_id_where_clause(i64::MIN, i64::MAX).unwrap();
         }
+        let mut where_clauses = self.where_clauses.unwrap();
+        let filter_properties = self
+            .filter
+            .as_ref()
+            .map_or_else(Vec::new, |f| f.get_properties());
+        let sort_properties = self.sort.iter().map(|(p, _, _, _)| *p).collect_vec();
+        self.collection
+            .record_property_usage(&filter_properties, &sort_properties);
+
+        if let Some(direction) = self.direction {
+            for where_clause in &mut where_clauses {
+                where_clause.set_sort(direction);
+            }
+            if direction == Sort::Descending {
+                where_clauses.reverse();
+            }
+        }
+        let filter = self.filter.map(|f| f.normalize());
+        let filter = match filter.as_ref().and_then(|f| f.get_static()) {
+            Some(true) => None,
+            Some(false) => {
+                where_clauses.clear();
+                None
+            }
+            None => filter,
+        };
         Query::new(
             self.collection.instance_id,
-            self.where_clauses.unwrap(),
-            self.filter,
+            self.collection.db,
+            self.collection.checksum_db,
+            where_clauses,
+            filter,
             self.sort,
             self.distinct,
+            self.distinct_keep,
             self.offset,
             self.limit,
+            self.collection.memory_budget.clone(),
         )
     }
 }
@@ -0,0 +1,423 @@
+use crate::collation::Collation;
+use crate::collection::IsarCollection;
+use crate::error::{illegal_arg, IsarError, Result};
+use crate::object::data_type::DataType;
+use crate::object::geo::GeoArea;
+use crate::object::isar_object::Property;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::object::object_info::ObjectInfo;
+use crate::query::filter::{Filter, FilterValue};
+use crate::query::query_builder::QueryBuilder;
+use crate::query::{DistinctKeep, Sort};
+use serde_json::{Map, Value};
+
+type JsonObject = Map<String, Value>;
+
+impl<'a> QueryBuilder<'a> {
+    /// Creates a query from its JSON representation. All keys are optional:
+    ///
+    /// ```json
+    /// {
+    ///   "whereClauses": [
+    ///     {"type": "id", "lower": 1, "upper": 10},
+    ///     {"type": "index", "index": "name_age", "lower": ["a"], "upper": ["b", 30],
+    ///      "includeLower": true, "includeUpper": true, "skipDuplicates": false},
+    ///     {"type": "link", "link": "friends", "id": 5},
+    ///     {"type": "geo", "index": "location", "lat": 52.5, "lon": 13.4, "radius": 1000}
+    ///   ],
+    ///   "filter": {"type": "and", "filters": [
+    ///     {"type": "gt", "property": "age", "value": 18},
+    ///     {"type": "not", "filter": {"type": "isNull", "property": "name"}}
+    ///   ]},
+    ///   "sort": [{"property": "name", "sort": "desc", "collation": "German"}],
+    ///   "direction": "asc",
+    ///   "distinct": [{"property": "name", "caseSensitive": false}, {"property": "age"}],
+    ///   "distinctKeep": "last",
+    ///   "offset": 0,
+    ///   "limit": 10
+    /// }
+    /// ```
+    ///
+    /// Index where clauses contain the values of the first index properties. A missing upper
+    /// key equals the lower key and shorter upper keys include all keys starting with them.
+    /// Geo where clauses and `geoWithin` filters either contain a `lat`, `lon` and `radius` in
+    /// meters or the `south`, `west`, `north` and `east` edges of a box.
+    ///
+    /// Filter types are `and`, `or`, `not`, `id`, `eq`, `gt`, `gte`, `lt`, `lte`, `between`,
+    /// `startsWith`, `endsWith`, `contains`, `matches`, `geoWithin`, `isNull`, `isNotNull` and
+    /// `embedded` (with a `filter` of the embedded object). `between` uses the bounds of the typed filter
+    /// of the property. String filters and sorts are case sensitive unless `caseSensitive` is
+    /// false. Objects are duplicates if all `distinct` properties are equal and `distinctKeep`
+    /// (`first` or `last`) selects which of them is returned. `offset` and `limit` are applied
+    /// after filter, distinct and sort and a `limit` of 0 returns no results.
+    pub fn from_json(collection: &'a IsarCollection, query: &Value) -> Result<QueryBuilder<'a>> {
+        let query = as_object(query)?;
+        let mut qb = collection.new_query_builder();
+
+        if let Some(where_clauses) = query.get("whereClauses") {
+            for where_clause in as_array(where_clauses)? {
+                qb.add_json_where_clause(collection, as_object(where_clause)?)?;
+            }
+        }
+        if let Some(filter) = query.get("filter") {
+            qb.set_filter(parse_filter(&collection.object_info, filter)?);
+        }
+        if let Some(sort) = query.get("sort") {
+            for sort in as_array(sort)? {
+                let sort = as_object(sort)?;
+                let property = get_property(&collection.object_info, sort)?;
+                let order = parse_sort(sort.get("sort"))?;
+                let case_sensitive = get_bool(sort, "caseSensitive", true)?;
+                if sort.contains_key("collation") || !case_sensitive {
+                    let collation = sort.get("collation").map_or(Ok(Collation::Binary), |c| {
+                        serde_json::from_value(c.clone()).map_err(|_| IsarError::IllegalArg {
+                            message: "Unknown collation.".to_string(),
+                        })
+                    })?;
+                    qb.add_collated_sort(property, order, collation, case_sensitive)?;
+                } else {
+                    qb.add_sort(property, order)?;
+                }
+            }
+        }
+        if let Some(direction) = query.get("direction") {
+            qb.set_direction(parse_sort(Some(direction))?)?;
+        }
+        if let Some(distinct) = query.get("distinct") {
+            for distinct in as_array(distinct)? {
+                let distinct = as_object(distinct)?;
+                let property = get_property(&collection.object_info, distinct)?;
+                qb.add_distinct(property, get_bool(distinct, "caseSensitive", true)?);
+            }
+        }
+        if let Some(keep) = query.get("distinctKeep") {
+            if query.get("distinct").is_none() {
+                return illegal_arg("distinctKeep requires distinct properties.");
+            }
+            qb.set_distinct_keep(parse_distinct_keep(keep)?);
+        }
+        if let Some(offset) = query.get("offset") {
+            qb.set_offset(as_usize(offset)?);
+        }
+        if let Some(limit) = query.get("limit") {
+            qb.set_limit(as_usize(limit)?);
+        }
+        Ok(qb)
+    }
+
+    fn add_json_where_clause(
+        &mut self,
+        collection: &IsarCollection,
+        where_clause: &JsonObject,
+    ) -> Result<()> {
+        match get_str(where_clause, "type")? {
+            "id" => {
+                let lower = get_i64(where_clause, "lower", i64::MIN)?;
+                let upper = get_i64(where_clause, "upper", i64::MAX)?;
+                self.add_id_where_clause(lower, upper)
+            }
+            "index" => {
+                let name = get_str(where_clause, "index")?;
+                let index_index = collection
+                    .indexes
+                    .iter()
+                    .position(|(n, _)| n == name)
+                    .ok_or(IsarError::UnknownIndex {})?;
+                let empty = Value::Array(vec![]);
+                let lower_values = as_array(where_clause.get("lower").unwrap_or(&empty))?;
+                let upper_values = if let Some(upper_values) = where_clause.get("upper") {
+                    as_array(upper_values)?
+                } else {
+                    lower_values
+                };
+
+                let mut lower = collection.new_index_key(index_index)?;
+                for value in lower_values {
+                    lower.add_json(value)?;
+                }
+                let mut upper = collection.new_index_key(index_index)?;
+                for value in upper_values {
+                    upper.add_json(value)?;
+                }
+                upper.add_upper_unbounded()?;
+
+                self.add_index_where_clause(
+                    index_index,
+                    lower.finish(),
+                    get_bool(where_clause, "includeLower", true)?,
+                    upper.finish(),
+                    get_bool(where_clause, "includeUpper", true)?,
+                    get_bool(where_clause, "skipDuplicates", false)?,
+                )
+            }
+            "link" => {
+                let name = get_str(where_clause, "link")?;
+                let link_index = collection
+                    .links
+                    .iter()
+                    .position(|(n, _)| n == name)
+                    .ok_or_else(|| unknown("link", name))?;
+                let id = get_value(where_clause, "id")?
+                    .as_i64()
+                    .ok_or(IsarError::InvalidJson {})?;
+                self.add_link_where_clause(link_index, id)
+            }
+            "geo" => {
+                let name = get_str(where_clause, "index")?;
+                let index_index = collection
+                    .indexes
+                    .iter()
+                    .position(|(n, _)| n == name)
+                    .ok_or(IsarError::UnknownIndex {})?;
+                self.add_geo_where_clause(index_index, parse_geo_area(where_clause)?)
+            }
+            other => Err(unknown("where clause type", other)),
+        }
+    }
+}
+
+pub(crate) fn parse_filter(object_info: &ObjectInfo, filter: &Value) -> Result<Filter> {
+    let filter = as_object(filter)?;
+    let filter_type = get_str(filter, "type")?;
+    match filter_type {
+        "and" | "or" => {
+            let filters = as_array(get_value(filter, "filters")?)?
+                .iter()
+                .map(|f| parse_filter(object_info, f))
+                .collect::<Result<Vec<_>>>()?;
+            if filter_type == "and" {
+                Ok(Filter::and(filters))
+            } else {
+                Ok(Filter::or(filters))
+            }
+        }
+        "not" => {
+            let filter = parse_filter(object_info, get_value(filter, "filter")?)?;
+            Ok(Filter::not(filter))
+        }
+        "id" => {
+            let lower = get_i64(filter, "lower", i64::MIN)?;
+            let upper = get_i64(filter, "upper", i64::MAX)?;
+            Filter::id(lower, upper)
+        }
+        "eq" | "gt" | "gte" | "lt" | "lte" => {
+            let property = get_property(object_info, filter)?;
+            let value = get_value(filter, "value")?;
+            if is_string(property) {
+                if filter_type != "eq" {
+                    return illegal_arg("String properties only support the eq comparison.");
+                }
+                let value = JsonEncodeDecode::value_to_string(value)?;
+                let case_sensitive = get_bool(filter, "caseSensitive", true)?;
+                return Filter::string(property, value, value, case_sensitive);
+            }
+            let value = parse_filter_value(property, value)?;
+            match filter_type {
+                "eq" => Filter::eq(property, value),
+                "gt" => Filter::gt(property, value),
+                "gte" => Filter::gte(property, value),
+                "lt" => Filter::lt(property, value),
+                _ => Filter::lte(property, value),
+            }
+        }
+        "between" => {
+            let property = get_property(object_info, filter)?;
+            parse_between(property, filter)
+        }
+        "startsWith" | "endsWith" | "contains" | "matches" => {
+            let property = get_property(object_info, filter)?;
+            let value = get_str(filter, "value")?;
+            let case_sensitive = get_bool(filter, "caseSensitive", true)?;
+            match filter_type {
+                "startsWith" => Filter::string_starts_with(property, value, case_sensitive),
+                "endsWith" => Filter::string_ends_with(property, value, case_sensitive),
+                "contains" => Filter::string_contains(property, value, case_sensitive),
+                _ => Filter::string_matches(property, value, case_sensitive),
+            }
+        }
+        "geoWithin" => {
+            let property = get_property(object_info, filter)?;
+            Filter::geo_within(property, parse_geo_area(filter)?)
+        }
+        "isNull" => Ok(Filter::null(get_property(object_info, filter)?)),
+        "isNotNull" => Ok(Filter::is_not_null(get_property(object_info, filter)?)),
+        "embedded" => {
+            let property = get_property(object_info, filter)?;
+            let embedded = object_info
+                .get_embedded(property)
+                .ok_or(IsarError::IllegalArg {
+                    message: "Property does not support this filter.".to_string(),
+                })?;
+            let embedded_filter = parse_filter(embedded, get_value(filter, "filter")?)?;
+            Filter::embedded(property, embedded_filter)
+        }
+        other => Err(unknown("filter type", other)),
+    }
+}
+
+fn parse_between(property: Property, filter: &JsonObject) -> Result<Filter> {
+    let lower = get_value(filter, "lower")?;
+    let upper = get_value(filter, "upper")?;
+    let data_type = property
+        .data_type
+        .get_element_type()
+        .unwrap_or(property.data_type);
+    match data_type {
+        DataType::Byte => Filter::byte(
+            property,
+            JsonEncodeDecode::value_to_byte(lower)?,
+            JsonEncodeDecode::value_to_byte(upper)?,
+        ),
+        DataType::Int => Filter::int(
+            property,
+            JsonEncodeDecode::value_to_int(lower)?,
+            JsonEncodeDecode::value_to_int(upper)?,
+        ),
+        DataType::Long => Filter::long(
+            property,
+            JsonEncodeDecode::value_to_long(lower)?,
+            JsonEncodeDecode::value_to_long(upper)?,
+        ),
+        DataType::DateTime => Filter::date_time(
+            property,
+            JsonEncodeDecode::value_to_date_time(lower)?,
+            JsonEncodeDecode::value_to_date_time(upper)?,
+        ),
+        DataType::Float => Filter::float(
+            property,
+            JsonEncodeDecode::value_to_float(lower)?,
+            JsonEncodeDecode::value_to_float(upper)?,
+        ),
+        DataType::Double => Filter::double(
+            property,
+            JsonEncodeDecode::value_to_double(lower)?,
+            JsonEncodeDecode::value_to_double(upper)?,
+        ),
+        DataType::String => Filter::string(
+            property,
+            JsonEncodeDecode::value_to_string(lower)?,
+            JsonEncodeDecode::value_to_string(upper)?,
+            get_bool(filter, "caseSensitive", true)?,
+        ),
+        _ => illegal_arg("Property does not support this filter."),
+    }
+}
+
+fn parse_filter_value(property: Property, value: &Value) -> Result<FilterValue> {
+    let data_type = property
+        .data_type
+        .get_element_type()
+        .unwrap_or(property.data_type);
+    let value = match data_type {
+        DataType::Byte => FilterValue::Long(JsonEncodeDecode::value_to_byte(value)? as i64),
+        DataType::DateTime => FilterValue::Long(JsonEncodeDecode::value_to_date_time(value)?),
+        _ => {
+            if let Some(value) = value.as_i64() {
+                FilterValue::Long(value)
+            } else if let Some(value) = value.as_f64() {
+                FilterValue::Double(value)
+            } else {
+                return Err(IsarError::InvalidJson {});
+            }
+        }
+    };
+    Ok(value)
+}
+
+fn parse_geo_area(object: &JsonObject) -> Result<GeoArea> {
+    if object.contains_key("radius") {
+        GeoArea::radius(
+            get_f64(object, "lat")?,
+            get_f64(object, "lon")?,
+            get_f64(object, "radius")?,
+        )
+    } else {
+        GeoArea::bounding_box(
+            get_f64(object, "south")?,
+            get_f64(object, "west")?,
+            get_f64(object, "north")?,
+            get_f64(object, "east")?,
+        )
+    }
+}
+
+fn parse_sort(sort: Option<&Value>) -> Result<Sort> {
+    match sort.and_then(|s| s.as_str()) {
+        None | Some("asc") => Ok(Sort::Ascending),
+        Some("desc") => Ok(Sort::Descending),
+        Some(other) => Err(unknown("sort", other)),
+    }
+}
+
+fn parse_distinct_keep(keep: &Value) -> Result<DistinctKeep> {
+    match keep.as_str() {
+        Some("first") => Ok(DistinctKeep::First),
+        Some("last") => Ok(DistinctKeep::Last),
+        Some(other) => Err(unknown("distinct keep", other)),
+        None => Err(IsarError::InvalidJson {}),
+    }
+}
+
+fn is_string(property: Property) -> bool {
+    matches!(property.data_type, DataType::String | DataType::StringList)
+}
+
+fn unknown(kind: &str, name: &str) -> IsarError {
+    IsarError::IllegalArg {
+        message: format!("Unknown {} \"{}\".", kind, name),
+    }
+}
+
+fn get_property(object_info: &ObjectInfo, object: &JsonObject) -> Result<Property> {
+    let name = get_str(object, "property")?;
+    object_info
+        .get_property(name)
+        .ok_or_else(|| unknown("property", name))
+}
+
+fn get_value<'v>(object: &'v JsonObject, key: &str) -> Result<&'v Value> {
+    object.get(key).ok_or_else(|| IsarError::IllegalArg {
+        message: format!("Missing key \"{}\".", key),
+    })
+}
+
+fn get_str<'v>(object: &'v JsonObject, key: &str) -> Result<&'v str> {
+    get_value(object, key)?
+        .as_str()
+        .ok_or(IsarError::InvalidJson {})
+}
+
+fn get_bool(object: &JsonObject, key: &str, default: bool) -> Result<bool> {
+    match object.get(key) {
+        Some(value) => value.as_bool().ok_or(IsarError::InvalidJson {}),
+        None => Ok(default),
+    }
+}
+
+fn get_i64(object: &JsonObject, key: &str, default: i64) -> Result<i64> {
+    match object.get(key) {
+        Some(value) => value.as_i64().ok_or(IsarError::InvalidJson {}),
+        None => Ok(default),
+    }
+}
+
+fn get_f64(object: &JsonObject, key: &str) -> Result<f64> {
+    get_value(object, key)?
+        .as_f64()
+        .ok_or(IsarError::InvalidJson {})
+}
+
+fn as_usize(value: &Value) -> Result<usize> {
+    value
+        .as_u64()
+        .map(|v| v as usize)
+        .ok_or(IsarError::InvalidJson {})
+}
+
+fn as_object(value: &Value) -> Result<&JsonObject> {
+    value.as_object().ok_or(IsarError::InvalidJson {})
+}
+
+fn as_array(value: &Value) -> Result<&Vec<Value>> {
+    value.as_array().ok_or(IsarError::InvalidJson {})
+}
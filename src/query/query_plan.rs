@@ -0,0 +1,50 @@
+use serde_json::{json, Value};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WhereClausePlan {
+    // "id", "index", "link" or "geo".
+    pub where_type: &'static str,
+    pub index: Option<String>,
+    pub estimated_entries: usize,
+}
+
+// Describes how a query is executed without running it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryPlan {
+    pub where_clauses: Vec<WhereClausePlan>,
+    // At least one where clause reads an index.
+    pub uses_index: bool,
+    // Every object of the collection is read.
+    pub full_scan: bool,
+    pub filter: bool,
+    // All results are collected in memory before offset and limit are applied because the
+    // query is sorted in a different order than the index or keeps the last distinct object.
+    pub buffered: bool,
+    // The number of index entries or objects the where clauses read. It is estimated from the
+    // database pages and may be inaccurate for large ranges.
+    pub estimated_entries: usize,
+}
+
+impl QueryPlan {
+    pub fn to_json(&self) -> Value {
+        let where_clauses: Vec<Value> = self
+            .where_clauses
+            .iter()
+            .map(|wc| {
+                json!({
+                    "type": wc.where_type,
+                    "index": wc.index,
+                    "estimated_entries": wc.estimated_entries,
+                })
+            })
+            .collect();
+        json!({
+            "where_clauses": where_clauses,
+            "uses_index": self.uses_index,
+            "full_scan": self.full_scan,
+            "filter": self.filter,
+            "buffered": self.buffered,
+            "estimated_entries": self.estimated_entries,
+        })
+    }
+}
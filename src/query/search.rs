@@ -0,0 +1,146 @@
+use crate::collection::IsarCollection;
+use crate::error::{illegal_arg, IsarError, Result};
+use crate::index::normalize_string;
+use crate::object::data_type::DataType;
+use crate::object::isar_object::{IsarObject, Property};
+use crate::schema::index_schema::StringSplitter;
+use crate::txn::IsarTxn;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchOptions {
+    // The names of the properties to search. All String and StringList properties are searched
+    // if not set.
+    pub properties: Option<Vec<String>>,
+    // Words of the objects only have to start with the searched words.
+    pub prefix: bool,
+    // Only objects that contain all searched words are returned.
+    pub match_all: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SearchResult {
+    pub collection_id: u64,
+    pub id: i64,
+    // The number of searched words found in each property, summed over all properties.
+    pub score: u32,
+}
+
+// Splits the text and the strings of the objects into words the same way as full text indexes
+// do and compares them case insensitively. Results are ordered by descending score, then by the
+// order of the collections and by id.
+pub(crate) fn search(
+    txn: &mut IsarTxn,
+    instance_id: u64,
+    text: &str,
+    collections: &[&IsarCollection],
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let mut words: Vec<String> = vec![];
+    for word in StringSplitter::Words.split(text) {
+        let word = normalize_string(word, false);
+        if !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    if words.is_empty() {
+        return illegal_arg("The search text does not contain any words.");
+    }
+
+    let mut results = vec![];
+    for (col_index, col) in collections.iter().enumerate() {
+        if col.instance_id != instance_id {
+            return Err(IsarError::InstanceMismatch {});
+        }
+        let properties = searched_properties(col, options)?;
+        if properties.is_empty() {
+            continue;
+        }
+        let collection_id = col.get_collection_id();
+        col.new_query_builder()
+            .build()
+            .find_while(txn, |id, object| {
+                let (score, matched) = score_object(object, &properties, &words, options.prefix);
+                if score > 0 && (!options.match_all || matched == words.len()) {
+                    let result = SearchResult {
+                        collection_id,
+                        id,
+                        score,
+                    };
+                    results.push((col_index, result));
+                }
+                true
+            })?;
+    }
+
+    results.sort_by(|(col1, r1), (col2, r2)| {
+        r2.score
+            .cmp(&r1.score)
+            .then(col1.cmp(col2))
+            .then(r1.id.cmp(&r2.id))
+    });
+    let limit = options.limit.unwrap_or(usize::MAX);
+    Ok(results.into_iter().take(limit).map(|(_, r)| r).collect())
+}
+
+fn searched_properties(col: &IsarCollection, options: &SearchOptions) -> Result<Vec<Property>> {
+    let is_string =
+        |p: &Property| p.data_type == DataType::String || p.data_type == DataType::StringList;
+    if let Some(names) = &options.properties {
+        let mut properties = vec![];
+        for (name, property) in &col.properties {
+            if names.contains(name) {
+                if !is_string(property) {
+                    return illegal_arg("Only String and StringList properties can be searched.");
+                }
+                properties.push(*property);
+            }
+        }
+        Ok(properties)
+    } else {
+        Ok(col
+            .properties
+            .iter()
+            .map(|(_, p)| *p)
+            .filter(is_string)
+            .collect())
+    }
+}
+
+// Returns the score and the number of distinct searched words that were found.
+fn score_object(
+    object: IsarObject,
+    properties: &[Property],
+    words: &[String],
+    prefix: bool,
+) -> (u32, usize) {
+    let mut found = vec![false; words.len()];
+    let mut score = 0;
+    for property in properties {
+        let values = if property.data_type == DataType::String {
+            vec![object.read_string(*property)]
+        } else {
+            object.read_string_list(*property).unwrap_or_default()
+        };
+        let object_words: Vec<String> = values
+            .into_iter()
+            .flatten()
+            .flat_map(|value| StringSplitter::Words.split(value))
+            .map(|word| normalize_string(word, false))
+            .collect();
+        for (i, word) in words.iter().enumerate() {
+            let contains = object_words.iter().any(|object_word| {
+                if prefix {
+                    object_word.starts_with(word.as_str())
+                } else {
+                    object_word == word
+                }
+            });
+            if contains {
+                found[i] = true;
+                score += 1;
+            }
+        }
+    }
+    (score, found.iter().filter(|f| **f).count())
+}
@@ -0,0 +1,321 @@
+use crate::cursor::IsarCursors;
+use crate::error::{IsarError, Result};
+use crate::id_key::IdKey;
+use crate::mdbx::cursor::{Cursor, UnboundCursor};
+use crate::mdbx::db::Db;
+use crate::mdbx::env::Env;
+use crate::mdbx::txn::Txn;
+use crate::object::isar_object::IsarObject;
+use crate::query::Query;
+use intmap::IntMap;
+use once_cell::unsync::OnceCell;
+use rand::random;
+use std::cmp::Ordering;
+use std::convert::TryInto;
+use std::fs::{create_dir_all, remove_dir_all};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+pub(crate) const SPILL_DIR_NAME: &str = "spill";
+
+const SPILL_PAGE_SIZE: usize = 4096;
+
+// mdbx does not allow fewer dirty pages per txn.
+const MIN_DIRTY_PAGES: usize = 128;
+
+const RESULT_SIZE: usize = mem::size_of::<(IdKey, IsarObject)>();
+
+const ENTRY_SIZE: usize = mem::size_of::<(u64, u64)>();
+
+// The number of bytes a query may buffer in memory and the directory of the temporary
+// databases that are used when it is exceeded.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryBudget {
+    limit: usize,
+    dir: PathBuf,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: usize, dir: PathBuf) -> Self {
+        MemoryBudget { limit, dir }
+    }
+
+    // Removes temporary databases that were not deleted because the app was killed.
+    pub fn clear_dir(instance_dir: &Path) {
+        let _ = remove_dir_all(instance_dir.join(SPILL_DIR_NAME));
+    }
+
+    fn create_env(&self) -> Result<SpillEnv> {
+        let path = self.dir.join(random::<u64>().to_string());
+        create_dir_all(&path).map_err(|_| IsarError::PathError {})?;
+        let dirty_pages = (self.limit / SPILL_PAGE_SIZE).max(MIN_DIRTY_PAGES);
+        match Env::create_temp(&path, SPILL_PAGE_SIZE, dirty_pages) {
+            Ok(env) => Ok(SpillEnv {
+                env: Some(env),
+                path,
+            }),
+            Err(e) => {
+                let _ = remove_dir_all(&path);
+                Err(e)
+            }
+        }
+    }
+}
+
+// A temporary environment that is created on first use and deleted when it is dropped. It has
+// to outlive the txns of the SortBuffer and SpillMap that use it.
+pub(crate) struct SpillEnv {
+    // Closed before the files are deleted.
+    env: Option<Env>,
+    path: PathBuf,
+}
+
+pub(crate) type LazySpillEnv = OnceCell<SpillEnv>;
+
+impl Drop for SpillEnv {
+    fn drop(&mut self) {
+        drop(self.env.take());
+        let _ = remove_dir_all(&self.path);
+    }
+}
+
+// A write txn of a temporary environment that is never committed.
+struct SpillDb<'s> {
+    txn: Txn<'s>,
+    db: Db,
+    cursor: Option<UnboundCursor>,
+}
+
+impl<'s> SpillDb<'s> {
+    fn open(budget: &MemoryBudget, env: &'s LazySpillEnv) -> Result<Self> {
+        let env = env.get_or_try_init(|| budget.create_env())?;
+        let txn = env.env.as_ref().unwrap().txn(true)?;
+        let db = Db::open(&txn, None, false, false, false)?;
+        Ok(SpillDb {
+            txn,
+            db,
+            cursor: None,
+        })
+    }
+
+    fn with_cursor<T>(&mut self, job: impl FnOnce(&mut Cursor) -> Result<T>) -> Result<T> {
+        let cursor = self.cursor.take().unwrap_or_else(UnboundCursor::new);
+        let mut cursor = cursor.bind(&self.txn, self.db)?;
+        let result = job(&mut cursor);
+        self.cursor = Some(cursor.unbind());
+        result
+    }
+
+    fn get_u64(&mut self, key: u64) -> Result<Option<u64>> {
+        self.with_cursor(|cursor| {
+            let value = cursor
+                .move_to(&key.to_be_bytes())?
+                .map(|(_, value)| u64::from_le_bytes(value.try_into().unwrap()));
+            Ok(value)
+        })
+    }
+}
+
+// Maps hashes to values. The entries are moved to a temporary database when they exceed the
+// memory budget.
+pub(crate) struct SpillMap<'s> {
+    budget: Option<MemoryBudget>,
+    env: &'s LazySpillEnv,
+    map: IntMap<u64>,
+    spill: Option<SpillDb<'s>>,
+}
+
+impl<'s> SpillMap<'s> {
+    pub fn new(budget: Option<MemoryBudget>, env: &'s LazySpillEnv) -> Self {
+        SpillMap {
+            budget,
+            env,
+            map: IntMap::new(),
+            spill: None,
+        }
+    }
+
+    pub fn get(&mut self, key: u64) -> Result<Option<u64>> {
+        if let Some(value) = self.map.get(key) {
+            Ok(Some(*value))
+        } else if let Some(spill) = &mut self.spill {
+            spill.get_u64(key)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn insert(&mut self, key: u64, value: u64) -> Result<()> {
+        if let Some(existing) = self.map.get_mut(key) {
+            *existing = value;
+        } else {
+            self.map.insert(key, value);
+        }
+        if let Some(budget) = &self.budget {
+            if self.map.len() * ENTRY_SIZE > budget.limit {
+                if self.spill.is_none() {
+                    self.spill = Some(SpillDb::open(budget, self.env)?);
+                }
+                let map = &mut self.map;
+                self.spill.as_mut().unwrap().with_cursor(|cursor| {
+                    for (key, value) in map.drain() {
+                        cursor.put(&key.to_be_bytes(), &value.to_le_bytes())?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Collects results for sorting. When the results exceed the memory budget, they are sorted and
+// written to a temporary database as a run. The runs are merged when the results are read.
+pub(crate) struct SortBuffer<'a, 's, 'txn> {
+    query: &'a Query,
+    env: &'s LazySpillEnv,
+    results: Vec<(IdKey<'txn>, IsarObject<'txn>)>,
+    spill: Option<SpillDb<'s>>,
+    // The number of results of every run.
+    runs: Vec<u64>,
+}
+
+impl<'a, 's, 'txn> SortBuffer<'a, 's, 'txn> {
+    pub fn new(query: &'a Query, env: &'s LazySpillEnv) -> Self {
+        SortBuffer {
+            query,
+            env,
+            results: vec![],
+            spill: None,
+            runs: vec![],
+        }
+    }
+
+    pub fn is_spilled(&self) -> bool {
+        !self.runs.is_empty()
+    }
+
+    pub fn into_results(self) -> Vec<(IdKey<'txn>, IsarObject<'txn>)> {
+        self.results
+    }
+
+    pub fn push(&mut self, id_key: IdKey<'txn>, object: IsarObject<'txn>) -> Result<()> {
+        self.results.push((id_key, object));
+        let query = self.query;
+        if let Some(budget) = &query.memory_budget {
+            if self.results.len() * RESULT_SIZE > budget.limit {
+                self.write_run(budget)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_run(&mut self, budget: &MemoryBudget) -> Result<()> {
+        if self.spill.is_none() {
+            self.spill = Some(SpillDb::open(budget, self.env)?);
+        }
+        let query = self.query;
+        self.results
+            .sort_by(|(_, o1), (_, o2)| query.compare_sorted(*o1, *o2));
+        let mut seq: u64 = self.runs.iter().sum();
+        let results = &mut self.results;
+        self.spill.as_mut().unwrap().with_cursor(|cursor| {
+            for (id_key, _) in results.iter() {
+                cursor.append(&seq.to_be_bytes(), id_key.as_bytes())?;
+                seq += 1;
+            }
+            Ok(())
+        })?;
+        self.runs.push(self.results.len() as u64);
+        self.results.clear();
+        Ok(())
+    }
+
+    // Sorts the results and passes them to the callback. Returns false if the callback stopped.
+    pub fn finish<F>(mut self, cursors: &IsarCursors<'txn, '_>, callback: &mut F) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        if self.is_spilled() {
+            self.merge(cursors, callback)
+        } else {
+            let query = self.query;
+            self.results
+                .sort_by(|(_, o1), (_, o2)| query.compare_sorted(*o1, *o2));
+            for (id_key, object) in self.results {
+                if !callback(id_key, object)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+    }
+
+    // Merges the runs in sort order. Equal results are returned in the order they were pushed.
+    pub fn merge<F>(&mut self, cursors: &IsarCursors<'txn, '_>, callback: &mut F) -> Result<bool>
+    where
+        F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
+    {
+        if !self.results.is_empty() {
+            let budget = self.query.memory_budget.clone().unwrap();
+            self.write_run(&budget)?;
+        }
+        let query = self.query;
+        let spill = self.spill.as_mut().unwrap();
+        let mut cursor = cursors.get_cursor(query.db)?;
+        let mut read = |seq: u64| -> Result<(IdKey<'txn>, IsarObject<'txn>)> {
+            let id = spill.with_cursor(|spill_cursor| {
+                let id = spill_cursor
+                    .move_to(&seq.to_be_bytes())?
+                    .map(|(_, id)| id.to_vec());
+                Ok(id)
+            })?;
+            let id = id.ok_or(IsarError::DbCorrupted {
+                message: "Spilled query result not found.".to_string(),
+            })?;
+            let (id, object) = cursor.move_to(&id)?.ok_or(IsarError::DbCorrupted {
+                message: "Query result not found.".to_string(),
+            })?;
+            Ok((IdKey::from_bytes(id), cursors.read_object(object)?))
+        };
+
+        // The next and the end sequence number of every run together with its next result.
+        let mut heads = vec![];
+        let mut start = 0;
+        for len in &self.runs {
+            heads.push((start + 1, start + len, Some(read(start)?)));
+            start += len;
+        }
+        loop {
+            let mut min: Option<usize> = None;
+            for (i, (_, _, result)) in heads.iter().enumerate() {
+                if let Some((_, object)) = result {
+                    let is_less = if let Some(min) = min {
+                        let (_, min_object) = heads[min].2.as_ref().unwrap();
+                        query.compare_sorted(*object, *min_object) == Ordering::Less
+                    } else {
+                        true
+                    };
+                    if is_less {
+                        min = Some(i);
+                    }
+                }
+            }
+            let min = if let Some(min) = min {
+                min
+            } else {
+                return Ok(true);
+            };
+            let (next, end, result) = &mut heads[min];
+            let (id_key, object) = if *next < *end {
+                *next += 1;
+                result.replace(read(*next - 1)?).unwrap()
+            } else {
+                result.take().unwrap()
+            };
+            if !callback(id_key, object)? {
+                return Ok(false);
+            }
+        }
+    }
+}
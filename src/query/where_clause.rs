@@ -2,9 +2,11 @@ use crate::cursor::IsarCursors;
 use crate::error::Result;
 use crate::id_key::IdKey;
 use crate::object::isar_object::IsarObject;
+use crate::query::geo_where_clause::GeoWhereClause;
 use crate::query::id_where_clause::IdWhereClause;
 use crate::query::index_where_clause::IndexWhereClause;
 use crate::query::link_where_clause::LinkWhereClause;
+use crate::query::Sort;
 use intmap::IntMap;
 
 #[derive(Clone)]
@@ -12,6 +14,7 @@ pub(crate) enum WhereClause {
     Id(IdWhereClause),
     Index(IndexWhereClause),
     Link(LinkWhereClause),
+    Geo(GeoWhereClause),
 }
 
 impl WhereClause {
@@ -20,22 +23,113 @@ impl WhereClause {
             WhereClause::Id(wc) => wc.id_matches(id),
             WhereClause::Index(wc) => wc.object_matches(object),
             WhereClause::Link(_) => true,
+            WhereClause::Geo(wc) => wc.object_matches(object),
         }
     }
 
+    pub fn get_type(&self) -> &'static str {
+        match self {
+            WhereClause::Id(_) => "id",
+            WhereClause::Index(_) => "index",
+            WhereClause::Link(_) => "link",
+            WhereClause::Geo(_) => "geo",
+        }
+    }
+
+    pub(crate) fn get_index_name(&self) -> Option<&str> {
+        match self {
+            WhereClause::Index(wc) => Some(&wc.index().name),
+            WhereClause::Geo(wc) => Some(&wc.index().name),
+            _ => None,
+        }
+    }
+
+    // Every object of the collection is read.
+    pub(crate) fn is_full_scan(&self) -> bool {
+        matches!(self, WhereClause::Id(wc) if wc.is_full_range())
+    }
+
+    pub(crate) fn estimate(&self, cursors: &IsarCursors) -> Result<usize> {
+        match self {
+            WhereClause::Id(wc) => wc.estimate(cursors),
+            WhereClause::Index(wc) => wc.estimate(cursors),
+            WhereClause::Link(wc) => wc.estimate(cursors),
+            WhereClause::Geo(wc) => wc.estimate(cursors),
+        }
+    }
+
+    pub(crate) fn is_link(&self) -> bool {
+        matches!(self, WhereClause::Link(_))
+    }
+
+    pub(crate) fn set_sort(&mut self, sort: Sort) {
+        match self {
+            WhereClause::Id(wc) => wc.set_sort(sort),
+            WhereClause::Index(wc) => wc.set_sort(sort),
+            WhereClause::Link(_) => {}
+            WhereClause::Geo(wc) => wc.set_sort(sort),
+        }
+    }
+
+    // The first `offset` results are skipped without passing them to the callback. The offset is
+    // decreased by the number of skipped results.
     pub fn iter<'txn, 'env, 'a, F>(
         &self,
         cursors: &IsarCursors<'txn, 'env>,
         result_ids: Option<&mut IntMap<()>>,
-        callback: F,
+        index_entries: &mut usize,
+        offset: &mut usize,
+        mut callback: F,
     ) -> Result<bool>
     where
         F: FnMut(IdKey<'txn>, IsarObject<'txn>) -> Result<bool>,
     {
+        match self {
+            WhereClause::Index(wc) => {
+                return wc.iter(cursors, result_ids, index_entries, offset, callback)
+            }
+            WhereClause::Geo(wc) => {
+                return wc.iter(cursors, result_ids, index_entries, offset, callback)
+            }
+            _ => {}
+        }
+        let callback = |id_key, object| {
+            if *offset > 0 {
+                *offset -= 1;
+                Ok(true)
+            } else {
+                callback(id_key, object)
+            }
+        };
         match self {
             WhereClause::Id(wc) => wc.iter(cursors, result_ids, callback),
-            WhereClause::Index(wc) => wc.iter(cursors, result_ids, callback),
             WhereClause::Link(wc) => wc.iter(cursors, result_ids, callback),
+            WhereClause::Index(_) | WhereClause::Geo(_) => unreachable!(),
+        }
+    }
+
+    pub fn iter_ids<F>(
+        &self,
+        cursors: &IsarCursors,
+        mut result_ids: Option<&mut IntMap<()>>,
+        mut callback: F,
+    ) -> Result<bool>
+    where
+        F: FnMut(i64) -> Result<bool>,
+    {
+        let mut callback = |id_key: &IdKey| {
+            if let Some(result_ids) = result_ids.as_deref_mut() {
+                if !result_ids.insert(id_key.get_unsigned_id(), ()) {
+                    return Ok(true);
+                }
+            }
+            callback(id_key.get_id())
+        };
+        match self {
+            WhereClause::Id(wc) => wc.iter_ids(cursors, callback),
+            WhereClause::Index(wc) => wc.iter_ids(cursors, |id_key| callback(&id_key)),
+            WhereClause::Link(wc) => wc.iter_ids(cursors, callback),
+            WhereClause::Geo(wc) => wc.iter_ids(cursors, |id_key| callback(&id_key)),
         }
     }
 
@@ -51,7 +145,8 @@ impl WhereClause {
         match self {
             WhereClause::Id(_) => false,
             WhereClause::Index(wc) => wc.has_duplicates(),
-            WhereClause::Link(_) => false,
+            WhereClause::Link(wc) => wc.has_duplicates(),
+            WhereClause::Geo(_) => false,
         }
     }
 }
@@ -0,0 +1,86 @@
+use crate::error::{illegal_arg, IsarError, Result};
+use crate::instance::IsarInstance;
+use crate::query::Query;
+use crate::txn::IsarTxn;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+type QueryJob = Box<dyn FnOnce(Result<&mut IsarTxn>) + Send + 'static>;
+
+// Runs queries on a fixed number of worker threads. While queries are waiting, a worker renews
+// its read txn for the next query instead of beginning a new one. Idle workers close their txn
+// so they don't hold on to an old snapshot.
+//
+// The pool keeps a reference to the instance, so the instance cannot be closed before the pool
+// has been dropped.
+pub struct QueryPool {
+    sender: Option<Sender<QueryJob>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl QueryPool {
+    pub(crate) fn new(instance: &Arc<IsarInstance>, n_threads: usize) -> Result<QueryPool> {
+        if n_threads == 0 {
+            return illegal_arg("The query pool needs at least one thread.");
+        }
+        let (sender, receiver) = unbounded();
+        let workers = (0..n_threads)
+            .map(|_| {
+                let instance = instance.clone();
+                let receiver = receiver.clone();
+                thread::spawn(move || run_worker(&instance, receiver))
+            })
+            .collect();
+        Ok(QueryPool {
+            sender: Some(sender),
+            workers,
+        })
+    }
+
+    // The callback runs on a worker thread with the query and a read txn that is only valid
+    // during the callback. WorkerStopped is returned if all workers have stopped because a
+    // callback panicked.
+    pub fn submit<F>(&self, query: Query, callback: F) -> Result<()>
+    where
+        F: FnOnce(&Query, Result<&mut IsarTxn>) + Send + 'static,
+    {
+        let job: QueryJob = Box::new(move |txn| callback(&query, txn));
+        match &self.sender {
+            Some(sender) if sender.send(job).is_ok() => Ok(()),
+            _ => Err(IsarError::WorkerStopped {}),
+        }
+    }
+}
+
+// Waits for the submitted queries to finish.
+impl Drop for QueryPool {
+    fn drop(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run_worker(instance: &IsarInstance, receiver: Receiver<QueryJob>) {
+    let mut txn: Option<IsarTxn> = None;
+    for job in receiver.iter() {
+        let renewed = match txn.as_mut() {
+            Some(txn) => txn.renew(),
+            None => instance.begin_txn(false, true).map(|new_txn| {
+                txn = Some(new_txn);
+            }),
+        };
+        match renewed {
+            Ok(()) => job(Ok(txn.as_mut().unwrap())),
+            Err(e) => {
+                txn = None;
+                job(Err(e));
+            }
+        }
+        if receiver.is_empty() {
+            txn = None;
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::aggregate::IsarAggregate;
+use crate::error::{schema_error, Result};
+use crate::link::IsarLink;
+use crate::mdbx::db::Db;
+use crate::object::data_type::DataType;
+use crate::object::object_info::ObjectInfo;
+use crate::query::query_json::parse_filter;
+use crate::schema::link_schema::LinkSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::hash::{Hash, Hasher};
+
+// A count or sum over the objects or a count of the links of a collection that is kept up to date
+// on every write.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct AggregateSchema {
+    pub(crate) name: String,
+    // The summed property. Objects are counted if there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) property: Option<String>,
+    // Only objects that match this filter in the format of QueryBuilder::from_json() are
+    // aggregated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) filter: Option<Value>,
+    // The links of this link of the collection are counted instead of the objects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) link: Option<String>,
+}
+
+impl Hash for AggregateSchema {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.property.hash(state);
+        self.filter.as_ref().map(|f| f.to_string()).hash(state);
+        self.link.hash(state);
+    }
+}
+
+impl AggregateSchema {
+    pub fn count(name: &str) -> Self {
+        AggregateSchema {
+            name: name.to_string(),
+            property: None,
+            filter: None,
+            link: None,
+        }
+    }
+
+    pub fn link_count(name: &str, link: &str) -> Self {
+        AggregateSchema {
+            name: name.to_string(),
+            property: None,
+            filter: None,
+            link: Some(link.to_string()),
+        }
+    }
+
+    pub fn sum(name: &str, property: &str) -> Self {
+        AggregateSchema {
+            name: name.to_string(),
+            property: Some(property.to_string()),
+            filter: None,
+            link: None,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Value) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub(crate) fn verify(&self, object_info: &ObjectInfo, links: &[LinkSchema]) -> Result<()> {
+        if let Some(link) = &self.link {
+            if self.property.is_some() || self.filter.is_some() {
+                schema_error("Link aggregates cannot have a property or filter.")?;
+            }
+            if !links.iter().any(|l| &l.name == link) {
+                schema_error("Aggregate link does not exist.")?;
+            }
+        }
+        if let Some(property) = &self.property {
+            let property = object_info.get_property(property);
+            if property.is_none() {
+                schema_error("Aggregate property does not exist.")?;
+            }
+            match property.unwrap().data_type {
+                DataType::Int | DataType::Long | DataType::Float | DataType::Double => {}
+                _ => schema_error("Only Int, Long, Float and Double properties can be summed.")?,
+            }
+        }
+        if let Some(filter) = &self.filter {
+            if parse_filter(object_info, filter).is_err() {
+                schema_error("Invalid aggregate filter.")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn as_aggregate(
+        &self,
+        col_name: &str,
+        info_db: Db,
+        object_info: &ObjectInfo,
+        links: &[(String, IsarLink)],
+    ) -> Result<IsarAggregate> {
+        let property = self
+            .property
+            .as_ref()
+            .and_then(|p| object_info.get_property(p));
+        let filter = if let Some(filter) = &self.filter {
+            Some(parse_filter(object_info, filter)?)
+        } else {
+            None
+        };
+        let link = self.link.as_ref().and_then(|name| {
+            links
+                .iter()
+                .find(|(n, l)| n == name && !l.is_backlink())
+                .map(|(_, l)| *l)
+        });
+        let key = IsarAggregate::key(col_name, &self.name);
+        Ok(IsarAggregate::new(key, info_db, property, filter, link))
+    }
+}
@@ -1,6 +1,8 @@
 use crate::error::{schema_error, IsarError, Result};
 use crate::object::data_type::DataType;
 use crate::object::isar_object::Property;
+use crate::object::object_info::ObjectInfo;
+use crate::schema::aggregate_schema::AggregateSchema;
 use crate::schema::index_schema::{IndexSchema, IndexType};
 use crate::schema::link_schema::LinkSchema;
 use crate::schema::property_schema::PropertySchema;
@@ -16,6 +18,10 @@ pub struct CollectionSchema {
     pub(crate) hidden_properties: Vec<String>,
     pub(crate) indexes: Vec<IndexSchema>,
     pub(crate) links: Vec<LinkSchema>,
+    #[serde(default, rename = "renamedFrom", skip_serializing)]
+    pub(crate) renamed_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) aggregates: Vec<AggregateSchema>,
 }
 
 impl PartialEq for CollectionSchema {
@@ -37,10 +43,25 @@ impl CollectionSchema {
             hidden_properties: vec![],
             indexes,
             links,
+            renamed_from: None,
+            aggregates: vec![],
         }
     }
 
-    fn verify_name(name: &str) -> Result<()> {
+    // The value of the aggregate is maintained by every write and can be read with
+    // IsarCollection::get_aggregate().
+    pub fn with_aggregate(mut self, aggregate: AggregateSchema) -> Self {
+        self.aggregates.push(aggregate);
+        self
+    }
+
+    // Existing data of the collection with the old name is kept when the instance is opened.
+    pub fn renamed_from(mut self, old_name: &str) -> Self {
+        self.renamed_from = Some(old_name.to_string());
+        self
+    }
+
+    pub(crate) fn verify_name(name: &str) -> Result<()> {
         if name.is_empty() {
             schema_error("Empty names are not allowed.")
         } else if name.starts_with('_') {
@@ -53,19 +74,12 @@ impl CollectionSchema {
     pub(crate) fn verify(&self) -> Result<()> {
         Self::verify_name(&self.name)?;
 
-        for property in &self.properties {
-            Self::verify_name(&property.name)?;
-        }
+        Self::verify_properties(&self.properties)?;
 
         for link in &self.links {
             Self::verify_name(&link.name)?;
         }
 
-        let property_names = self.properties.iter().map(|p| p.name.as_str());
-        if property_names.unique().count() != self.properties.len() {
-            schema_error("Duplicate property name")?;
-        }
-
         let index_names = self.indexes.iter().map(|i| i.name.as_str());
         if index_names.unique().count() != self.indexes.len() {
             schema_error("Duplicate index name")?;
@@ -83,6 +97,7 @@ impl CollectionSchema {
                 schema_error("No more than three properties may be used as a composite index")?;
             }
 
+            let mut has_multi_entry = false;
             for (i, index_property) in index.properties.iter().enumerate() {
                 let property = self
                     .properties
@@ -93,6 +108,16 @@ impl CollectionSchema {
                 }
                 let property = property.unwrap();
 
+                if property.is_embedded() {
+                    schema_error("Embedded objects cannot be indexed.")?;
+                }
+
+                if property.data_type == DataType::GeoPoint
+                    && (index.properties.len() > 1 || index_property.index_type != IndexType::Value)
+                {
+                    schema_error("GeoPoint indexes must have a single value property.")?;
+                }
+
                 if property.data_type == DataType::Float
                     || property.data_type == DataType::Double
                     || property.data_type == DataType::FloatList
@@ -100,21 +125,29 @@ impl CollectionSchema {
                 {
                     if index_property.index_type == IndexType::Hash {
                         schema_error("Float values cannot be hashed.")?;
-                    } else if i != index.properties.len() - 1 {
+                    } else if i != index.properties.len() - 1 && index_property.decimals.is_none() {
                         schema_error(
                             "Float indexes must only be at the end of a composite index.",
                         )?;
                     }
                 }
 
-                if property.data_type.get_element_type().is_some() {
-                    if index.properties.len() > 1 && index_property.index_type != IndexType::Hash {
-                        schema_error("Composite list indexes are not supported.")?;
-                    }
-                } else if property.data_type == DataType::String
-                    && i != index.properties.len() - 1
+                if (property.data_type.get_element_type().is_some()
+                    || index_property.splitter.is_some())
                     && index_property.index_type != IndexType::Hash
                 {
+                    if has_multi_entry {
+                        schema_error("Composite indexes may only contain one list property.")?;
+                    }
+                    has_multi_entry = true;
+                }
+
+                let is_string_value = match property.data_type {
+                    DataType::String => index_property.index_type != IndexType::Hash,
+                    DataType::StringList => index_property.index_type == IndexType::Value,
+                    _ => false,
+                };
+                if is_string_value && i != index.properties.len() - 1 {
                     schema_error(
                         "Non-hashed string indexes must only be at the end of a composite index.",
                     )?;
@@ -137,6 +170,50 @@ impl CollectionSchema {
                 {
                     schema_error("Only String and StringList indexes may be case sensitive.")?;
                 }
+                if property.data_type != DataType::String
+                    && property.data_type != DataType::StringList
+                    && index_property.normalize
+                {
+                    schema_error("Only String and StringList indexes may be normalized.")?;
+                }
+                if !index_property.collation.is_binary() {
+                    if property.data_type != DataType::String
+                        && property.data_type != DataType::StringList
+                    {
+                        schema_error("Only String and StringList indexes may use a collation.")?;
+                    } else if index_property.index_type != IndexType::Value {
+                        schema_error("Hashed indexes cannot use a collation.")?;
+                    } else if index_property.normalize {
+                        schema_error("Normalized indexes cannot use a collation.")?;
+                    } else if !index_property.collation.is_supported() {
+                        schema_error("Collations are not supported by this build.")?;
+                    }
+                }
+                if let Some(decimals) = index_property.decimals {
+                    if property.data_type != DataType::Float
+                        && property.data_type != DataType::Double
+                        && property.data_type != DataType::FloatList
+                        && property.data_type != DataType::DoubleList
+                    {
+                        schema_error("Only Float and Double indexes may be rounded.")?;
+                    } else if !(-15..=15).contains(&decimals) {
+                        schema_error("Decimal places must be between -15 and 15.")?;
+                    }
+                }
+                if index_property.splitter.is_some() {
+                    if property.data_type != DataType::String {
+                        schema_error("Only String indexes may be split.")?;
+                    } else if index_property.index_type != IndexType::Value {
+                        schema_error("Hashed indexes cannot be split.")?;
+                    }
+                } else if !index_property.stopwords.is_empty() || index_property.stemmer.is_some() {
+                    schema_error("Only split indexes may have stopwords or a stemmer.")?;
+                }
+                if let Some(stemmer) = index_property.stemmer {
+                    if !stemmer.is_supported() {
+                        schema_error("Stemmers are not supported by this build.")?;
+                    }
+                }
             }
         }
 
@@ -144,28 +221,109 @@ impl CollectionSchema {
             Self::verify_name(&link.name)?;
         }
 
+        let aggregate_names = self.aggregates.iter().map(|a| a.name.as_str());
+        if aggregate_names.unique().count() != self.aggregates.len() {
+            schema_error("Duplicate aggregate name")?;
+        }
+        let object_info = self.get_object_info();
+        for aggregate in &self.aggregates {
+            Self::verify_name(&aggregate.name)?;
+            aggregate.verify(&object_info, &self.links)?;
+        }
+
         Ok(())
     }
 
+    fn verify_properties(properties: &[PropertySchema]) -> Result<()> {
+        for property in properties {
+            Self::verify_name(&property.name)?;
+            property.verify_default()?;
+            if property.compressed
+                && property.data_type != DataType::String
+                && property.data_type != DataType::ByteList
+            {
+                schema_error("Only String and ByteList properties may be compressed.")?;
+            }
+            if let Some(old_name) = &property.renamed_from {
+                if properties.iter().any(|p| &p.name == old_name) {
+                    schema_error("A renamed property must not exist anymore.")?;
+                }
+            }
+            if property.is_embedded() {
+                if property.properties.is_empty() {
+                    schema_error("Embedded objects need at least one property.")?;
+                }
+                if property.properties.iter().any(|p| p.compressed) {
+                    schema_error("Properties of embedded objects cannot be compressed.")?;
+                }
+                Self::verify_properties(&property.properties)?;
+            } else if !property.properties.is_empty() {
+                schema_error("Only Object and ObjectList properties may have properties.")?;
+            }
+        }
+
+        let property_names = properties.iter().map(|p| p.name.as_str());
+        if property_names.unique().count() != properties.len() {
+            schema_error("Duplicate property name")?;
+        }
+
+        Ok(())
+    }
+
+    // Renames existing properties that are renamed by the new schema including their index
+    // properties.
+    pub(crate) fn apply_property_renames(&mut self, new: &Self) {
+        for property in &new.properties {
+            let old_name = if let Some(old_name) = &property.renamed_from {
+                old_name
+            } else {
+                continue;
+            };
+            if self.properties.iter().any(|p| p.name == property.name) {
+                continue;
+            }
+            if let Some(existing) = self.properties.iter_mut().find(|p| &p.name == old_name) {
+                existing.name = property.name.clone();
+                for index in &mut self.indexes {
+                    for index_property in &mut index.properties {
+                        if &index_property.name == old_name {
+                            index_property.name = property.name.clone();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     pub(crate) fn merge_properties(&mut self, existing: &Self) -> Result<()> {
         let mut properties = existing.properties.clone();
         for property in &self.properties {
-            let existing_property = existing.properties.iter().find(|p| p.name == property.name);
+            let existing_property = properties.iter_mut().find(|p| p.name == property.name);
             if let Some(existing_property) = existing_property {
-                if property.data_type != existing_property.data_type {
+                existing_property.compressed = property.compressed;
+                if existing_property.can_widen_to(property.data_type) {
+                    existing_property.data_type = property.data_type;
+                } else if property.data_type != existing_property.data_type {
                     return Err(IsarError::SchemaError {
                         message: format!(
                             "Property \"{}\" already exists but has a different type",
                             property.name
                         ),
                     });
+                } else if property.properties != existing_property.properties {
+                    return Err(IsarError::SchemaError {
+                        message: format!(
+                            "Embedded object \"{}\" already exists but has different properties",
+                            property.name
+                        ),
+                    });
                 }
             } else {
                 properties.push(property.clone());
             }
         }
         for property in &existing.properties {
-            if !self.properties.contains(property) {
+            if !self.properties.iter().any(|p| p.name == property.name) {
                 self.hidden_properties.push(property.name.clone())
             }
         }
@@ -186,6 +344,46 @@ impl CollectionSchema {
         }
         properties
     }
+
+    // Layout of the stored objects including hidden properties.
+    pub(crate) fn get_object_info(&self) -> ObjectInfo {
+        let mut properties = vec![];
+        let mut embedded = vec![];
+        let mut offset = 2;
+        for property_schema in &self.properties {
+            let property = Property::new(property_schema.data_type, offset);
+            properties.push((property_schema.name.clone(), property));
+            if let Some(object_info) = property_schema.get_object_info() {
+                embedded.push((property, object_info));
+            }
+            offset += property_schema.data_type.get_static_size();
+        }
+        ObjectInfo::new(properties, embedded)
+    }
+
+    pub(crate) fn get_compressed_properties(&self) -> Vec<Property> {
+        let properties = self.get_object_info();
+        self.properties
+            .iter()
+            .filter(|p| p.compressed && !self.hidden_properties.contains(&p.name))
+            .filter_map(|p| properties.get_property(&p.name))
+            .collect()
+    }
+
+    pub(crate) fn get_embedded(&self) -> Vec<(Property, ObjectInfo)> {
+        let mut embedded = vec![];
+        let mut offset = 2;
+        for property_schema in &self.properties {
+            if !self.hidden_properties.contains(&property_schema.name) {
+                if let Some(object_info) = property_schema.get_object_info() {
+                    let property = Property::new(property_schema.data_type, offset);
+                    embedded.push((property, object_info));
+                }
+            }
+            offset += property_schema.data_type.get_static_size();
+        }
+        embedded
+    }
 }
 
 /*#[cfg(test)]
@@ -1,6 +1,8 @@
+use crate::collation::Collation;
 use crate::index::{IndexProperty, IsarIndex};
 use crate::mdbx::db::Db;
 use crate::object::isar_object::Property;
+use crate::stemmer::Stemmer;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +13,23 @@ pub enum IndexType {
     HashElements,
 }
 
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum StringSplitter {
+    Whitespace,
+    Words,
+}
+
+impl StringSplitter {
+    // Returns the non-empty parts of the value.
+    pub fn split<'a>(&self, value: &'a str) -> Vec<&'a str> {
+        let parts: Box<dyn Iterator<Item = &'a str>> = match self {
+            StringSplitter::Whitespace => Box::new(value.split(char::is_whitespace)),
+            StringSplitter::Words => Box::new(value.split(|c: char| !c.is_alphanumeric())),
+        };
+        parts.filter(|part| !part.is_empty()).collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct IndexPropertySchema {
     pub(crate) name: String,
@@ -18,6 +37,18 @@ pub struct IndexPropertySchema {
     pub(crate) index_type: IndexType,
     #[serde(rename = "caseSensitive")]
     pub(crate) case_sensitive: bool,
+    #[serde(default)]
+    pub(crate) normalize: bool,
+    #[serde(default)]
+    pub(crate) collation: Collation,
+    #[serde(default)]
+    pub(crate) decimals: Option<i8>,
+    #[serde(default)]
+    pub(crate) splitter: Option<StringSplitter>,
+    #[serde(default)]
+    pub(crate) stopwords: Vec<String>,
+    #[serde(default)]
+    pub(crate) stemmer: Option<Stemmer>,
 }
 
 impl IndexPropertySchema {
@@ -26,8 +57,102 @@ impl IndexPropertySchema {
             name: name.to_string(),
             index_type,
             case_sensitive,
+            normalize: false,
+            collation: Collation::Binary,
+            decimals: None,
+            splitter: None,
+            stopwords: vec![],
+            stemmer: None,
+        }
+    }
+
+    // String keys are NFC normalized and, if case insensitive, unicode case folded.
+    pub fn normalized(name: &str, index_type: IndexType, case_sensitive: bool) -> Self {
+        IndexPropertySchema {
+            name: name.to_string(),
+            index_type,
+            case_sensitive,
+            normalize: true,
+            collation: Collation::Binary,
+            decimals: None,
+            splitter: None,
+            stopwords: vec![],
+            stemmer: None,
+        }
+    }
+
+    // String values are ordered using the rules of the collation.
+    pub fn collated(name: &str, case_sensitive: bool, collation: Collation) -> Self {
+        IndexPropertySchema {
+            name: name.to_string(),
+            index_type: IndexType::Value,
+            case_sensitive,
+            normalize: false,
+            collation,
+            decimals: None,
+            splitter: None,
+            stopwords: vec![],
+            stemmer: None,
         }
     }
+
+    // Float and double values are rounded to the given number of decimal places. Negative
+    // values round to tens, hundreds and so on.
+    pub fn rounded(name: &str, decimals: i8) -> Self {
+        IndexPropertySchema {
+            name: name.to_string(),
+            index_type: IndexType::Value,
+            case_sensitive: false,
+            normalize: false,
+            collation: Collation::Binary,
+            decimals: Some(decimals),
+            splitter: None,
+            stopwords: vec![],
+            stemmer: None,
+        }
+    }
+
+    // Each part of the string gets its own index entry so objects can be found by a single word.
+    pub fn split(name: &str, case_sensitive: bool, splitter: StringSplitter) -> Self {
+        IndexPropertySchema {
+            name: name.to_string(),
+            index_type: IndexType::Value,
+            case_sensitive,
+            normalize: false,
+            collation: Collation::Binary,
+            decimals: None,
+            splitter: Some(splitter),
+            stopwords: vec![],
+            stemmer: None,
+        }
+    }
+
+    // Case insensitive, normalized word index that can be used by text_contains filters.
+    pub fn full_text(name: &str) -> Self {
+        IndexPropertySchema {
+            name: name.to_string(),
+            index_type: IndexType::Value,
+            case_sensitive: false,
+            normalize: true,
+            collation: Collation::Binary,
+            decimals: None,
+            splitter: Some(StringSplitter::Words),
+            stopwords: vec![],
+            stemmer: None,
+        }
+    }
+
+    // Parts of a split string that equal one of the words, ignoring case, are not indexed.
+    pub fn with_stopwords(mut self, stopwords: &[&str]) -> Self {
+        self.stopwords = stopwords.iter().map(|w| w.to_string()).collect();
+        self
+    }
+
+    // Parts of a split string are reduced to their stem before they are indexed.
+    pub fn with_stemmer(mut self, stemmer: Stemmer) -> Self {
+        self.stemmer = Some(stemmer);
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
@@ -52,9 +177,19 @@ impl IndexSchema {
             .iter()
             .map(|p| {
                 let (_, property) = properties.iter().find(|(n, _)| &p.name == n).unwrap();
-                IndexProperty::new(*property, p.index_type, p.case_sensitive)
+                IndexProperty::new(
+                    *property,
+                    p.index_type,
+                    p.case_sensitive,
+                    p.normalize,
+                    p.collation,
+                    p.decimals,
+                    p.splitter,
+                    &p.stopwords,
+                    p.stemmer,
+                )
             })
             .collect_vec();
-        IsarIndex::new(db, index_properties, self.unique)
+        IsarIndex::new(&self.name, db, index_properties, self.unique)
     }
 }
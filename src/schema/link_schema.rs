@@ -1,10 +1,35 @@
 use serde::{Deserialize, Serialize};
 
+// What happens to the linked objects when an object with links is deleted. Clearing a
+// collection only removes its links.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum OnDelete {
+    // Only the links are removed.
+    Unlink,
+    // The linked objects are deleted as well including the objects linked by them.
+    Cascade,
+    // Fails with DeleteRestricted as long as the object has links.
+    Restrict,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for OnDelete {
+    fn default() -> Self {
+        OnDelete::Unlink
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct LinkSchema {
     pub(crate) name: String,
     #[serde(rename = "target")]
     pub(crate) target_col: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) backlink: Option<String>,
+    #[serde(default, rename = "onDelete", skip_serializing)]
+    pub(crate) on_delete: OnDelete,
 }
 
 impl LinkSchema {
@@ -12,6 +37,29 @@ impl LinkSchema {
         LinkSchema {
             name: name.to_string(),
             target_col: target_collection_name.to_string(),
+            backlink: None,
+            on_delete: OnDelete::Unlink,
         }
     }
+
+    // The backlink is exposed as a link of the target collection with the given name.
+    pub fn with_backlink(name: &str, target_collection_name: &str, backlink_name: &str) -> Self {
+        LinkSchema {
+            name: name.to_string(),
+            target_col: target_collection_name.to_string(),
+            backlink: Some(backlink_name.to_string()),
+            on_delete: OnDelete::Unlink,
+        }
+    }
+
+    // Changing the policy does not affect the stored links.
+    pub fn with_on_delete(mut self, on_delete: OnDelete) -> Self {
+        self.on_delete = on_delete;
+        self
+    }
+
+    // Renaming or adding a backlink does not affect the stored links.
+    pub(crate) fn is_same_link(&self, other: &Self) -> bool {
+        self.name == other.name && self.target_col == other.target_col
+    }
 }
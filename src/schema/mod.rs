@@ -1,10 +1,11 @@
+pub mod aggregate_schema;
 pub mod collection_schema;
 pub mod index_schema;
 pub mod link_schema;
 pub mod property_schema;
 pub(crate) mod schema_manager;
 
-use crate::error::{schema_error, Result};
+use crate::error::{schema_error, IsarError, Result};
 use crate::schema::collection_schema::CollectionSchema;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -14,19 +15,123 @@ use std::hash::{Hash, Hasher};
 #[derive(Serialize, Deserialize, Clone, Debug, Hash)]
 pub struct Schema {
     pub(crate) collections: Vec<CollectionSchema>,
+    #[serde(default)]
+    pub(crate) version: u64,
+}
+
+// Schema errors that concern a single collection contain its name.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct SchemaValidationError {
+    pub collection: Option<String>,
+    pub message: String,
+}
+
+impl SchemaValidationError {
+    fn new(collection: Option<&str>, message: &str) -> Self {
+        SchemaValidationError {
+            collection: collection.map(|c| c.to_string()),
+            message: message.to_string(),
+        }
+    }
+
+    fn from_error(collection: Option<&str>, err: IsarError) -> Self {
+        match err {
+            IsarError::SchemaError { message } => Self::new(collection, &message),
+            err => Self::new(collection, &err.to_string()),
+        }
+    }
+}
+
+impl From<SchemaValidationError> for IsarError {
+    fn from(err: SchemaValidationError) -> Self {
+        let message = if let Some(collection) = err.collection {
+            format!("{} (collection {})", err.message, collection)
+        } else {
+            err.message
+        };
+        IsarError::SchemaError { message }
+    }
 }
 
 impl Schema {
-    pub fn new(mut collections: Vec<CollectionSchema>) -> Result<Schema> {
+    pub fn new(collections: Vec<CollectionSchema>) -> Result<Schema> {
+        Self::validate(&collections)?;
+        let schema = Schema {
+            collections,
+            version: 0,
+        };
+        Ok(schema)
+    }
+
+    // Instances cannot be opened with a schema older than the one used before. Unversioned
+    // schemas (version 0) are always accepted.
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn validate(
+        collections: &[CollectionSchema],
+    ) -> std::result::Result<(), SchemaValidationError> {
         if collections.iter().unique_by(|c| &c.name).count() != collections.len() {
-            return schema_error("Duplicate collections");
+            return Err(SchemaValidationError::new(None, "Duplicate collections"));
         }
-        for col in &mut collections {
-            col.verify()?;
+        for col in collections {
+            col.verify()
+                .map_err(|e| SchemaValidationError::from_error(Some(&col.name), e))?;
         }
+        Self::verify_links(collections)
+    }
 
-        let schema = Schema { collections };
-        Ok(schema)
+    // Validates a schema without creating an instance.
+    pub fn validate_json(json: &[u8]) -> std::result::Result<(), SchemaValidationError> {
+        match serde_json::from_slice::<Vec<CollectionSchema>>(json) {
+            Ok(collections) => Self::validate(&collections),
+            Err(e) => Err(SchemaValidationError::new(
+                None,
+                &format!("Could not deserialize schema JSON: {}", e),
+            )),
+        }
+    }
+
+    fn verify_links(
+        collections: &[CollectionSchema],
+    ) -> std::result::Result<(), SchemaValidationError> {
+        for col in collections {
+            for link in &col.links {
+                if !collections.iter().any(|c| c.name == link.target_col) {
+                    return Err(SchemaValidationError::new(
+                        Some(&col.name),
+                        "Link target collection does not exist",
+                    ));
+                }
+            }
+        }
+        for col in collections {
+            let backlink_names = collections
+                .iter()
+                .flat_map(|c| &c.links)
+                .filter(|l| l.target_col == col.name)
+                .filter_map(|l| l.backlink.as_deref())
+                .collect_vec();
+            for name in &backlink_names {
+                CollectionSchema::verify_name(name)
+                    .map_err(|e| SchemaValidationError::from_error(Some(&col.name), e))?;
+            }
+            let link_names = col.links.iter().map(|l| l.name.as_str());
+            let names = link_names.chain(backlink_names.iter().copied());
+            if names.unique().count() != col.links.len() + backlink_names.len() {
+                return Err(SchemaValidationError::new(
+                    Some(&col.name),
+                    "Duplicate link name",
+                ));
+            }
+        }
+        Ok(())
     }
 
     pub fn from_json(json: &[u8]) -> Result<Schema> {
@@ -1,11 +1,46 @@
+use crate::error::{schema_error, Result};
 use crate::object::data_type::DataType;
+use crate::object::isar_object::Property;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::object::object_info::ObjectInfo;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::hash::{Hash, Hasher};
 
-#[derive(PartialEq, Serialize, Deserialize, Clone, Debug, Eq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PropertySchema {
     pub(crate) name: String,
     #[serde(rename = "type")]
     pub(crate) data_type: DataType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) properties: Vec<PropertySchema>,
+    // Migration hints are only used while opening an instance and are not stored.
+    #[serde(default, rename = "renamedFrom", skip_serializing)]
+    pub(crate) renamed_from: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub(crate) default: Option<Value>,
+    // Compressed values are detected when they are read so the property can be compressed or
+    // decompressed without a migration.
+    #[serde(default, skip_serializing)]
+    pub(crate) compressed: bool,
+}
+
+impl PartialEq for PropertySchema {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.data_type == other.data_type
+            && self.properties == other.properties
+    }
+}
+
+impl Eq for PropertySchema {}
+
+impl Hash for PropertySchema {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.data_type.hash(state);
+        self.properties.hash(state);
+    }
 }
 
 impl PropertySchema {
@@ -13,6 +48,90 @@ impl PropertySchema {
         PropertySchema {
             name: name.to_string(),
             data_type,
+            properties: vec![],
+            renamed_from: None,
+            default: None,
+            compressed: false,
+        }
+    }
+
+    // Object and ObjectList properties contain objects with the given properties.
+    pub fn embedded(name: &str, data_type: DataType, properties: Vec<PropertySchema>) -> Self {
+        PropertySchema {
+            name: name.to_string(),
+            data_type,
+            properties,
+            renamed_from: None,
+            default: None,
+            compressed: false,
+        }
+    }
+
+    // Existing data of the property with the old name is kept when the instance is opened.
+    pub fn renamed_from(mut self, old_name: &str) -> Self {
+        self.renamed_from = Some(old_name.to_string());
+        self
+    }
+
+    // The value is written to all existing objects when the property is added.
+    pub fn with_default(mut self, value: Value) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    // Large String and ByteList values are stored compressed. Only new or changed objects are
+    // compressed.
+    pub fn compressed(mut self) -> Self {
+        self.compressed = true;
+        self
+    }
+
+    // Int and Float properties can be migrated to Long and Double without losing data.
+    pub(crate) fn can_widen_to(&self, data_type: DataType) -> bool {
+        matches!(
+            (self.data_type, data_type),
+            (DataType::Int, DataType::Long)
+                | (DataType::Float, DataType::Double)
+                | (DataType::IntList, DataType::LongList)
+                | (DataType::FloatList, DataType::DoubleList)
+        )
+    }
+
+    pub(crate) fn verify_default(&self) -> Result<()> {
+        if let Some(default) = &self.default {
+            let property = Property::new(self.data_type, 2);
+            let embedded = self
+                .get_object_info()
+                .map(|object_info| vec![(property, object_info)])
+                .unwrap_or_default();
+            let object_info = ObjectInfo::new(vec![(self.name.clone(), property)], embedded);
+            let json = json!({ self.name.clone(): default });
+            if JsonEncodeDecode::decode(&object_info, &json, None).is_err() {
+                schema_error("The default value does not match the property type.")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_embedded(&self) -> bool {
+        self.data_type == DataType::Object || self.data_type == DataType::ObjectList
+    }
+
+    pub(crate) fn get_object_info(&self) -> Option<ObjectInfo> {
+        if !self.is_embedded() {
+            return None;
+        }
+        let mut properties = vec![];
+        let mut embedded = vec![];
+        let mut offset = 2;
+        for property_schema in &self.properties {
+            let property = Property::new(property_schema.data_type, offset);
+            properties.push((property_schema.name.clone(), property));
+            if let Some(object_info) = property_schema.get_object_info() {
+                embedded.push((property, object_info));
+            }
+            offset += property_schema.data_type.get_static_size();
         }
+        Some(ObjectInfo::new(properties, embedded))
     }
 }
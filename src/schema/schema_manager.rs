@@ -1,43 +1,81 @@
-use crate::collection::IsarCollection;
-use crate::cursor::IsarCursors;
-use crate::error::{IsarError, Result};
+use crate::aggregate::IsarAggregate;
+use crate::change_log::CHANGE_LOG_DB_NAME;
+use crate::checksum;
+use crate::collection::{ChangeToken, IsarCollection};
+use crate::config::IsarConfig;
+use crate::cursor::{CursorCache, IsarCursors};
+use crate::error::{schema_error, IsarError, Result};
+use crate::id_key::IdKey;
 use crate::link::IsarLink;
 use crate::mdbx::cursor::{Cursor, UnboundCursor};
 use crate::mdbx::db::Db;
+use crate::mdbx::env::Env;
 use crate::mdbx::txn::Txn;
+use crate::object::compression;
+use crate::object::json_encode_decode::JsonEncodeDecode;
+use crate::query::spill::MemoryBudget;
+use crate::schema::aggregate_schema::AggregateSchema;
 use crate::schema::collection_schema::CollectionSchema;
 use crate::schema::index_schema::IndexSchema;
 use crate::schema::link_schema::LinkSchema;
 use crate::schema::Schema;
-use itertools::Itertools;
-use std::collections::HashMap;
 use std::convert::TryInto;
+use std::thread;
+use xxhash_rust::xxh3::xxh3_64;
 
 const ISAR_VERSION: u64 = 1;
 const INFO_VERSION_KEY: &[u8] = b"version";
 const INFO_SCHEMA_KEY: &[u8] = b"schema";
+const INFO_CHECKSUMS_KEY: &[u8] = b"checksums";
+const INFO_SCHEMA_HASH_KEY: &[u8] = b"schema_hash";
 
 pub(crate) struct SchemaManger<'a> {
     instance_id: u64,
     txn: &'a Txn<'a>,
+    info_db: Db,
     info_cursor: Cursor<'a>,
-    new_indexes: HashMap<String, Vec<usize>>,
+    config: &'a IsarConfig,
+    existing_checksums: bool,
+    change_log_db: Option<Db>,
 }
 
 impl<'a> SchemaManger<'a> {
-    pub fn create(instance_id: u64, txn: &'a Txn<'a>) -> Result<Self> {
+    pub fn create(instance_id: u64, txn: &'a Txn<'a>, config: &'a IsarConfig) -> Result<Self> {
         let info_db = Db::open(txn, Some("_info"), false, false, false)?;
         let info_cursor = UnboundCursor::new();
+        let change_log_db = if config.change_log {
+            Some(Db::open(
+                txn,
+                Some(CHANGE_LOG_DB_NAME),
+                false,
+                false,
+                false,
+            )?)
+        } else {
+            None
+        };
         let mut manager = SchemaManger {
             instance_id,
             txn,
+            info_db,
             info_cursor: info_cursor.bind(txn, info_db)?,
-            new_indexes: HashMap::new(),
+            config,
+            existing_checksums: false,
+            change_log_db,
         };
         manager.check_isar_version()?;
+        manager.existing_checksums = manager.info_cursor.move_to(INFO_CHECKSUMS_KEY)?.is_some();
         Ok(manager)
     }
 
+    pub fn get_info_db(&self) -> Db {
+        self.info_db
+    }
+
+    pub fn get_change_log_db(&self) -> Option<Db> {
+        self.change_log_db
+    }
+
     fn check_isar_version(&mut self) -> Result<()> {
         let version = self.info_cursor.move_to(INFO_VERSION_KEY)?;
         if let Some((_, version)) = version {
@@ -52,6 +90,28 @@ impl<'a> SchemaManger<'a> {
         Ok(())
     }
 
+    // The hash of the schema that was passed to the last migration, before it was merged with the
+    // existing schema.
+    fn schema_hash(schema: &Schema) -> Result<u64> {
+        let bytes = serde_json::to_vec(schema).map_err(|_| IsarError::SchemaError {
+            message: "Could not serialize schema.".to_string(),
+        })?;
+        Ok(xxh3_64(&bytes))
+    }
+
+    // Whether opening the instance with this schema and config changes the database. Interrupted
+    // index builds are resumed so they count as a migration.
+    pub fn needs_migration(&mut self, schema: &Schema) -> Result<bool> {
+        let hash = Self::schema_hash(schema)?;
+        let same_schema = match self.info_cursor.move_to(INFO_SCHEMA_HASH_KEY)? {
+            Some((_, existing_hash)) => existing_hash == hash.to_le_bytes(),
+            None => false,
+        };
+        Ok(!same_schema
+            || self.config.checksums != self.existing_checksums
+            || !self.get_pending_index_builds(schema)?.is_empty())
+    }
+
     fn get_existing_schema(&mut self) -> Result<Schema> {
         let existing_schema_bytes = self.info_cursor.move_to(INFO_SCHEMA_KEY)?;
 
@@ -68,6 +128,11 @@ impl<'a> SchemaManger<'a> {
         Db::open(self.txn, Some(&col.name), true, false, false)
     }
 
+    fn open_checksum_db(&mut self, col: &CollectionSchema) -> Result<Db> {
+        let db_name = format!("_s_{}", col.name);
+        Db::open(self.txn, Some(&db_name), true, false, false)
+    }
+
     fn open_index_db(&mut self, col: &CollectionSchema, index: &IndexSchema) -> Result<Db> {
         let db_name = format!("_i_{}_{}", col.name, index.name);
         Db::open(self.txn, Some(&db_name), false, !index.unique, false)
@@ -90,12 +155,43 @@ impl<'a> SchemaManger<'a> {
         for link in &col.links {
             self.delete_link(col, link)?;
         }
+        if self.existing_checksums {
+            self.open_checksum_db(col)?.drop(self.txn)?;
+        }
+        if self
+            .info_cursor
+            .move_to(&ChangeToken::key(&col.name))?
+            .is_some()
+        {
+            self.info_cursor.delete_current()?;
+        }
+        for aggregate in &col.aggregates {
+            self.delete_aggregate(col, aggregate)?;
+        }
+        Ok(())
+    }
+
+    // The aggregate is computed again when the collection is opened.
+    fn delete_aggregate(
+        &mut self,
+        col: &CollectionSchema,
+        aggregate: &AggregateSchema,
+    ) -> Result<()> {
+        let key = IsarAggregate::key(&col.name, &aggregate.name);
+        if self.info_cursor.move_to(&key)?.is_some() {
+            self.info_cursor.delete_current()?;
+        }
         Ok(())
     }
 
     fn delete_index(&mut self, col: &CollectionSchema, index: &IndexSchema) -> Result<()> {
         let db = self.open_index_db(col, index)?;
-        db.drop(self.txn)
+        db.drop(self.txn)?;
+        let build_key = index_build_key(&col.name, &index.name);
+        if self.info_cursor.move_to(&build_key)?.is_some() {
+            self.info_cursor.delete_current()?;
+        }
+        Ok(())
     }
 
     fn delete_link(&mut self, col: &CollectionSchema, link: &LinkSchema) -> Result<()> {
@@ -104,8 +200,185 @@ impl<'a> SchemaManger<'a> {
         bl_db.drop(self.txn)
     }
 
+    // Copies all entries to the new db and drops the old one.
+    fn move_db(&mut self, from: Db, to: Db) -> Result<()> {
+        {
+            let mut from_cursor = UnboundCursor::new().bind(self.txn, from)?;
+            let mut to_cursor = UnboundCursor::new().bind(self.txn, to)?;
+            let mut entry = from_cursor.move_to_first()?;
+            while let Some((key, val)) = entry {
+                to_cursor.put(key, val)?;
+                entry = from_cursor.move_to_next()?;
+            }
+        }
+        from.drop(self.txn)
+    }
+
+    fn move_info_entry(&mut self, from: &[u8], to: &[u8]) -> Result<()> {
+        if let Some((_, val)) = self.info_cursor.move_to(from)? {
+            let val = val.to_vec();
+            self.info_cursor.delete_current()?;
+            self.info_cursor.put(to, &val)?;
+        }
+        Ok(())
+    }
+
+    fn rename_collection(&mut self, existing_col: &CollectionSchema, new_name: &str) -> Result<()> {
+        let mut renamed_col = existing_col.clone();
+        renamed_col.name = new_name.to_string();
+
+        let db = self.open_collection_db(existing_col)?;
+        let renamed_db = self.open_collection_db(&renamed_col)?;
+        self.move_db(db, renamed_db)?;
+        if self.existing_checksums {
+            let db = self.open_checksum_db(existing_col)?;
+            let renamed_db = self.open_checksum_db(&renamed_col)?;
+            self.move_db(db, renamed_db)?;
+        }
+        for index in &existing_col.indexes {
+            let db = self.open_index_db(existing_col, index)?;
+            let renamed_db = self.open_index_db(&renamed_col, index)?;
+            self.move_db(db, renamed_db)?;
+            self.move_info_entry(
+                &index_build_key(&existing_col.name, &index.name),
+                &index_build_key(new_name, &index.name),
+            )?;
+        }
+        for link in &existing_col.links {
+            let (db, bl_db) = self.open_link_dbs(existing_col, link)?;
+            let (renamed_db, renamed_bl_db) = self.open_link_dbs(&renamed_col, link)?;
+            self.move_db(db, renamed_db)?;
+            self.move_db(bl_db, renamed_bl_db)?;
+        }
+        for aggregate in &existing_col.aggregates {
+            self.move_info_entry(
+                &IsarAggregate::key(&existing_col.name, &aggregate.name),
+                &IsarAggregate::key(new_name, &aggregate.name),
+            )?;
+        }
+        self.move_info_entry(
+            &ChangeToken::key(&existing_col.name),
+            &ChangeToken::key(new_name),
+        )
+    }
+
+    // Moves the data of renamed collections and updates the existing schema accordingly. Hints
+    // are ignored if the old collection does not exist (anymore).
+    fn apply_collection_renames(&mut self, schema: &Schema, existing: &mut Schema) -> Result<()> {
+        for col in &schema.collections {
+            let old_name = if let Some(old_name) = &col.renamed_from {
+                old_name
+            } else {
+                continue;
+            };
+            if existing.get_collection(&col.name).is_some() {
+                continue;
+            }
+            let existing_col = existing
+                .collections
+                .iter_mut()
+                .find(|c| &c.name == old_name);
+            if let Some(existing_col) = existing_col {
+                let old_col = existing_col.clone();
+                existing_col.name = col.name.clone();
+                self.rename_collection(&old_col, &col.name)?;
+                for existing_col in &mut existing.collections {
+                    for link in &mut existing_col.links {
+                        if &link.target_col == old_name {
+                            link.target_col = col.name.clone();
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Rewrites all objects if properties have been widened or added properties have a default
+    // value. Returns the names of the widened properties.
+    fn migrate_objects(
+        &mut self,
+        col: &CollectionSchema,
+        existing_col: &CollectionSchema,
+    ) -> Result<Vec<String>> {
+        let widened: Vec<String> = existing_col
+            .properties
+            .iter()
+            .zip(&col.properties)
+            .filter(|(existing, p)| existing.data_type != p.data_type)
+            .map(|(_, p)| p.name.clone())
+            .collect();
+        let defaults: Vec<_> = col.properties[existing_col.properties.len()..]
+            .iter()
+            .filter_map(|p| p.default.as_ref().map(|d| (p.name.clone(), d.clone())))
+            .collect();
+        if widened.is_empty() && defaults.is_empty() {
+            return Ok(widened);
+        }
+
+        let existing_info = existing_col.get_object_info();
+        let object_info = col.get_object_info();
+        let compressed_properties = col.get_compressed_properties();
+        let db = self.open_collection_db(col)?;
+        let checksum_db = if self.existing_checksums {
+            Some(self.open_checksum_db(col)?)
+        } else {
+            None
+        };
+        let cursors = IsarCursors::new(self.txn, CursorCache::default());
+        let mut cursor = cursors.get_cursor(db)?;
+        let mut entry = cursor.move_to_first()?;
+        while let Some((id_bytes, bytes)) = entry {
+            let id_bytes = id_bytes.to_vec();
+            // The job only reads the object itself.
+            let mut json = unsafe {
+                cursors.with_object(bytes, |object| {
+                    Ok(JsonEncodeDecode::encode(
+                        &existing_info,
+                        object,
+                        true,
+                        false,
+                    ))
+                })
+            }?;
+            for (name, default) in &defaults {
+                json.insert(name.clone(), default.clone());
+            }
+            let ob = JsonEncodeDecode::decode(&object_info, &json.into(), None)?;
+            let new_object = ob.finish();
+            let compressed = compression::compress(new_object, &compressed_properties);
+            let bytes = compressed
+                .as_deref()
+                .unwrap_or_else(|| new_object.as_bytes());
+            cursor.put(&id_bytes, bytes)?;
+            if let Some(checksum_db) = checksum_db {
+                let id_key = IdKey::from_bytes(&id_bytes);
+                checksum::write(&cursors, checksum_db, &id_key, new_object.as_bytes())?;
+            }
+            entry = cursor.move_to_next()?;
+        }
+        Ok(widened)
+    }
+
     pub fn perform_migration(&mut self, schema: &mut Schema) -> Result<()> {
-        let existing_schema = self.get_existing_schema()?;
+        let hash = Self::schema_hash(schema)?;
+        let mut existing_schema = self.get_existing_schema()?;
+        if schema.version == 0 {
+            schema.version = existing_schema.version;
+        } else if schema.version < existing_schema.version {
+            return schema_error("The schema is older than the schema of the existing database.");
+        }
+
+        self.apply_collection_renames(schema, &mut existing_schema)?;
+        for col in &schema.collections {
+            if let Some(existing_col) = existing_schema
+                .collections
+                .iter_mut()
+                .find(|c| c.name == col.name)
+            {
+                existing_col.apply_property_renames(col);
+            }
+        }
 
         let deleted_cols = get_added(&schema.collections, &existing_schema.collections);
         for col in deleted_cols {
@@ -116,21 +389,48 @@ impl<'a> SchemaManger<'a> {
             let existing_col = existing_schema.get_collection(&col.name);
             if let Some(existing_col) = existing_col {
                 col.merge_properties(existing_col)?;
-
-                let added_indexes = get_added(&existing_col.indexes, &col.indexes)
-                    .iter()
-                    .map(|new_i| col.indexes.iter().position(|i| i == *new_i).unwrap())
-                    .collect_vec();
-                if !added_indexes.is_empty() {
-                    self.new_indexes.insert(col.name.clone(), added_indexes);
-                }
+                let widened = self.migrate_objects(col, existing_col)?;
 
                 let deleted_indexes = get_added(&col.indexes, &existing_col.indexes);
                 for index in deleted_indexes {
                     self.delete_index(existing_col, index)?;
                 }
 
-                let deleted_links = get_added(&existing_col.links, &col.links);
+                // New indexes are filled in batches after the migration has been committed.
+                let added_indexes = get_added(&existing_col.indexes, &col.indexes);
+                for index in added_indexes {
+                    let build_key = index_build_key(&col.name, &index.name);
+                    self.info_cursor.put(&build_key, &i64::MIN.to_le_bytes())?;
+                }
+
+                // Keys of widened properties have a different size so the index is rebuilt.
+                let widened_indexes = col.indexes.iter().filter(|index| {
+                    existing_col.indexes.contains(index)
+                        && index.properties.iter().any(|p| widened.contains(&p.name))
+                });
+                for index in widened_indexes {
+                    self.delete_index(existing_col, index)?;
+                    let build_key = index_build_key(&col.name, &index.name);
+                    self.info_cursor.put(&build_key, &i64::MIN.to_le_bytes())?;
+                }
+
+                // Migrated objects may have different values so all aggregates are recomputed.
+                let migrated = !widened.is_empty()
+                    || col
+                        .properties
+                        .iter()
+                        .skip(existing_col.properties.len())
+                        .any(|p| p.default.is_some());
+                for aggregate in &existing_col.aggregates {
+                    if migrated || !col.aggregates.contains(aggregate) {
+                        self.delete_aggregate(existing_col, aggregate)?;
+                    }
+                }
+
+                let deleted_links = existing_col
+                    .links
+                    .iter()
+                    .filter(|existing| !col.links.iter().any(|l| l.is_same_link(existing)));
                 for link in deleted_links {
                     self.delete_link(existing_col, link)?;
                 }
@@ -138,6 +438,8 @@ impl<'a> SchemaManger<'a> {
         }
 
         self.save_schema(schema)?;
+        self.info_cursor
+            .put(INFO_SCHEMA_HASH_KEY, &hash.to_le_bytes())?;
 
         Ok(())
     }
@@ -150,26 +452,65 @@ impl<'a> SchemaManger<'a> {
         Ok(())
     }
 
-    pub fn open_collections(&mut self, schema: &Schema) -> Result<Vec<IsarCollection>> {
-        let cursors = IsarCursors::new(self.txn, vec![]);
+    fn save_checksums(&mut self) -> Result<()> {
+        if self.config.checksums && !self.existing_checksums {
+            self.info_cursor.put(INFO_CHECKSUMS_KEY, &[1])?;
+        } else if !self.config.checksums && self.existing_checksums {
+            self.info_cursor.move_to(INFO_CHECKSUMS_KEY)?;
+            self.info_cursor.delete_current()?;
+        }
+        Ok(())
+    }
+
+    pub fn open_collections(
+        &mut self,
+        schema: &Schema,
+        memory_budget: Option<MemoryBudget>,
+    ) -> Result<Vec<IsarCollection>> {
+        let cursors = IsarCursors::new(self.txn, CursorCache::default());
         let mut cols = vec![];
         for col_schema in &schema.collections {
-            let col = self.open_collection(schema, col_schema)?;
+            let col = self.open_collection(schema, col_schema, memory_budget.clone())?;
             col.init_auto_increment(&cursors)?;
-            if let Some(new_indexes) = self.new_indexes.get(&col.name) {
-                col.fill_indexes(new_indexes, &cursors)?;
+            col.init_aggregates(&cursors)?;
+            if self.config.checksums && !self.existing_checksums {
+                col.fill_checksums(&cursors)?;
+            } else if !self.config.checksums && self.existing_checksums {
+                self.open_checksum_db(col_schema)?.drop(self.txn)?;
             }
             cols.push(col);
         }
+        self.save_checksums()?;
         Ok(cols)
     }
 
+    // Returns the collection and index names of all indexes that have not been filled yet. This
+    // includes builds that were interrupted before.
+    pub fn get_pending_index_builds(&mut self, schema: &Schema) -> Result<Vec<(String, String)>> {
+        let mut pending = vec![];
+        for col in &schema.collections {
+            for index in &col.indexes {
+                let build_key = index_build_key(&col.name, &index.name);
+                if self.info_cursor.move_to(&build_key)?.is_some() {
+                    pending.push((col.name.clone(), index.name.clone()));
+                }
+            }
+        }
+        Ok(pending)
+    }
+
     fn open_collection(
         &mut self,
         schema: &Schema,
         col_schema: &CollectionSchema,
+        memory_budget: Option<MemoryBudget>,
     ) -> Result<IsarCollection> {
         let db = self.open_collection_db(col_schema)?;
+        let checksum_db = if self.config.checksums {
+            Some(self.open_checksum_db(col_schema)?)
+        } else {
+            None
+        };
         let mut properties = col_schema.get_properties();
         properties.sort_by(|(a, _), (b, _)| a.cmp(b));
 
@@ -186,22 +527,101 @@ impl<'a> SchemaManger<'a> {
             let (link_db, backlink_db) = self.open_link_dbs(col_schema, link_schema)?;
             let target_col_schema = schema.get_collection(&link_schema.target_col).unwrap();
             let target_db = self.open_collection_db(target_col_schema)?;
-            let link = IsarLink::new(link_db, backlink_db, db, target_db);
+            let link = IsarLink::new(link_db, backlink_db, db, target_db)
+                .with_on_delete(link_schema.on_delete);
             links.push((link_schema.name.clone(), link));
         }
+        for source_col_schema in &schema.collections {
+            for link_schema in &source_col_schema.links {
+                if link_schema.target_col != col_schema.name {
+                    continue;
+                }
+                if let Some(backlink_name) = &link_schema.backlink {
+                    let (link_db, backlink_db) =
+                        self.open_link_dbs(source_col_schema, link_schema)?;
+                    let source_db = self.open_collection_db(source_col_schema)?;
+                    let link = IsarLink::new(link_db, backlink_db, source_db, db);
+                    links.push((backlink_name.clone(), link.to_backlink()));
+                }
+            }
+        }
         links.sort_by(|(a, _), (b, _)| a.cmp(b));
 
+        let object_info = col_schema.get_object_info();
+        let mut aggregates = vec![];
+        for aggregate_schema in &col_schema.aggregates {
+            let aggregate = aggregate_schema.as_aggregate(
+                &col_schema.name,
+                self.info_db,
+                &object_info,
+                &links,
+            )?;
+            aggregates.push((aggregate_schema.name.clone(), aggregate));
+        }
+
         Ok(IsarCollection::new(
             db,
+            checksum_db,
+            self.change_log_db,
             self.instance_id,
             col_schema.name.clone(),
             properties,
+            col_schema.get_embedded(),
+            col_schema.get_compressed_properties(),
             indexes,
             links,
+            aggregates,
+            self.config.property_stats,
+            memory_budget,
         ))
     }
 }
 
+fn index_build_key(col_name: &str, index_name: &str) -> Vec<u8> {
+    format!("_ib_{}_{}", col_name, index_name).into_bytes()
+}
+
+// Fills the index using one write transaction per batch. The progress is stored after every batch
+// so an interrupted build continues where it stopped the next time the instance is opened.
+pub(crate) fn build_index(
+    env: &Env,
+    info_db: Db,
+    collections: &[IsarCollection],
+    col: &IsarCollection,
+    index_name: &str,
+    batch_size: usize,
+) -> Result<()> {
+    let build_key = index_build_key(&col.name, index_name);
+    loop {
+        let txn = env.txn(true)?;
+        let finished = {
+            let cursors =
+                IsarCursors::new(&txn, CursorCache::default()).with_collections(collections);
+            let mut info_cursor = cursors.get_cursor(info_db)?;
+            let start_id = if let Some((_, start_id)) = info_cursor.move_to(&build_key)? {
+                i64::from_le_bytes(start_id.try_into().unwrap())
+            } else {
+                break;
+            };
+            let next_id = col.fill_index(index_name, start_id, batch_size, &cursors)?;
+            if let Some(next_id) = next_id {
+                info_cursor.put(&build_key, &next_id.to_le_bytes())?;
+                false
+            } else {
+                info_cursor.move_to(&build_key)?;
+                info_cursor.delete_current()?;
+                true
+            }
+        };
+        txn.commit()?;
+        if finished {
+            break;
+        }
+        thread::yield_now();
+    }
+    Ok(())
+}
+
 fn get_added<'a, E>(left: &'a [E], right: &'a [E]) -> Vec<&'a E>
 where
     E: Eq,
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Stemmer {
+    Arabic,
+    Danish,
+    Dutch,
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+}
+
+impl Stemmer {
+    // Snowball stemmers are only available with the snowball feature.
+    pub fn is_supported(&self) -> bool {
+        cfg!(feature = "snowball")
+    }
+
+    // Reduces a lowercase word to its stem so that for example "running" and "runs" produce the
+    // same key. Words are returned unchanged if stemming is not supported.
+    pub fn stem(&self, word: &str) -> String {
+        #[cfg(feature = "snowball")]
+        return rust_stemmers::Stemmer::create(self.algorithm())
+            .stem(word)
+            .into_owned();
+        #[cfg(not(feature = "snowball"))]
+        word.to_string()
+    }
+
+    #[cfg(feature = "snowball")]
+    fn algorithm(&self) -> rust_stemmers::Algorithm {
+        use rust_stemmers::Algorithm;
+        match self {
+            Stemmer::Arabic => Algorithm::Arabic,
+            Stemmer::Danish => Algorithm::Danish,
+            Stemmer::Dutch => Algorithm::Dutch,
+            Stemmer::English => Algorithm::English,
+            Stemmer::Finnish => Algorithm::Finnish,
+            Stemmer::French => Algorithm::French,
+            Stemmer::German => Algorithm::German,
+            Stemmer::Greek => Algorithm::Greek,
+            Stemmer::Hungarian => Algorithm::Hungarian,
+            Stemmer::Italian => Algorithm::Italian,
+            Stemmer::Norwegian => Algorithm::Norwegian,
+            Stemmer::Portuguese => Algorithm::Portuguese,
+            Stemmer::Romanian => Algorithm::Romanian,
+            Stemmer::Russian => Algorithm::Russian,
+            Stemmer::Spanish => Algorithm::Spanish,
+            Stemmer::Swedish => Algorithm::Swedish,
+            Stemmer::Tamil => Algorithm::Tamil,
+            Stemmer::Turkish => Algorithm::Turkish,
+        }
+    }
+}
@@ -1,37 +1,280 @@
-use crate::cursor::IsarCursors;
-use crate::error::{IsarError, Result};
+use crate::collection::{ChangeToken, IsarCollection};
+use crate::cursor::{CursorCache, IsarCursors, ObjectBuffers};
+use crate::error::{illegal_arg, IsarError, Result};
+use crate::histogram::LatencyHistogram;
 use crate::mdbx::cursor::UnboundCursor;
 use crate::mdbx::db::Db;
-use crate::mdbx::txn::Txn;
+use crate::mdbx::env::Env;
+use crate::mdbx::txn::{Txn, WriteCounter};
+use crate::txn_queue::WriteTicket;
 use crate::watch::change_set::ChangeSet;
-use std::cell::RefCell;
+use crate::watch::log::log_warning;
+use crate::write_profile::{CollectionWriteProfile, WriteProfile};
+use byteorder::{ByteOrder, LittleEndian};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Sequence number of the last change applied with IsarInstance::apply_changes().
+const APPLIED_SEQ_KEY: &[u8] = b"_applied_seq";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TxnStats {
+    pub dirty_pages: u64,
+    pub bytes_written: u64,
+    pub operations: u64,
+    pub elapsed: Duration,
+    pub cursors_opened: u64,
+    // Cursors that were taken from the cursor cache instead of being opened.
+    pub cursors_reused: u64,
+    // Cursors that were closed because the cursor cache was full.
+    pub cursors_evicted: u64,
+}
+
+// Write transactions with interactive priority are started before waiting background writes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TxnPriority {
+    Interactive,
+    Background,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Backoff doubles after every failed attempt.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+// Nested txns of the savepoints with the innermost savepoint last. They are closed before their
+// parents.
+struct Savepoints<'env>(Vec<Txn<'env>>);
+
+impl<'env> Drop for Savepoints<'env> {
+    fn drop(&mut self) {
+        while self.0.pop().is_some() {}
+    }
+}
 
 pub struct IsarTxn<'env> {
     instance_id: u64,
+    env: &'env Env,
+    collections: &'env [IsarCollection],
+    // Has to be dropped before txn.
+    savepoints: Savepoints<'env>,
     txn: Txn<'env>,
+    snapshot: Option<Txn<'env>>,
+    info_db: Db,
     write: bool,
+    changed_collections: HashSet<String>,
     change_set: RefCell<Option<ChangeSet<'env>>>,
-    unbound_cursors: RefCell<Option<Vec<UnboundCursor>>>,
+    cursor_cache: RefCell<Option<CursorCache>>,
+    // Objects of the last operation that were decompressed.
+    object_buffers: RefCell<ObjectBuffers>,
+    // Instance name and the number of dirty bytes after which a warning is logged once.
+    size_warning: Option<(&'env str, u64)>,
+    size_warned: Cell<bool>,
+    // The number of savepoints when an operation failed. The partial writes of the operation
+    // have to be rolled back before the txn can be committed.
+    failed_savepoints: Cell<Option<usize>>,
+    commit_latency: &'env LatencyHistogram,
+    write_profile: Option<RefCell<WriteProfile>>,
+    // Released after the transaction has been committed or aborted.
+    _write_ticket: Option<WriteTicket<'env>>,
 }
 
 impl<'env> IsarTxn<'env> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         instance_id: u64,
+        env: &'env Env,
+        collections: &'env [IsarCollection],
         txn: Txn<'env>,
+        info_db: Db,
         write: bool,
         change_set: Option<ChangeSet<'env>>,
+        cursor_cache: CursorCache,
+        size_warning: Option<(&'env str, u64)>,
+        commit_latency: &'env LatencyHistogram,
+        write_ticket: Option<WriteTicket<'env>>,
     ) -> Result<Self> {
         Ok(IsarTxn {
             instance_id,
+            env,
+            collections,
+            savepoints: Savepoints(vec![]),
             txn,
+            snapshot: None,
+            info_db,
             write,
+            changed_collections: HashSet::new(),
             change_set: RefCell::new(change_set),
-            unbound_cursors: RefCell::new(Some(vec![])),
+            cursor_cache: RefCell::new(Some(cursor_cache)),
+            object_buffers: RefCell::new(vec![]),
+            size_warning,
+            size_warned: Cell::new(false),
+            failed_savepoints: Cell::new(None),
+            commit_latency,
+            write_profile: None,
+            _write_ticket: write_ticket,
         })
     }
 
     pub fn is_active(&self) -> bool {
-        self.unbound_cursors.borrow().is_some()
+        self.cursor_cache.borrow().is_some()
+    }
+
+    // The innermost savepoint or the txn itself.
+    fn current_txn(&self) -> &Txn<'env> {
+        self.savepoints.0.last().unwrap_or(&self.txn)
+    }
+
+    // Cursors of a parent txn are shadowed by MDBX in nested txns, so the reused cursors are
+    // closed whenever a savepoint begins or ends.
+    fn begin_savepoint_change(&self) -> Result<()> {
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        if let Some(cursor_cache) = self.cursor_cache.borrow_mut().as_mut() {
+            cursor_cache.clear();
+            Ok(())
+        } else {
+            Err(IsarError::TransactionClosed {})
+        }
+    }
+
+    // Savepoints allow rolling back some changes of a write txn without aborting it. They can
+    // be nested and are released when the txn is committed. Not supported with write_map.
+    pub fn savepoint(&mut self) -> Result<()> {
+        self.begin_savepoint_change()?;
+        let savepoint = self.env.nested_txn(self.current_txn())?;
+        self.savepoints.0.push(savepoint);
+        Ok(())
+    }
+
+    // Discards all changes since the last savepoint and removes it.
+    pub fn rollback_to_savepoint(&mut self) -> Result<()> {
+        self.begin_savepoint_change()?;
+        if self.savepoints.0.pop().is_none() {
+            return illegal_arg("There is no savepoint to roll back to.");
+        }
+        if matches!(self.failed_savepoints.get(), Some(failed) if failed > self.savepoints.0.len())
+        {
+            self.failed_savepoints.set(None);
+        }
+        if let Some(change_set) = self.change_set.borrow_mut().as_mut() {
+            change_set.register_rollback();
+        }
+        Ok(())
+    }
+
+    // Keeps the changes since the last savepoint and removes it.
+    pub fn release_savepoint(&mut self) -> Result<()> {
+        self.begin_savepoint_change()?;
+        self.check_not_failed()?;
+        if let Some(savepoint) = self.savepoints.0.pop() {
+            self.current_txn()
+                .write_counter
+                .add(&savepoint.write_counter);
+            savepoint.commit()
+        } else {
+            illegal_arg("There is no savepoint to release.")
+        }
+    }
+
+    pub fn stats(&self) -> Result<TxnStats> {
+        let cursor_cache = self.cursor_cache.borrow();
+        let cursor_cache = cursor_cache
+            .as_ref()
+            .ok_or(IsarError::TransactionClosed {})?;
+        Ok(TxnStats {
+            dirty_pages: self.current_txn().dirty_bytes()? / self.current_txn().page_size()?,
+            bytes_written: self.write_counters().map(|c| c.get_bytes()).sum(),
+            operations: self.write_counters().map(|c| c.get_operations()).sum(),
+            elapsed: self.txn.get_started().elapsed(),
+            cursors_opened: cursor_cache.opened,
+            cursors_reused: cursor_cache.reused,
+            cursors_evicted: cursor_cache.evicted,
+        })
+    }
+
+    // Starts counting the objects and the index and link entries written by this txn.
+    pub fn profile_writes(&mut self) -> Result<()> {
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        if self.write_profile.is_none() {
+            self.write_profile = Some(RefCell::new(WriteProfile::new()));
+        }
+        Ok(())
+    }
+
+    // The writes since profile_writes() of every collection that has been written to. Writes
+    // that were rolled back to a savepoint are included.
+    pub fn write_profile(&self) -> Result<Vec<(String, CollectionWriteProfile)>> {
+        let write_profile = if let Some(write_profile) = &self.write_profile {
+            write_profile.borrow()
+        } else {
+            return illegal_arg("Writes are not profiled.");
+        };
+        let profiles = self
+            .collections
+            .iter()
+            .map(|col| (col.name.clone(), write_profile.collection_profile(col)))
+            .filter(|(_, profile)| !profile.is_empty())
+            .collect();
+        Ok(profiles)
+    }
+
+    fn check_not_failed(&self) -> Result<()> {
+        if self.failed_savepoints.get().is_some() {
+            illegal_arg("An operation failed. Roll back to the last savepoint first.")
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_counters(&self) -> impl Iterator<Item = &WriteCounter> {
+        let savepoints = self.savepoints.0.iter();
+        savepoints
+            .chain(Some(&self.txn))
+            .map(|txn| &txn.write_counter)
+    }
+
+    fn check_size(&self) -> Result<()> {
+        if let Some((name, limit)) = self.size_warning {
+            if !self.size_warned.get() {
+                let dirty_bytes = self.current_txn().dirty_bytes()?;
+                if dirty_bytes > limit {
+                    self.size_warned.set(true);
+                    let message = format!(
+                        "Transaction has {} dirty bytes which exceeds the warning limit of {}.",
+                        dirty_bytes, limit
+                    );
+                    log_warning(name, &message);
+                }
+            }
+        }
+        Ok(())
     }
 
     fn verify_instance_id(&self, instance_id: u64) -> Result<()> {
@@ -47,16 +290,42 @@ impl<'env> IsarTxn<'env> {
         F: FnOnce(&IsarCursors<'txn, 'env>) -> Result<T>,
     {
         self.verify_instance_id(instance_id)?;
-        if let Some(unbound_cursors) = self.unbound_cursors.take() {
-            let cursors = IsarCursors::new(&self.txn, unbound_cursors);
+        if let Some(cursor_cache) = self.cursor_cache.take() {
+            let cursors = IsarCursors::new(self.current_txn(), cursor_cache)
+                .with_collections(self.collections);
             let result = job(&cursors);
-            self.unbound_cursors.borrow_mut().replace(cursors.close());
+            let (cursor_cache, object_buffers) = cursors.close();
+            self.cursor_cache.borrow_mut().replace(cursor_cache);
+            self.object_buffers.replace(object_buffers);
             result
         } else {
             Err(IsarError::TransactionClosed {})
         }
     }
 
+    // Runs the job against the last committed state. Write txns lazily open a read txn for
+    // that so changes made by this txn are not visible to the job.
+    pub(crate) fn read_committed<'txn, T, F>(&'txn mut self, instance_id: u64, job: F) -> Result<T>
+    where
+        F: FnOnce(&IsarCursors<'txn, 'env>) -> Result<T>,
+    {
+        if !self.write {
+            return self.read(instance_id, job);
+        }
+        self.verify_instance_id(instance_id)?;
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        if self.snapshot.is_none() {
+            self.snapshot = Some(self.env.detached_read_txn()?);
+        }
+        let cursors = IsarCursors::new(self.snapshot.as_ref().unwrap(), CursorCache::default());
+        let result = job(&cursors);
+        let (_, object_buffers) = cursors.close();
+        self.object_buffers.replace(object_buffers);
+        result
+    }
+
     pub(crate) fn write<'txn, T, F>(&'txn mut self, instance_id: u64, job: F) -> Result<T>
     where
         F: FnOnce(&IsarCursors<'txn, 'env>, Option<&mut ChangeSet<'_>>) -> Result<T>,
@@ -65,13 +334,23 @@ impl<'env> IsarTxn<'env> {
         if !self.write {
             return Err(IsarError::WriteTxnRequired {});
         }
-        if let Some(unbound_cursors) = self.unbound_cursors.take() {
+        if let Some(cursor_cache) = self.cursor_cache.take() {
             let mut change_set = self.change_set.take();
-            let cursors = IsarCursors::new(&self.txn, unbound_cursors);
+            let cursors = IsarCursors::new(self.current_txn(), cursor_cache)
+                .with_collections(self.collections)
+                .with_write_profile(self.write_profile.as_ref());
             let result = job(&cursors, change_set.as_mut());
-            let unbounded_cursors = cursors.close();
-            if result.is_ok() {
-                self.unbound_cursors.borrow_mut().replace(unbounded_cursors);
+            let (cursor_cache, object_buffers) = cursors.close();
+            self.object_buffers.replace(object_buffers);
+            let result = result.and_then(|r| self.check_size().map(|_| r));
+            // Without a savepoint the partial writes of a failed operation cannot be undone so
+            // the txn is closed.
+            let savepoints = self.savepoints.0.len();
+            if result.is_err() && savepoints > 0 && self.failed_savepoints.get().is_none() {
+                self.failed_savepoints.set(Some(savepoints));
+            }
+            if result.is_ok() || savepoints > 0 {
+                self.cursor_cache.borrow_mut().replace(cursor_cache);
                 if let Some(change_set) = change_set {
                     self.change_set.borrow_mut().replace(change_set);
                 }
@@ -86,7 +365,7 @@ impl<'env> IsarTxn<'env> {
         if !self.write {
             return Err(IsarError::WriteTxnRequired {});
         }
-        db.clear(&self.txn)
+        db.clear(self.current_txn())
     }
 
     pub(crate) fn register_all_changed(&mut self, col_id: u64) -> Result<()> {
@@ -99,14 +378,93 @@ impl<'env> IsarTxn<'env> {
         Ok(())
     }
 
-    pub fn commit(self) -> Result<()> {
+    pub(crate) fn register_collection_change(&mut self, col_name: &str) {
+        if self.write && !self.changed_collections.contains(col_name) {
+            self.changed_collections.insert(col_name.to_string());
+        }
+    }
+
+    pub(crate) fn get_change_token(
+        &mut self,
+        instance_id: u64,
+        col_name: &str,
+    ) -> Result<ChangeToken> {
+        self.verify_instance_id(instance_id)?;
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        let mut cursor = UnboundCursor::new().bind(self.current_txn(), self.info_db)?;
+        let token = cursor
+            .move_to(&ChangeToken::key(col_name))?
+            .map(|(_, bytes)| ChangeToken::from_bytes(bytes))
+            .unwrap_or_default();
+        Ok(token)
+    }
+
+    pub(crate) fn get_applied_seq(&mut self, instance_id: u64) -> Result<u64> {
+        self.verify_instance_id(instance_id)?;
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        let mut cursor = UnboundCursor::new().bind(self.current_txn(), self.info_db)?;
+        let seq = cursor
+            .move_to(APPLIED_SEQ_KEY)?
+            .map_or(0, |(_, bytes)| LittleEndian::read_u64(bytes));
+        Ok(seq)
+    }
+
+    pub(crate) fn set_applied_seq(&mut self, instance_id: u64, seq: u64) -> Result<()> {
+        self.verify_instance_id(instance_id)?;
+        if !self.write {
+            return Err(IsarError::WriteTxnRequired {});
+        }
+        if !self.is_active() {
+            return Err(IsarError::TransactionClosed {});
+        }
+        let mut cursor = UnboundCursor::new().bind(self.current_txn(), self.info_db)?;
+        cursor.put(APPLIED_SEQ_KEY, &seq.to_le_bytes())
+    }
+
+    fn bump_change_tokens(&self) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let mut cursor = UnboundCursor::new().bind(self.current_txn(), self.info_db)?;
+        for col_name in &self.changed_collections {
+            let key = ChangeToken::key(col_name);
+            let token = cursor
+                .move_to(&key)?
+                .map(|(_, bytes)| ChangeToken::from_bytes(bytes))
+                .unwrap_or_default();
+            let token = ChangeToken {
+                counter: token.counter + 1,
+                last_modified: now,
+            };
+            cursor.put(&key, &token.to_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn commit(mut self) -> Result<()> {
         if !self.is_active() {
             return Err(IsarError::TransactionClosed {});
         }
 
         if self.write {
+            self.check_not_failed()?;
+            let start = Instant::now();
+            while !self.savepoints.0.is_empty() {
+                self.release_savepoint()?;
+            }
+            self.bump_change_tokens()?;
+            let mut change_set = self.change_set.take();
+            if let Some(change_set) = change_set.as_mut() {
+                change_set.register_fill_ratio(self.txn.fill_ratio()?);
+            }
             self.txn.commit()?;
-            if let Some(change_set) = self.change_set.take() {
+            self.commit_latency.record(start.elapsed());
+            if let Some(change_set) = change_set {
                 change_set.notify_watchers();
             }
         }
@@ -114,13 +472,22 @@ impl<'env> IsarTxn<'env> {
     }
 
     pub fn abort(self) {
+        drop(self.savepoints);
         self.txn.abort()
     }
 
+    // Moves a read txn to the latest committed state instead of beginning a new txn.
+    pub(crate) fn renew(&mut self) -> Result<()> {
+        if self.write {
+            return illegal_arg("Only read transactions can be renewed.");
+        }
+        self.txn.renew()
+    }
+
     pub(crate) fn debug_db_names(&mut self) -> Result<Vec<String>> {
-        let unnamed_db = Db::open(&self.txn, None, false, false, false)?;
+        let unnamed_db = Db::open(self.current_txn(), None, false, false, false)?;
         let cursor = UnboundCursor::new();
-        let mut cursor = cursor.bind(&self.txn, unnamed_db)?;
+        let mut cursor = cursor.bind(self.current_txn(), unnamed_db)?;
 
         let mut names = vec![];
         cursor.iter_between(&[], &[255], false, false, true, |_, name, _| {
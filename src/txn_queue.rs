@@ -0,0 +1,111 @@
+use crate::error::{IsarError, Result};
+use crate::txn::TxnPriority;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+struct QueueState {
+    // The thread that holds the write ticket.
+    writer: Option<ThreadId>,
+    next_ticket: u64,
+    interactive: VecDeque<u64>,
+    background: VecDeque<u64>,
+}
+
+impl QueueState {
+    fn queue(&mut self, priority: TxnPriority) -> &mut VecDeque<u64> {
+        match priority {
+            TxnPriority::Interactive => &mut self.interactive,
+            TxnPriority::Background => &mut self.background,
+        }
+    }
+
+    // Interactive writers are served first. Writers of the same priority are served in order.
+    fn is_next(&self, ticket: u64, priority: TxnPriority) -> bool {
+        match priority {
+            TxnPriority::Interactive => self.interactive.front() == Some(&ticket),
+            TxnPriority::Background => {
+                self.interactive.is_empty() && self.background.front() == Some(&ticket)
+            }
+        }
+    }
+}
+
+// Hands out write transactions one at a time. Waiting on the queue instead of the mdbx write lock
+// allows timeouts and makes sure background writers cannot starve interactive writers.
+pub(crate) struct TxnQueue {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+impl TxnQueue {
+    pub fn new() -> Self {
+        TxnQueue {
+            state: Mutex::new(QueueState {
+                writer: None,
+                next_ticket: 0,
+                interactive: VecDeque::new(),
+                background: VecDeque::new(),
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    pub fn acquire(
+        &self,
+        priority: TxnPriority,
+        timeout: Option<Duration>,
+    ) -> Result<WriteTicket<'_>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let thread_id = thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        // Waiting for our own ticket would never return.
+        if state.writer == Some(thread_id) {
+            return Err(IsarError::TxnActive {});
+        }
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.queue(priority).push_back(ticket);
+
+        while state.writer.is_some() || !state.is_next(ticket, priority) {
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    state.queue(priority).retain(|t| *t != ticket);
+                    // Leaving the queue may allow a background writer to continue.
+                    self.available.notify_all();
+                    return Err(IsarError::TxnTimeout {});
+                }
+                state = self
+                    .available
+                    .wait_timeout(state, deadline - now)
+                    .unwrap()
+                    .0;
+            } else {
+                state = self.available.wait(state).unwrap();
+            }
+        }
+
+        state.queue(priority).pop_front();
+        state.writer = Some(thread_id);
+        Ok(WriteTicket { queue: self })
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.writer = None;
+        self.available.notify_all();
+    }
+}
+
+// Held by a write transaction until it is committed or aborted.
+pub(crate) struct WriteTicket<'a> {
+    queue: &'a TxnQueue,
+}
+
+impl<'a> Drop for WriteTicket<'a> {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
@@ -46,9 +46,11 @@ pub fn verify_isar(
     for (col, objects, links) in data {
         let mut entries = HashSet::new();
         let mut index_entries = col.indexes.iter().map(|_| HashSet::new()).collect_vec();
+        // Backlinks are verified through the link of the source collection.
         let mut link_entries: HashMap<String, HashSet<(Vec<u8>, Vec<u8>)>> = col
             .links
             .iter()
+            .filter(|(_, l)| !l.is_backlink())
             .map(|(n, _)| (n.clone(), HashSet::new()))
             .collect();
 
@@ -85,7 +87,7 @@ pub fn verify_isar(
                 assert_eq!(index.debug_dump(cur), index_entries[i]);
             }
 
-            for (name, link) in &col.links {
+            for (name, link) in col.links.iter().filter(|(_, l)| !l.is_backlink()) {
                 assert_eq!(link.debug_dump(cur), link_entries[name]);
 
                 let bl_entries: HashSet<(Vec<u8>, Vec<u8>)> = link_entries[name]
@@ -106,11 +108,14 @@ fn verify_db_names(txn: &mut IsarTxn, cols: &[&IsarCollection]) {
     db_names.insert("_info".to_string());
     for col in cols {
         db_names.insert(col.name.clone());
+        if col.checksum_db.is_some() {
+            db_names.insert(format!("_s_{}", col.name));
+        }
         for (name, _) in &col.indexes {
             db_names.insert(format!("_i_{}_{}", col.name, name));
         }
 
-        for (name, _) in &col.links {
+        for (name, _) in col.links.iter().filter(|(_, l)| !l.is_backlink()) {
             db_names.insert(format!("_l_{}_{}", col.name, name));
             db_names.insert(format!("_b_{}_{}", col.name, name));
         }
@@ -1,12 +1,51 @@
 use crate::object::isar_object::IsarObject;
 use crate::watch::isar_watchers::IsarWatchers;
-use crate::watch::watcher::Watcher;
+use crate::watch::watcher::{catch_panic, ChangesWatcher, PayloadWatcher, Watcher};
+use crate::watch::{ChangeKind, ObjectChange};
 use intmap::IntMap;
 use std::sync::{Arc, MutexGuard};
 
+// Objects that have been inserted and deleted again have no kind.
+struct PendingChanges {
+    watcher: Arc<ChangesWatcher>,
+    changes: Vec<(i64, Option<ChangeKind>)>,
+    positions: IntMap<usize>,
+    unknown: bool,
+}
+
+impl PendingChanges {
+    fn register(&mut self, oid: i64, deleted: bool) {
+        if !self.unknown {
+            if let Some(position) = self.positions.get(oid as u64) {
+                let (_, kind) = &mut self.changes[*position];
+                *kind = match (*kind, deleted) {
+                    (Some(ChangeKind::Inserted), true) => None,
+                    (Some(ChangeKind::Inserted), false) | (None, false) => {
+                        Some(ChangeKind::Inserted)
+                    }
+                    (Some(ChangeKind::Updated), false) | (Some(ChangeKind::Deleted), false) => {
+                        Some(ChangeKind::Updated)
+                    }
+                    (_, true) => Some(ChangeKind::Deleted),
+                };
+            } else {
+                let kind = if deleted {
+                    ChangeKind::Deleted
+                } else {
+                    ChangeKind::Inserted
+                };
+                self.positions.insert(oid as u64, self.changes.len());
+                self.changes.push((oid, Some(kind)));
+            }
+        }
+    }
+}
+
 pub(crate) struct ChangeSet<'a> {
     watchers: MutexGuard<'a, IsarWatchers>,
     changed_watchers: IntMap<Arc<Watcher>>,
+    changed_payload_watchers: IntMap<(Arc<PayloadWatcher>, Option<Vec<u8>>)>,
+    changed_changes_watchers: IntMap<PendingChanges>,
 }
 
 impl<'a> ChangeSet<'a> {
@@ -14,6 +53,31 @@ impl<'a> ChangeSet<'a> {
         ChangeSet {
             watchers,
             changed_watchers: IntMap::new(),
+            changed_payload_watchers: IntMap::new(),
+            changed_changes_watchers: IntMap::new(),
+        }
+    }
+
+    fn get_pending_changes<'b>(
+        changed_changes_watchers: &'b mut IntMap<PendingChanges>,
+        watcher: &Arc<ChangesWatcher>,
+    ) -> &'b mut PendingChanges {
+        if !changed_changes_watchers.contains_key(watcher.get_id()) {
+            let pending = PendingChanges {
+                watcher: watcher.clone(),
+                changes: vec![],
+                positions: IntMap::new(),
+                unknown: false,
+            };
+            changed_changes_watchers.insert(watcher.get_id(), pending);
+        }
+        changed_changes_watchers.get_mut(watcher.get_id()).unwrap()
+    }
+
+    fn register_unknown_changes(&mut self, col_id: u64) {
+        let cw = self.watchers.get_col_watchers(col_id);
+        for (_, w) in &cw.changes_watchers {
+            Self::get_pending_changes(&mut self.changed_changes_watchers, w).unknown = true;
         }
     }
 
@@ -29,8 +93,21 @@ impl<'a> ChangeSet<'a> {
     }
 
     pub fn register_change(&mut self, col_id: u64, oid: Option<i64>, object: Option<IsarObject>) {
+        if oid.is_none() {
+            self.register_unknown_changes(col_id);
+        }
         let cw = self.watchers.get_col_watchers(col_id);
         Self::register_watchers(&mut self.changed_watchers, &cw.watchers);
+        for (lower, upper, w) in &cw.id_range_watchers {
+            // changes without an id (e.g. links) may affect any object
+            let in_range = match oid {
+                Some(oid) => (*lower..=*upper).contains(&oid),
+                None => true,
+            };
+            if in_range {
+                self.changed_watchers.insert(w.get_id(), w.clone());
+            }
+        }
         if let Some(oid) = oid {
             let oid_u = unsafe { std::mem::transmute(oid) };
             if let Some(object_watchers) = cw.object_watchers.get(oid_u) {
@@ -49,6 +126,40 @@ impl<'a> ChangeSet<'a> {
         }
     }
 
+    // Has to be called with the old object before it is deleted or replaced and with the new
+    // object after it has been put.
+    pub fn register_object_change(
+        &mut self,
+        col_id: u64,
+        oid: i64,
+        object: IsarObject,
+        deleted: bool,
+    ) {
+        let cw = self.watchers.get_col_watchers(col_id);
+        for (q, w) in &cw.changes_watchers {
+            if let Some(q) = q {
+                if !q.maybe_matches_wc_filter(oid, object) {
+                    continue;
+                }
+            }
+            Self::get_pending_changes(&mut self.changed_changes_watchers, w).register(oid, deleted);
+        }
+    }
+
+    // Has to be called after register_change() with the final state of the object.
+    pub fn register_payload(&mut self, col_id: u64, oid: i64, object: Option<&[u8]>) {
+        let cw = self.watchers.get_col_watchers(col_id);
+        if let Some(payload_watchers) = cw.payload_watchers.get(oid as u64) {
+            for w in payload_watchers {
+                let payload = w.get_payload(object);
+                // IntMap::insert() does not replace existing entries
+                self.changed_payload_watchers.remove(w.get_id());
+                self.changed_payload_watchers
+                    .insert(w.get_id(), (w.clone(), payload));
+            }
+        }
+    }
+
     pub fn register_all(&mut self, col_id: u64) {
         let cw = self.watchers.get_col_watchers(col_id);
         Self::register_watchers(&mut self.changed_watchers, &cw.watchers);
@@ -58,11 +169,70 @@ impl<'a> ChangeSet<'a> {
         for (_, w) in &cw.query_watchers {
             self.changed_watchers.insert(w.get_id(), w.clone());
         }
+        for (_, _, w) in &cw.id_range_watchers {
+            self.changed_watchers.insert(w.get_id(), w.clone());
+        }
+        for watchers in cw.payload_watchers.values() {
+            for w in watchers {
+                self.changed_payload_watchers.remove(w.get_id());
+                self.changed_payload_watchers
+                    .insert(w.get_id(), (w.clone(), None));
+            }
+        }
+        self.register_unknown_changes(col_id);
+    }
+
+    // Rolled back changes cannot be removed so all registered changes become unknown.
+    pub fn register_rollback(&mut self) {
+        for (_, payload) in self.changed_payload_watchers.values_mut() {
+            *payload = None;
+        }
+        for pending in self.changed_changes_watchers.values_mut() {
+            pending.unknown = true;
+        }
+    }
+
+    pub fn register_fill_ratio(&mut self, fill_ratio: f64) {
+        for (threshold, w) in &self.watchers.fill_ratio_watchers {
+            if fill_ratio > *threshold {
+                self.changed_watchers.insert(w.get_id(), w.clone());
+            }
+        }
     }
 
     pub fn notify_watchers(self) {
+        let mut errors = vec![];
         for watcher in self.changed_watchers.values() {
-            watcher.notify();
+            if let Err(error) = watcher.notify() {
+                errors.push(error);
+            }
+        }
+        for (watcher, payload) in self.changed_payload_watchers.values() {
+            if let Err(error) = watcher.notify(payload.as_deref()) {
+                errors.push(error);
+            }
+        }
+        for pending in self.changed_changes_watchers.values() {
+            let changes: Vec<_> = pending
+                .changes
+                .iter()
+                .filter_map(|(id, kind)| kind.map(|kind| ObjectChange { id: *id, kind }))
+                .collect();
+            let result = if pending.unknown {
+                pending.watcher.notify(None)
+            } else if !changes.is_empty() {
+                pending.watcher.notify(Some(&changes))
+            } else {
+                continue;
+            };
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+        for error in &errors {
+            for (_, callback) in &self.watchers.error_watchers {
+                let _ = catch_panic(|| callback(error));
+            }
         }
     }
 }
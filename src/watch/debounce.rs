@@ -0,0 +1,129 @@
+use crate::watch::watcher::{
+    catch_panic, ChangesWatcherCallback, PayloadWatcherCallback, WatcherCallback,
+};
+use crate::watch::ObjectChange;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type Delivery = Box<dyn FnOnce() + Send>;
+
+// Delivers the first notification right away and merges the notifications of the following
+// interval into a single one that is delivered on a background thread once the interval has
+// passed.
+struct Debouncer {
+    interval: Duration,
+    state: Mutex<DebounceState>,
+}
+
+struct DebounceState {
+    last_delivery: Option<Instant>,
+    pending: Option<Delivery>,
+}
+
+impl Debouncer {
+    fn new(interval: Duration) -> Arc<Self> {
+        Arc::new(Debouncer {
+            interval,
+            state: Mutex::new(DebounceState {
+                last_delivery: None,
+                pending: None,
+            }),
+        })
+    }
+
+    // Returns whether the notification should be delivered right away. Otherwise the delivery
+    // is created with whether it replaces a pending notification.
+    fn intercept(self: &Arc<Self>, delivery: impl FnOnce(bool) -> Delivery) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.is_some() {
+            state.pending.replace(delivery(true));
+            return false;
+        }
+        let now = Instant::now();
+        match state.last_delivery {
+            Some(last_delivery) if now < last_delivery + self.interval => {
+                state.pending.replace(delivery(false));
+                let deadline = last_delivery + self.interval;
+                // A pending notification is dropped if the watcher has been removed.
+                let debouncer = Arc::downgrade(self);
+                thread::spawn(move || {
+                    thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                    if let Some(debouncer) = debouncer.upgrade() {
+                        debouncer.deliver_pending();
+                    }
+                });
+                false
+            }
+            _ => {
+                state.last_delivery = Some(now);
+                true
+            }
+        }
+    }
+
+    fn deliver_pending(&self) {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            state.last_delivery = Some(Instant::now());
+            state.pending.take()
+        };
+        if let Some(pending) = pending {
+            // There is no commit to report the panic to.
+            let _ = catch_panic(pending);
+        }
+    }
+}
+
+// The callback is called at most once per interval.
+pub fn debounce(callback: WatcherCallback, interval: Duration) -> WatcherCallback {
+    let debouncer = Debouncer::new(interval);
+    let callback = Arc::new(callback);
+    Box::new(move || {
+        let delivery = |_| -> Delivery {
+            let callback = callback.clone();
+            Box::new(move || callback())
+        };
+        if debouncer.intercept(delivery) {
+            callback()
+        }
+    })
+}
+
+// Only the latest payload of an interval is delivered.
+pub fn debounce_payload(
+    callback: PayloadWatcherCallback,
+    interval: Duration,
+) -> PayloadWatcherCallback {
+    let debouncer = Debouncer::new(interval);
+    let callback = Arc::new(callback);
+    Box::new(move |payload: Option<&[u8]>| {
+        let delivery = |_| -> Delivery {
+            let callback = callback.clone();
+            let payload = payload.map(|p| p.to_vec());
+            Box::new(move || callback(payload.as_deref()))
+        };
+        if debouncer.intercept(delivery) {
+            callback(payload)
+        }
+    })
+}
+
+// Notifications that have been merged are delivered as unknown changes.
+pub fn debounce_changes(
+    callback: ChangesWatcherCallback,
+    interval: Duration,
+) -> ChangesWatcherCallback {
+    let debouncer = Debouncer::new(interval);
+    let callback = Arc::new(callback);
+    Box::new(move |changes: Option<&[ObjectChange]>| {
+        let delivery = |merged: bool| -> Delivery {
+            let callback = callback.clone();
+            let changes = changes.filter(|_| !merged).map(|c| c.to_vec());
+            Box::new(move || callback(changes.as_deref()))
+        };
+        if debouncer.intercept(delivery) {
+            callback(changes)
+        }
+    })
+}
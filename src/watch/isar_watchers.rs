@@ -1,5 +1,8 @@
 use crate::query::Query;
-use crate::watch::watcher::{Watcher, WatcherCallback};
+use crate::watch::watcher::{
+    ChangesWatcher, ChangesWatcherCallback, PayloadWatcher, PayloadWatcherCallback, Watcher,
+    WatcherCallback, WatcherErrorCallback,
+};
 use crossbeam_channel::Receiver;
 use intmap::IntMap;
 use itertools::Itertools;
@@ -10,6 +13,8 @@ pub(crate) type WatcherModifier = Box<dyn FnOnce(&mut IsarWatchers) + Send + 'st
 pub(crate) struct IsarWatchers {
     modifiers: Receiver<WatcherModifier>,
     collection_watchers: IntMap<IsarCollectionWatchers>,
+    pub(super) fill_ratio_watchers: Vec<(f64, Arc<Watcher>)>,
+    pub(super) error_watchers: Vec<(u64, WatcherErrorCallback)>,
 }
 
 impl IsarWatchers {
@@ -17,6 +22,8 @@ impl IsarWatchers {
         IsarWatchers {
             modifiers,
             collection_watchers: IntMap::new(),
+            fill_ratio_watchers: Vec::new(),
+            error_watchers: Vec::new(),
         }
     }
 
@@ -28,6 +35,33 @@ impl IsarWatchers {
         self.collection_watchers.get_mut(col_id).unwrap()
     }
 
+    pub(crate) fn add_fill_ratio_watcher(
+        &mut self,
+        watcher_id: u64,
+        threshold: f64,
+        callback: WatcherCallback,
+    ) {
+        let watcher = Arc::new(Watcher::new(watcher_id, callback));
+        self.fill_ratio_watchers.push((threshold, watcher));
+    }
+
+    pub(crate) fn remove_fill_ratio_watcher(&mut self, watcher_id: u64) {
+        let position = self
+            .fill_ratio_watchers
+            .iter()
+            .position(|(_, w)| w.get_id() == watcher_id)
+            .unwrap();
+        self.fill_ratio_watchers.remove(position);
+    }
+
+    pub(crate) fn add_error_watcher(&mut self, watcher_id: u64, callback: WatcherErrorCallback) {
+        self.error_watchers.push((watcher_id, callback));
+    }
+
+    pub(crate) fn remove_error_watcher(&mut self, watcher_id: u64) {
+        self.error_watchers.retain(|(id, _)| *id != watcher_id);
+    }
+
     pub(crate) fn sync(&mut self) {
         let modifiers = self.modifiers.try_iter().collect_vec();
         for modifier in modifiers {
@@ -39,7 +73,11 @@ impl IsarWatchers {
 pub struct IsarCollectionWatchers {
     pub(super) watchers: Vec<Arc<Watcher>>,
     pub(super) object_watchers: IntMap<Vec<Arc<Watcher>>>,
+    pub(super) payload_watchers: IntMap<Vec<Arc<PayloadWatcher>>>,
     pub(super) query_watchers: Vec<(Query, Arc<Watcher>)>,
+    pub(super) id_range_watchers: Vec<(i64, i64, Arc<Watcher>)>,
+    // Watchers without a query receive the changes of all objects.
+    pub(super) changes_watchers: Vec<(Option<Query>, Arc<ChangesWatcher>)>,
 }
 
 impl IsarCollectionWatchers {
@@ -47,7 +85,10 @@ impl IsarCollectionWatchers {
         IsarCollectionWatchers {
             watchers: Vec::new(),
             object_watchers: IntMap::new(),
+            payload_watchers: IntMap::new(),
             query_watchers: Vec::new(),
+            id_range_watchers: Vec::new(),
+            changes_watchers: Vec::new(),
         }
     }
 
@@ -85,6 +126,51 @@ impl IsarCollectionWatchers {
         watchers.remove(position);
     }
 
+    pub fn add_payload_watcher(
+        &mut self,
+        watcher_id: u64,
+        oid: i64,
+        max_size: usize,
+        callback: PayloadWatcherCallback,
+    ) {
+        let oid = oid as u64;
+        let watcher = Arc::new(PayloadWatcher::new(watcher_id, max_size, callback));
+        if let Some(payload_watchers) = self.payload_watchers.get_mut(oid) {
+            payload_watchers.push(watcher);
+        } else {
+            self.payload_watchers.insert(oid, vec![watcher]);
+        }
+    }
+
+    pub fn remove_payload_watcher(&mut self, oid: i64, watcher_id: u64) {
+        let watchers = self.payload_watchers.get_mut(oid as u64).unwrap();
+        let position = watchers
+            .iter()
+            .position(|w| w.get_id() == watcher_id)
+            .unwrap();
+        watchers.remove(position);
+    }
+
+    pub fn add_id_range_watcher(
+        &mut self,
+        watcher_id: u64,
+        lower: i64,
+        upper: i64,
+        callback: WatcherCallback,
+    ) {
+        let watcher = Arc::new(Watcher::new(watcher_id, callback));
+        self.id_range_watchers.push((lower, upper, watcher));
+    }
+
+    pub fn remove_id_range_watcher(&mut self, watcher_id: u64) {
+        let position = self
+            .id_range_watchers
+            .iter()
+            .position(|(_, _, w)| w.get_id() == watcher_id)
+            .unwrap();
+        self.id_range_watchers.remove(position);
+    }
+
     pub fn add_query_watcher(&mut self, watcher_id: u64, query: Query, callback: WatcherCallback) {
         let watcher = Arc::new(Watcher::new(watcher_id, callback));
         self.query_watchers.push((query, watcher));
@@ -98,4 +184,23 @@ impl IsarCollectionWatchers {
             .unwrap();
         self.query_watchers.remove(position);
     }
+
+    pub fn add_changes_watcher(
+        &mut self,
+        watcher_id: u64,
+        query: Option<Query>,
+        callback: ChangesWatcherCallback,
+    ) {
+        let watcher = Arc::new(ChangesWatcher::new(watcher_id, callback));
+        self.changes_watchers.push((query, watcher));
+    }
+
+    pub fn remove_changes_watcher(&mut self, watcher_id: u64) {
+        let position = self
+            .changes_watchers
+            .iter()
+            .position(|(_, w)| w.get_id() == watcher_id)
+            .unwrap();
+        self.changes_watchers.remove(position);
+    }
 }
@@ -0,0 +1,44 @@
+use crate::watch::watcher::catch_panic;
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LifecycleEvent {
+    Opened,
+    Closed,
+    MigrationStarted,
+    MigrationFinished,
+    MigrationFailed,
+    Compacted,
+    VerificationPassed,
+    VerificationFailed,
+}
+
+pub type LifecycleCallback = Box<dyn Fn(&str, LifecycleEvent) + Send + Sync + 'static>;
+
+type LifecycleWatchers = Vec<(u64, Arc<LifecycleCallback>)>;
+
+static LIFECYCLE_WATCHERS: Lazy<Mutex<LifecycleWatchers>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub(crate) fn add_lifecycle_watcher(watcher_id: u64, callback: LifecycleCallback) {
+    let mut watchers = LIFECYCLE_WATCHERS.lock().unwrap();
+    watchers.push((watcher_id, Arc::new(callback)));
+}
+
+pub(crate) fn remove_lifecycle_watcher(watcher_id: u64) {
+    let mut watchers = LIFECYCLE_WATCHERS.lock().unwrap();
+    watchers.retain(|(id, _)| *id != watcher_id);
+}
+
+pub(crate) fn notify_lifecycle(name: &str, event: LifecycleEvent) {
+    // The lock is released before the callbacks run so they can add or remove watchers.
+    let watchers: Vec<_> = LIFECYCLE_WATCHERS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, callback)| callback.clone())
+        .collect();
+    for callback in watchers {
+        let _ = catch_panic(|| callback(name, event));
+    }
+}
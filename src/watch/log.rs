@@ -0,0 +1,31 @@
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+
+// Called with the instance name and the message.
+pub type LogCallback = Box<dyn Fn(&str, &str) + Send + Sync + 'static>;
+
+type LogWatchers = Vec<(u64, Arc<LogCallback>)>;
+
+static LOG_WATCHERS: Lazy<Mutex<LogWatchers>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub(crate) fn add_log_watcher(watcher_id: u64, callback: LogCallback) {
+    let mut watchers = LOG_WATCHERS.lock().unwrap();
+    watchers.push((watcher_id, Arc::new(callback)));
+}
+
+pub(crate) fn remove_log_watcher(watcher_id: u64) {
+    let mut watchers = LOG_WATCHERS.lock().unwrap();
+    watchers.retain(|(id, _)| *id != watcher_id);
+}
+
+pub(crate) fn log_warning(name: &str, message: &str) {
+    let watchers: Vec<_> = LOG_WATCHERS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, callback)| callback.clone())
+        .collect();
+    for callback in watchers {
+        callback(name, message);
+    }
+}
@@ -1,18 +1,55 @@
 pub(crate) mod change_set;
+pub mod debounce;
 pub(crate) mod isar_watchers;
+pub mod lifecycle;
+pub mod log;
+pub mod pause;
 pub(crate) mod watcher;
 
+use crate::watch::pause::{PausePolicy, PauseState};
+use std::sync::Arc;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChangeKind {
+    Inserted,
+    Updated,
+    Deleted,
+}
+
+// Changes of a transaction are merged per object. Objects of query watchers that stop matching
+// the query are reported as deleted and objects that start matching as inserted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ObjectChange {
+    pub id: i64,
+    pub kind: ChangeKind,
+}
+
 pub struct WatchHandle {
     stop_callback: Option<Box<dyn FnOnce()>>,
+    pause_state: Arc<PauseState>,
 }
 
 impl WatchHandle {
-    pub(crate) fn new(stop_callback: Box<dyn FnOnce()>) -> Self {
+    pub(crate) fn new(stop_callback: Box<dyn FnOnce()>, pause_state: Arc<PauseState>) -> Self {
         WatchHandle {
             stop_callback: Some(stop_callback),
+            pause_state,
         }
     }
 
+    pub fn pause(&self, policy: PausePolicy) {
+        self.pause_state.pause(policy);
+    }
+
+    // A buffered notification is delivered on the calling thread.
+    pub fn resume(&self) {
+        self.pause_state.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_state.is_paused()
+    }
+
     pub fn stop(self) {}
 }
 
@@ -0,0 +1,164 @@
+use crate::watch::lifecycle::{LifecycleCallback, LifecycleEvent};
+use crate::watch::log::LogCallback;
+use crate::watch::watcher::{
+    ChangesWatcherCallback, PayloadWatcherCallback, WatcherCallback, WatcherErrorCallback,
+};
+use crate::watch::ObjectChange;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PausePolicy {
+    // Only the latest notification is kept and delivered on resume.
+    Buffer,
+    Drop,
+}
+
+type Replay = Box<dyn FnOnce() + Send>;
+
+pub(crate) struct PauseState {
+    paused: AtomicBool,
+    buffer: AtomicBool,
+    pending: Mutex<Option<Replay>>,
+}
+
+impl PauseState {
+    fn new() -> Arc<Self> {
+        Arc::new(PauseState {
+            paused: AtomicBool::new(false),
+            buffer: AtomicBool::new(false),
+            pending: Mutex::new(None),
+        })
+    }
+
+    pub fn pause(&self, policy: PausePolicy) {
+        self.buffer
+            .store(policy == PausePolicy::Buffer, Ordering::SeqCst);
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        let replay = self.pending.lock().unwrap().take();
+        if let Some(replay) = replay {
+            replay();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    // Returns whether the notification should be delivered right away.
+    fn intercept(&self, replay: impl FnOnce() -> Replay) -> bool {
+        if !self.paused.load(Ordering::SeqCst) {
+            return true;
+        }
+        if self.buffer.load(Ordering::SeqCst) {
+            self.pending.lock().unwrap().replace(replay());
+        }
+        false
+    }
+
+    pub fn wrap(callback: WatcherCallback) -> (WatcherCallback, Arc<Self>) {
+        let state = PauseState::new();
+        let callback = Arc::new(callback);
+        let wrapper_state = state.clone();
+        let wrapper = Box::new(move || {
+            let replay = || -> Replay {
+                let callback = callback.clone();
+                Box::new(move || callback())
+            };
+            if wrapper_state.intercept(replay) {
+                callback()
+            }
+        });
+        (wrapper, state)
+    }
+
+    pub fn wrap_payload(callback: PayloadWatcherCallback) -> (PayloadWatcherCallback, Arc<Self>) {
+        let state = PauseState::new();
+        let callback = Arc::new(callback);
+        let wrapper_state = state.clone();
+        let wrapper = Box::new(move |payload: Option<&[u8]>| {
+            let replay = || -> Replay {
+                let callback = callback.clone();
+                let payload = payload.map(|p| p.to_vec());
+                Box::new(move || callback(payload.as_deref()))
+            };
+            if wrapper_state.intercept(replay) {
+                callback(payload)
+            }
+        });
+        (wrapper, state)
+    }
+
+    // Earlier notifications may have been dropped so buffered changes are replayed as unknown.
+    pub fn wrap_changes(callback: ChangesWatcherCallback) -> (ChangesWatcherCallback, Arc<Self>) {
+        let state = PauseState::new();
+        let callback = Arc::new(callback);
+        let wrapper_state = state.clone();
+        let wrapper = Box::new(move |changes: Option<&[ObjectChange]>| {
+            let replay = || -> Replay {
+                let callback = callback.clone();
+                Box::new(move || callback(None))
+            };
+            if wrapper_state.intercept(replay) {
+                callback(changes)
+            }
+        });
+        (wrapper, state)
+    }
+
+    pub fn wrap_lifecycle(callback: LifecycleCallback) -> (LifecycleCallback, Arc<Self>) {
+        let state = PauseState::new();
+        let callback = Arc::new(callback);
+        let wrapper_state = state.clone();
+        let wrapper = Box::new(move |name: &str, event: LifecycleEvent| {
+            let replay = || -> Replay {
+                let callback = callback.clone();
+                let name = name.to_string();
+                Box::new(move || callback(&name, event))
+            };
+            if wrapper_state.intercept(replay) {
+                callback(name, event)
+            }
+        });
+        (wrapper, state)
+    }
+
+    pub fn wrap_log(callback: LogCallback) -> (LogCallback, Arc<Self>) {
+        let state = PauseState::new();
+        let callback = Arc::new(callback);
+        let wrapper_state = state.clone();
+        let wrapper = Box::new(move |name: &str, message: &str| {
+            let replay = || -> Replay {
+                let callback = callback.clone();
+                let name = name.to_string();
+                let message = message.to_string();
+                Box::new(move || callback(&name, &message))
+            };
+            if wrapper_state.intercept(replay) {
+                callback(name, message)
+            }
+        });
+        (wrapper, state)
+    }
+
+    pub fn wrap_error(callback: WatcherErrorCallback) -> (WatcherErrorCallback, Arc<Self>) {
+        let state = PauseState::new();
+        let callback = Arc::new(callback);
+        let wrapper_state = state.clone();
+        let wrapper = Box::new(move |message: &str| {
+            let replay = || -> Replay {
+                let callback = callback.clone();
+                let message = message.to_string();
+                Box::new(move || callback(&message))
+            };
+            if wrapper_state.intercept(replay) {
+                callback(message)
+            }
+        });
+        (wrapper, state)
+    }
+}
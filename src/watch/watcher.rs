@@ -1,5 +1,17 @@
+use crate::watch::ObjectChange;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
 pub type WatcherCallback = Box<dyn Fn() + Send + Sync + 'static>;
 
+pub type WatcherErrorCallback = Box<dyn Fn(&str) + Send + Sync + 'static>;
+
+// The payload is the new object or None if the object was deleted or is larger than the limit.
+pub type PayloadWatcherCallback = Box<dyn Fn(Option<&[u8]>) + Send + Sync + 'static>;
+
+// The changes are None if they are unknown, for example after the collection has been cleared
+// or links have changed.
+pub type ChangesWatcherCallback = Box<dyn Fn(Option<&[ObjectChange]>) + Send + Sync + 'static>;
+
 pub(super) struct Watcher {
     id: u64,
     callback: WatcherCallback,
@@ -14,7 +26,69 @@ impl Watcher {
         self.id
     }
 
-    pub fn notify(&self) {
-        (*self.callback)()
+    pub fn notify(&self) -> Result<(), String> {
+        catch_panic(|| (*self.callback)())
+    }
+}
+
+pub(super) struct PayloadWatcher {
+    id: u64,
+    max_size: usize,
+    callback: PayloadWatcherCallback,
+}
+
+impl PayloadWatcher {
+    pub fn new(id: u64, max_size: usize, callback: PayloadWatcherCallback) -> Self {
+        PayloadWatcher {
+            id,
+            max_size,
+            callback,
+        }
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn get_payload(&self, object: Option<&[u8]>) -> Option<Vec<u8>> {
+        object
+            .filter(|object| object.len() <= self.max_size)
+            .map(|object| object.to_vec())
     }
+
+    pub fn notify(&self, payload: Option<&[u8]>) -> Result<(), String> {
+        catch_panic(|| (*self.callback)(payload))
+    }
+}
+
+pub(super) struct ChangesWatcher {
+    id: u64,
+    callback: ChangesWatcherCallback,
+}
+
+impl ChangesWatcher {
+    pub fn new(id: u64, callback: ChangesWatcherCallback) -> Self {
+        ChangesWatcher { id, callback }
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn notify(&self, changes: Option<&[ObjectChange]>) -> Result<(), String> {
+        catch_panic(|| (*self.callback)(changes))
+    }
+}
+
+// Watchers are notified after the commit so a panicking callback must not unwind into it.
+pub(super) fn catch_panic(callback: impl FnOnce()) -> Result<(), String> {
+    catch_unwind(AssertUnwindSafe(callback)).map_err(|e| {
+        if let Some(message) = e.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = e.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Watcher callback panicked.".to_string()
+        }
+    })
 }
@@ -0,0 +1,142 @@
+use crate::collection::{IsarCollection, OnConflict};
+use crate::error::Result;
+use crate::instance::IsarInstance;
+use crate::object::isar_object::IsarObject;
+
+enum BatchOp<'a> {
+    Put {
+        collection: &'a IsarCollection,
+        id: Option<i64>,
+        object: Vec<u8>,
+        on_conflict: OnConflict,
+    },
+    Delete {
+        collection: &'a IsarCollection,
+        id: i64,
+    },
+    Link {
+        collection: &'a IsarCollection,
+        link_index: usize,
+        id: i64,
+        target_id: i64,
+    },
+    Unlink {
+        collection: &'a IsarCollection,
+        link_index: usize,
+        id: i64,
+        target_id: i64,
+    },
+}
+
+pub struct WriteBatch<'a> {
+    instance: &'a IsarInstance,
+    ops: Vec<BatchOp<'a>>,
+}
+
+impl<'a> WriteBatch<'a> {
+    pub(crate) fn new(instance: &'a IsarInstance) -> Self {
+        WriteBatch {
+            instance,
+            ops: vec![],
+        }
+    }
+
+    pub fn put(
+        &mut self,
+        collection: &'a IsarCollection,
+        id: Option<i64>,
+        object: IsarObject,
+        on_conflict: OnConflict,
+    ) {
+        self.ops.push(BatchOp::Put {
+            collection,
+            id,
+            object: object.as_bytes().to_vec(),
+            on_conflict,
+        });
+    }
+
+    pub fn delete(&mut self, collection: &'a IsarCollection, id: i64) {
+        self.ops.push(BatchOp::Delete { collection, id });
+    }
+
+    pub fn link(
+        &mut self,
+        collection: &'a IsarCollection,
+        link_index: usize,
+        id: i64,
+        target_id: i64,
+    ) {
+        self.ops.push(BatchOp::Link {
+            collection,
+            link_index,
+            id,
+            target_id,
+        });
+    }
+
+    pub fn unlink(
+        &mut self,
+        collection: &'a IsarCollection,
+        link_index: usize,
+        id: i64,
+        target_id: i64,
+    ) {
+        self.ops.push(BatchOp::Unlink {
+            collection,
+            link_index,
+            id,
+            target_id,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    // Applies all queued operations in a single write txn and returns the ids of the put
+    // objects in the order they were queued. Nothing is written if any operation fails.
+    pub fn commit(self) -> Result<Vec<i64>> {
+        let mut txn = self.instance.begin_txn(true, false)?;
+        let mut ids = vec![];
+        for op in self.ops {
+            match op {
+                BatchOp::Put {
+                    collection,
+                    id,
+                    object,
+                    on_conflict,
+                } => {
+                    let object = IsarObject::from_bytes(&object);
+                    let id = collection.put(&mut txn, id, object, on_conflict)?;
+                    ids.push(id);
+                }
+                BatchOp::Delete { collection, id } => {
+                    collection.delete(&mut txn, id)?;
+                }
+                BatchOp::Link {
+                    collection,
+                    link_index,
+                    id,
+                    target_id,
+                } => {
+                    collection.link(&mut txn, link_index, id, target_id)?;
+                }
+                BatchOp::Unlink {
+                    collection,
+                    link_index,
+                    id,
+                    target_id,
+                } => {
+                    collection.unlink(&mut txn, link_index, id, target_id)?;
+                }
+            }
+        }
+        txn.commit()?;
+        Ok(ids)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::collection::IsarCollection;
+use crate::mdbx::db::Db;
+use intmap::IntMap;
+
+// The number of entries written to and deleted from every db of a txn.
+pub(crate) struct WriteProfile {
+    dbs: IntMap<(u64, u64)>,
+}
+
+impl WriteProfile {
+    pub fn new() -> Self {
+        WriteProfile { dbs: IntMap::new() }
+    }
+
+    pub fn record(&mut self, db: Db, written: u64, deleted: u64) {
+        if let Some(entry) = self.dbs.get_mut(db.runtime_id()) {
+            entry.0 += written;
+            entry.1 += deleted;
+        } else {
+            self.dbs.insert(db.runtime_id(), (written, deleted));
+        }
+    }
+
+    fn get(&self, db: Db) -> (u64, u64) {
+        self.dbs.get(db.runtime_id()).copied().unwrap_or((0, 0))
+    }
+
+    pub fn collection_profile(&self, collection: &IsarCollection) -> CollectionWriteProfile {
+        let (objects_put, objects_deleted) = self.get(collection.db);
+        let mut profile = CollectionWriteProfile {
+            objects_put,
+            objects_deleted,
+            ..Default::default()
+        };
+        for (_, index) in &collection.indexes {
+            let (written, deleted) = self.get(index.db);
+            profile.index_entries_written += written;
+            profile.index_entries_deleted += deleted;
+        }
+        // Backlinks are counted for the collection that defines the link.
+        for (_, link) in &collection.links {
+            if link.is_backlink() {
+                continue;
+            }
+            for db in link.get_dbs().iter().copied() {
+                let (written, deleted) = self.get(db);
+                profile.link_entries_written += written;
+                profile.link_entries_deleted += deleted;
+            }
+        }
+        profile
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CollectionWriteProfile {
+    // Objects replaced by a put are not counted as deleted.
+    pub objects_put: u64,
+    pub objects_deleted: u64,
+    pub index_entries_written: u64,
+    pub index_entries_deleted: u64,
+    // Entries of both directions of the links.
+    pub link_entries_written: u64,
+    pub link_entries_deleted: u64,
+}
+
+impl CollectionWriteProfile {
+    pub fn is_empty(&self) -> bool {
+        *self == CollectionWriteProfile::default()
+    }
+
+    // Index entries written or deleted per object put, including the entries removed for
+    // replaced objects.
+    pub fn index_entries_per_put(&self) -> f64 {
+        per_put(
+            self.index_entries_written + self.index_entries_deleted,
+            self.objects_put,
+        )
+    }
+
+    pub fn link_entries_per_put(&self) -> f64 {
+        per_put(
+            self.link_entries_written + self.link_entries_deleted,
+            self.objects_put,
+        )
+    }
+}
+
+fn per_put(entries: u64, objects_put: u64) -> f64 {
+    if objects_put == 0 {
+        0.0
+    } else {
+        entries as f64 / objects_put as f64
+    }
+}
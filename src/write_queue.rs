@@ -0,0 +1,139 @@
+use crate::error::{IsarError, Result};
+use crate::instance::IsarInstance;
+use crate::txn::IsarTxn;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+trait QueuedWrite: Send {
+    fn run(&mut self, txn: &mut IsarTxn) -> Result<()>;
+
+    fn finish(self: Box<Self>, result: Result<()>);
+}
+
+struct Write<T, F, C> {
+    job: F,
+    result: Option<T>,
+    callback: C,
+}
+
+impl<T, F, C> QueuedWrite for Write<T, F, C>
+where
+    T: Send,
+    F: FnMut(&mut IsarTxn) -> Result<T> + Send,
+    C: FnOnce(Result<T>) + Send,
+{
+    fn run(&mut self, txn: &mut IsarTxn) -> Result<()> {
+        self.result = Some((self.job)(txn)?);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>, result: Result<()>) {
+        let value = self.result;
+        (self.callback)(result.map(|_| value.unwrap()))
+    }
+}
+
+// Runs write jobs on a background thread. Jobs submitted within the flush interval of each
+// other are coalesced into a single write txn, so many small writes only pay the commit cost
+// once.
+//
+// If a job fails, the txn is aborted, the job's callback receives the error and the other jobs
+// of the batch run again in a new txn. Jobs may therefore run more than once and should not
+// have side effects outside of the txn.
+//
+// The queue keeps a reference to the instance, so the instance cannot be closed before the
+// queue has been dropped.
+pub struct WriteQueue {
+    sender: Option<Sender<Box<dyn QueuedWrite>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WriteQueue {
+    pub(crate) fn new(instance: &Arc<IsarInstance>, flush_interval: Duration) -> Self {
+        let (sender, receiver) = unbounded();
+        let instance = instance.clone();
+        let worker = thread::spawn(move || run_worker(&instance, receiver, flush_interval));
+        WriteQueue {
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    // The callback runs on the worker thread with the result of the job after its txn has been
+    // committed, or with the error that prevented the job from being written. WorkerStopped is
+    // returned if the worker has stopped because a job or callback panicked.
+    pub fn submit<T, F, C>(&self, job: F, callback: C) -> Result<()>
+    where
+        T: Send + 'static,
+        F: FnMut(&mut IsarTxn) -> Result<T> + Send + 'static,
+        C: FnOnce(Result<T>) + Send + 'static,
+    {
+        let write = Box::new(Write {
+            job,
+            result: None,
+            callback,
+        });
+        match &self.sender {
+            Some(sender) if sender.send(write).is_ok() => Ok(()),
+            _ => Err(IsarError::WorkerStopped {}),
+        }
+    }
+}
+
+// Writes the submitted jobs that have not been flushed yet.
+impl Drop for WriteQueue {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run_worker(
+    instance: &IsarInstance,
+    receiver: Receiver<Box<dyn QueuedWrite>>,
+    flush_interval: Duration,
+) {
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        let deadline = Instant::now() + flush_interval;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(write) => batch.push(write),
+                Err(_) => break,
+            }
+        }
+        flush(instance, batch);
+    }
+}
+
+fn flush(instance: &IsarInstance, mut batch: Vec<Box<dyn QueuedWrite>>) {
+    while !batch.is_empty() {
+        let mut txn = match instance.begin_txn(true, false) {
+            Ok(txn) => txn,
+            Err(e) => {
+                for write in batch {
+                    write.finish(Err(e.clone()));
+                }
+                return;
+            }
+        };
+        let failed = batch
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, write)| write.run(&mut txn).err().map(|e| (i, e)));
+        if let Some((i, e)) = failed {
+            txn.abort();
+            batch.remove(i).finish(Err(e));
+            continue;
+        }
+        let result = txn.commit();
+        for write in batch.drain(..) {
+            write.finish(result.clone());
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::vec;
 
-use isar_core::collection::IsarCollection;
+use isar_core::collection::{IsarCollection, OnConflict};
 use isar_core::object::data_type::DataType;
 use isar_core::object::isar_object::{IsarObject, Property};
 use isar_core::schema::collection_schema::CollectionSchema;
@@ -220,6 +220,11 @@ impl TestObj {
                 DataType::FloatList => builder.write_float_list(self.float_list.as_deref()),
                 DataType::LongList => builder.write_long_list(self.long_list.as_deref()),
                 DataType::DoubleList => builder.write_double_list(self.double_list.as_deref()),
+                DataType::DateTime
+                | DataType::DateTimeList
+                | DataType::GeoPoint
+                | DataType::Object
+                | DataType::ObjectList => builder.write_null(),
                 DataType::StringList => {
                     let string_list = self
                         .string_list
@@ -239,8 +244,13 @@ impl TestObj {
 
     pub fn save(&self, txn: &mut IsarTxn, col: &IsarCollection) {
         let bytes = self.to_bytes(col);
-        col.put(txn, Some(self.id), IsarObject::from_bytes(&bytes), false)
-            .unwrap();
+        col.put(
+            txn,
+            Some(self.id),
+            IsarObject::from_bytes(&bytes),
+            OnConflict::Abort,
+        )
+        .unwrap();
     }
 
     pub fn from_object(col: &IsarCollection, item: IsarObject) -> Self {
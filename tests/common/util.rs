@@ -25,16 +25,16 @@ macro_rules! isar (
 
     ($path:expr, $isar:ident,) => {
         let schema = isar_core::schema::Schema::new(vec![]).unwrap();
-        let path = $path.to_string();
-        let name = xxhash_rust::xxh3::xxh3_64(path.as_bytes()).to_string();
+        let path = std::path::PathBuf::from($path);
+        let name = xxhash_rust::xxh3::xxh3_64(path.to_string_lossy().as_bytes()).to_string();
         let $isar = isar_core::instance::IsarInstance::open(&name, &path, false, schema).unwrap();
     };
 
     ($path:expr, $isar:ident, $($col:ident => $schema:expr),+) => {
         let col_schemas = vec![$($schema.clone()),*];
         let schema = isar_core::schema::Schema::new(col_schemas).unwrap();
-        let path = $path.to_string();
-        let name = xxhash_rust::xxh3::xxh3_64(path.as_bytes()).to_string();
+        let path = std::path::PathBuf::from($path);
+        let name = xxhash_rust::xxh3::xxh3_64(path.to_string_lossy().as_bytes()).to_string();
         let $isar = isar_core::instance::IsarInstance::open(&name, &path, false, schema).unwrap();
         isar!(col $isar, 0, $($col),+)
     };
@@ -0,0 +1,250 @@
+use crate::common::test_obj::TestObj;
+use isar_core::error::IsarError;
+use isar_core::instance::IsarInstance;
+use isar_core::query::aggregation::AggregationResult;
+use isar_core::schema::aggregate_schema::AggregateSchema;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::link_schema::LinkSchema;
+use isar_core::schema::{Schema, SchemaValidationError};
+use serde_json::json;
+use std::path::Path;
+
+mod common;
+
+fn aggregate_schema() -> CollectionSchema {
+    TestObj::default_schema()
+        .with_aggregate(AggregateSchema::count("count"))
+        .with_aggregate(AggregateSchema::sum("intSum", "int"))
+        .with_aggregate(AggregateSchema::sum("doubleSum", "double"))
+        .with_aggregate(
+            AggregateSchema::count("bigCount")
+                .with_filter(json!({"type": "gte", "property": "int", "value": 5})),
+        )
+}
+
+fn open(dir: &Path, col: CollectionSchema) -> std::sync::Arc<IsarInstance> {
+    let schema = Schema::new(vec![col]).unwrap();
+    IsarInstance::open("aggregate", dir, false, schema).unwrap()
+}
+
+fn open_posts(dir: &Path, aggregate: bool) -> std::sync::Arc<IsarInstance> {
+    let link = LinkSchema::with_backlink("tags", "tags", "posts");
+    let mut posts = TestObj::schema("posts", &[], &[link]);
+    if aggregate {
+        posts = posts.with_aggregate(AggregateSchema::link_count("tagCount", "tags"));
+    }
+    let tags = TestObj::schema("tags", &[], &[]);
+    let schema = Schema::new(vec![posts, tags]).unwrap();
+    IsarInstance::open("aggregate", dir, false, schema).unwrap()
+}
+
+fn obj(id: i64, int: i32, double: f64) -> TestObj {
+    let mut obj = TestObj::default(id);
+    obj.int = int;
+    obj.double = double;
+    obj
+}
+
+#[test]
+fn test_aggregate() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let isar = open(&dir, aggregate_schema());
+    let col = isar.collections.first().unwrap();
+
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    let mut get = |name: &str| col.get_aggregate(&mut txn, name).unwrap();
+    assert_eq!(get("count"), AggregationResult::Long(0));
+    assert_eq!(get("intSum"), AggregationResult::Long(0));
+    assert_eq!(get("doubleSum"), AggregationResult::Double(0.0));
+
+    obj(1, 3, 1.5).save(&mut txn, col);
+    obj(2, 5, 2.0).save(&mut txn, col);
+    obj(3, 7, 0.5).save(&mut txn, col);
+    let mut get = |name: &str| col.get_aggregate(&mut txn, name).unwrap();
+    assert_eq!(get("count"), AggregationResult::Long(3));
+    assert_eq!(get("intSum"), AggregationResult::Long(15));
+    assert_eq!(get("doubleSum"), AggregationResult::Double(4.0));
+    assert_eq!(get("bigCount"), AggregationResult::Long(2));
+
+    // replacing an object removes the old values
+    obj(2, 1, 1.0).save(&mut txn, col);
+    col.delete(&mut txn, 3).unwrap();
+    let mut get = |name: &str| col.get_aggregate(&mut txn, name).unwrap();
+    assert_eq!(get("count"), AggregationResult::Long(2));
+    assert_eq!(get("intSum"), AggregationResult::Long(4));
+    assert_eq!(get("doubleSum"), AggregationResult::Double(2.5));
+    assert_eq!(get("bigCount"), AggregationResult::Long(0));
+    txn.commit().unwrap();
+
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    col.clear(&mut txn).unwrap();
+    assert_eq!(
+        col.get_aggregate(&mut txn, "count").unwrap(),
+        AggregationResult::Long(0)
+    );
+    assert!(matches!(
+        col.get_aggregate(&mut txn, "unknown"),
+        Err(IsarError::IllegalArg { .. })
+    ));
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_aggregate_added_to_existing_objects() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let isar = open(&dir, TestObj::default_schema());
+    let col = isar.collections.first().unwrap();
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    obj(1, 4, 0.0).save(&mut txn, col);
+    obj(2, 6, 0.0).save(&mut txn, col);
+    txn.commit().unwrap();
+    assert!(isar.close());
+
+    let isar = open(&dir, aggregate_schema());
+    let col = isar.collections.first().unwrap();
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    let mut get = |name: &str| col.get_aggregate(&mut txn, name).unwrap();
+    assert_eq!(get("count"), AggregationResult::Long(2));
+    assert_eq!(get("intSum"), AggregationResult::Long(10));
+    assert_eq!(get("bigCount"), AggregationResult::Long(1));
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_aggregate_sum_overflow() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let col = TestObj::default_schema().with_aggregate(AggregateSchema::sum("longSum", "long"));
+    let isar = open(&dir, col);
+    let col = isar.collections.first().unwrap();
+
+    // the long property holds the id
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    let id = i64::MAX / 2 + 1;
+    obj(id, 0, 0.0).save(&mut txn, col);
+    obj(id + 1, 0, 0.0).save(&mut txn, col);
+    let mut get = |name: &str| col.get_aggregate(&mut txn, name).unwrap();
+    assert_eq!(get("longSum"), AggregationResult::Long(i64::MAX));
+
+    // removing an object after the sum saturated restores the exact sum
+    col.delete(&mut txn, id + 1).unwrap();
+    let mut get = |name: &str| col.get_aggregate(&mut txn, name).unwrap();
+    assert_eq!(get("longSum"), AggregationResult::Long(id));
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_link_aggregate() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let isar = open_posts(&dir, true);
+    let posts = isar.collections.iter().find(|c| c.name == "posts").unwrap();
+    let tags = isar.collections.iter().find(|c| c.name == "tags").unwrap();
+
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    let mut get = |txn: &mut _| posts.get_aggregate(txn, "tagCount").unwrap();
+    obj(1, 0, 0.0).save(&mut txn, posts);
+    obj(2, 0, 0.0).save(&mut txn, posts);
+    for id in 1..=3 {
+        obj(id, 0, 0.0).save(&mut txn, tags);
+    }
+    posts.link(&mut txn, 0, 1, 1).unwrap();
+    posts.link(&mut txn, 0, 2, 1).unwrap();
+    posts.link(&mut txn, 0, 2, 3).unwrap();
+    // existing links are not counted twice
+    posts.link(&mut txn, 0, 1, 1).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(3));
+
+    posts.unlink(&mut txn, 0, 2, 3).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(2));
+
+    // links created through the backlink are counted by the posts
+    tags.link(&mut txn, 0, 2, 1).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(3));
+
+    tags.delete(&mut txn, 1).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(1));
+
+    posts.replace_links(&mut txn, 0, 2, &[2, 3]).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(3));
+
+    posts.delete(&mut txn, 1).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(2));
+
+    tags.clear(&mut txn).unwrap();
+    assert_eq!(get(&mut txn), AggregationResult::Long(0));
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_link_aggregate_added_to_existing_links() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let isar = open_posts(&dir, false);
+    let posts = isar.collections.iter().find(|c| c.name == "posts").unwrap();
+    let tags = isar.collections.iter().find(|c| c.name == "tags").unwrap();
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    obj(1, 0, 0.0).save(&mut txn, posts);
+    obj(1, 0, 0.0).save(&mut txn, tags);
+    obj(2, 0, 0.0).save(&mut txn, tags);
+    posts.replace_links(&mut txn, 0, 1, &[1, 2]).unwrap();
+    txn.commit().unwrap();
+    assert!(isar.close());
+
+    let isar = open_posts(&dir, true);
+    let posts = isar.collections.iter().find(|c| c.name == "posts").unwrap();
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    assert_eq!(
+        posts.get_aggregate(&mut txn, "tagCount").unwrap(),
+        AggregationResult::Long(2)
+    );
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_aggregate_schema_validation() {
+    let validate = |aggregate: AggregateSchema| {
+        let col = TestObj::default_schema().with_aggregate(aggregate);
+        Schema::validate(&[col]).map_err(|e| e.message)
+    };
+    assert_eq!(validate(AggregateSchema::sum("sum", "long")), Ok(()));
+    assert_eq!(
+        validate(AggregateSchema::sum("sum", "unknown")),
+        Err("Aggregate property does not exist.".to_string())
+    );
+    assert_eq!(
+        validate(AggregateSchema::sum("sum", "string")),
+        Err("Only Int, Long, Float and Double properties can be summed.".to_string())
+    );
+    assert_eq!(
+        validate(AggregateSchema::count("count").with_filter(json!({"type": "unknown"}))),
+        Err("Invalid aggregate filter.".to_string())
+    );
+    assert_eq!(
+        validate(AggregateSchema::link_count("count", "unknown")),
+        Err("Aggregate link does not exist.".to_string())
+    );
+    let filter = json!({"type": "gte", "property": "int", "value": 5});
+    assert_eq!(
+        validate(AggregateSchema::link_count("count", "unknown").with_filter(filter)),
+        Err("Link aggregates cannot have a property or filter.".to_string())
+    );
+
+    let duplicate = TestObj::default_schema()
+        .with_aggregate(AggregateSchema::count("count"))
+        .with_aggregate(AggregateSchema::sum("count", "int"));
+    assert_eq!(
+        Schema::validate(&[duplicate]),
+        Err(SchemaValidationError {
+            collection: Some("obj".to_string()),
+            message: "Duplicate aggregate name".to_string()
+        })
+    );
+}
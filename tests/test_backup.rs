@@ -0,0 +1,79 @@
+use isar_core::config::IsarConfig;
+use isar_core::error::IsarError;
+use isar_core::instance::IsarInstance;
+use isar_core::schema::Schema;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_export_restore_encrypted() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    txn.commit().unwrap();
+
+    let key = [42; 32];
+    let mut backup = std::env::temp_dir();
+    backup.push(format!("{}.isarbak", rand::random::<u64>()));
+    isar.export_encrypted(&backup, &key).unwrap();
+    // the plaintext copy is deleted
+    let tmp_dir = isar.dir.join(&isar.name).join("backup");
+    assert_eq!(tmp_dir.read_dir().unwrap().count(), 0);
+
+    let result = IsarInstance::restore_encrypted(&backup, &key, &isar.name, &isar.dir);
+    assert!(matches!(result, Err(IsarError::IllegalArg { .. })));
+
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let result = IsarInstance::restore_encrypted(&backup, &[0; 32], "restored", &dir);
+    assert!(matches!(result, Err(IsarError::BackupError { .. })));
+
+    IsarInstance::restore_encrypted(&backup, &key, "restored", &dir).unwrap();
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let restored = IsarInstance::open("restored", &dir, false, schema).unwrap();
+    let col = restored.collections.first().unwrap();
+    txn!(restored, txn);
+    verify!(txn, col, obj1, obj2);
+    txn.abort();
+
+    assert!(restored.close());
+    assert!(isar.close());
+}
+
+#[test]
+fn test_copy_and_open_from_backup() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    txn.commit().unwrap();
+
+    let mut backup = std::env::temp_dir();
+    backup.push(format!("{}.isar", rand::random::<u64>()));
+    isar.copy_to_file(&backup).unwrap();
+
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let result = IsarInstance::open_from_backup(
+        &backup,
+        &isar.name,
+        &isar.dir,
+        schema.clone(),
+        IsarConfig::default(),
+    );
+    assert!(matches!(result, Err(IsarError::IllegalArg { .. })));
+
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let name = rand::random::<u64>().to_string();
+    let restored =
+        IsarInstance::open_from_backup(&backup, &name, &dir, schema, IsarConfig::default())
+            .unwrap();
+    let col = restored.collections.first().unwrap();
+    txn!(restored, txn);
+    verify!(txn, col, obj1, obj2);
+    txn.abort();
+
+    assert!(restored.close_and_delete());
+    assert!(isar.close());
+}
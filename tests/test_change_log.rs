@@ -0,0 +1,162 @@
+use crate::common::test_obj::TestObj;
+use isar_core::change_log::{ApplyPolicy, ApplySummary};
+use isar_core::config::IsarConfig;
+use isar_core::instance::IsarInstance;
+use isar_core::schema::Schema;
+use serde_json::Value;
+use std::sync::Arc;
+
+mod common;
+
+fn open(config: IsarConfig) -> Arc<IsarInstance> {
+    let mut dir = std::env::temp_dir();
+    let r: u64 = rand::random();
+    dir.push(&r.to_string());
+    let schema = Schema::new(vec![TestObj::schema("col", &[], &[])]).unwrap();
+    IsarInstance::open_with_config(&r.to_string(), &dir, schema, config).unwrap()
+}
+
+fn export_changes(isar: &IsarInstance, since_seq: u64) -> (u64, Vec<Value>) {
+    let mut bytes = vec![];
+    let seq = isar.export_changes_json(since_seq, &mut bytes).unwrap();
+    let changes = String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    (seq, changes)
+}
+
+#[test]
+fn test_export_changes_json() {
+    let isar = open(IsarConfig {
+        change_log: true,
+        ..Default::default()
+    });
+    let col = isar.collections.first().unwrap();
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, _obj2 => 2);
+    let mut obj1 = obj1;
+    obj1.int = 5;
+    obj1.save(&mut txn, col);
+    txn.commit().unwrap();
+
+    let (seq, changes) = export_changes(&isar, 0);
+    assert_eq!(seq, 3);
+    let ops: Vec<_> = changes
+        .iter()
+        .map(|c| {
+            (
+                c["seq"].as_u64().unwrap(),
+                c["op"].as_str().unwrap(),
+                c["id"].as_i64(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        ops,
+        vec![
+            (1, "put", Some(1)),
+            (2, "put", Some(2)),
+            (3, "put", Some(1))
+        ]
+    );
+    assert_eq!(changes[0]["collection"], "col");
+    assert_eq!(changes[0]["object"]["int"], 5);
+
+    txn!(isar, txn);
+    col.delete(&mut txn, 2).unwrap();
+    col.delete(&mut txn, 3).unwrap();
+    txn.commit().unwrap();
+
+    let (seq, changes) = export_changes(&isar, seq);
+    assert_eq!(seq, 4);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0]["op"], "delete");
+    assert_eq!(changes[0]["id"], 2);
+
+    // Puts of objects that have been deleted since are skipped.
+    let (_, changes) = export_changes(&isar, 1);
+    assert_eq!(changes.len(), 2);
+
+    txn!(isar, txn);
+    col.clear(&mut txn).unwrap();
+    txn.commit().unwrap();
+    let (seq, changes) = export_changes(&isar, seq);
+    assert_eq!(seq, 5);
+    assert_eq!(changes[0]["op"], "clear");
+    assert_eq!(export_changes(&isar, seq), (5, vec![]));
+
+    isar.close_and_delete();
+}
+
+#[test]
+fn test_export_changes_json_disabled() {
+    isar!(isar, _col => TestObj::default_schema());
+    assert!(isar.export_changes_json(0, &mut vec![]).is_err());
+    isar.close();
+}
+
+#[test]
+fn test_apply_changes() {
+    let source = open(IsarConfig {
+        change_log: true,
+        ..Default::default()
+    });
+    let col = &source.collections[0];
+    txn!(source, txn);
+    put!(id: col, txn, obj1 => 1, _obj2 => 2, _obj3 => 3);
+    col.delete(&mut txn, 2).unwrap();
+    txn.commit().unwrap();
+    let mut changes = vec![];
+    source.export_changes_json(0, &mut changes).unwrap();
+
+    let target = open(IsarConfig::default());
+    let summary = target
+        .apply_changes(changes.as_slice(), ApplyPolicy::Abort)
+        .unwrap();
+    assert_eq!(
+        summary,
+        ApplySummary {
+            applied: 3,
+            duplicates: 0,
+            skipped: 0,
+            last_seq: 4,
+        }
+    );
+    let target_col = &target.collections[0];
+    txn!(target, txn);
+    assert_eq!(TestObj::get(target_col, &mut txn, 1), Some(obj1));
+    assert_eq!(TestObj::get(target_col, &mut txn, 2), None);
+    assert!(TestObj::get(target_col, &mut txn, 3).is_some());
+    txn.abort();
+
+    let summary = target
+        .apply_changes(changes.as_slice(), ApplyPolicy::Abort)
+        .unwrap();
+    assert_eq!(summary.applied, 0);
+    assert_eq!(summary.duplicates, 3);
+
+    let invalid = b"{\"seq\": 5, \"collection\": \"unknown\", \"op\": \"clear\"}\n\
+        {\"seq\": 6, \"collection\": \"col\", \"op\": \"delete\", \"id\": 3}\n";
+    assert!(target
+        .apply_changes(&invalid[..], ApplyPolicy::Abort)
+        .is_err());
+    txn!(target, txn);
+    assert!(TestObj::get(target_col, &mut txn, 3).is_some());
+    txn.abort();
+
+    let summary = target
+        .apply_changes(&invalid[..], ApplyPolicy::Skip)
+        .unwrap();
+    assert_eq!(summary.applied, 1);
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(summary.last_seq, 6);
+    txn!(target, txn);
+    assert_eq!(TestObj::get(target_col, &mut txn, 3), None);
+    txn.abort();
+
+    source.close_and_delete();
+    target.close_and_delete();
+}
@@ -0,0 +1,51 @@
+use isar_core::config::IsarConfig;
+use isar_core::instance::IsarInstance;
+use isar_core::schema::Schema;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+fn open(dir: &std::path::Path, checksums: bool) -> std::sync::Arc<IsarInstance> {
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let config = IsarConfig {
+        checksums,
+        ..Default::default()
+    };
+    IsarInstance::open_with_config("checksum", dir, schema, config).unwrap()
+}
+
+#[test]
+fn test_checksums() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+
+    let isar = open(&dir, true);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3);
+    col.delete(&mut txn, 3).unwrap();
+    assert_eq!(TestObj::get(col, &mut txn, 1).as_ref(), Some(&obj1));
+    let q = col.new_query_builder().build();
+    assert_eq!(q.count(&mut txn).unwrap(), 2);
+    verify!(txn, col, obj1, obj2);
+    txn.commit().unwrap();
+    assert!(isar.verify().is_ok());
+    assert!(isar.close());
+
+    let isar = open(&dir, false);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+    put!(id: col, txn, obj4 => 4);
+    txn.commit().unwrap();
+    assert!(isar.close());
+
+    let isar = open(&dir, true);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+    assert_eq!(TestObj::get(col, &mut txn, 4).as_ref(), Some(&obj4));
+    verify!(txn, col, obj1, obj2, obj4);
+    txn.abort();
+    assert!(isar.verify().is_ok());
+    assert!(isar.close());
+}
@@ -0,0 +1,89 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::IsarCollection;
+use isar_core::index::index_key::IndexKey;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::txn::IsarTxn;
+
+mod common;
+
+fn find_ids(col: &IsarCollection, txn: &mut IsarTxn, lower: IndexKey, upper: IndexKey) -> Vec<i64> {
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    let mut ids = vec![];
+    qb.build()
+        .find_while(txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    ids
+}
+
+#[test]
+fn test_composite_key() {
+    let index = IndexSchema::new(
+        "int_string",
+        vec![
+            IndexPropertySchema::new("int", IndexType::Value, false),
+            IndexPropertySchema::new("string", IndexType::Value, false),
+        ],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let values = [
+        (1, Some("b")),
+        (1, Some("A")),
+        (2, None),
+        (i32::MAX, Some("a")),
+    ];
+    for (i, (int, string)) in values.iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.int = *int;
+        obj.string = string.map(|s| s.to_string());
+        obj.save(&mut txn, col);
+    }
+
+    let mut lower = col.new_index_key(0).unwrap();
+    lower.add_int(1).unwrap();
+    lower.add_string(Some("a")).unwrap();
+    let mut upper = col.new_index_key(0).unwrap();
+    upper.add_int(1).unwrap();
+    upper.add_string(Some("B")).unwrap();
+    assert_eq!(
+        find_ids(col, &mut txn, lower.finish(), upper.finish()),
+        vec![2, 1]
+    );
+
+    let mut lower = col.new_index_key(0).unwrap();
+    lower.add_int(2).unwrap();
+    let mut upper = col.new_index_key(0).unwrap();
+    upper.add_upper_unbounded().unwrap();
+    assert_eq!(
+        find_ids(col, &mut txn, lower.finish(), upper.finish()),
+        vec![3, 4]
+    );
+
+    let mut lower = col.new_index_key(0).unwrap();
+    lower.add_int(1).unwrap();
+    let mut upper = col.new_index_key(0).unwrap();
+    upper.add_int(1).unwrap();
+    upper.add_upper_unbounded().unwrap();
+    assert_eq!(
+        find_ids(col, &mut txn, lower.finish(), upper.finish()),
+        vec![2, 1]
+    );
+
+    let mut key = col.new_index_key(0).unwrap();
+    assert!(key.add_string(Some("a")).is_err());
+    assert!(key.add_long(1).is_err());
+    key.add_int(1).unwrap();
+    key.add_string(None).unwrap();
+    assert!(key.add_string(None).is_err());
+    assert!(col.new_index_key(1).is_err());
+
+    txn.abort();
+    isar.close();
+}
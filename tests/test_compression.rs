@@ -0,0 +1,123 @@
+use isar_core::collection::OnConflict;
+use isar_core::config::IsarConfig;
+use isar_core::instance::IsarInstance;
+use isar_core::object::data_type::DataType;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::schema::Schema;
+use std::path::Path;
+use std::sync::Arc;
+
+fn schema(compressed: bool) -> CollectionSchema {
+    let mut body = PropertySchema::new("body", DataType::String);
+    if compressed {
+        body = body.compressed();
+    }
+    let properties = vec![body, PropertySchema::new("title", DataType::String)];
+    let index = IndexSchema::new(
+        "body",
+        vec![IndexPropertySchema::new("body", IndexType::Hash, true)],
+        false,
+    );
+    CollectionSchema::new("notes", properties, vec![index], vec![])
+}
+
+fn open(dir: &Path, compressed: bool) -> Arc<IsarInstance> {
+    let schema = Schema::new(vec![schema(compressed)]).unwrap();
+    let config = IsarConfig {
+        checksums: true,
+        ..Default::default()
+    };
+    IsarInstance::open_with_config("compression", dir, schema, config).unwrap()
+}
+
+#[test]
+fn test_compressed_property() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let bodies = (0..20)
+        .map(|i| format!("# Note {}\n\n{}", i, "Some *markdown* text. ".repeat(50)))
+        .collect::<Vec<_>>();
+
+    let isar = open(&dir, true);
+    let col = isar.collections.first().unwrap();
+    let body = col.property_by_name("body").unwrap();
+    let title = col.property_by_name("title").unwrap();
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    for (id, text) in bodies.iter().enumerate() {
+        let mut ob = col.new_object_builder(None);
+        ob.write_string(Some(text));
+        ob.write_string(Some("title"));
+        col.put(&mut txn, Some(id as i64), ob.finish(), OnConflict::Replace)
+            .unwrap();
+    }
+    let stats = col.get_stats(&mut txn).unwrap();
+    let body_size: usize = bodies.iter().map(|b| b.len()).sum();
+    assert!((stats.object_size as usize) < body_size / 2);
+    assert_eq!(stats.indexes[0].1.entries, 20);
+
+    let object = col.get(&mut txn, 3).unwrap().unwrap();
+    assert_eq!(object.read_string(body), Some(bodies[3].as_str()));
+    assert_eq!(object.read_string(title), Some("title"));
+
+    let mut results = vec![];
+    col.new_query_builder()
+        .build()
+        .find_while(&mut txn, |id, object| {
+            assert_eq!(object.read_string(body), Some(bodies[id as usize].as_str()));
+            results.push(id);
+            true
+        })
+        .unwrap();
+    assert_eq!(results, (0..20).collect::<Vec<_>>());
+
+    // The index entries of the decompressed object are deleted.
+    assert!(col.delete(&mut txn, 3).unwrap());
+    assert_eq!(col.get_stats(&mut txn).unwrap().indexes[0].1.entries, 19);
+    txn.commit().unwrap();
+    assert!(isar.verify().is_ok());
+    assert!(isar.close());
+
+    // Compressed objects can still be read after the compression is disabled.
+    let isar = open(&dir, false);
+    let col = isar.collections.first().unwrap();
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    let object = col.get(&mut txn, 5).unwrap().unwrap();
+    assert_eq!(object.read_string(body), Some(bodies[5].as_str()));
+    let mut ob = col.new_object_builder(None);
+    ob.write_string(Some(&bodies[3]));
+    ob.write_null();
+    col.put(&mut txn, Some(3), ob.finish(), OnConflict::Replace)
+        .unwrap();
+    let object = col.get(&mut txn, 3).unwrap().unwrap();
+    assert_eq!(object.read_string(body), Some(bodies[3].as_str()));
+    txn.commit().unwrap();
+    assert!(isar.verify().is_ok());
+    assert!(isar.close());
+}
+
+#[test]
+fn test_compressed_property_validation() {
+    let validate = |property: PropertySchema| {
+        let col = CollectionSchema::new("col", vec![property], vec![], vec![]);
+        Schema::validate(&[col]).map_err(|e| e.message)
+    };
+    assert_eq!(
+        validate(PropertySchema::new("bytes", DataType::ByteList).compressed()),
+        Ok(())
+    );
+    assert_eq!(
+        validate(PropertySchema::new("int", DataType::Int).compressed()),
+        Err("Only String and ByteList properties may be compressed.".to_string())
+    );
+    let embedded = PropertySchema::embedded(
+        "object",
+        DataType::Object,
+        vec![PropertySchema::new("text", DataType::String).compressed()],
+    );
+    assert_eq!(
+        validate(embedded),
+        Err("Properties of embedded objects cannot be compressed.".to_string())
+    );
+}
@@ -0,0 +1,65 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::IsarCollection;
+use isar_core::instance::IsarInstance;
+use std::thread;
+
+mod common;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_collection_is_send_sync() {
+    assert_send_sync::<IsarCollection>();
+    assert_send_sync::<IsarInstance>();
+}
+
+#[test]
+fn test_parallel_reads_with_writer() {
+    isar!(isar, _col => TestObj::schema("col", &[], &[]));
+
+    let writer_isar = isar.clone();
+    let writer = thread::spawn(move || {
+        let col = &writer_isar.collections[0];
+        for _ in 0..20 {
+            let mut txn = writer_isar.begin_txn(true, false).unwrap();
+            for _ in 0..5 {
+                let id = col.auto_increment(&mut txn).unwrap();
+                TestObj::default(id).save(&mut txn, col);
+            }
+            txn.commit().unwrap();
+        }
+    });
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let isar = isar.clone();
+            thread::spawn(move || {
+                let col = &isar.collections[0];
+                let mut last_count = 0;
+                for _ in 0..50 {
+                    let mut txn = isar.begin_txn(false, false).unwrap();
+                    let ids = col.new_query_builder().build().find_ids(&mut txn).unwrap();
+                    txn.abort();
+
+                    // Readers only see committed transactions.
+                    assert_eq!(ids.len() % 5, 0);
+                    assert!(ids.len() >= last_count);
+                    assert_eq!(ids, (1..=ids.len() as i64).collect::<Vec<_>>());
+                    last_count = ids.len();
+                }
+            })
+        })
+        .collect();
+
+    writer.join().unwrap();
+    for reader in readers {
+        reader.join().unwrap();
+    }
+
+    let col = &isar.collections[0];
+    txn!(isar, txn);
+    let ids = col.new_query_builder().build().find_ids(&mut txn).unwrap();
+    assert_eq!(ids, (1..=100).collect::<Vec<_>>());
+    txn.abort();
+    assert!(isar.close());
+}
@@ -0,0 +1,194 @@
+use isar_core::collection::IsarCollection;
+use isar_core::error::IsarError;
+use isar_core::instance::IsarInstance;
+use isar_core::object::csv_encode_decode::CsvOptions;
+use isar_core::object::data_type::DataType;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::schema::Schema;
+use isar_core::txn::IsarTxn;
+use std::sync::Arc;
+
+fn open() -> Arc<IsarInstance> {
+    let name = rand::random::<u64>().to_string();
+    let dir = std::env::temp_dir().join(&name);
+    let properties = vec![
+        PropertySchema::new("active", DataType::Byte),
+        PropertySchema::new("born", DataType::DateTime),
+        PropertySchema::new("name", DataType::String),
+        PropertySchema::new("score", DataType::Float),
+        PropertySchema::new("tags", DataType::StringList),
+    ];
+    let col = CollectionSchema::new("people", properties, vec![], vec![]);
+    let schema = Schema::new(vec![col]).unwrap();
+    IsarInstance::open(&name, &dir, false, schema).unwrap()
+}
+
+fn export(txn: &mut IsarTxn, col: &IsarCollection, options: &CsvOptions) -> String {
+    let mut csv = vec![];
+    col.new_query_builder()
+        .build()
+        .export_csv(txn, col, Some("id"), &mut csv, options, true, true)
+        .unwrap();
+    String::from_utf8(csv).unwrap()
+}
+
+#[test]
+fn test_csv_import_export() {
+    let isar = open();
+    let col = isar.collections.first().unwrap();
+    let options = CsvOptions {
+        delimiter: b';',
+        header: true,
+    };
+    let csv = "name;id;score;active;born;tags\n\
+        \"Doe; Jane\";1;1.5;true;2020-01-02T03:04:05Z;\"[\"\"a\"\",\"\"b\"\"]\"\n\
+        \"multi\nline\";;;false;1000000;\n";
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    col.import_csv(&mut txn, Some("id"), csv.as_bytes(), &options, false)
+        .unwrap();
+
+    let name = col.property_by_name("name").unwrap();
+    let score = col.property_by_name("score").unwrap();
+    let tags = col.property_by_name("tags").unwrap();
+    let object = col.get(&mut txn, 1).unwrap().unwrap();
+    assert_eq!(object.read_string(name), Some("Doe; Jane"));
+    assert_eq!(object.read_float(score), 1.5);
+    assert_eq!(
+        object.read_string_list(tags),
+        Some(vec![Some("a"), Some("b")])
+    );
+    let object = col.get(&mut txn, 2).unwrap().unwrap();
+    assert_eq!(object.read_string(name), Some("multi\nline"));
+    assert!(object.read_float(score).is_nan());
+    assert_eq!(object.read_string_list(tags), None);
+
+    let exported = export(&mut txn, col, &CsvOptions::default());
+    assert_eq!(
+        exported,
+        "id,active,born,name,score,tags\n\
+        1,true,2020-01-02T03:04:05Z,Doe; Jane,1.5,\"[\"\"a\"\",\"\"b\"\"]\"\n\
+        2,false,1970-01-01T00:00:01Z,\"multi\nline\",\\N,\\N\n"
+    );
+
+    // Exported files can be imported again.
+    col.clear(&mut txn).unwrap();
+    col.import_csv(
+        &mut txn,
+        Some("id"),
+        exported.as_bytes(),
+        &CsvOptions::default(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(export(&mut txn, col, &CsvOptions::default()), exported);
+
+    let options = CsvOptions {
+        delimiter: b'\t',
+        header: false,
+    };
+    assert_eq!(
+        export(&mut txn, col, &options),
+        "1\ttrue\t2020-01-02T03:04:05Z\tDoe; Jane\t1.5\t\"[\"\"a\"\",\"\"b\"\"]\"\n\
+        2\tfalse\t1970-01-01T00:00:01Z\t\"multi\nline\"\t\\N\t\\N\n"
+    );
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_csv_null_and_empty_strings() {
+    let isar = open();
+    let col = isar.collections.first().unwrap();
+    let csv = "id,name,score\n1,,\n2,\\N,\\N\n3,\\\\N,1\n4,\\x,2\n";
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    col.import_csv(
+        &mut txn,
+        Some("id"),
+        csv.as_bytes(),
+        &CsvOptions::default(),
+        false,
+    )
+    .unwrap();
+
+    let name = col.property_by_name("name").unwrap();
+    let score = col.property_by_name("score").unwrap();
+    let read_name = |txn: &mut IsarTxn, id: i64| {
+        let object = col.get(txn, id).unwrap().unwrap();
+        object.read_string(name).map(|name| name.to_string())
+    };
+    assert_eq!(read_name(&mut txn, 1), Some("".to_string()));
+    assert_eq!(read_name(&mut txn, 2), None);
+    assert_eq!(read_name(&mut txn, 3), Some("\\N".to_string()));
+    assert_eq!(read_name(&mut txn, 4), Some("\\x".to_string()));
+    let object = col.get(&mut txn, 1).unwrap().unwrap();
+    assert!(object.read_float(score).is_nan());
+
+    let exported = export(&mut txn, col, &CsvOptions::default());
+    assert_eq!(
+        exported,
+        "id,active,born,name,score,tags\n\
+        1,\\N,\\N,,\\N,\\N\n\
+        2,\\N,\\N,\\N,\\N,\\N\n\
+        3,\\N,\\N,\\\\N,1,\\N\n\
+        4,\\N,\\N,\\\\x,2,\\N\n"
+    );
+
+    // Empty strings and null stay different after a round trip.
+    col.clear(&mut txn).unwrap();
+    col.import_csv(
+        &mut txn,
+        Some("id"),
+        exported.as_bytes(),
+        &CsvOptions::default(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(read_name(&mut txn, 1), Some("".to_string()));
+    assert_eq!(read_name(&mut txn, 2), None);
+    assert_eq!(read_name(&mut txn, 3), Some("\\N".to_string()));
+    assert_eq!(read_name(&mut txn, 4), Some("\\x".to_string()));
+    assert_eq!(export(&mut txn, col, &CsvOptions::default()), exported);
+
+    txn.abort();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_csv_import_errors() {
+    let isar = open();
+    let col = isar.collections.first().unwrap();
+    let import = |csv: &str| {
+        let mut txn = isar.begin_txn(true, false).unwrap();
+        let result = col.import_csv(
+            &mut txn,
+            Some("id"),
+            csv.as_bytes(),
+            &CsvOptions::default(),
+            false,
+        );
+        txn.abort();
+        result
+    };
+
+    assert_eq!(
+        import("id,age\n1,2\n"),
+        Err(IsarError::CsvError {
+            message: "Unknown column \"age\".".to_string()
+        })
+    );
+    assert_eq!(
+        import("id,score\n1,1.5\n2,high\n"),
+        Err(IsarError::CsvError {
+            message: "Invalid value in line 3.".to_string()
+        })
+    );
+    assert_eq!(
+        import("id,tags\n1,[\n"),
+        Err(IsarError::CsvError {
+            message: "Invalid value in line 2.".to_string()
+        })
+    );
+    assert!(import("id,name\n1,a,b\n").is_err());
+    assert!(isar.close());
+}
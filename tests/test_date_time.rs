@@ -0,0 +1,87 @@
+use crate::common::test_obj::TestObj;
+use isar_core::index::index_key::IndexKey;
+use isar_core::object::data_type::DataType;
+use isar_core::query::filter::{Filter, FilterValue};
+use isar_core::query::Query;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::txn::IsarTxn;
+use serde_json::json;
+
+mod common;
+
+fn schema() -> CollectionSchema {
+    let properties = vec![
+        PropertySchema::new("date", DataType::DateTime),
+        PropertySchema::new("dates", DataType::DateTimeList),
+    ];
+    let index = IndexSchema::new(
+        "date",
+        vec![IndexPropertySchema::new("date", IndexType::Value, false)],
+        false,
+    );
+    CollectionSchema::new("col", properties, vec![index], vec![])
+}
+
+fn find_ids(txn: &mut IsarTxn, query: &Query) -> Vec<i64> {
+    let mut ids = vec![];
+    query
+        .find_while(txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    ids
+}
+
+#[test]
+fn test_date_time() {
+    isar!(isar, col => schema());
+    txn!(isar, txn);
+
+    let json = json!([
+        {"id": 1, "date": "2000-01-01T00:00:00Z", "dates": ["2000-01-01", null]},
+        {"id": 2, "date": "2000-01-01T01:00:00+02:00", "dates": []},
+        {"id": 3, "date": 946_684_800_000_001i64, "dates": null},
+        {"id": 4, "date": null, "dates": [0]},
+    ]);
+    col.import_json(&mut txn, Some("id"), json, false).unwrap();
+
+    let date = col.properties[0].1;
+    let dates = col.properties[1].1;
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::gte(date, FilterValue::Long(946_684_800_000_000)).unwrap());
+    assert_eq!(find_ids(&mut txn, &qb.build()), vec![1, 3]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::date_time(dates, 0, 0).unwrap());
+    assert_eq!(find_ids(&mut txn, &qb.build()), vec![4]);
+
+    let mut lower = IndexKey::new();
+    lower.add_long(i64::MIN + 1);
+    let mut upper = IndexKey::new();
+    upper.add_long(i64::MAX);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_eq!(find_ids(&mut txn, &qb.build()), vec![2, 1, 3]);
+
+    let q = col.new_query_builder().build();
+    let exported = q
+        .export_json(&mut txn, col, Some("id"), true, false)
+        .unwrap();
+    assert_eq!(
+        exported,
+        json!([
+            {"id": 1, "date": "2000-01-01T00:00:00Z", "dates": ["2000-01-01T00:00:00Z", null]},
+            {"id": 2, "date": "1999-12-31T23:00:00Z", "dates": []},
+            {"id": 3, "date": "2000-01-01T00:00:00.000001Z", "dates": null},
+            {"id": 4, "date": null, "dates": ["1970-01-01T00:00:00Z"]},
+        ])
+    );
+
+    txn.abort();
+    isar.close();
+}
@@ -1,4 +1,6 @@
 use crossbeam_channel::unbounded;
+use isar_core::object::data_type::DataType;
+use isar_core::query::Sort;
 use isar_core::schema::link_schema::LinkSchema;
 
 use crate::common::test_obj::TestObj;
@@ -26,18 +28,50 @@ fn test_delete() {
     isar.close();
 }
 
+#[test]
+fn test_query_delete() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj1 => 5, obj2 => 1, obj3 => 4, obj4 => 2, obj5 => 3);
+    let byte = TestObj::get_prop(col, DataType::Byte);
+
+    // delete the second and third smallest objects
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Ascending).unwrap();
+    qb.set_offset(1);
+    let count = qb.build().delete(&mut txn, col, 2).unwrap();
+    assert_eq!(count, 2);
+    verify!(txn, col, obj1, obj2, obj3);
+
+    // delete with limit 0
+    let qb = col.new_query_builder();
+    let count = qb.build().delete(&mut txn, col, 0).unwrap();
+    assert_eq!(count, 0);
+    verify!(txn, col, obj1, obj2, obj3);
+
+    // delete everything
+    let qb = col.new_query_builder();
+    let count = qb.build().delete(&mut txn, col, usize::MAX).unwrap();
+    assert_eq!(count, 3);
+    verify!(txn, col);
+
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_delete_clears_links() {
-    let link_schema = LinkSchema::new("link", "obj");
+    let link_schema = LinkSchema::with_backlink("link", "obj", "parent");
     let schema = TestObj::schema("obj", &[], &[link_schema]);
     isar!(isar, col => schema);
     txn!(isar, txn);
 
     // put new objects
     put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3);
-    col.link(&mut txn, 0, false, 1, 2).unwrap();
-    col.link(&mut txn, 0, false, 2, 3).unwrap();
-    col.link(&mut txn, 0, false, 3, 1).unwrap();
+    col.link(&mut txn, 0, 1, 2).unwrap();
+    col.link(&mut txn, 0, 2, 3).unwrap();
+    col.link(&mut txn, 0, 3, 1).unwrap();
     verify!(txn, col, obj1, obj2, obj3; "link", 1 => 2, 2 => 3, 3 => 1);
 
     // delete obj 1
@@ -0,0 +1,55 @@
+use isar_core::error::IsarError;
+use isar_core::schema::link_schema::LinkSchema;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_delete_orphans() {
+    let posts_schema = TestObj::schema(
+        "posts",
+        &[],
+        &[LinkSchema::with_backlink("tags", "tags", "posts")],
+    );
+    let tags_schema = TestObj::schema("tags", &[], &[]);
+    isar!(isar, posts => posts_schema, tags => tags_schema);
+    txn!(isar, txn);
+
+    put!(id: posts, txn, post1 => 1, post2 => 2);
+    put!(id: tags, txn, tag1 => 1, tag2 => 2, tag3 => 3, tag4 => 4);
+    posts.link(&mut txn, 0, 1, 1).unwrap();
+    posts.link(&mut txn, 0, 2, 1).unwrap();
+    posts.link(&mut txn, 0, 2, 3).unwrap();
+
+    assert_eq!(isar.delete_orphans(&mut txn, tags, 0).unwrap(), 2);
+    verify!(txn,
+        col!(posts, post1, post2; "tags", 1 => 1, 2 => 1, 2 => 3);
+        col!(tags, tag1, tag3)
+    );
+
+    posts.unlink(&mut txn, 0, 2, 3).unwrap();
+    assert_eq!(isar.delete_orphans(&mut txn, tags, 0).unwrap(), 1);
+    verify!(txn,
+        col!(posts, post1, post2; "tags", 1 => 1, 2 => 1);
+        col!(tags, tag1)
+    );
+
+    assert_eq!(isar.delete_orphans(&mut txn, tags, 0).unwrap(), 0);
+    assert!(matches!(
+        isar.delete_orphans(&mut txn, tags, 1),
+        Err(IsarError::IllegalArg { .. })
+    ));
+    // links that are not backlinks are rejected
+    assert!(matches!(
+        isar.delete_orphans(&mut txn, posts, 0),
+        Err(IsarError::IllegalArg { .. })
+    ));
+    verify!(txn,
+        col!(posts, post1, post2; "tags", 1 => 1, 2 => 1);
+        col!(tags, tag1)
+    );
+
+    txn.abort();
+    isar.close();
+}
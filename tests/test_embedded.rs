@@ -0,0 +1,141 @@
+use crate::common::test_obj::TestObj;
+use isar_core::object::data_type::DataType;
+use isar_core::query::filter::Filter;
+use isar_core::query::Query;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::schema::Schema;
+use isar_core::txn::IsarTxn;
+use serde_json::json;
+
+mod common;
+
+fn address() -> Vec<PropertySchema> {
+    vec![
+        PropertySchema::new("city", DataType::String),
+        PropertySchema::new("zip", DataType::Int),
+    ]
+}
+
+fn properties() -> Vec<PropertySchema> {
+    vec![
+        PropertySchema::embedded("address", DataType::Object, address()),
+        PropertySchema::embedded("previous", DataType::ObjectList, address()),
+    ]
+}
+
+fn schema() -> CollectionSchema {
+    CollectionSchema::new("col", properties(), vec![], vec![])
+}
+
+fn find_ids(txn: &mut IsarTxn, query: &Query) -> Vec<i64> {
+    let mut ids = vec![];
+    query
+        .find_while(txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    ids
+}
+
+#[test]
+fn test_embedded_json() {
+    isar!(isar, col => schema());
+    txn!(isar, txn);
+
+    let json = json!([
+        {
+            "id": 1,
+            "address": {"city": "Berlin", "zip": 10115},
+            "previous": [{"city": "Paris", "zip": 75001}, null],
+        },
+        {"id": 2, "address": null, "previous": []},
+        {"id": 3, "address": {"city": null}, "previous": null},
+    ]);
+    col.import_json(&mut txn, Some("id"), json, false).unwrap();
+
+    let q = col.new_query_builder().build();
+    let exported = q
+        .export_json(&mut txn, col, Some("id"), true, false)
+        .unwrap();
+    assert_eq!(
+        exported,
+        json!([
+            {
+                "id": 1,
+                "address": {"city": "Berlin", "zip": 10115},
+                "previous": [{"city": "Paris", "zip": 75001}, null],
+            },
+            {"id": 2, "address": null, "previous": []},
+            {"id": 3, "address": {"city": null, "zip": null}, "previous": null},
+        ])
+    );
+
+    let invalid = json!([{"id": 4, "address": "Berlin"}]);
+    assert!(col
+        .import_json(&mut txn, Some("id"), invalid, false)
+        .is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_embedded_filter() {
+    isar!(isar, col => schema());
+    txn!(isar, txn);
+
+    let json = json!([
+        {"id": 1, "address": {"city": "Berlin"}, "previous": [{"city": "Paris"}]},
+        {"id": 2, "address": {"city": "Paris"}, "previous": [null, {"city": "Berlin"}]},
+        {"id": 3, "address": null, "previous": null},
+    ]);
+    col.import_json(&mut txn, Some("id"), json, false).unwrap();
+
+    let address = col.properties[0].1;
+    let previous = col.properties[1].1;
+    let embedded = col.get_embedded(address).unwrap();
+    let city = embedded.get_property("city").unwrap();
+
+    let berlin = || Filter::string(city, Some("Berlin"), Some("Berlin"), true).unwrap();
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::embedded(address, berlin()).unwrap());
+    assert_eq!(find_ids(&mut txn, &qb.build()), vec![1]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::embedded(previous, berlin()).unwrap());
+    assert_eq!(find_ids(&mut txn, &qb.build()), vec![2]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::any_null_element(previous).unwrap());
+    assert_eq!(find_ids(&mut txn, &qb.build()), vec![2]);
+
+    assert!(Filter::embedded(city, berlin()).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_embedded_schema() {
+    let empty = PropertySchema::embedded("address", DataType::Object, vec![]);
+    let col = CollectionSchema::new("col", vec![empty], vec![], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+
+    let nested = PropertySchema::embedded("city", DataType::String, address());
+    let col = CollectionSchema::new("col", vec![nested], vec![], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+
+    let index = IndexSchema::new(
+        "address",
+        vec![IndexPropertySchema::new("address", IndexType::Value, false)],
+        false,
+    );
+    let col = CollectionSchema::new("col", properties(), vec![index], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+
+    assert!(Schema::validate(&[schema()]).is_ok());
+}
@@ -0,0 +1,170 @@
+use isar_core::error::IsarError;
+use isar_core::schema::link_schema::LinkSchema;
+use serde_json::Value;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+fn exported_ids(json: &Value, col: &str) -> Vec<i64> {
+    json[col]
+        .as_array()
+        .map(|items| items.iter().map(|i| i["id"].as_i64().unwrap()).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_export_json_cascade() {
+    let projects_schema = TestObj::schema("projects", &[], &[LinkSchema::new("items", "items")]);
+    let items_schema = TestObj::schema("items", &[], &[LinkSchema::new("tags", "tags")]);
+    let tags_schema = TestObj::schema("tags", &[], &[]);
+    isar!(isar, projects => projects_schema, items => items_schema, tags => tags_schema);
+    txn!(isar, txn);
+
+    put!(id: projects, txn, project1 => 1, project2 => 2);
+    put!(id: items, txn, item1 => 1, item2 => 2, item3 => 3);
+    put!(id: tags, txn, tag1 => 1, tag2 => 2);
+    projects.link(&mut txn, 0, 1, 1).unwrap();
+    projects.link(&mut txn, 0, 1, 2).unwrap();
+    projects.link(&mut txn, 0, 2, 3).unwrap();
+    items.link(&mut txn, 0, 1, 1).unwrap();
+    items.link(&mut txn, 0, 2, 1).unwrap();
+    items.link(&mut txn, 0, 3, 2).unwrap();
+
+    let mut qb = projects.new_query_builder();
+    qb.add_id_where_clause(1, 1).unwrap();
+    let q = qb.build();
+    let links = [(projects, 0), (items, 0)];
+
+    let json = q
+        .export_json_cascade(
+            &mut txn,
+            &isar.collections,
+            projects,
+            &links,
+            0,
+            Some("id"),
+            true,
+            true,
+        )
+        .unwrap();
+    assert_eq!(exported_ids(&json, "projects"), vec![1]);
+    assert!(json.get("items").is_none());
+
+    let json = q
+        .export_json_cascade(
+            &mut txn,
+            &isar.collections,
+            projects,
+            &links,
+            1,
+            Some("id"),
+            true,
+            true,
+        )
+        .unwrap();
+    assert_eq!(exported_ids(&json, "items"), vec![1, 2]);
+    assert!(json.get("tags").is_none());
+
+    let json = q
+        .export_json_cascade(
+            &mut txn,
+            &isar.collections,
+            projects,
+            &links,
+            5,
+            Some("id"),
+            true,
+            true,
+        )
+        .unwrap();
+    assert_eq!(exported_ids(&json, "projects"), vec![1]);
+    assert_eq!(exported_ids(&json, "items"), vec![1, 2]);
+    assert_eq!(exported_ids(&json, "tags"), vec![1]);
+
+    let json = q
+        .export_json_cascade(
+            &mut txn,
+            &isar.collections,
+            projects,
+            &links[1..],
+            5,
+            Some("id"),
+            true,
+            true,
+        )
+        .unwrap();
+    assert!(json.get("items").is_none());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_export_json_stream() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    for id in 1..=20 {
+        let mut obj = TestObj::default(id);
+        obj.string = Some(format!("object {}", id));
+        obj.save(&mut txn, col);
+    }
+
+    let q = col.new_query_builder().build();
+    let expected = q
+        .export_json(&mut txn, col, Some("id"), true, false)
+        .unwrap();
+    let mut stream = vec![];
+    q.export_json_stream(&mut txn, col, Some("id"), &mut stream, true, false)
+        .unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&stream).unwrap(), expected);
+
+    for chunk_size in &[1, 100, 100_000] {
+        let mut export = q
+            .export_json_chunked(&mut txn, Some("id"), true, false)
+            .unwrap();
+        let mut chunks = vec![];
+        while let Some(chunk) = export.next_chunk(&mut txn, col, *chunk_size).unwrap() {
+            assert!(chunk.len() >= *chunk_size || chunks.is_empty() || chunk.ends_with(b"]"));
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks.concat(), stream);
+    }
+
+    let mut export = q
+        .export_json_chunked(&mut txn, Some("id"), true, false)
+        .unwrap();
+    let result = export.next_chunk(&mut txn, col, 0);
+    assert!(matches!(result, Err(IsarError::IllegalArg { .. })));
+
+    // Objects deleted while the export is read are skipped.
+    let mut export = q
+        .export_json_chunked(&mut txn, Some("id"), true, false)
+        .unwrap();
+    let first = export.next_chunk(&mut txn, col, 1).unwrap().unwrap();
+    col.delete(&mut txn, 2).unwrap();
+    col.delete(&mut txn, 20).unwrap();
+    let mut json = first;
+    while let Some(chunk) = export.next_chunk(&mut txn, col, 1).unwrap() {
+        json.extend(chunk);
+    }
+    let json = serde_json::from_slice::<Value>(&json).unwrap();
+    let ids = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["id"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, (1..20).filter(|id| *id != 2).collect::<Vec<_>>());
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(100, 200).unwrap();
+    let mut stream = vec![];
+    qb.build()
+        .export_json_stream(&mut txn, col, None, &mut stream, true, false)
+        .unwrap();
+    assert_eq!(stream, b"[]");
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,145 @@
+use crate::common::test_obj::TestObj;
+use isar_core::object::data_type::DataType;
+use isar_core::object::geo::GeoArea;
+use isar_core::query::filter::Filter;
+use isar_core::query::query_builder::QueryBuilder;
+use isar_core::query::Query;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::schema::Schema;
+use isar_core::txn::IsarTxn;
+use serde_json::json;
+
+mod common;
+
+fn index(properties: Vec<IndexPropertySchema>) -> IndexSchema {
+    IndexSchema::new("location", properties, false)
+}
+
+fn schema() -> CollectionSchema {
+    let properties = vec![
+        PropertySchema::new("location", DataType::GeoPoint),
+        PropertySchema::new("name", DataType::String),
+    ];
+    let index = index(vec![IndexPropertySchema::new(
+        "location",
+        IndexType::Value,
+        false,
+    )]);
+    CollectionSchema::new("col", properties, vec![index], vec![])
+}
+
+fn find_ids(txn: &mut IsarTxn, query: &Query) -> Vec<i64> {
+    let mut ids = vec![];
+    query
+        .find_while(txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn test_geo() {
+    isar!(isar, col => schema());
+    txn!(isar, txn);
+
+    let json = json!([
+        {"id": 1, "location": {"lat": 52.52, "lon": 13.405}, "name": "Berlin"},
+        {"id": 2, "location": {"lat": 52.39, "lon": 13.065}, "name": "Potsdam"},
+        {"id": 3, "location": {"lat": -17.0, "lon": 179.9}, "name": null},
+        {"id": 4, "location": {"lat": -17.0, "lon": -179.9}, "name": null},
+        {"id": 5, "location": null, "name": null},
+    ]);
+    col.import_json(&mut txn, Some("id"), json.clone(), false)
+        .unwrap();
+
+    let location = col.properties[0].1;
+    let filter = |filter| {
+        let mut qb = col.new_query_builder();
+        qb.set_filter(filter);
+        qb.build()
+    };
+    let where_clause = |area| {
+        let mut qb = col.new_query_builder();
+        qb.add_geo_where_clause(0, area).unwrap();
+        qb.build()
+    };
+
+    let query = filter(Filter::geo_within_radius(location, 52.52, 13.405, 30_000.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), vec![1, 2]);
+    let query = filter(Filter::geo_within_radius(location, 52.52, 13.405, 5_000.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), vec![1]);
+    let query = filter(Filter::geo_within_box(location, -20.0, 179.0, -10.0, -179.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), vec![3, 4]);
+
+    let query = where_clause(GeoArea::radius(52.52, 13.405, 30_000.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), vec![1, 2]);
+    let query = where_clause(GeoArea::radius(-17.0, 180.0, 20_000.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), vec![3, 4]);
+    let query = where_clause(GeoArea::bounding_box(-90.0, -180.0, 90.0, 180.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), vec![1, 2, 3, 4]);
+    let query = where_clause(GeoArea::bounding_box(0.0, 0.0, 1.0, 1.0).unwrap());
+    assert_eq!(find_ids(&mut txn, &query), Vec::<i64>::new());
+
+    let query = json!({
+        "whereClauses": [
+            {"type": "geo", "index": "location", "lat": 52.52, "lon": 13.405, "radius": 30000}
+        ],
+        "filter": {"type": "geoWithin", "property": "location",
+            "south": 52.4, "west": 13.0, "north": 53.0, "east": 14.0}
+    });
+    let query = QueryBuilder::from_json(col, &query).unwrap().build();
+    assert_eq!(find_ids(&mut txn, &query), vec![1]);
+
+    let mut qb = col.new_query_builder();
+    assert!(qb
+        .add_geo_where_clause(1, GeoArea::radius(0.0, 0.0, 1.0).unwrap())
+        .is_err());
+    assert!(GeoArea::radius(0.0, 0.0, -1.0).is_err());
+    assert!(GeoArea::bounding_box(10.0, 0.0, 0.0, 10.0).is_err());
+    assert!(GeoArea::bounding_box(0.0, 0.0, 10.0, 181.0).is_err());
+
+    let q = col.new_query_builder().build();
+    let exported = q
+        .export_json(&mut txn, col, Some("id"), true, false)
+        .unwrap();
+    assert_eq!(exported, json);
+
+    let invalid = json!([{"id": 6, "location": {"lat": 91.0, "lon": 0.0}}]);
+    assert!(col
+        .import_json(&mut txn, Some("id"), invalid, false)
+        .is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_geo_schema() {
+    let properties = || {
+        vec![
+            PropertySchema::new("location", DataType::GeoPoint),
+            PropertySchema::new("name", DataType::String),
+        ]
+    };
+    let hash = index(vec![IndexPropertySchema::new(
+        "location",
+        IndexType::Hash,
+        false,
+    )]);
+    let col = CollectionSchema::new("col", properties(), vec![hash], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+
+    let composite = index(vec![
+        IndexPropertySchema::new("location", IndexType::Value, false),
+        IndexPropertySchema::new("name", IndexType::Value, false),
+    ]);
+    let col = CollectionSchema::new("col", properties(), vec![composite], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+
+    assert!(Schema::validate(&[schema()]).is_ok());
+}
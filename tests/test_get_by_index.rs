@@ -0,0 +1,152 @@
+use isar_core::index::index_key::IndexKey;
+use isar_core::index::{normalize_string, round_double, round_float};
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_get_by_normalized_string_index() {
+    let indexes = [
+        IndexSchema::new(
+            "string",
+            vec![IndexPropertySchema::normalized(
+                "string",
+                IndexType::Value,
+                false,
+            )],
+            false,
+        ),
+        IndexSchema::new(
+            "stringHash",
+            vec![IndexPropertySchema::normalized(
+                "string",
+                IndexType::Hash,
+                false,
+            )],
+            false,
+        ),
+        IndexSchema::new(
+            "stringList",
+            vec![IndexPropertySchema::normalized(
+                "stringList",
+                IndexType::Value,
+                false,
+            )],
+            false,
+        ),
+    ];
+    isar!(isar, col => TestObj::schema("col", &indexes, &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string, obj1 => Some("Straße".to_string()), obj2 => Some("Cafe\u{301}".to_string()));
+    let mut obj3 = TestObj::default(3);
+    obj3.string_list = Some(vec![Some("MASSE".to_string())]);
+    obj3.save(&mut txn, col);
+
+    let lookup = |txn: &mut _, index: usize, key: &IndexKey| {
+        col.get_by_index(txn, index, key).unwrap().map(|(id, _)| id)
+    };
+
+    let mut key = IndexKey::new();
+    key.add_string(Some(&normalize_string("STRASSE", false)), true);
+    assert_eq!(lookup(&mut txn, 0, &key), Some(obj1.id));
+
+    let mut key = IndexKey::new();
+    key.add_string(Some(&normalize_string("CAF\u{c9}", false)), true);
+    assert_eq!(lookup(&mut txn, 0, &key), Some(obj2.id));
+
+    let mut key = IndexKey::new();
+    let hash = IsarObject::hash_string(Some(&normalize_string("strasse", false)), true, 0);
+    key.add_hash(hash);
+    assert_eq!(lookup(&mut txn, 1, &key), Some(obj1.id));
+
+    let mut key = IndexKey::new();
+    key.add_string(Some(&normalize_string("Maße", false)), true);
+    assert_eq!(lookup(&mut txn, 2, &key), Some(obj3.id));
+
+    let mut key = IndexKey::new();
+    key.add_string(Some("STRAßE"), false);
+    assert_eq!(lookup(&mut txn, 0, &key), None);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_normalize_string() {
+    assert_eq!(normalize_string("Straße", false), "strasse");
+    assert_eq!(normalize_string("Straße", true), "Straße");
+    assert_eq!(normalize_string("Cafe\u{301}", true), "Caf\u{e9}");
+    assert_eq!(normalize_string("CAFE\u{301}", false), "caf\u{e9}");
+}
+
+#[test]
+fn test_get_by_rounded_float_index() {
+    let indexes = [
+        IndexSchema::new(
+            "double",
+            vec![IndexPropertySchema::rounded("double", 2)],
+            false,
+        ),
+        IndexSchema::new(
+            "floatInt",
+            vec![
+                IndexPropertySchema::rounded("float", -1),
+                IndexPropertySchema::new("int", IndexType::Value, false),
+            ],
+            false,
+        ),
+    ];
+    isar!(isar, col => TestObj::schema("col", &indexes, &[]));
+    txn!(isar, txn);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.double = 0.1 + 0.2;
+    obj1.float = 23.7;
+    obj1.int = 5;
+    obj1.save(&mut txn, col);
+    let mut obj2 = TestObj::default(2);
+    obj2.double = -0.001;
+    obj2.float = 26.0;
+    obj2.int = 6;
+    obj2.save(&mut txn, col);
+
+    let lookup = |txn: &mut _, index: usize, key: &IndexKey| {
+        col.get_by_index(txn, index, key).unwrap().map(|(id, _)| id)
+    };
+
+    let mut key = IndexKey::new();
+    key.add_double(round_double(0.3, 2));
+    assert_eq!(lookup(&mut txn, 0, &key), Some(obj1.id));
+
+    let mut key = IndexKey::new();
+    key.add_double(0.0);
+    assert_eq!(lookup(&mut txn, 0, &key), Some(obj2.id));
+
+    let mut key = IndexKey::new();
+    key.add_float(round_float(20.0, -1));
+    key.add_int(5);
+    assert_eq!(lookup(&mut txn, 1, &key), Some(obj1.id));
+
+    let mut key = IndexKey::new();
+    key.add_float(round_float(30.0, -1));
+    key.add_int(5);
+    assert_eq!(lookup(&mut txn, 1, &key), None);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_round() {
+    assert_eq!(round_double(1.005, 1), 1.0);
+    assert_eq!(round_double(1234.5, -2), 1200.0);
+    assert_eq!(round_double(-0.04, 1), 0.0);
+    assert!(round_double(-0.04, 1).is_sign_positive());
+    assert!(round_double(f64::NAN, 2).is_nan());
+    assert_eq!(round_float(0.15, 1), 0.2);
+    assert_eq!(round_float(f32::INFINITY, 1), f32::INFINITY);
+}
@@ -0,0 +1,91 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::{IsarCollection, IMPORT_PROGRESS_INTERVAL};
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_import_json_progress() {
+    isar!(isar, col => TestObj::schema("col", &[], &[]));
+    txn!(isar, txn);
+
+    let count = IMPORT_PROGRESS_INTERVAL * 2 + 5;
+    let objects = (1..=count).map(|id| json!({ "id": id })).collect();
+    let mut progress = vec![];
+    col.import_json_with_progress(&mut txn, Some("id"), Value::Array(objects), false, |n| {
+        progress.push(n)
+    })
+    .unwrap();
+
+    assert_eq!(
+        progress,
+        vec![
+            IMPORT_PROGRESS_INTERVAL,
+            IMPORT_PROGRESS_INTERVAL * 2,
+            count
+        ]
+    );
+    let q = col.new_query_builder().build();
+    assert_eq!(q.count(&mut txn).unwrap() as usize, count);
+
+    let mut progress = vec![];
+    let result =
+        col.import_json_with_progress(&mut txn, Some("id"), json!([{ "id": "a" }]), false, |n| {
+            progress.push(n)
+        });
+    assert!(result.is_err());
+    assert!(progress.is_empty());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_import_json_parallel() {
+    let indexes = TestObj::default_indexes();
+    let schema = TestObj::schema("col", &indexes, &[]);
+    let serial_schema = TestObj::schema("serial", &indexes, &[]);
+    isar!(isar, col => schema, serial_col => serial_schema);
+    txn!(isar, txn);
+
+    let count = IMPORT_PROGRESS_INTERVAL * 2 + 5;
+    let mut objects: Vec<_> = (1..=count as i64)
+        .map(|id| json!({"id": id, "int": id % 5, "string": format!("s{}", id)}))
+        .collect();
+    objects.push(json!({"int": 1, "stringList": ["a", "],"]}));
+    let json = serde_json::to_vec(&objects).unwrap();
+    let mut progress = vec![];
+    col.import_json_parallel(&mut txn, Some("id"), json, false, 3, |n| progress.push(n))
+        .unwrap();
+    serial_col
+        .import_json(&mut txn, Some("id"), Value::Array(objects), false)
+        .unwrap();
+
+    assert_eq!(
+        progress,
+        vec![
+            IMPORT_PROGRESS_INTERVAL,
+            IMPORT_PROGRESS_INTERVAL * 2,
+            count + 1
+        ]
+    );
+    let mut export = |col: &IsarCollection| {
+        let q = col.new_query_builder().build();
+        q.export_json(&mut txn, col, Some("id"), true, false)
+            .unwrap()
+    };
+    let exported = export(col);
+    assert_eq!(exported.as_array().unwrap().len(), count + 1);
+    assert_eq!(exported, export(serial_col));
+    txn.commit().unwrap();
+
+    for (json, threads) in [(r#"[{"id": 1},]"#, 2), ("[1]", 2), ("[]", 0)] {
+        txn!(isar, txn);
+        let json = json.as_bytes().to_vec();
+        let result = col.import_json_parallel(&mut txn, None, json, false, threads, |_| {});
+        assert!(result.is_err());
+        txn.abort();
+    }
+
+    isar.close();
+}
@@ -0,0 +1,113 @@
+use crate::common::test_obj::TestObj;
+use crate::common::util::assert_find;
+use isar_core::config::IsarConfig;
+use isar_core::index::index_key::IndexKey;
+use isar_core::instance::IsarInstance;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::Schema;
+use std::sync::Arc;
+
+mod common;
+
+fn open(path: &std::path::Path, indexes: &[IndexSchema]) -> Arc<IsarInstance> {
+    let schema = Schema::new(vec![TestObj::schema("col", indexes, &[])]).unwrap();
+    let config = IsarConfig {
+        index_build_batch_size: 3,
+        ..Default::default()
+    };
+    let name = path.file_name().unwrap().to_str().unwrap();
+    IsarInstance::open_with_config(name, path, schema, config).unwrap()
+}
+
+#[test]
+fn test_index_build_in_batches() {
+    let mut dir = std::env::temp_dir();
+    let r: u64 = rand::random();
+    dir.push(&r.to_string());
+
+    let isar = open(&dir, &[]);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+    let mut objects = vec![];
+    for id in 1..=10 {
+        let mut obj = TestObj::default(id);
+        obj.int = (id % 4) as i32;
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+    txn.commit().unwrap();
+    isar.close();
+
+    let index = IndexSchema::new(
+        "int",
+        vec![IndexPropertySchema::new("int", IndexType::Value, false)],
+        false,
+    );
+    let isar = open(&dir, &[index]);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+
+    let mut key = IndexKey::new();
+    key.add_int(2);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_find(
+        &mut txn,
+        col,
+        qb.build(),
+        &[&objects[1], &objects[5], &objects[9]],
+    );
+
+    let mut lower = IndexKey::new();
+    lower.add_int(i32::MIN);
+    let mut upper = IndexKey::new();
+    upper.add_int(i32::MAX);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_eq!(qb.build().count(&mut txn).unwrap(), 10);
+
+    txn.abort();
+    isar.close_and_delete();
+}
+
+#[test]
+fn test_unique_index_build_in_batches() {
+    let mut dir = std::env::temp_dir();
+    let r: u64 = rand::random();
+    dir.push(&r.to_string());
+
+    let isar = open(&dir, &[]);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+    for id in 1..=8 {
+        let mut obj = TestObj::default(id);
+        obj.int = if id == 2 || id == 7 { 100 } else { id as i32 };
+        obj.save(&mut txn, col);
+    }
+    txn.commit().unwrap();
+    isar.close();
+
+    let index = IndexSchema::new(
+        "int",
+        vec![IndexPropertySchema::new("int", IndexType::Value, false)],
+        true,
+    );
+    let isar = open(&dir, &[index]);
+    let col = isar.collections.first().unwrap();
+    txn!(isar, txn);
+
+    let mut key = IndexKey::new();
+    key.add_int(100);
+    let id = col
+        .get_by_index(&mut txn, 0, &key)
+        .unwrap()
+        .map(|(id, _)| id);
+    assert_eq!(id, Some(7));
+    assert!(TestObj::get(col, &mut txn, 2).is_none());
+    assert_eq!(col.new_query_builder().build().count(&mut txn).unwrap(), 7);
+
+    txn.abort();
+    isar.close_and_delete();
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "inspector")]
+
+use crate::common::test_obj::TestObj;
+use serde_json::{json, Value};
+use std::net::TcpStream;
+use tungstenite::{Message, WebSocket};
+
+mod common;
+
+fn request(socket: &mut WebSocket<TcpStream>, request: Value) -> Value {
+    socket
+        .write_message(Message::Text(request.to_string()))
+        .unwrap();
+    receive(socket)
+}
+
+fn receive(socket: &mut WebSocket<TcpStream>) -> Value {
+    loop {
+        if let Message::Text(text) = socket.read_message().unwrap() {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_inspector() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    TestObj::default(1).save(&mut txn, col);
+    txn.commit().unwrap();
+
+    let inspector = isar.start_inspector("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", inspector.local_addr());
+    let stream = TcpStream::connect(inspector.local_addr()).unwrap();
+    let (mut socket, _) = tungstenite::client(url, stream).unwrap();
+
+    let response = request(&mut socket, json!({"id": 1, "method": "listCollections"}));
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"][0]["name"], col.name.as_str());
+    assert_eq!(response["result"][0]["count"], 1);
+
+    let response = request(
+        &mut socket,
+        json!({"id": 2, "method": "watch", "collection": col.name}),
+    );
+    assert_eq!(response, json!({"id": 2, "result": null}));
+
+    let object = json!({"id": 2, "int": 5});
+    let response = request(
+        &mut socket,
+        json!({"id": 3, "method": "put", "collection": col.name, "objects": [object]}),
+    );
+    assert_eq!(response, json!({"id": 3, "result": null}));
+    assert_eq!(
+        receive(&mut socket),
+        json!({"event": "change", "collection": col.name})
+    );
+
+    let query = json!({"filter": {"type": "eq", "property": "int", "value": 5}});
+    let response = request(
+        &mut socket,
+        json!({"id": 4, "method": "query", "collection": col.name, "query": query}),
+    );
+    assert_eq!(response["result"].as_array().unwrap().len(), 1);
+    assert_eq!(response["result"][0]["id"], 2);
+
+    let response = request(
+        &mut socket,
+        json!({"id": 5, "method": "delete", "collection": col.name, "ids": [1, 2, 3]}),
+    );
+    assert_eq!(response, json!({"id": 5, "result": 2}));
+    assert_eq!(
+        receive(&mut socket),
+        json!({"event": "change", "collection": col.name})
+    );
+
+    let response = request(
+        &mut socket,
+        json!({"id": 6, "method": "query", "collection": "unknown"}),
+    );
+    assert!(response["error"].is_string());
+
+    drop(socket);
+    drop(inspector);
+    isar.close();
+}
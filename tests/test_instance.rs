@@ -1,7 +1,12 @@
 use crate::common::test_obj::TestObj;
+use isar_core::error::IsarError;
+use isar_core::instance::IsarInstance;
 use isar_core::schema::index_schema::IndexSchema;
 use isar_core::schema::link_schema::LinkSchema;
+use isar_core::schema::Schema;
 use isar_core::verify::verify_isar;
+use rand::random;
+use std::sync::Arc;
 
 mod common;
 
@@ -12,7 +17,7 @@ fn test_open_instance_multiple_times() {
     txn!(isar1, txn);
     put!(id: col, txn, obj1 => 1, obj2 => 2);
 
-    isar!(path, isar2, col => TestObj::default_schema());
+    isar!(&path, isar2, col => TestObj::default_schema());
     txn.commit().unwrap();
 
     txn!(isar1, txn);
@@ -39,16 +44,16 @@ fn test_open_instance_added_collection() {
     isar.close();
 
     // database with one collection
-    isar!(path, isar, col1 => schema1);
+    isar!(&path, isar, col1 => schema1);
     txn!(isar, txn);
     put!(id: col1, txn, obj1 => 1, obj2 => 2);
-    col1.link(&mut txn, 0, false, 1, 2).unwrap();
+    col1.link(&mut txn, 0, 1, 2).unwrap();
     verify!(txn, col1, obj1, obj2; "testlink", 1 => 2);
     txn.commit().unwrap();
     isar.close();
 
     // database with two collections
-    isar!(path, isar, col1 => schema1, col2 => schema2);
+    isar!(&path, isar, col1 => schema1, col2 => schema2);
     txn!(isar, txn);
     put!(id: col2, txn, obj3 => 3);
     verify!(txn, col!(col1, obj1, obj2; "testlink", 1 => 2); col!(col2, obj3));
@@ -70,21 +75,21 @@ fn test_open_instance_removed_collection() {
     txn!(isar, txn);
     put!(id: col1, txn, obj1 => 1, obj2 => 2);
     put!(id: col2, txn, obj3 => 3, obj4 => 4);
-    col1.link(&mut txn, 0, false, 1, 2).unwrap();
-    col2.link(&mut txn, 0, false, 3, 4).unwrap();
+    col1.link(&mut txn, 0, 1, 2).unwrap();
+    col2.link(&mut txn, 0, 3, 4).unwrap();
     verify!(txn, col!(col1, obj1, obj2; "testlink1", 1 => 2); col!(col2, obj3, obj4; "testlink2", 3 => 4));
     txn.commit().unwrap();
     isar.close();
 
     // database with one collection
-    isar!(path, isar, col2 => schema2);
+    isar!(&path, isar, col2 => schema2);
     txn!(isar, txn);
     verify!(txn, col2, obj3, obj4; "testlink2", 3 => 4);
     txn.commit().unwrap();
     isar.close();
 
     // empty database
-    isar!(path, isar);
+    isar!(&path, isar);
     txn!(isar, txn);
     verify!(txn);
     txn.abort();
@@ -104,7 +109,7 @@ fn test_open_instance_added_index() {
 
     let byte_index = IndexSchema::new("byte", vec![TestObj::byte_index()], true);
     let schema = TestObj::schema("obj", &[byte_index], &[]);
-    isar!(path, isar, col => schema);
+    isar!(&path, isar, col => schema);
     txn!(isar, txn);
     verify!(txn, col, obj3, obj4, obj5);
     txn.commit().unwrap();
@@ -113,3 +118,25 @@ fn test_open_instance_added_index() {
 
 #[test]
 fn test_open_instance_removed_index() {}
+
+#[test]
+fn test_get_instance() {
+    isar!(isar);
+    let name = isar.name.clone();
+    let instance = IsarInstance::get_instance(&name).unwrap();
+    assert!(Arc::ptr_eq(&isar, &instance));
+    assert!(!instance.close());
+    assert!(isar.close());
+    assert!(IsarInstance::get_instance(&name).is_none());
+}
+
+#[test]
+fn test_open_instance_different_dir() {
+    isar!(isar);
+    let mut other_dir = std::env::temp_dir();
+    other_dir.push(random::<u64>().to_string());
+    let schema = Schema::new(vec![]).unwrap();
+    let result = IsarInstance::open(&isar.name, &other_dir, false, schema);
+    assert!(matches!(result, Err(IsarError::PathMismatch {})));
+    assert!(isar.close());
+}
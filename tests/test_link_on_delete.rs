@@ -0,0 +1,67 @@
+use crate::common::test_obj::TestObj;
+use isar_core::error::IsarError;
+use isar_core::schema::link_schema::{LinkSchema, OnDelete};
+
+mod common;
+
+#[test]
+fn test_link_on_delete() {
+    let col1_schema = TestObj::schema(
+        "col1",
+        &[],
+        &[
+            LinkSchema::new("cascade", "col2").with_on_delete(OnDelete::Cascade),
+            LinkSchema::new("restrict", "col3").with_on_delete(OnDelete::Restrict),
+        ],
+    );
+    let col2_schema = TestObj::schema(
+        "col2",
+        &[],
+        &[
+            LinkSchema::new("cascade", "col2").with_on_delete(OnDelete::Cascade),
+            LinkSchema::new("unlink", "col3"),
+        ],
+    );
+    let col3_schema = TestObj::schema("col3", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema, col3 => col3_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, _obj1 => 1, _obj2 => 2);
+    put!(id: col2, txn, _obj3 => 1, _obj4 => 2, _obj5 => 3, _obj6 => 4);
+    put!(id: col3, txn, _obj7 => 1);
+
+    // col2 objects form a cycle 1 -> 2 -> 3 -> 1 and 4 is not linked.
+    col1.link(&mut txn, 0, 1, 1).unwrap();
+    col2.link(&mut txn, 0, 1, 2).unwrap();
+    col2.link(&mut txn, 0, 2, 3).unwrap();
+    col2.link(&mut txn, 0, 3, 1).unwrap();
+    col2.link(&mut txn, 1, 3, 1).unwrap();
+    col1.link(&mut txn, 1, 2, 1).unwrap();
+
+    assert!(col1.delete(&mut txn, 1).unwrap());
+    for id in 1..=3 {
+        assert!(col2.get(&mut txn, id).unwrap().is_none());
+    }
+    assert!(col2.get(&mut txn, 4).unwrap().is_some());
+
+    // Unlinked targets are kept.
+    assert!(col3.get(&mut txn, 1).unwrap().is_some());
+
+    txn.commit().unwrap();
+
+    // A failed delete closes the txn so nothing is deleted.
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    assert_eq!(
+        col1.delete(&mut txn, 2),
+        Err(IsarError::DeleteRestricted { id: 2 })
+    );
+    assert_eq!(col1.get(&mut txn, 2), Err(IsarError::TransactionClosed {}));
+    txn.abort();
+
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    assert!(col1.get(&mut txn, 2).unwrap().is_some());
+    col1.unlink(&mut txn, 1, 2, 1).unwrap();
+    assert!(col1.delete(&mut txn, 2).unwrap());
+    txn.abort();
+    isar.close();
+}
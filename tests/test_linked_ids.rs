@@ -0,0 +1,29 @@
+use crate::common::test_obj::TestObj;
+use isar_core::schema::link_schema::LinkSchema;
+
+mod common;
+
+#[test]
+fn test_replace_links() {
+    let col1_schema = TestObj::schema("col1", &[], &[LinkSchema::new("link", "col2")]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+
+    put!(id: col1, txn, _obj1 => 1, _obj2 => 2);
+    put!(id: col2, txn, _obj3 => 1, _obj4 => 2, _obj5 => 3);
+    col1.link(&mut txn, 0, 1, 1).unwrap();
+    col1.link(&mut txn, 0, 1, 2).unwrap();
+    col1.link(&mut txn, 0, 2, 2).unwrap();
+
+    col1.replace_links(&mut txn, 0, 1, &[2, 3, 4]).unwrap();
+    assert_eq!(col1.get_linked_ids(&mut txn, 0, 1).unwrap(), vec![2, 3]);
+    assert_eq!(col1.get_linked_ids(&mut txn, 0, 2).unwrap(), vec![2]);
+
+    col1.replace_links(&mut txn, 0, 1, &[]).unwrap();
+    assert!(col1.get_linked_ids(&mut txn, 0, 1).unwrap().is_empty());
+    assert!(col1.get_linked_ids(&mut txn, 1, 1).is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,129 @@
+use isar_core::config::IsarConfig;
+use isar_core::index::index_key::IndexKey;
+use isar_core::instance::IsarInstance;
+use isar_core::object::data_type::DataType;
+use isar_core::query::{DistinctKeep, Sort};
+use isar_core::schema::index_schema::IndexSchema;
+use isar_core::schema::Schema;
+use std::fs;
+use std::path::Path;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+fn spill_files(dir: &Path) -> usize {
+    fs::read_dir(dir.join("budget").join("spill")).map_or(0, |d| d.count())
+}
+
+#[test]
+fn test_query_memory_budget() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let index = IndexSchema::new("int", vec![TestObj::int_index()], false);
+    let schema = Schema::new(vec![TestObj::schema("obj", &[index], &[])]).unwrap();
+    let config = IsarConfig {
+        query_memory_budget: Some(256),
+        ..Default::default()
+    };
+    let isar = IsarInstance::open_with_config("budget", &dir, schema, config).unwrap();
+    let col = isar.collections.first().unwrap();
+
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    let mut objects = vec![];
+    for id in 0..200 {
+        let mut obj = TestObj::default(id);
+        obj.int = (id * 7 % 13) as i32;
+        obj.byte = (id % 5) as u8;
+        obj.string = Some(format!("s{}", id % 11));
+        obj.save(&mut txn, col);
+        objects.push(obj);
+    }
+    let int = TestObj::get_prop(col, DataType::Int);
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let string = TestObj::get_prop(col, DataType::String);
+
+    let mut sorted = objects.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|o| (o.int, -(o.byte as i32)));
+    let ids = |objects: &[&TestObj]| objects.iter().map(|o| o.id).collect::<Vec<_>>();
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(int, Sort::Ascending).unwrap();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    let query = qb.build();
+    let mut results = vec![];
+    query
+        .find_while(&mut txn, |id, _| {
+            assert_eq!(spill_files(&dir), 1);
+            results.push(id);
+            true
+        })
+        .unwrap();
+    assert_eq!(results, ids(&sorted));
+    assert_eq!(spill_files(&dir), 0);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(int, Sort::Ascending).unwrap();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    qb.set_offset(5);
+    qb.set_limit(20);
+    let expected = sorted.iter().skip(5).take(20).copied().collect::<Vec<_>>();
+    assert_eq!(qb.build().find_ids(&mut txn).unwrap(), ids(&expected));
+
+    for keep in &[DistinctKeep::First, DistinctKeep::Last] {
+        let mut qb = col.new_query_builder();
+        qb.add_sort(int, Sort::Ascending).unwrap();
+        qb.add_sort(byte, Sort::Descending).unwrap();
+        qb.add_distinct(int, true);
+        qb.add_distinct(string, true);
+        qb.set_distinct_keep(*keep);
+        let expected = sorted
+            .iter()
+            .enumerate()
+            .filter(|(i, o)| {
+                let mut same = sorted
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| s.int == o.int && s.string == o.string);
+                let kept = if *keep == DistinctKeep::First {
+                    same.next()
+                } else {
+                    same.last()
+                };
+                kept.unwrap().0 == *i
+            })
+            .map(|(_, o)| *o)
+            .collect::<Vec<_>>();
+        assert_eq!(qb.build().find_ids(&mut txn).unwrap(), ids(&expected));
+    }
+
+    // The distinct hashes of unsorted queries are spilled as well.
+    let mut qb = col.new_query_builder();
+    qb.add_distinct(int, true);
+    qb.add_distinct(string, true);
+    assert_eq!(
+        qb.build().find_ids(&mut txn).unwrap(),
+        (0..143).collect::<Vec<_>>()
+    );
+
+    // Objects that are equal in the index sorted properties are spilled.
+    let mut lower = IndexKey::new();
+    lower.add_int(i32::MIN);
+    let mut upper = IndexKey::new();
+    upper.add_int(i32::MAX);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    qb.add_sort(int, Sort::Ascending).unwrap();
+    qb.add_sort(byte, Sort::Ascending).unwrap();
+    qb.add_sort(string, Sort::Ascending).unwrap();
+    let query = qb.build();
+    assert!(!query.explain(&mut txn).unwrap().buffered);
+    let mut expected = objects.iter().collect::<Vec<_>>();
+    expected.sort_by_key(|o| (o.int, o.byte, o.string.clone()));
+    assert_eq!(query.find_ids(&mut txn).unwrap(), ids(&expected));
+    assert_eq!(spill_files(&dir), 0);
+
+    txn.abort();
+    assert!(isar.close());
+}
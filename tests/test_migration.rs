@@ -0,0 +1,114 @@
+use isar_core::config::IsarConfig;
+use isar_core::instance::IsarInstance;
+use isar_core::object::data_type::DataType;
+use isar_core::schema::collection_schema::CollectionSchema;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::schema::property_schema::PropertySchema;
+use isar_core::schema::Schema;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+
+fn open(
+    dir: &Path,
+    col: CollectionSchema,
+    version: u64,
+) -> isar_core::error::Result<Arc<IsarInstance>> {
+    let schema = Schema::new(vec![col]).unwrap().with_version(version);
+    let config = IsarConfig {
+        checksums: true,
+        ..Default::default()
+    };
+    IsarInstance::open_with_config("migration", dir, schema, config)
+}
+
+fn export(isar: &IsarInstance) -> Value {
+    let col = &isar.collections[0];
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    let q = col.new_query_builder().build();
+    let json = q
+        .export_json(&mut txn, col, Some("id"), true, false)
+        .unwrap();
+    txn.abort();
+    json
+}
+
+fn index(property: &str) -> IndexSchema {
+    IndexSchema::new(
+        "age",
+        vec![IndexPropertySchema::new(property, IndexType::Value, false)],
+        false,
+    )
+}
+
+#[test]
+fn test_migration() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+
+    let properties = vec![
+        PropertySchema::new("age", DataType::Int),
+        PropertySchema::new("name", DataType::String),
+    ];
+    let users = CollectionSchema::new("users", properties, vec![index("age")], vec![]);
+    let isar = open(&dir, users, 1).unwrap();
+    let col = &isar.collections[0];
+    let mut txn = isar.begin_txn(true, false).unwrap();
+    let json = json!([
+        {"id": 1, "age": 30, "name": "a"},
+        {"id": 2, "age": null, "name": "b"},
+    ]);
+    col.import_json(&mut txn, Some("id"), json, false).unwrap();
+    txn.commit().unwrap();
+    assert!(isar.close());
+
+    let properties = vec![
+        PropertySchema::new("years", DataType::Long).renamed_from("age"),
+        PropertySchema::new("name", DataType::String),
+        PropertySchema::new("score", DataType::Int).with_default(json!(10)),
+    ];
+    let people = CollectionSchema::new("people", properties, vec![index("years")], vec![])
+        .renamed_from("users");
+    let isar = open(&dir, people.clone(), 2).unwrap();
+    assert!(isar.collection_by_name("users").is_none());
+    assert_eq!(
+        export(&isar),
+        json!([
+            {"id": 1, "years": 30, "name": "a", "score": 10},
+            {"id": 2, "years": null, "name": "b", "score": 10},
+        ])
+    );
+    assert!(isar.verify().is_ok());
+
+    let col = &isar.collections[0];
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    let mut key = col.new_index_key(0).unwrap();
+    key.add_long(30).unwrap();
+    let key = key.finish();
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_eq!(qb.build().find_ids(&mut txn).unwrap(), vec![1]);
+    txn.abort();
+    assert!(isar.close());
+
+    assert!(open(&dir, people.clone(), 1).is_err());
+
+    let isar = open(&dir, people, 0).unwrap();
+    assert_eq!(export(&isar).as_array().unwrap().len(), 2);
+    assert!(isar.close());
+}
+
+#[test]
+fn test_invalid_migration_hints() {
+    let properties = vec![PropertySchema::new("age", DataType::Int).with_default(json!("old"))];
+    let col = CollectionSchema::new("col", properties, vec![], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+
+    let properties = vec![
+        PropertySchema::new("age", DataType::Int),
+        PropertySchema::new("years", DataType::Int).renamed_from("age"),
+    ];
+    let col = CollectionSchema::new("col", properties, vec![], vec![]);
+    assert!(Schema::validate(&[col]).is_err());
+}
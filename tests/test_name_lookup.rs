@@ -0,0 +1,22 @@
+use crate::common::test_obj::TestObj;
+use isar_core::object::data_type::DataType;
+
+mod common;
+
+#[test]
+fn test_name_lookup() {
+    let col1_schema = TestObj::schema("col1", &[], &[]);
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+
+    assert_eq!(isar.collection_by_name("col1").unwrap().name, col1.name);
+    assert_eq!(isar.collection_by_name("col2").unwrap().name, col2.name);
+    assert!(isar.collection_by_name("col3").is_none());
+
+    let int = col1.property_by_name("int").unwrap();
+    assert_eq!(int, TestObj::get_prop(col1, DataType::Int));
+    assert_eq!(col2.property_by_name("int"), Some(int));
+    assert!(col1.property_by_name("missing").is_none());
+
+    isar.close();
+}
@@ -0,0 +1,64 @@
+use isar_core::config::IsarConfig;
+use isar_core::instance::IsarInstance;
+use isar_core::object::data_type::DataType;
+use isar_core::query::filter::Filter;
+use isar_core::query::Sort;
+use isar_core::schema::Schema;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_property_stats() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let config = IsarConfig {
+        property_stats: true,
+        ..Default::default()
+    };
+    let isar = IsarInstance::open_with_config("stats", &dir, schema, config).unwrap();
+    let col = isar.collections.first().unwrap();
+    let int = TestObj::get_prop(col, DataType::Int);
+    let string = TestObj::get_prop(col, DataType::String);
+
+    let mut qb = col.new_query_builder();
+    let filter = Filter::and(vec![
+        Filter::int(int, 1, 5).unwrap(),
+        Filter::not(Filter::int(int, 3, 3).unwrap()),
+        Filter::null(string),
+    ]);
+    qb.set_filter(filter);
+    qb.add_sort(int, Sort::Ascending).unwrap();
+    qb.build();
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(string, Sort::Descending).unwrap();
+    qb.build();
+
+    let stats = col.property_stats().unwrap();
+    let get = |name: &str| stats.iter().find(|(n, _)| n == name).unwrap().1;
+    assert_eq!(get("int").filter_count, 1);
+    assert_eq!(get("int").sort_count, 1);
+    assert_eq!(get("string").filter_count, 1);
+    assert_eq!(get("string").sort_count, 1);
+    assert_eq!(get("long").filter_count, 0);
+    assert_eq!(get("long").sort_count, 0);
+
+    col.reset_property_stats();
+    assert!(col
+        .property_stats()
+        .unwrap()
+        .iter()
+        .all(|(_, s)| s.filter_count == 0 && s.sort_count == 0));
+    assert!(isar.close());
+}
+
+#[test]
+fn test_property_stats_disabled() {
+    isar!(isar, col => TestObj::default_schema());
+    col.new_query_builder().build();
+    assert!(col.property_stats().is_none());
+    assert!(isar.close());
+}
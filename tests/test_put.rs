@@ -1,4 +1,10 @@
 use crate::common::test_obj::TestObj;
+use isar_core::collection::OnConflict;
+use isar_core::error::IsarError;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::txn::IsarTxn;
+use isar_core::verify::ObjectEntry;
 
 mod common;
 
@@ -123,6 +129,215 @@ fn test_put_with_list() {
     isar.close();
 }
 
+#[test]
+fn test_change_token() {
+    let mut dir = std::env::temp_dir();
+    let r: u64 = rand::random();
+    dir.push(&r.to_string());
+    let path = dir.to_str().unwrap();
+
+    isar!(path, isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    assert_eq!(col.change_token(&mut txn).unwrap().counter, 0);
+    put!(col, txn, id, obj1 => 1);
+    put!(col, txn, id, obj2 => 2);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    let token = col.change_token(&mut txn).unwrap();
+    assert_eq!(token.counter, 1);
+    assert!(token.last_modified > 0);
+
+    // aborted and read-only transactions do not change the token
+    col.delete(&mut txn, 1).unwrap();
+    txn.abort();
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    let _ = col.get(&mut txn, 1).unwrap();
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    assert_eq!(col.change_token(&mut txn).unwrap(), token);
+    col.clear(&mut txn).unwrap();
+    txn.commit().unwrap();
+    isar.close();
+
+    // the token is persisted
+    isar!(path, isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    assert_eq!(col.change_token(&mut txn).unwrap().counter, 2);
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_on_conflict() {
+    let int_index = IndexSchema::new(
+        "int",
+        vec![IndexPropertySchema::new("int", IndexType::Value, false)],
+        true,
+    );
+    let string_index = IndexSchema::new(
+        "string",
+        vec![IndexPropertySchema::new("string", IndexType::Value, true)],
+        true,
+    );
+    isar!(isar, col => TestObj::schema("obj", &[int_index, string_index], &[]));
+    let obj = |id, int, string: &str| {
+        let mut obj = TestObj::default(id);
+        obj.int = int;
+        obj.string = Some(string.to_string());
+        obj
+    };
+    let put = |txn: &mut IsarTxn, id, obj: &TestObj, on_conflict| {
+        let bytes = obj.to_bytes(col);
+        col.put(txn, id, IsarObject::from_bytes(&bytes), on_conflict)
+    };
+
+    txn!(isar, txn);
+    let obj1 = obj(1, 1, "a");
+    let obj2 = obj(2, 2, "b");
+    obj1.save(&mut txn, col);
+    obj2.save(&mut txn, col);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    let result = put(&mut txn, Some(3), &obj(3, 1, "c"), OnConflict::Abort);
+    assert_eq!(result, Err(IsarError::UniqueViolated { id: 1 }));
+    txn.abort();
+
+    txn!(isar, txn);
+    let result = put(&mut txn, Some(3), &obj(3, 1, "c"), OnConflict::Ignore);
+    assert_eq!(result, Ok(1));
+    verify!(txn, col, obj1, obj2);
+
+    // The existing object is updated and keeps its id.
+    let obj1 = obj(1, 1, "c");
+    assert_eq!(put(&mut txn, None, &obj1, OnConflict::Update), Ok(1));
+    verify!(txn, col, obj1, obj2);
+
+    let obj3 = obj(3, 2, "d");
+    assert_eq!(put(&mut txn, Some(3), &obj3, OnConflict::Replace), Ok(3));
+    verify!(txn, col, obj1, obj3);
+
+    // The object cannot be merged into two objects.
+    let result = put(&mut txn, None, &obj(1, 1, "d"), OnConflict::Update);
+    assert_eq!(result, Err(IsarError::UniqueViolated { id: 3 }));
+    txn.abort();
+
+    isar.close();
+}
+
+#[test]
+fn test_put_all() {
+    let int_index = IndexSchema::new(
+        "int",
+        vec![IndexPropertySchema::new("int", IndexType::Value, false)],
+        false,
+    );
+    let string_index = IndexSchema::new(
+        "string",
+        vec![IndexPropertySchema::new("string", IndexType::Value, true)],
+        true,
+    );
+    isar!(isar, col => TestObj::schema("obj", &[int_index, string_index], &[]));
+    let obj = |id, int, string: &str| {
+        let mut obj = TestObj::default(id);
+        obj.int = int;
+        obj.string = Some(string.to_string());
+        obj
+    };
+
+    txn!(isar, txn);
+    let obj1 = obj(1, 1, "a");
+    let obj2 = obj(2, 2, "b");
+    obj1.save(&mut txn, col);
+    obj2.save(&mut txn, col);
+
+    let obj2 = obj(2, 3, "c");
+    let obj5 = obj(5, 5, "e");
+    let obj9 = obj(9, 1, "f");
+    let obj10 = obj(10, 7, "g");
+    // Replaces obj(4, 4, "d") because it is written after it.
+    let obj8 = obj(8, 8, "d");
+    let objects = vec![
+        (Some(8), obj8.to_bytes(col)),
+        (None, obj9.to_bytes(col)),
+        (Some(5), obj(5, 4, "x").to_bytes(col)),
+        (Some(2), obj2.to_bytes(col)),
+        (Some(4), obj(4, 4, "d").to_bytes(col)),
+        (None, obj10.to_bytes(col)),
+        (Some(5), obj5.to_bytes(col)),
+    ];
+    let objects: Vec<_> = objects
+        .iter()
+        .map(|(id, bytes)| (*id, IsarObject::from_bytes(bytes)))
+        .collect();
+    let ids = col.put_all(&mut txn, &objects, OnConflict::Replace);
+    assert_eq!(ids, Ok(vec![8, 9, 5, 2, 4, 10, 5]));
+    verify!(txn, col, obj1, obj2, obj5, obj8, obj9, obj10);
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 11);
+
+    let bytes = obj(3, 3, "a").to_bytes(col);
+    let objects = vec![(Some(3), IsarObject::from_bytes(&bytes))];
+    let result = col.put_all(&mut txn, &objects, OnConflict::Abort);
+    assert_eq!(result, Err(IsarError::UniqueViolated { id: 1 }));
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_put_all_sorted() {
+    isar!(isar, col => TestObj::default_schema());
+    let obj = |id: i64| {
+        let mut obj = TestObj::default(id);
+        obj.int = (id % 7) as i32;
+        obj.int_list = Some(vec![(id % 3) as i32, (id % 3) as i32, 5]);
+        obj
+    };
+    let put_all_sorted = |txn: &mut IsarTxn, objects: &[TestObj]| {
+        let bytes: Vec<_> = objects.iter().map(|o| (o.id, o.to_bytes(col))).collect();
+        let objects: Vec<_> = bytes
+            .iter()
+            .map(|(id, bytes)| (*id, IsarObject::from_bytes(bytes)))
+            .collect();
+        col.put_all_sorted(txn, &objects)
+    };
+
+    txn!(isar, txn);
+    // The ids are greater than 255 so their bytes are not in the order of the ids.
+    let objects: Vec<_> = (1..=300).map(obj).collect();
+    put_all_sorted(&mut txn, &objects).unwrap();
+    let more_objects: Vec<_> = (301..=320).map(obj).collect();
+    put_all_sorted(&mut txn, &more_objects).unwrap();
+    assert_eq!(col.auto_increment(&mut txn).unwrap(), 321);
+
+    let entries = objects
+        .iter()
+        .chain(&more_objects)
+        .map(|o| ObjectEntry::new(o.id, o.to_bytes(col)))
+        .collect();
+    verify!(col txn, (col, entries, vec![]));
+    txn.commit().unwrap();
+
+    let err = IsarError::IllegalArg {
+        message: "Ids have to be ascending and greater than the ids of existing objects."
+            .to_string(),
+    };
+    txn!(isar, txn);
+    assert_eq!(
+        put_all_sorted(&mut txn, &[obj(400), obj(399)]),
+        Err(err.clone())
+    );
+    txn.abort();
+
+    txn!(isar, txn);
+    assert_eq!(put_all_sorted(&mut txn, &[obj(320)]), Err(err));
+    txn.abort();
+
+    isar.close();
+}
+
 /*#[test]
 fn test_put_calls_notifiers() {
     isar!(isar, col =>TestObj::default_schema());
@@ -0,0 +1,119 @@
+use crate::common::test_obj::TestObj;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::query::aggregation::{AggregationOp, AggregationResult};
+use isar_core::query::filter::Filter;
+use isar_core::schema::link_schema::LinkSchema;
+
+mod common;
+
+#[test]
+fn test_aggregate_int() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, int, _obj1 => 3, _obj2 => IsarObject::NULL_INT, _obj3 => -1, _obj4 => 10);
+    let int = TestObj::get_prop(col, DataType::Int);
+    let q = col.new_query_builder().build();
+
+    assert_eq!(q.min(&mut txn, int).unwrap(), AggregationResult::Long(-1));
+    assert_eq!(q.max(&mut txn, int).unwrap(), AggregationResult::Long(10));
+    assert_eq!(q.sum(&mut txn, int).unwrap(), AggregationResult::Long(12));
+    assert_eq!(
+        q.average(&mut txn, int).unwrap(),
+        AggregationResult::Double(4.0)
+    );
+    assert_eq!(q.count(&mut txn).unwrap(), 4);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::int(int, 100, 200).unwrap());
+    let q = qb.build();
+    assert_eq!(q.min(&mut txn, int).unwrap(), AggregationResult::Null);
+    assert_eq!(q.average(&mut txn, int).unwrap(), AggregationResult::Null);
+    assert_eq!(q.sum(&mut txn, int).unwrap(), AggregationResult::Long(0));
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_aggregate_double() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, double, _obj1 => 1.5, _obj2 => f64::NAN, _obj3 => -2.5);
+    let double = TestObj::get_prop(col, DataType::Double);
+    let q = col.new_query_builder().build();
+
+    assert_eq!(
+        q.min(&mut txn, double).unwrap(),
+        AggregationResult::Double(-2.5)
+    );
+    assert_eq!(
+        q.max(&mut txn, double).unwrap(),
+        AggregationResult::Double(1.5)
+    );
+    assert_eq!(
+        q.sum(&mut txn, double).unwrap(),
+        AggregationResult::Double(-1.0)
+    );
+    assert_eq!(
+        q.average(&mut txn, double).unwrap(),
+        AggregationResult::Double(-0.5)
+    );
+
+    let string = TestObj::get_prop(col, DataType::String);
+    assert!(q.sum(&mut txn, string).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_aggregate_linked() {
+    let parent_schema = TestObj::schema("parent", &[], &[LinkSchema::new("children", "child")]);
+    isar!(isar, parent => parent_schema, child => TestObj::schema("child", &[], &[]));
+    txn!(isar, txn);
+
+    put!(id: parent, txn, p1 => 1, p2 => 2, p3 => 3);
+    for (id, int) in &[(10, 5), (11, 7), (12, IsarObject::NULL_INT), (13, 4)] {
+        let mut obj = TestObj::default(*id);
+        obj.int = *int;
+        obj.save(&mut txn, child);
+    }
+    for (id, target_id) in &[(p1.id, 10), (p1.id, 11), (p2.id, 12), (p3.id, 13)] {
+        parent.link(&mut txn, 0, *id, *target_id).unwrap();
+    }
+
+    let int = TestObj::get_prop(child, DataType::Int);
+    let mut qb = parent.new_query_builder();
+    qb.add_id_where_clause(1, 2).unwrap();
+    let q = qb.build();
+    assert_eq!(
+        q.aggregate_linked(&mut txn, parent, 0, AggregationOp::Sum, int)
+            .unwrap(),
+        vec![
+            (1, AggregationResult::Long(12)),
+            (2, AggregationResult::Long(0))
+        ]
+    );
+    assert_eq!(
+        q.aggregate_linked(&mut txn, parent, 0, AggregationOp::Average, int)
+            .unwrap(),
+        vec![
+            (1, AggregationResult::Double(6.0)),
+            (2, AggregationResult::Null)
+        ]
+    );
+
+    let string = TestObj::get_prop(child, DataType::String);
+    assert!(q
+        .aggregate_linked(&mut txn, parent, 0, AggregationOp::Sum, string)
+        .is_err());
+    assert!(q
+        .aggregate_linked(&mut txn, parent, 1, AggregationOp::Sum, int)
+        .is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -2,8 +2,10 @@ use std::vec;
 
 use isar_core::collection::IsarCollection;
 use isar_core::object::data_type::DataType;
-use isar_core::query::filter::Filter;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::query::filter::{Filter, FilterValue};
 use isar_core::txn::IsarTxn;
+use itertools::Itertools;
 
 use crate::common::test_obj::TestObj;
 
@@ -335,6 +337,238 @@ fn test_string_matches_filter() {
     isar.close();
 }
 
+#[test]
+fn test_byte_list_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::ByteList);
+
+    put!(col, txn, byte_list,
+        obj1 => None,
+        obj2 => Some(vec![]),
+        obj3 => Some(vec![1, 2]),
+        obj4 => Some(vec![1, 2, 3])
+    );
+
+    let equals_result = vec![
+        (vec![], vec![&obj2]),
+        (vec![1], vec![]),
+        (vec![1, 2], vec![&obj3]),
+        (vec![1, 2, 3], vec![&obj4]),
+    ];
+    for (value, objects) in equals_result {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::byte_list_equals(p, &value).unwrap(),
+            &objects,
+        );
+    }
+
+    let starts_with_result = vec![
+        (vec![], vec![&obj2, &obj3, &obj4]),
+        (vec![1], vec![&obj3, &obj4]),
+        (vec![1, 2, 3], vec![&obj4]),
+        (vec![2], vec![]),
+    ];
+    for (value, objects) in starts_with_result {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::byte_list_starts_with(p, &value).unwrap(),
+            &objects,
+        );
+    }
+
+    let string = TestObj::get_prop(col, DataType::String);
+    assert!(Filter::byte_list_equals(string, &[]).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_is_empty_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let string = TestObj::get_prop(col, DataType::String);
+    let string_list = TestObj::get_prop(col, DataType::StringList);
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+
+    let mut obj1 = TestObj::default(1);
+    obj1.string = Some("".to_string());
+    obj1.int_list = Some(vec![]);
+    obj1.string_list = Some(vec![Some("a".to_string()), Some("".to_string())]);
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.string = Some("a".to_string());
+    obj2.int_list = Some(vec![1]);
+    obj2.string_list = Some(vec![]);
+    obj2.save(&mut txn, col);
+
+    let obj3 = TestObj::default(3);
+    obj3.save(&mut txn, col);
+
+    let results = vec![
+        (Filter::string_is_empty(string), vec![&obj1]),
+        (Filter::string_is_empty(string_list), vec![&obj1]),
+        (Filter::list_is_empty(int_list), vec![&obj1]),
+        (Filter::list_is_empty(string_list), vec![&obj2]),
+    ];
+    for (filter, objects) in results {
+        expect_filter(&mut txn, col, filter.unwrap(), &objects);
+    }
+
+    assert!(Filter::string_is_empty(int_list).is_err());
+    assert!(Filter::list_is_empty(string).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_string_length_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::String);
+
+    put!(col, txn, string,
+        obj1 => None,
+        obj2 => Some("".to_string()),
+        obj3 => Some("ab".to_string()),
+        obj4 => Some("abcd".to_string()),
+        obj5 => Some("äb".to_string())
+    );
+
+    let results = vec![
+        (0, 0, vec![&obj2]),
+        (1, 2, vec![&obj3]),
+        (2, 3, vec![&obj3, &obj5]),
+        (3, 1, vec![]),
+        (0, usize::MAX, vec![&obj2, &obj3, &obj4, &obj5]),
+    ];
+    for (lower, upper, objects) in results {
+        expect_filter(
+            &mut txn,
+            col,
+            Filter::string_length_between(p, lower, upper).unwrap(),
+            &objects,
+        );
+    }
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    assert!(Filter::string_length_between(int, 0, 1).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_all_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+    let double_list = TestObj::get_prop(col, DataType::DoubleList);
+    let string_list = TestObj::get_prop(col, DataType::StringList);
+
+    let obj1 = TestObj::default(1);
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.int_list = Some(vec![]);
+    obj2.double_list = Some(vec![]);
+    obj2.string_list = Some(vec![]);
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.int_list = Some(vec![1, 2, 3]);
+    obj3.double_list = Some(vec![1.0, 2.0]);
+    obj3.string_list = Some(vec![Some("a".to_string()), Some("b".to_string())]);
+    obj3.save(&mut txn, col);
+
+    let mut obj4 = TestObj::default(4);
+    obj4.int_list = Some(vec![2, 5]);
+    obj4.double_list = Some(vec![2.0, 5.0]);
+    obj4.string_list = Some(vec![Some("b".to_string()), None]);
+    obj4.save(&mut txn, col);
+
+    let results = vec![
+        (Filter::all_int_between(int_list, 1, 3), vec![&obj2, &obj3]),
+        (Filter::all_int_between(int_list, 2, 5), vec![&obj2, &obj4]),
+        (Filter::all_int_between(int_list, 6, 9), vec![&obj2]),
+        (
+            Filter::all_double_between(double_list, 0.5, 2.5),
+            vec![&obj2, &obj3],
+        ),
+        (
+            Filter::all_string_between(string_list, Some("a"), Some("b"), true),
+            vec![&obj2, &obj3],
+        ),
+        (
+            Filter::all_string_between(string_list, None, Some("b"), true),
+            vec![&obj2, &obj3, &obj4],
+        ),
+    ];
+    for (filter, objects) in results {
+        expect_filter(&mut txn, col, filter.unwrap(), &objects);
+    }
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    assert!(Filter::all_int_between(int, 0, 1).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_null_element_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+    let string_list = TestObj::get_prop(col, DataType::StringList);
+
+    let obj1 = TestObj::default(1);
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.int_list = Some(vec![1, IsarObject::NULL_INT]);
+    obj2.string_list = Some(vec![Some("a".to_string()), None]);
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.int_list = Some(vec![1, 2]);
+    obj3.string_list = Some(vec![Some("a".to_string())]);
+    obj3.save(&mut txn, col);
+
+    let mut obj4 = TestObj::default(4);
+    obj4.int_list = Some(vec![]);
+    obj4.string_list = Some(vec![]);
+    obj4.save(&mut txn, col);
+
+    let results = vec![
+        (Filter::any_null_element(int_list), vec![&obj2]),
+        (Filter::any_null_element(string_list), vec![&obj2]),
+        (Filter::no_null_elements(int_list), vec![&obj3, &obj4]),
+        (Filter::no_null_elements(string_list), vec![&obj3, &obj4]),
+    ];
+    for (filter, objects) in results {
+        expect_filter(&mut txn, col, filter.unwrap(), &objects);
+    }
+
+    let string = TestObj::get_prop(col, DataType::String);
+    assert!(Filter::any_null_element(string).is_err());
+    assert!(Filter::no_null_elements(string).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
 #[test]
 fn test_and_filter() {
     isar!(isar, col =>TestObj::default_schema());
@@ -434,3 +668,192 @@ fn test_static_filter() {
     txn.abort();
     isar.close();
 }
+
+#[test]
+fn test_comparison_filter() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::Int);
+
+    put!(col, txn, int,
+        obj1 => IsarObject::NULL_INT,
+        obj2 => -5,
+        obj3 => 0,
+        obj4 => i32::MAX
+    );
+
+    let results = vec![
+        (Filter::eq(p, FilterValue::Long(0)), vec![&obj3]),
+        (Filter::eq(p, FilterValue::Long(i64::MAX)), vec![]),
+        (Filter::gt(p, FilterValue::Long(-5)), vec![&obj3, &obj4]),
+        (Filter::gt(p, FilterValue::Long(i32::MAX as i64)), vec![]),
+        (Filter::gt(p, FilterValue::Long(-1)), vec![&obj3, &obj4]),
+        (
+            Filter::gte(p, FilterValue::Long(-5)),
+            vec![&obj2, &obj3, &obj4],
+        ),
+        (Filter::lt(p, FilterValue::Long(0)), vec![&obj1, &obj2]),
+        (Filter::lt(p, FilterValue::Long(i64::MIN)), vec![]),
+        (
+            Filter::lte(p, FilterValue::Long(0)),
+            vec![&obj1, &obj2, &obj3],
+        ),
+        (
+            Filter::lte(p, FilterValue::Long(i64::MAX)),
+            vec![&obj1, &obj2, &obj3, &obj4],
+        ),
+    ];
+    for (filter, objects) in results {
+        expect_filter(&mut txn, col, filter.unwrap(), &objects);
+    }
+
+    expect_filter(
+        &mut txn,
+        col,
+        Filter::is_not_null(p),
+        &[&obj2, &obj3, &obj4],
+    );
+    assert!(Filter::eq(p, FilterValue::Double(1.0)).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_comparison_filter_double() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::Double);
+
+    put!(col, txn, double,
+        obj1 => f64::NAN,
+        obj2 => f64::NEG_INFINITY,
+        obj3 => 1.5,
+        obj4 => f64::MAX,
+        obj5 => f64::INFINITY
+    );
+
+    let results = vec![
+        (Filter::eq(p, FilterValue::Double(f64::NAN)), vec![&obj1]),
+        (Filter::eq(p, FilterValue::Double(1.5)), vec![&obj3]),
+        (Filter::eq(p, FilterValue::Long(1)), vec![]),
+        (
+            Filter::eq(p, FilterValue::Double(f64::NEG_INFINITY)),
+            vec![&obj2],
+        ),
+        (Filter::eq(p, FilterValue::Double(f64::MAX)), vec![&obj4]),
+        (
+            Filter::eq(p, FilterValue::Double(f64::INFINITY)),
+            vec![&obj5],
+        ),
+        (
+            Filter::gt(p, FilterValue::Double(f64::NAN)),
+            vec![&obj2, &obj3, &obj4, &obj5],
+        ),
+        (Filter::gt(p, FilterValue::Double(1.5)), vec![&obj4, &obj5]),
+        (
+            Filter::gte(p, FilterValue::Double(1.5)),
+            vec![&obj3, &obj4, &obj5],
+        ),
+        (
+            Filter::gte(p, FilterValue::Double(f64::NEG_INFINITY)),
+            vec![&obj2, &obj3, &obj4, &obj5],
+        ),
+        (Filter::lt(p, FilterValue::Double(1.5)), vec![&obj1, &obj2]),
+        (
+            Filter::lt(p, FilterValue::Double(f64::INFINITY)),
+            vec![&obj1, &obj2, &obj3, &obj4],
+        ),
+        (
+            Filter::lte(p, FilterValue::Double(1.5)),
+            vec![&obj1, &obj2, &obj3],
+        ),
+        (
+            Filter::lte(p, FilterValue::Double(f64::MAX)),
+            vec![&obj1, &obj2, &obj3, &obj4],
+        ),
+    ];
+    for (filter, objects) in results {
+        let mut qb = col.new_query_builder();
+        qb.set_filter(filter.unwrap());
+        let ids = qb.build().find_all_vec(&mut txn).unwrap();
+        let ids = ids.iter().map(|(id, _)| *id).collect_vec();
+        let expected = objects.iter().map(|o: &&TestObj| o.id).collect_vec();
+        assert_eq!(ids, expected);
+    }
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_comparison_filter_list() {
+    isar!(isar, col =>TestObj::default_schema());
+    txn!(isar, txn);
+
+    let p = TestObj::get_prop(col, DataType::DoubleList);
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+
+    let obj1 = TestObj::default(1);
+    obj1.save(&mut txn, col);
+
+    let mut obj2 = TestObj::default(2);
+    obj2.int_list = Some(vec![]);
+    obj2.double_list = Some(vec![]);
+    obj2.save(&mut txn, col);
+
+    let mut obj3 = TestObj::default(3);
+    obj3.int_list = Some(vec![IsarObject::NULL_INT, 1]);
+    obj3.double_list = Some(vec![f64::NAN, 1.5]);
+    obj3.save(&mut txn, col);
+
+    let mut obj4 = TestObj::default(4);
+    obj4.double_list = Some(vec![f64::INFINITY]);
+    obj4.save(&mut txn, col);
+
+    let mut obj5 = TestObj::default(5);
+    obj5.double_list = Some(vec![f64::NEG_INFINITY, f64::MAX]);
+    obj5.save(&mut txn, col);
+
+    // Null and empty lists never match a comparison.
+    let results = vec![
+        (Filter::eq(p, FilterValue::Double(f64::NAN)), vec![3]),
+        (Filter::eq(p, FilterValue::Double(1.5)), vec![3]),
+        (Filter::eq(p, FilterValue::Double(f64::INFINITY)), vec![4]),
+        (Filter::gt(p, FilterValue::Double(f64::NAN)), vec![3, 4, 5]),
+        (Filter::gt(p, FilterValue::Double(1.5)), vec![4, 5]),
+        (Filter::gte(p, FilterValue::Double(f64::NAN)), vec![3, 4, 5]),
+        (
+            Filter::gte(p, FilterValue::Double(f64::NEG_INFINITY)),
+            vec![3, 4, 5],
+        ),
+        (Filter::lt(p, FilterValue::Double(f64::NAN)), vec![]),
+        (
+            Filter::lt(p, FilterValue::Double(f64::INFINITY)),
+            vec![3, 5],
+        ),
+        (Filter::lte(p, FilterValue::Double(1.5)), vec![3, 5]),
+        (Filter::lte(p, FilterValue::Double(f64::MAX)), vec![3, 5]),
+        (
+            Filter::lte(p, FilterValue::Double(f64::INFINITY)),
+            vec![3, 4, 5],
+        ),
+        (Filter::gte(int_list, FilterValue::Long(i64::MIN)), vec![3]),
+        (Filter::lte(int_list, FilterValue::Long(i64::MAX)), vec![3]),
+        (Filter::lt(int_list, FilterValue::Long(i64::MIN)), vec![]),
+        // Only the list itself is checked, so empty lists match.
+        (Ok(Filter::is_not_null(p)), vec![2, 3, 4, 5]),
+    ];
+    for (filter, expected) in results {
+        let mut qb = col.new_query_builder();
+        qb.set_filter(filter.unwrap());
+        let ids = qb.build().find_all_vec(&mut txn).unwrap();
+        let ids = ids.iter().map(|(id, _)| *id).collect_vec();
+        assert_eq!(ids, expected);
+    }
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,147 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::IsarCollection;
+use isar_core::query::query_builder::QueryBuilder;
+use isar_core::schema::index_schema::{IndexPropertySchema, IndexSchema, IndexType};
+use isar_core::txn::IsarTxn;
+use serde_json::{json, Value};
+
+mod common;
+
+fn find_ids(col: &IsarCollection, txn: &mut IsarTxn, query: Value) -> Vec<i64> {
+    let q = QueryBuilder::from_json(col, &query).unwrap().build();
+    q.find_ids(txn).unwrap()
+}
+
+#[test]
+fn test_query_json() {
+    let index = IndexSchema::new(
+        "int",
+        vec![IndexPropertySchema::new("int", IndexType::Value, false)],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("obj", &[index], &[]));
+    txn!(isar, txn);
+
+    let values = [(1, "a"), (5, "B"), (3, "c"), (5, "d")];
+    for (i, (int, string)) in values.iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.int = *int;
+        obj.string = Some(string.to_string());
+        obj.save(&mut txn, col);
+    }
+
+    assert_eq!(find_ids(col, &mut txn, json!({})), vec![1, 2, 3, 4]);
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({"whereClauses": [{"type": "id", "lower": 2, "upper": 3}]})
+        ),
+        vec![2, 3]
+    );
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({"whereClauses": [{"type": "index", "index": "int", "lower": [5]}]})
+        ),
+        vec![2, 4]
+    );
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({
+                "filter": {"type": "or", "filters": [
+                    {"type": "gte", "property": "int", "value": 5},
+                    {"type": "eq", "property": "string", "value": "A", "caseSensitive": false},
+                ]},
+                "sort": [{"property": "string", "sort": "desc"}],
+            })
+        ),
+        vec![4, 1, 2]
+    );
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({
+                "filter": {"type": "not", "filter": {"type": "between", "property": "int", "lower": 2, "upper": 4}},
+                "distinct": [{"property": "int"}],
+                "offset": 1,
+                "limit": 1,
+            })
+        ),
+        vec![2]
+    );
+
+    let invalid = [
+        json!([]),
+        json!({"filter": {"type": "eq", "property": "unknown", "value": 1}}),
+        json!({"filter": {"type": "gt", "property": "string", "value": "a"}}),
+        json!({"filter": {"type": "startsWith", "property": "int", "value": "a"}}),
+        json!({"whereClauses": [{"type": "index", "index": "unknown"}]}),
+        json!({"whereClauses": [{"type": "index", "index": "int", "lower": ["a"]}]}),
+        json!({"sort": [{"property": "int", "sort": "up"}]}),
+        json!({"limit": -1}),
+        json!({"distinct": [{"property": "int"}], "distinctKeep": "middle"}),
+        json!({"distinctKeep": "last"}),
+    ];
+    for query in &invalid {
+        assert!(QueryBuilder::from_json(col, query).is_err());
+    }
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_query_json_distinct_keep() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let values = [(1, "a"), (2, "A"), (1, "b"), (1, "A"), (2, "a"), (3, "b")];
+    for (i, (int, string)) in values.iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.int = *int;
+        obj.string = Some(string.to_string());
+        obj.save(&mut txn, col);
+    }
+
+    let distinct = json!([{"property": "string", "caseSensitive": false}, {"property": "int"}]);
+    assert_eq!(
+        find_ids(col, &mut txn, json!({"distinct": distinct})),
+        vec![1, 2, 3, 6]
+    );
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({"distinct": distinct, "distinctKeep": "last"})
+        ),
+        vec![3, 4, 5, 6]
+    );
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({
+                "sort": [{"property": "int", "sort": "desc"}],
+                "distinct": [{"property": "string", "caseSensitive": false}],
+                "distinctKeep": "last",
+            })
+        ),
+        vec![3, 4]
+    );
+    assert_eq!(
+        find_ids(
+            col,
+            &mut txn,
+            json!({"distinct": distinct, "distinctKeep": "last", "offset": 1, "limit": 2})
+        ),
+        vec![4, 5]
+    );
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,108 @@
+use crate::common::test_obj::TestObj;
+use crossbeam_channel::unbounded;
+use isar_core::error::IsarError;
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_query_pool() {
+    isar!(isar, col => TestObj::schema("col", &[], &[]));
+    let pool = isar.query_pool(2).unwrap();
+
+    let (tx, rx) = unbounded();
+    for i in 0..3 {
+        let tx = tx.clone();
+        pool.submit(col.new_query_builder().build(), move |query, txn| {
+            let count = query.count(txn.unwrap()).unwrap();
+            tx.send((i, count)).unwrap();
+        })
+        .unwrap();
+    }
+    let mut results: Vec<_> = (0..3).map(|_| rx.recv().unwrap()).collect();
+    results.sort_unstable();
+    assert_eq!(results, vec![(0, 0), (1, 0), (2, 0)]);
+
+    txn!(isar, txn);
+    put!(id: col, txn, _obj1 => 1, _obj2 => 2);
+    txn.commit().unwrap();
+
+    let (tx, rx) = unbounded();
+    for _ in 0..4 {
+        let tx = tx.clone();
+        pool.submit(col.new_query_builder().build(), move |query, txn| {
+            tx.send(query.find_ids(txn.unwrap()).unwrap()).unwrap();
+        })
+        .unwrap();
+    }
+    for _ in 0..4 {
+        assert_eq!(rx.recv().unwrap(), vec![1, 2]);
+    }
+
+    assert!(isar.query_pool(0).is_err());
+    drop(pool);
+    assert!(isar.close());
+}
+
+#[test]
+fn test_query_pool_renews_snapshot() {
+    isar!(isar, col => TestObj::schema("col", &[], &[]));
+    let pool = isar.query_pool(1).unwrap();
+
+    let (started_tx, started_rx) = unbounded();
+    let (gate_tx, gate_rx) = unbounded::<()>();
+    let (tx, rx) = unbounded();
+    let first_tx = tx.clone();
+    pool.submit(col.new_query_builder().build(), move |query, txn| {
+        let txn = txn.unwrap();
+        started_tx.send(()).unwrap();
+        gate_rx.recv().unwrap();
+        first_tx.send(query.find_ids(txn).unwrap()).unwrap();
+    })
+    .unwrap();
+    started_rx.recv().unwrap();
+
+    txn!(isar, txn);
+    put!(id: col, txn, _obj1 => 1);
+    txn.commit().unwrap();
+
+    for _ in 0..2 {
+        let tx = tx.clone();
+        pool.submit(col.new_query_builder().build(), move |query, txn| {
+            tx.send(query.find_ids(txn.unwrap()).unwrap()).unwrap();
+        })
+        .unwrap();
+    }
+    gate_tx.send(()).unwrap();
+
+    assert_eq!(rx.recv().unwrap(), Vec::<i64>::new());
+    assert_eq!(rx.recv().unwrap(), vec![1]);
+    assert_eq!(rx.recv().unwrap(), vec![1]);
+
+    drop(pool);
+    assert!(isar.close());
+}
+
+#[test]
+fn test_query_pool_stopped_worker() {
+    isar!(isar, col => TestObj::schema("col", &[], &[]));
+    let pool = isar.query_pool(1).unwrap();
+
+    pool.submit(col.new_query_builder().build(), |_, _| {
+        panic!("worker failed");
+    })
+    .unwrap();
+    let mut result = Ok(());
+    for _ in 0..100 {
+        result = pool.submit(col.new_query_builder().build(), |_, _| {});
+        if result.is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(result, Err(IsarError::WorkerStopped {}));
+
+    drop(pool);
+    assert!(isar.close());
+}
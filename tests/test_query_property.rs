@@ -0,0 +1,68 @@
+use crate::common::test_obj::TestObj;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::{IsarObject, PropertyValue};
+
+mod common;
+
+#[test]
+fn test_find_property_while() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    let values = [
+        (5, Some("b")),
+        (IsarObject::NULL_INT, Some("a")),
+        (3, None),
+        (5, Some("b")),
+    ];
+    for (i, (int, string)) in values.iter().enumerate() {
+        let mut obj = TestObj::default(i as i64 + 1);
+        obj.int = *int;
+        obj.string = string.map(|s| s.to_string());
+        obj.save(&mut txn, col);
+    }
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    let q = col.new_query_builder().build();
+    let mut ints = vec![];
+    q.find_property_while(&mut txn, int, |id, value| {
+        ints.push((id, value));
+        true
+    })
+    .unwrap();
+    assert_eq!(
+        ints,
+        vec![
+            (1, PropertyValue::Int(5)),
+            (2, PropertyValue::Int(IsarObject::NULL_INT)),
+            (3, PropertyValue::Int(3)),
+            (4, PropertyValue::Int(5)),
+        ]
+    );
+
+    let string = TestObj::get_prop(col, DataType::String);
+    let mut qb = col.new_query_builder();
+    qb.add_distinct(string, true);
+    let mut strings = vec![];
+    qb.build()
+        .find_property_while(&mut txn, string, |_, value| {
+            strings.push(value);
+            strings.len() < 2
+        })
+        .unwrap();
+    assert_eq!(
+        strings,
+        vec![
+            PropertyValue::String(Some("b")),
+            PropertyValue::String(Some("a"))
+        ]
+    );
+
+    let int_list = TestObj::get_prop(col, DataType::IntList);
+    assert!(q
+        .find_property_while(&mut txn, int_list, |_, _| true)
+        .is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,60 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::IsarCollection;
+use isar_core::object::data_type::DataType;
+use isar_core::query::filter::Filter;
+use isar_core::query::multi_query::MultiQuery;
+
+mod common;
+
+#[test]
+fn test_query_union() {
+    isar!(isar, col1 => TestObj::schema("col1", &[], &[]), col2 => TestObj::schema("col2", &[], &[]));
+    txn!(isar, txn);
+
+    put!(col1, txn, int, _obj1 => 1, obj2 => 2, obj3 => 3);
+    put!(col2, txn, int, _obj4 => 2, obj5 => 3);
+
+    let query = |col: &IsarCollection, lower| {
+        let mut qb = col.new_query_builder();
+        let int = TestObj::get_prop(col, DataType::Int);
+        qb.set_filter(Filter::int(int, lower, i32::MAX).unwrap());
+        qb.build()
+    };
+    let union = MultiQuery::union(vec![
+        (col1, query(col1, 2)),
+        (col2, query(col2, 3)),
+        (col1, query(col1, 3)),
+    ])
+    .unwrap();
+    assert!(union
+        .get_shared_properties()
+        .contains(&("int".to_string(), DataType::Int)));
+
+    let mut results = vec![];
+    union
+        .find_while(&mut txn, |col_id, id, _| {
+            results.push((col_id, id));
+            true
+        })
+        .unwrap();
+    let (id1, id2) = (col1.get_collection_id(), col2.get_collection_id());
+    assert_eq!(
+        results,
+        vec![(id1, obj2.id), (id1, obj3.id), (id2, obj5.id)]
+    );
+    assert_eq!(union.count(&mut txn).unwrap(), 3);
+
+    let mut results = vec![];
+    union
+        .find_while(&mut txn, |_, id, _| {
+            results.push(id);
+            results.len() < 2
+        })
+        .unwrap();
+    assert_eq!(results, vec![obj2.id, obj3.id]);
+
+    assert!(MultiQuery::union(vec![]).is_err());
+
+    txn.abort();
+    isar.close();
+}
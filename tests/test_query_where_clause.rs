@@ -2,8 +2,19 @@ mod common;
 
 use std::vec;
 
+use isar_core::collation::Collation;
 use isar_core::index::index_key::IndexKey;
+use isar_core::object::data_type::DataType;
+use isar_core::object::isar_object::Property;
+use isar_core::query::filter::Filter;
+use isar_core::query::Sort;
+use isar_core::schema::index_schema::{
+    IndexPropertySchema, IndexSchema, IndexType, StringSplitter,
+};
+use isar_core::schema::Schema;
+use isar_core::schema::link_schema::LinkSchema;
 use itertools::Itertools;
+use serde_json::Value;
 
 use crate::common::test_obj::TestObj;
 use crate::common::util::assert_find;
@@ -94,3 +105,868 @@ fn test_single_index_where_clause() {
     txn.abort();
     isar.close();
 }
+
+#[test]
+fn test_query_stats() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj0 => 0, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+
+    let qb = col.new_query_builder();
+    let stats = qb.build().find_while_with_stats(&mut txn, |_, _| true).unwrap();
+    assert_eq!(stats.scanned, 5);
+    assert_eq!(stats.index_entries, 0);
+    assert_eq!(stats.filter_evaluations, 0);
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(1);
+    let mut upper = IndexKey::new();
+    upper.add_byte(3);
+    let byte = TestObj::get_prop(col, DataType::Byte);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    qb.set_filter(Filter::not(Filter::byte(byte, 2, 2).unwrap()));
+    let query = qb.build();
+
+    let mut ids = vec![];
+    let stats = query
+        .find_while_with_stats(&mut txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    assert_eq!(ids, vec![obj1.id, obj3.id]);
+    assert_eq!(stats.scanned, 3);
+    assert_eq!(stats.index_entries, 3);
+    assert_eq!(stats.filter_evaluations, 3);
+
+    let (count, stats) = query
+        .delete_while_with_stats(&mut txn, col, |id, _| id == obj1.id)
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(stats.scanned, 3);
+    assert_find(&mut txn, &col, query.clone(), &[&obj3]);
+
+    let count = query.delete_while(&mut txn, col, |_, _| true).unwrap();
+    assert_eq!(count, 1);
+    assert_find(&mut txn, &col, query, &[]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_offset_limit_pushdown() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    for id in 0..10 {
+        let mut obj = TestObj::default(id);
+        obj.byte = id as u8;
+        obj.save(&mut txn, col);
+    }
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(0);
+    let mut upper = IndexKey::new();
+    upper.add_byte(9);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    qb.set_offset(7);
+    qb.set_limit(2);
+    let mut ids = vec![];
+    let stats = qb
+        .build()
+        .find_while_with_stats(&mut txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    assert_eq!(ids, vec![7, 8]);
+    assert_eq!(stats.scanned, 3);
+
+    // The offset continues in the next where clause.
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(0, 2).unwrap();
+    qb.add_id_where_clause(5, 9).unwrap();
+    qb.set_offset(4);
+    let stats = qb
+        .build()
+        .find_while_with_stats(&mut txn, |_, _| true)
+        .unwrap();
+    assert_eq!(stats.scanned, 4);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(0, 2).unwrap();
+    qb.add_id_where_clause(5, 9).unwrap();
+    qb.set_offset(4);
+    qb.set_limit(3);
+    assert_eq!(qb.build().find_ids(&mut txn).unwrap(), vec![6, 7, 8]);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 2, 9).unwrap());
+    qb.set_offset(3);
+    qb.set_limit(2);
+    let query = qb.build();
+    let stats = query.find_while_with_stats(&mut txn, |_, _| true).unwrap();
+    assert_eq!(stats.scanned, 8);
+    assert_eq!(query.find_ids(&mut txn).unwrap(), vec![5, 6]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_offset_limit_semantics() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    for id in 0..10 {
+        let mut obj = TestObj::default(id);
+        obj.byte = (id / 2) as u8;
+        obj.save(&mut txn, col);
+    }
+    let byte = TestObj::get_prop(col, DataType::Byte);
+
+    // The offset and limit are applied after distinct with and without sorting.
+    let mut qb = col.new_query_builder();
+    qb.add_distinct(byte, true);
+    qb.set_offset(1);
+    qb.set_limit(2);
+    assert_eq!(qb.build().find_ids(&mut txn).unwrap(), vec![2, 4]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    qb.add_distinct(byte, true);
+    qb.set_offset(3);
+    qb.set_limit(usize::MAX);
+    assert_eq!(qb.build().find_ids(&mut txn).unwrap(), vec![2, 0]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_offset(usize::MAX);
+    qb.set_limit(usize::MAX);
+    assert_eq!(qb.build().find_ids(&mut txn).unwrap(), Vec::<i64>::new());
+
+    let mut qb = col.new_query_builder();
+    qb.set_limit(0);
+    let query = qb.build();
+    assert_eq!(query.find_ids(&mut txn).unwrap(), Vec::<i64>::new());
+    assert_eq!(query.count(&mut txn).unwrap(), 0);
+    let stats = query.find_while_with_stats(&mut txn, |_, _| true).unwrap();
+    assert_eq!(stats.scanned, 0);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_explain_analyze() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj0 => 0, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(1);
+    let mut upper = IndexKey::new();
+    upper.add_byte(3);
+    let byte = TestObj::get_prop(col, DataType::Byte);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    qb.set_filter(Filter::or(vec![
+        Filter::byte(byte, 1, 1).unwrap(),
+        Filter::byte(byte, 3, 3).unwrap(),
+    ]));
+    let analysis = qb.build().explain_analyze(&mut txn).unwrap();
+    assert_eq!(analysis["results"], 2);
+    assert_eq!(analysis["sort"], Value::Null);
+
+    let where_clause = &analysis["where_clauses"][0];
+    assert_eq!(where_clause["type"], "index");
+    assert_eq!(where_clause["index_entries"], 3);
+    assert_eq!(where_clause["rows"], 3);
+    assert_eq!(where_clause["matches"], 2);
+
+    let filter = &analysis["filter"];
+    assert_eq!(filter["type"], "Or");
+    assert_eq!(filter["evaluations"], 3);
+    assert_eq!(filter["matches"], 2);
+    assert_eq!(filter["children"][0]["type"], "ByteBetween");
+    assert_eq!(filter["children"][0]["property_offset"], byte.offset);
+    assert_eq!(filter["children"][0]["evaluations"], 3);
+    assert_eq!(filter["children"][0]["matches"], 1);
+    assert_eq!(filter["children"][1]["evaluations"], 2);
+    assert_eq!(filter["children"][1]["matches"], 1);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    qb.set_limit(2);
+    let analysis = qb.build().explain_analyze(&mut txn).unwrap();
+    assert_eq!(analysis["results"], 2);
+    assert_eq!(analysis["where_clauses"][0]["type"], "id");
+    assert_eq!(analysis["where_clauses"][0]["rows"], 5);
+    assert_eq!(analysis["filter"], Value::Null);
+    assert_eq!(analysis["sort"]["properties"], 1);
+
+    // The analyzed query runs like the query itself, including distinct, offset and limit.
+    let mut qb = col.new_query_builder();
+    qb.add_distinct(byte, true);
+    qb.set_offset(1);
+    qb.set_limit(3);
+    let query = qb.build();
+    let analysis = query.explain_analyze(&mut txn).unwrap();
+    assert_eq!(analysis["results"], query.find_all_vec(&mut txn).unwrap().len());
+    assert_eq!(analysis["results"], 3);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_explain() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj0 => 0, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+    let byte = TestObj::get_prop(col, DataType::Byte);
+
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 1, 3).unwrap());
+    let plan = qb.build().explain(&mut txn).unwrap();
+    assert_eq!(plan.where_clauses.len(), 1);
+    assert_eq!(plan.where_clauses[0].where_type, "id");
+    assert_eq!(plan.where_clauses[0].index, None);
+    assert!(!plan.uses_index);
+    assert!(plan.full_scan);
+    assert!(plan.filter);
+    assert!(!plan.buffered);
+    assert_eq!(plan.estimated_entries, 5);
+
+    let mut qb = col.new_query_builder();
+    for (lower, upper) in &[(1, 3), (4, 4)] {
+        let mut lower_key = IndexKey::new();
+        lower_key.add_byte(*lower);
+        let mut upper_key = IndexKey::new();
+        upper_key.add_byte(*upper);
+        qb.add_index_where_clause(0, lower_key, true, upper_key, true, false)
+            .unwrap();
+    }
+    qb.add_sort(byte, Sort::Descending).unwrap();
+    let plan = qb.build().explain(&mut txn).unwrap();
+    assert_eq!(plan.where_clauses[0].index.as_deref(), Some("byte"));
+    assert_eq!(plan.where_clauses[0].estimated_entries, 3);
+    assert_eq!(plan.where_clauses[1].estimated_entries, 1);
+    assert!(plan.uses_index);
+    assert!(!plan.full_scan);
+    assert!(!plan.filter);
+    assert!(plan.buffered);
+    assert_eq!(plan.estimated_entries, 4);
+
+    let json = plan.to_json();
+    assert_eq!(json["where_clauses"][0]["type"], "index");
+    assert_eq!(json["where_clauses"][0]["index"], "byte");
+    assert_eq!(json["uses_index"], true);
+    assert_eq!(json["estimated_entries"], 4);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_set_direction() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj0 => 0, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+
+    let mut qb = col.new_query_builder();
+    qb.set_direction(Sort::Descending).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj4, &obj3, &obj2, &obj1, &obj0]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(obj1.id, obj3.id).unwrap();
+    qb.set_direction(Sort::Descending).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj3, &obj2, &obj1]);
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(1);
+    let mut upper = IndexKey::new();
+    upper.add_byte(3);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower.clone(), true, upper.clone(), true, false)
+        .unwrap();
+    qb.set_direction(Sort::Descending).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj3, &obj2, &obj1]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, upper, true, lower, true, false)
+        .unwrap();
+    qb.set_direction(Sort::Ascending).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj1, &obj2, &obj3]);
+
+    // The where clauses are returned in reverse order too.
+    let mut qb = col.new_query_builder();
+    qb.add_id_where_clause(obj0.id, obj1.id).unwrap();
+    qb.add_id_where_clause(obj3.id, obj4.id).unwrap();
+    qb.set_direction(Sort::Descending).unwrap();
+    assert_find(&mut txn, &col, qb.build(), &[&obj4, &obj3, &obj1, &obj0]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_set_direction_link_where_clause() {
+    let col_schema = TestObj::schema("col", &[], &[LinkSchema::new("link", "col")]);
+    isar!(isar, col => col_schema);
+
+    let mut qb = col.new_query_builder();
+    qb.add_link_where_clause(0, 1).unwrap();
+    assert!(qb.set_direction(Sort::Descending).is_err());
+
+    let mut qb = col.new_query_builder();
+    qb.set_direction(Sort::Descending).unwrap();
+    assert!(qb.add_link_where_clause(0, 1).is_err());
+
+    isar.close();
+}
+
+#[test]
+fn test_find_ids() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(col, txn, byte, obj0 => 0, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4);
+
+    let qb = col.new_query_builder();
+    let ids = qb.build().find_ids(&mut txn).unwrap();
+    assert_eq!(ids, vec![obj0.id, obj1.id, obj2.id, obj3.id, obj4.id]);
+
+    let mut lower = IndexKey::new();
+    lower.add_byte(1);
+    let mut upper = IndexKey::new();
+    upper.add_byte(3);
+
+    // overlapping where clauses only return each id once
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, upper, true, lower, true, false)
+        .unwrap();
+    qb.add_id_where_clause(obj2.id, obj4.id).unwrap();
+    let ids = qb.build().find_ids(&mut txn).unwrap();
+    assert_eq!(ids, vec![obj3.id, obj2.id, obj1.id, obj4.id]);
+
+    let mut qb = col.new_query_builder();
+    qb.set_offset(1);
+    qb.set_limit(2);
+    let ids = qb.build().find_ids(&mut txn).unwrap();
+    assert_eq!(ids, vec![obj1.id, obj2.id]);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::byte(byte, 3, 4).unwrap());
+    let ids = qb.build().find_ids(&mut txn).unwrap();
+    assert_eq!(ids, vec![obj3.id, obj4.id]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_find_while_committed() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+    put!(col, txn, byte, obj0 => 0, obj1 => 1, obj2 => 2);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    put!(col, txn, byte, obj3 => 3);
+    col.delete(&mut txn, obj0.id).unwrap();
+    let query = col.new_query_builder().build();
+
+    let mut ids = vec![];
+    query
+        .find_while_committed(&mut txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    assert_eq!(ids, vec![obj0.id, obj1.id, obj2.id]);
+    assert_find(&mut txn, &col, query.clone(), &[&obj1, &obj2, &obj3]);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    let mut ids = vec![];
+    query
+        .find_while_committed(&mut txn, |id, _| {
+            ids.push(id);
+            true
+        })
+        .unwrap();
+    assert_eq!(ids, vec![obj1.id, obj2.id, obj3.id]);
+    txn.abort();
+
+    isar.close();
+}
+
+#[test]
+fn test_link_range_where_clause() {
+    let col_schema = TestObj::schema("col", &[], &[LinkSchema::new("link", "col")]);
+    isar!(isar, col => col_schema);
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4, obj5 => 5, obj6 => 6, obj7 => 7);
+    col.link(&mut txn, 0, 1, 4).unwrap();
+    col.link(&mut txn, 0, 1, 5).unwrap();
+    col.link(&mut txn, 0, 2, 5).unwrap();
+    col.link(&mut txn, 0, 3, 6).unwrap();
+    col.link(&mut txn, 0, 7, 1).unwrap();
+
+    let mut qb = col.new_query_builder();
+    qb.add_link_range_where_clause(0, 1, 3).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj4, &obj5, &obj6]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_link_range_where_clause(0, 3, 2).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj5, &obj6]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_link_range_where_clause(0, 8, 10).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_link_where_clause(0, 2).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj5]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_named_backlink_where_clause() {
+    let posts_schema = TestObj::schema(
+        "posts",
+        &[],
+        &[LinkSchema::with_backlink("comments", "comments", "post")],
+    );
+    let comments_schema = TestObj::schema("comments", &[], &[]);
+    isar!(isar, posts => posts_schema, comments => comments_schema);
+    txn!(isar, txn);
+
+    put!(id: posts, txn, post1 => 1, post2 => 2);
+    put!(id: comments, txn, comment1 => 1, comment2 => 2, comment3 => 3);
+    posts.link(&mut txn, 0, 1, 1).unwrap();
+    comments.link(&mut txn, 0, 2, 1).unwrap();
+    comments.link(&mut txn, 0, 3, 2).unwrap();
+
+    let mut qb = comments.new_query_builder();
+    qb.add_backlink_where_clause(posts, 0, 1).unwrap();
+    assert_find(&mut txn, comments, qb.build(), &[&comment1, &comment2]);
+
+    let mut qb = posts.new_query_builder();
+    qb.add_backlink_where_clause(comments, 0, 3).unwrap();
+    assert_find(&mut txn, posts, qb.build(), &[&post2]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_named_backlink_duplicate_name() {
+    let posts_schema = TestObj::schema(
+        "posts",
+        &[],
+        &[LinkSchema::with_backlink("comments", "comments", "post")],
+    );
+    let comments_schema = TestObj::schema("comments", &[], &[LinkSchema::new("post", "posts")]);
+    assert!(Schema::new(vec![posts_schema, comments_schema]).is_err());
+}
+
+#[test]
+fn test_collated_index_where_clause_and_sort() {
+    let index = IndexSchema::new(
+        "string",
+        vec![IndexPropertySchema::collated("string", false, Collation::Swedish)],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        obj1 => Some("öl".to_string()),
+        obj2 => Some("ål".to_string()),
+        obj3 => Some("zebra".to_string()),
+        obj4 => Some("Äng".to_string()),
+        obj5 => Some("apa".to_string())
+    );
+
+    let mut lower = IndexKey::new();
+    lower.add_collated_string(Some("ZEBRA"), Collation::Swedish, false);
+    let mut upper = IndexKey::new();
+    upper.add_collated_string(Some("öl"), Collation::Swedish, false);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj3, &obj2, &obj4, &obj1]);
+
+    let string = TestObj::get_prop(col, DataType::String);
+    let mut qb = col.new_query_builder();
+    qb.add_collated_sort(string, Sort::Ascending, Collation::Swedish, true)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj5, &obj3, &obj2, &obj4, &obj1]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(string, Sort::Ascending).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj5, &obj3, &obj4, &obj2, &obj1]);
+
+    let byte = TestObj::get_prop(col, DataType::Byte);
+    let mut qb = col.new_query_builder();
+    assert!(qb
+        .add_collated_sort(byte, Sort::Ascending, Collation::Swedish, true)
+        .is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_case_insensitive_index_matches_sort() {
+    let index = IndexSchema::new(
+        "string",
+        vec![IndexPropertySchema::new("string", IndexType::Value, false)],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        obj1 => Some("banana".to_string()),
+        obj2 => Some("Apple".to_string()),
+        obj3 => Some("cherry".to_string()),
+        obj4 => Some("apricot".to_string()),
+        obj5 => Some("Blueberry".to_string())
+    );
+
+    let mut lower = IndexKey::new();
+    lower.add_string(Some("A"), false);
+    let mut upper = IndexKey::new();
+    upper.add_string(Some("Z"), false);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2, &obj4, &obj1, &obj5, &obj3]);
+
+    let string = TestObj::get_prop(col, DataType::String);
+    let mut qb = col.new_query_builder();
+    qb.add_collated_sort(string, Sort::Ascending, Collation::Binary, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2, &obj4, &obj1, &obj5, &obj3]);
+
+    let mut qb = col.new_query_builder();
+    qb.add_sort(string, Sort::Ascending).unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2, &obj5, &obj4, &obj1, &obj3]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_split_string_index_where_clause() {
+    let indexes = [
+        IndexSchema::new(
+            "words",
+            vec![IndexPropertySchema::split(
+                "string",
+                false,
+                StringSplitter::Words,
+            )],
+            false,
+        ),
+        IndexSchema::new(
+            "tags",
+            vec![IndexPropertySchema::split(
+                "string",
+                true,
+                StringSplitter::Whitespace,
+            )],
+            false,
+        ),
+    ];
+    isar!(isar, col => TestObj::schema("col", &indexes, &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        obj1 => Some("red green, blue".to_string()),
+        obj2 => Some("Green-tea green".to_string()),
+        obj3 => Some("blue".to_string()),
+        obj4 => None
+    );
+
+    let find = |txn: &mut _,
+                index: usize,
+                word: &str,
+                case_sensitive: bool,
+                objects: &[&TestObj]| {
+        let mut key = IndexKey::new();
+        key.add_string(Some(word), case_sensitive);
+        let mut qb = col.new_query_builder();
+        qb.add_index_where_clause(index, key.clone(), true, key, true, false)
+            .unwrap();
+        assert_find(txn, col, qb.build(), objects);
+    };
+
+    find(&mut txn, 1, "green", false, &[&obj1, &obj2]);
+    find(&mut txn, 1, "tea", false, &[&obj2]);
+    find(&mut txn, 1, "blue", false, &[&obj1, &obj3]);
+    find(&mut txn, 1, "gre", false, &[]);
+    find(&mut txn, 0, "green,", true, &[&obj1]);
+    find(&mut txn, 0, "Green-tea", true, &[&obj2]);
+    find(&mut txn, 0, "green-tea", true, &[]);
+
+    col.delete(&mut txn, obj1.id).unwrap();
+    find(&mut txn, 1, "green", false, &[&obj2]);
+    find(&mut txn, 1, "red", false, &[]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_split_string_unique_index() {
+    let index = IndexSchema::new(
+        "words",
+        vec![IndexPropertySchema::split(
+            "string",
+            false,
+            StringSplitter::Words,
+        )],
+        true,
+    );
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string, obj1 => Some("Tag tag TAG".to_string()));
+    let mut key = IndexKey::new();
+    key.add_string(Some("tag"), false);
+    let id = col.get_by_index(&mut txn, 0, &key).unwrap().map(|(id, _)| id);
+    assert_eq!(id, Some(obj1.id));
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_composite_list_index_where_clause() {
+    let indexes = [
+        IndexSchema::new(
+            "intTags",
+            vec![
+                IndexPropertySchema::new("int", IndexType::Value, false),
+                IndexPropertySchema::new("stringList", IndexType::Value, false),
+            ],
+            false,
+        ),
+        IndexSchema::new(
+            "listByte",
+            vec![
+                IndexPropertySchema::new("intList", IndexType::Value, false),
+                IndexPropertySchema::new("byte", IndexType::Value, false),
+            ],
+            false,
+        ),
+    ];
+    isar!(isar, col => TestObj::schema("col", &indexes, &[]));
+    txn!(isar, txn);
+
+    let tags = |tags: &[&str]| Some(tags.iter().map(|t| Some(t.to_string())).collect());
+    let mut obj1 = TestObj::default(1);
+    obj1.int = 1;
+    obj1.string_list = tags(&["a", "b"]);
+    obj1.int_list = Some(vec![5, 6]);
+    obj1.byte = 1;
+    obj1.save(&mut txn, col);
+    let mut obj2 = TestObj::default(2);
+    obj2.int = 2;
+    obj2.string_list = tags(&["B", "c"]);
+    obj2.int_list = Some(vec![6]);
+    obj2.byte = 2;
+    obj2.save(&mut txn, col);
+    let mut obj3 = TestObj::default(3);
+    obj3.int = 1;
+    obj3.string_list = tags(&["c"]);
+    obj3.save(&mut txn, col);
+
+    let mut key = IndexKey::new();
+    key.add_int(1);
+    key.add_string(Some("b"), false);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj1]);
+
+    let mut key = IndexKey::new();
+    key.add_int(2);
+    key.add_string(Some("b"), false);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, key.clone(), true, key, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2]);
+
+    let mut lower = IndexKey::new();
+    lower.add_int(1);
+    lower.add_string(None, false);
+    let mut upper = IndexKey::new();
+    upper.add_int(1);
+    upper.add_string(Some("z"), false);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj1, &obj3]);
+
+    let mut lower = IndexKey::new();
+    lower.add_int(6);
+    lower.add_byte(2);
+    let mut upper = IndexKey::new();
+    upper.add_int(6);
+    upper.add_byte(u8::MAX);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(1, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj2]);
+
+    let mut lower = IndexKey::new();
+    lower.add_int(6);
+    lower.add_byte(0);
+    let mut upper = IndexKey::new();
+    upper.add_int(6);
+    upper.add_byte(u8::MAX);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(1, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj1, &obj2]);
+
+    col.delete(&mut txn, obj1.id).unwrap();
+    let mut lower = IndexKey::new();
+    lower.add_int(1);
+    lower.add_string(None, false);
+    let mut upper = IndexKey::new();
+    upper.add_int(1);
+    upper.add_string(Some("z"), false);
+    let mut qb = col.new_query_builder();
+    qb.add_index_where_clause(0, lower, true, upper, true, false)
+        .unwrap();
+    assert_find(&mut txn, col, qb.build(), &[&obj3]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_composite_list_index_invalid() {
+    let index = IndexSchema::new(
+        "lists",
+        vec![
+            IndexPropertySchema::new("intList", IndexType::Value, false),
+            IndexPropertySchema::new("longList", IndexType::Value, false),
+        ],
+        false,
+    );
+    let schema = TestObj::schema("col", &[index], &[]);
+    assert!(Schema::new(vec![schema]).is_err());
+
+    let index = IndexSchema::new(
+        "tagsInt",
+        vec![
+            IndexPropertySchema::new("stringList", IndexType::Value, false),
+            IndexPropertySchema::new("int", IndexType::Value, false),
+        ],
+        false,
+    );
+    let schema = TestObj::schema("col", &[index], &[]);
+    assert!(Schema::new(vec![schema]).is_err());
+}
+
+#[test]
+fn test_index_where_clause_sorted() {
+    let index = IndexSchema::new(
+        "intString",
+        vec![TestObj::int_index(), TestObj::string_index(false, true)],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    // Long strings are truncated in the index and ordered by their hash.
+    let long = "x".repeat(2000);
+    for id in 0..40 {
+        let mut obj = TestObj::default(id);
+        obj.int = (id % 4) as i32;
+        obj.string = match id % 5 {
+            0 => None,
+            1 => Some(format!("{}b", long)),
+            2 => Some(format!("{}a", long)),
+            3 => Some("a".to_string()),
+            _ => Some(format!("s{}", id % 3)),
+        };
+        obj.byte = (id % 7) as u8;
+        obj.save(&mut txn, col);
+    }
+    let int = TestObj::get_prop(col, DataType::Int);
+    let string = TestObj::get_prop(col, DataType::String);
+    let byte = TestObj::get_prop(col, DataType::Byte);
+
+    let mut check = |descending: bool,
+                     sort: &[(Property, Sort)],
+                     distinct: Option<Property>,
+                     offset: usize,
+                     buffered: bool| {
+        let mut lower = IndexKey::new();
+        lower.add_int(i32::MIN);
+        let mut upper = IndexKey::new();
+        upper.add_int(i32::MAX);
+        let mut index_qb = col.new_query_builder();
+        if descending {
+            index_qb.add_index_where_clause(0, upper, true, lower, true, false)
+        } else {
+            index_qb.add_index_where_clause(0, lower, true, upper, true, false)
+        }
+        .unwrap();
+        let mut qb = col.new_query_builder();
+        for qb in [&mut index_qb, &mut qb].iter_mut() {
+            for (property, sort) in sort {
+                qb.add_sort(*property, *sort).unwrap();
+            }
+            if let Some(distinct) = distinct {
+                qb.add_distinct(distinct, true);
+            }
+            qb.set_offset(offset);
+            qb.set_limit(10);
+        }
+        let index_query = index_qb.build();
+        assert_eq!(index_query.explain(&mut txn).unwrap().buffered, buffered);
+        assert_eq!(
+            index_query.find_ids(&mut txn).unwrap(),
+            qb.build().find_ids(&mut txn).unwrap()
+        );
+    };
+
+    let asc = Sort::Ascending;
+    let desc = Sort::Descending;
+    check(false, &[(int, asc), (string, asc), (byte, asc)], None, 0, false);
+    check(true, &[(int, desc), (string, desc), (byte, asc)], None, 5, false);
+    check(false, &[(int, asc), (byte, desc)], None, 3, false);
+    check(true, &[(int, desc), (string, desc), (byte, asc)], Some(int), 1, false);
+    check(false, &[(int, asc), (string, desc), (byte, asc)], Some(string), 0, false);
+    check(false, &[(int, desc), (string, asc), (byte, asc)], None, 0, true);
+    check(true, &[(byte, asc), (int, desc), (string, asc)], None, 0, true);
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,35 @@
+use crate::common::test_obj::TestObj;
+use isar_core::schema::link_schema::LinkSchema;
+use isar_core::schema::{Schema, SchemaValidationError};
+
+mod common;
+
+#[test]
+fn test_validate_json() {
+    let col1 = TestObj::schema("col1", &[], &[LinkSchema::new("link", "col2")]);
+    let col2 = TestObj::schema("col2", &[], &[]);
+    let valid = serde_json::to_vec(&vec![col1.clone(), col2.clone()]).unwrap();
+    assert_eq!(Schema::validate_json(&valid), Ok(()));
+
+    let duplicate = serde_json::to_vec(&vec![col1.clone(), col2, col1.clone()]).unwrap();
+    assert_eq!(
+        Schema::validate_json(&duplicate),
+        Err(SchemaValidationError {
+            collection: None,
+            message: "Duplicate collections".to_string()
+        })
+    );
+
+    let missing_target = serde_json::to_vec(&vec![col1]).unwrap();
+    assert_eq!(
+        Schema::validate_json(&missing_target),
+        Err(SchemaValidationError {
+            collection: Some("col1".to_string()),
+            message: "Link target collection does not exist".to_string()
+        })
+    );
+
+    let err = Schema::validate_json(b"{}").unwrap_err();
+    assert_eq!(err.collection, None);
+    assert!(err.message.starts_with("Could not deserialize schema JSON"));
+}
@@ -0,0 +1,70 @@
+use crate::common::test_obj::TestObj;
+use isar_core::query::search::{SearchOptions, SearchResult};
+
+mod common;
+
+fn string(value: &str) -> Option<String> {
+    Some(value.to_string())
+}
+
+#[test]
+fn test_search() {
+    isar!(isar, col1 => TestObj::schema("col1", &[], &[]), col2 => TestObj::schema("col2", &[], &[]));
+    txn!(isar, txn);
+
+    put!(col1, txn, string, obj1 => string("The quick brown fox"), obj2 => string("Brown bear"));
+    put!(col1, txn, string, _obj3 => string("Nothing here"));
+    put!(col2, txn, string_list, obj4 => Some(vec![string("FOX"), None, string("brownie")]));
+
+    let search = |txn: &mut _, text, options: &SearchOptions| {
+        isar.search(txn, text, &[col1, col2], options)
+            .unwrap()
+            .into_iter()
+            .map(|r: SearchResult| (r.collection_id, r.id, r.score))
+            .collect::<Vec<_>>()
+    };
+    let (id1, id2) = (col1.get_collection_id(), col2.get_collection_id());
+
+    let options = SearchOptions::default();
+    assert_eq!(
+        search(&mut txn, "brown, fox!", &options),
+        vec![(id1, obj1.id, 2), (id1, obj2.id, 1), (id2, obj4.id, 1)]
+    );
+
+    let options = SearchOptions {
+        prefix: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        search(&mut txn, "BROWN fox", &options),
+        vec![(id1, obj1.id, 2), (id2, obj4.id, 2), (id1, obj2.id, 1)]
+    );
+
+    let options = SearchOptions {
+        match_all: true,
+        limit: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(
+        search(&mut txn, "fox brown", &options),
+        vec![(id1, obj1.id, 2)]
+    );
+
+    let options = SearchOptions {
+        properties: Some(vec!["stringList".to_string()]),
+        ..Default::default()
+    };
+    assert_eq!(search(&mut txn, "fox", &options), vec![(id2, obj4.id, 1)]);
+
+    assert!(isar
+        .search(&mut txn, " ,. ", &[col1], &SearchOptions::default())
+        .is_err());
+    let options = SearchOptions {
+        properties: Some(vec!["int".to_string()]),
+        ..Default::default()
+    };
+    assert!(isar.search(&mut txn, "fox", &[col1], &options).is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,47 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::IndexStats;
+
+mod common;
+
+#[test]
+fn test_collection_stats() {
+    isar!(isar, col => TestObj::default_schema());
+
+    txn!(isar, txn);
+    let stats = col.get_stats(&mut txn).unwrap();
+    assert_eq!(stats.object_count, 0);
+    assert_eq!(stats.size, 0);
+    assert_eq!(stats.indexes.len(), 12);
+    assert_eq!(stats.indexes[0].1, IndexStats::default());
+
+    for id in 1..=100 {
+        let mut obj = TestObj::default(id);
+        obj.int_list = Some(vec![1, 2, 3]);
+        obj.save(&mut txn, col);
+    }
+    let stats = col.get_stats(&mut txn).unwrap();
+    assert_eq!(stats.object_count, 100);
+    assert!(stats.object_size > 0);
+    let index = |name: &str| stats.indexes.iter().find(|(n, _)| n == name).unwrap().1;
+    assert_eq!(index("int").entries, 100);
+    assert_eq!(index("intList").entries, 300);
+    assert_eq!(index("string").entries, 100);
+    let index_size: u64 = stats.indexes.iter().map(|(_, s)| s.size).sum();
+    assert_eq!(stats.size, stats.object_size + index_size);
+    txn.commit().unwrap();
+
+    let instance_stats = isar.get_stats().unwrap();
+    assert!(instance_stats.size >= stats.size);
+    assert_eq!(instance_stats.collections, vec![("obj".to_string(), stats)]);
+
+    // Only write txns that were committed are recorded.
+    let latency = instance_stats.commit_latency;
+    assert_eq!(latency.count(), 1);
+    assert!(latency.percentile(0.5) <= latency.max());
+    isar.begin_txn(false, false).unwrap().commit().unwrap();
+    isar.begin_txn(true, false).unwrap().abort();
+    isar.begin_txn(true, false).unwrap().commit().unwrap();
+    assert_eq!(isar.get_stats().unwrap().commit_latency.count(), 2);
+
+    isar.close();
+}
@@ -0,0 +1,158 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::IsarCollection;
+use isar_core::object::data_type::DataType;
+use isar_core::query::filter::Filter;
+use isar_core::schema::index_schema::{
+    IndexPropertySchema, IndexSchema, IndexType, StringSplitter,
+};
+use isar_core::schema::Schema;
+use isar_core::stemmer::Stemmer;
+use isar_core::txn::IsarTxn;
+
+mod common;
+
+fn find_ids(txn: &mut IsarTxn, col: &IsarCollection, words: &[&str]) -> Vec<i64> {
+    let string = TestObj::get_prop(col, DataType::String);
+    let mut qb = col.new_query_builder();
+    qb.set_filter(Filter::text_contains(col, string, words).unwrap());
+    qb.build().find_ids(txn).unwrap()
+}
+
+#[test]
+fn test_text_contains() {
+    let index = IndexSchema::new(
+        "string",
+        vec![IndexPropertySchema::full_text("string")],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        _obj1 => Some("Hello World".to_string()),
+        _obj2 => Some("hello, how are you?".to_string()),
+        _obj3 => Some("Straße".to_string()),
+        _obj4 => None
+    );
+
+    assert_eq!(find_ids(&mut txn, col, &["hello"]), vec![1, 2]);
+    assert_eq!(find_ids(&mut txn, col, &["HELLO", "world"]), vec![1]);
+    assert_eq!(find_ids(&mut txn, col, &["hello world"]), vec![1]);
+    assert_eq!(find_ids(&mut txn, col, &["STRASSE"]), vec![3]);
+    assert_eq!(find_ids(&mut txn, col, &["hell"]), Vec::<i64>::new());
+    assert_eq!(find_ids(&mut txn, col, &[]), vec![1, 2, 3, 4]);
+
+    let int = TestObj::get_prop(col, DataType::Int);
+    assert!(Filter::text_contains(col, int, &["hello"]).is_err());
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_text_contains_stopwords() {
+    let index_property = IndexPropertySchema::full_text("string").with_stopwords(&["The", "a"]);
+    let index = IndexSchema::new("string", vec![index_property], false);
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        _obj1 => Some("The quick fox".to_string()),
+        _obj2 => Some("A lazy dog".to_string())
+    );
+
+    assert_eq!(find_ids(&mut txn, col, &["the fox"]), vec![1]);
+    assert_eq!(find_ids(&mut txn, col, &["a", "DOG"]), vec![2]);
+    // Queries that only contain stopwords match all objects.
+    assert_eq!(find_ids(&mut txn, col, &["THE"]), vec![1, 2]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[cfg(feature = "snowball")]
+#[test]
+fn test_text_contains_stemmer() {
+    let index_property = IndexPropertySchema::full_text("string")
+        .with_stopwords(&["is"])
+        .with_stemmer(Stemmer::English);
+    let index = IndexSchema::new("string", vec![index_property], false);
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        _obj1 => Some("Running is fun".to_string()),
+        _obj2 => Some("He runs".to_string()),
+        _obj3 => Some("Runner".to_string())
+    );
+
+    assert_eq!(find_ids(&mut txn, col, &["run"]), vec![1, 2]);
+    assert_eq!(find_ids(&mut txn, col, &["RUNS", "is", "fun"]), vec![1]);
+    assert_eq!(find_ids(&mut txn, col, &["runners"]), vec![3]);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_text_contains_highlighted() {
+    let index = IndexSchema::new(
+        "string",
+        vec![IndexPropertySchema::full_text("string")],
+        false,
+    );
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put!(col, txn, string,
+        _obj1 => Some("Hello World, hello!".to_string()),
+        _obj2 => Some("Straße world".to_string())
+    );
+
+    let string = TestObj::get_prop(col, DataType::String);
+    let mut qb = col.new_query_builder();
+    let filter = Filter::or(vec![
+        Filter::text_contains(col, string, &["HELLO"]).unwrap(),
+        Filter::text_contains(col, string, &["world", "strasse"]).unwrap(),
+    ]);
+    qb.set_filter(filter);
+    let mut results = vec![];
+    qb.build()
+        .find_while_highlighted(&mut txn, |id, _, matches| {
+            let ranges = matches.iter().map(|m| (m.start, m.end)).collect::<Vec<_>>();
+            assert!(matches.iter().all(|m| m.property == string));
+            results.push((id, ranges));
+            true
+        })
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            (1, vec![(0, 5), (6, 11), (13, 18)]),
+            (2, vec![(0, 7), (8, 13)])
+        ]
+    );
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_text_index_schema() {
+    let schema = |index_property: IndexPropertySchema| {
+        let index = IndexSchema::new("string", vec![index_property], false);
+        TestObj::schema("col", &[index], &[])
+    };
+
+    let unsplit = IndexPropertySchema::new("string", IndexType::Value, false);
+    let col = schema(unsplit.clone().with_stopwords(&["a"]));
+    assert!(Schema::validate(&[col]).is_err());
+    let col = schema(unsplit.with_stemmer(Stemmer::German));
+    assert!(Schema::validate(&[col]).is_err());
+
+    let split = IndexPropertySchema::split("string", false, StringSplitter::Whitespace);
+    let col = schema(split.clone().with_stopwords(&["a"]));
+    assert!(Schema::validate(&[col]).is_ok());
+    let col = schema(split.with_stemmer(Stemmer::German));
+    assert_eq!(Schema::validate(&[col]).is_ok(), cfg!(feature = "snowball"));
+}
@@ -0,0 +1,64 @@
+use isar_core::schema::link_schema::LinkSchema;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_traverse() {
+    let col_schema = TestObj::schema(
+        "col",
+        &[],
+        &[
+            LinkSchema::new("children", "col"),
+            LinkSchema::new("other", "other"),
+        ],
+    );
+    let other_schema = TestObj::schema("other", &[], &[]);
+    isar!(isar, col => col_schema, _other => other_schema);
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3, obj4 => 4, obj5 => 5);
+    col.link(&mut txn, 0, 1, 2).unwrap();
+    col.link(&mut txn, 0, 1, 3).unwrap();
+    col.link(&mut txn, 0, 2, 4).unwrap();
+    col.link(&mut txn, 0, 3, 4).unwrap();
+    col.link(&mut txn, 0, 4, 1).unwrap();
+
+    let mut visited = vec![];
+    col.traverse(&mut txn, 0, 1, 10, |id, depth, _| {
+        visited.push((id, depth));
+        true
+    })
+    .unwrap();
+    assert_eq!(visited, vec![(1, 0), (2, 1), (3, 1), (4, 2)]);
+
+    let mut visited = vec![];
+    col.traverse(&mut txn, 0, 1, 1, |id, depth, _| {
+        visited.push((id, depth));
+        true
+    })
+    .unwrap();
+    assert_eq!(visited, vec![(1, 0), (2, 1), (3, 1)]);
+
+    let mut visited = vec![];
+    col.traverse(&mut txn, 0, 1, 10, |id, _, _| {
+        visited.push(id);
+        visited.len() < 2
+    })
+    .unwrap();
+    assert_eq!(visited, vec![1, 2]);
+
+    let mut visited = vec![];
+    col.traverse(&mut txn, 0, 5, 10, |id, _, _| {
+        visited.push(id);
+        true
+    })
+    .unwrap();
+    assert_eq!(visited, vec![5]);
+
+    assert!(col.traverse(&mut txn, 1, 1, 10, |_, _, _| true).is_err());
+
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,93 @@
+use crate::common::test_obj::TestObj;
+use isar_core::error::IsarError;
+use isar_core::txn::TxnPriority;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_try_begin_txn_timeout() {
+    isar!(isar, _col => TestObj::schema("col", &[], &[]));
+
+    let txn = isar.begin_txn(true, true).unwrap();
+    let isar_clone = isar.clone();
+    let result = thread::spawn(move || {
+        isar_clone
+            .try_begin_txn(
+                true,
+                true,
+                TxnPriority::Interactive,
+                Some(Duration::from_millis(10)),
+            )
+            .err()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(result, Some(IsarError::TxnTimeout {}));
+    txn.abort();
+
+    let txn = isar.try_begin_txn(
+        true,
+        true,
+        TxnPriority::Background,
+        Some(Duration::from_millis(10)),
+    );
+    assert!(txn.is_ok());
+    txn.unwrap().abort();
+
+    isar.close();
+}
+
+#[test]
+fn test_nested_write_txn() {
+    isar!(isar, _col => TestObj::schema("col", &[], &[]));
+
+    let txn = isar.begin_txn(true, true).unwrap();
+    let result = isar.try_begin_txn(true, true, TxnPriority::Background, None);
+    assert_eq!(result.err(), Some(IsarError::TxnActive {}));
+    txn.abort();
+
+    let txn = isar.begin_txn(true, true);
+    assert!(txn.is_ok());
+    txn.unwrap().abort();
+
+    isar.close();
+}
+
+#[test]
+fn test_interactive_before_background() {
+    isar!(isar, _col => TestObj::schema("col", &[], &[]));
+    let order = Arc::new(Mutex::new(vec![]));
+
+    let txn = isar.begin_txn(true, true).unwrap();
+    let mut handles = vec![];
+    for (name, priority) in &[
+        ("background1", TxnPriority::Background),
+        ("background2", TxnPriority::Background),
+        ("interactive", TxnPriority::Interactive),
+    ] {
+        let isar = isar.clone();
+        let order = order.clone();
+        let name = *name;
+        let priority = *priority;
+        handles.push(thread::spawn(move || {
+            let txn = isar.try_begin_txn(true, true, priority, None).unwrap();
+            order.lock().unwrap().push(name);
+            txn.abort();
+        }));
+        thread::sleep(Duration::from_millis(50));
+    }
+    txn.abort();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec!["interactive", "background1", "background2"]
+    );
+
+    isar.close();
+}
@@ -0,0 +1,71 @@
+use crate::common::test_obj::TestObj;
+use isar_core::error::IsarError;
+use isar_core::txn::RetryPolicy;
+use std::time::Duration;
+
+mod common;
+
+// MDBX_BUSY
+fn busy() -> IsarError {
+    IsarError::MdbxError {
+        code: -30778,
+        message: "busy".to_string(),
+    }
+}
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+    }
+}
+
+#[test]
+fn test_txn_with_retry() {
+    isar!(isar, col => TestObj::schema("col", &[], &[]));
+
+    let mut attempts = 0;
+    let result = isar.txn_with_retry(true, &policy(3), |txn| {
+        attempts += 1;
+        TestObj::default(attempts).save(txn, col);
+        if attempts < 3 {
+            Err(busy())
+        } else {
+            Ok(attempts)
+        }
+    });
+    assert_eq!(result, Ok(3));
+
+    txn!(isar, txn);
+    assert!(TestObj::get(col, &mut txn, 1).is_none());
+    assert!(TestObj::get(col, &mut txn, 2).is_none());
+    assert!(TestObj::get(col, &mut txn, 3).is_some());
+    txn.abort();
+
+    isar.close();
+}
+
+#[test]
+fn test_txn_with_retry_gives_up() {
+    isar!(isar, _col => TestObj::schema("col", &[], &[]));
+
+    let mut attempts = 0;
+    let result: Result<(), _> = isar.txn_with_retry(true, &policy(2), |_| {
+        attempts += 1;
+        Err(busy())
+    });
+    assert_eq!(result, Err(busy()));
+    assert_eq!(attempts, 2);
+
+    let mut attempts = 0;
+    let result: Result<(), _> = isar.txn_with_retry(false, &policy(5), |_| {
+        attempts += 1;
+        Err(IsarError::UniqueViolated { id: 1 })
+    });
+    assert_eq!(result, Err(IsarError::UniqueViolated { id: 1 }));
+    assert_eq!(attempts, 1);
+    assert!(!IsarError::DbFull {}.is_retryable());
+
+    isar.close();
+}
@@ -0,0 +1,120 @@
+use crate::common::test_obj::TestObj;
+use isar_core::collection::{IsarCollection, OnConflict};
+use isar_core::error::{IsarError, Result};
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::index_schema::IndexSchema;
+use isar_core::txn::IsarTxn;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[test]
+fn test_savepoint() {
+    isar!(isar, col => TestObj::default_schema());
+    txn!(isar, txn);
+
+    put!(id: col, txn, obj1 => 1);
+    txn.savepoint().unwrap();
+    put!(id: col, txn, obj2 => 2);
+    let operations = txn.stats().unwrap().operations;
+    txn.savepoint().unwrap();
+    put!(id: col, txn, _obj3 => 3);
+    col.delete(&mut txn, obj1.id).unwrap();
+
+    txn.rollback_to_savepoint().unwrap();
+    verify!(txn, col, obj1, obj2);
+    txn.release_savepoint().unwrap();
+    verify!(txn, col, obj1, obj2);
+    // Writes of savepoints that have been rolled back are not counted.
+    assert_eq!(txn.stats().unwrap().operations, operations);
+
+    assert!(txn.rollback_to_savepoint().is_err());
+    assert!(txn.release_savepoint().is_err());
+
+    // Savepoints are released when the txn is committed.
+    txn.savepoint().unwrap();
+    put!(id: col, txn, obj4 => 4);
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj2, obj4);
+    txn.abort();
+
+    let mut txn = isar.begin_txn(false, false).unwrap();
+    assert_eq!(txn.savepoint(), Err(IsarError::WriteTxnRequired {}));
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_savepoint_rollback_notifies_unknown_changes() {
+    isar!(isar, col => TestObj::default_schema());
+    let changes = Arc::new(Mutex::new(vec![]));
+    let changes_clone = changes.clone();
+    let _handle = isar.watch_collection_with_changes(
+        col,
+        Box::new(move |c| {
+            changes_clone.lock().unwrap().push(c.map(|c| c.len()));
+        }),
+    );
+
+    txn!(isar, txn);
+    txn.savepoint().unwrap();
+    put!(id: col, txn, _obj1 => 1);
+    txn.rollback_to_savepoint().unwrap();
+    txn.commit().unwrap();
+
+    assert_eq!(*changes.lock().unwrap(), vec![None]);
+    isar.close();
+}
+
+fn put_int(txn: &mut IsarTxn, col: &IsarCollection, id: i64, int: i32) -> Result<i64> {
+    let mut obj = TestObj::default(id);
+    obj.int = int;
+    let bytes = obj.to_bytes(col);
+    col.put(
+        txn,
+        Some(id),
+        IsarObject::from_bytes(&bytes),
+        OnConflict::Abort,
+    )
+}
+
+#[test]
+fn test_savepoint_failed_operation() {
+    let index = IndexSchema::new("int", vec![TestObj::int_index()], true);
+    isar!(isar, col => TestObj::schema("col", &[index], &[]));
+    txn!(isar, txn);
+
+    put_int(&mut txn, col, 1, 5).unwrap();
+    txn.savepoint().unwrap();
+    put_int(&mut txn, col, 2, 6).unwrap();
+    assert_eq!(
+        put_int(&mut txn, col, 3, 5),
+        Err(IsarError::UniqueViolated { id: 1 })
+    );
+    assert!(matches!(
+        txn.release_savepoint(),
+        Err(IsarError::IllegalArg { .. })
+    ));
+    txn.rollback_to_savepoint().unwrap();
+    put_int(&mut txn, col, 4, 7).unwrap();
+    txn.commit().unwrap();
+
+    txn!(isar, txn);
+    assert!(col.get(&mut txn, 1).unwrap().is_some());
+    assert!(col.get(&mut txn, 2).unwrap().is_none());
+    assert!(col.get(&mut txn, 4).unwrap().is_some());
+
+    // The partial writes of a failed operation cannot be committed.
+    txn.savepoint().unwrap();
+    assert!(put_int(&mut txn, col, 5, 5).is_err());
+    assert!(matches!(txn.commit(), Err(IsarError::IllegalArg { .. })));
+
+    // Without a savepoint the txn is closed.
+    txn!(isar, txn);
+    assert!(put_int(&mut txn, col, 5, 5).is_err());
+    assert_eq!(col.get(&mut txn, 1), Err(IsarError::TransactionClosed {}));
+    txn.abort();
+    isar.close();
+}
@@ -0,0 +1,161 @@
+use crate::common::test_obj::TestObj;
+use isar_core::config::IsarConfig;
+use isar_core::error::IsarError;
+use isar_core::instance::IsarInstance;
+use isar_core::schema::index_schema::IndexSchema;
+use isar_core::schema::link_schema::LinkSchema;
+use isar_core::schema::Schema;
+use isar_core::write_profile::CollectionWriteProfile;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[test]
+fn test_txn_stats() {
+    isar!(isar, col => TestObj::schema("col", &[], &[]));
+    txn!(isar, txn);
+
+    let stats = txn.stats().unwrap();
+    assert_eq!(stats.operations, 0);
+    assert_eq!(stats.bytes_written, 0);
+
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    let stats = txn.stats().unwrap();
+    assert_eq!(stats.operations, 2);
+    assert!(stats.bytes_written > 16);
+    assert!(stats.dirty_pages > 0);
+
+    col.delete(&mut txn, obj1.id).unwrap();
+    assert_eq!(txn.stats().unwrap().operations, 3);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_txn_write_profile() {
+    let indexes = vec![
+        IndexSchema::new("int", vec![TestObj::int_index()], false),
+        IndexSchema::new("intList", vec![TestObj::int_list_index(false)], false),
+    ];
+    let col1_schema = TestObj::schema(
+        "col1",
+        &indexes,
+        &[LinkSchema::with_backlink("l", "col2", "bl")],
+    );
+    let col2_schema = TestObj::schema("col2", &[], &[]);
+    isar!(isar, col1 => col1_schema, col2 => col2_schema);
+    txn!(isar, txn);
+    assert!(matches!(
+        txn.write_profile(),
+        Err(IsarError::IllegalArg { .. })
+    ));
+    txn.profile_writes().unwrap();
+    assert_eq!(txn.write_profile().unwrap(), vec![]);
+
+    let mut obj = TestObj::default(1);
+    obj.int_list = Some(vec![1, 2, 3]);
+    obj.save(&mut txn, col1);
+    put!(id: col2, txn, obj2 => 2);
+    col1.link(&mut txn, 0, obj.id, obj2.id).unwrap();
+
+    // Updating one element of the list rewrites all index entries of the object.
+    obj.int_list = Some(vec![1, 2, 4]);
+    obj.save(&mut txn, col1);
+
+    let profile = txn.write_profile().unwrap();
+    let col1_profile = CollectionWriteProfile {
+        objects_put: 2,
+        objects_deleted: 0,
+        index_entries_written: 8,
+        index_entries_deleted: 4,
+        link_entries_written: 2,
+        link_entries_deleted: 0,
+    };
+    assert_eq!(profile[0], ("col1".to_string(), col1_profile));
+    assert_eq!(profile[0].1.index_entries_per_put(), 6.0);
+    assert_eq!(profile[0].1.link_entries_per_put(), 1.0);
+    assert_eq!(profile[1].1.objects_put, 1);
+    assert_eq!(profile[1].1.index_entries_per_put(), 0.0);
+
+    col2.delete(&mut txn, obj2.id).unwrap();
+    let profile = txn.write_profile().unwrap();
+    assert_eq!(profile[0].1.link_entries_deleted, 2);
+    assert_eq!(profile[1].1.objects_deleted, 1);
+
+    txn.abort();
+    isar.close();
+}
+
+#[test]
+fn test_txn_cursor_stats() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    for cache_size in &[16, 0] {
+        let schema = Schema::new(vec![TestObj::schema("col", &[], &[])]).unwrap();
+        let config = IsarConfig {
+            cursor_cache_size: *cache_size,
+            ..Default::default()
+        };
+        let isar = IsarInstance::open_with_config("cursors", &dir, schema, config).unwrap();
+        let col = isar.collections.first().unwrap();
+
+        txn!(isar, txn);
+        put!(id: col, txn, obj => 1);
+        let stats = txn.stats().unwrap();
+        assert!(stats.cursors_opened > 0);
+
+        col.get(&mut txn, obj.id).unwrap();
+        col.get(&mut txn, obj.id).unwrap();
+        let get_stats = txn.stats().unwrap();
+        if *cache_size == 0 {
+            assert_eq!(get_stats.cursors_opened, stats.cursors_opened + 2);
+            assert_eq!(get_stats.cursors_reused, 0);
+            assert_eq!(get_stats.cursors_evicted, stats.cursors_evicted + 2);
+        } else {
+            assert_eq!(get_stats.cursors_opened, stats.cursors_opened);
+            assert_eq!(get_stats.cursors_reused, stats.cursors_reused + 2);
+            assert_eq!(get_stats.cursors_evicted, 0);
+        }
+
+        txn.abort();
+        isar.close();
+    }
+}
+
+#[test]
+fn test_txn_size_warning() {
+    let mut dir = std::env::temp_dir();
+    let r: u64 = rand::random();
+    dir.push(&r.to_string());
+    let name = r.to_string();
+
+    let messages = Arc::new(Mutex::new(vec![]));
+    let messages_clone = messages.clone();
+    let log_name = name.clone();
+    let handle = IsarInstance::watch_log(Box::new(move |instance_name, message| {
+        if instance_name == log_name {
+            messages_clone.lock().unwrap().push(message.to_string());
+        }
+    }));
+
+    let schema = Schema::new(vec![TestObj::schema("col", &[], &[])]).unwrap();
+    let config = IsarConfig {
+        txn_size_warning: Some(1),
+        ..Default::default()
+    };
+    let isar = IsarInstance::open_with_config(&name, &dir, schema, config).unwrap();
+    let col = isar.collections.first().unwrap();
+
+    let txn = isar.begin_txn(true, false).unwrap();
+    txn.abort();
+    assert!(messages.lock().unwrap().is_empty());
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    assert_eq!(messages.lock().unwrap().len(), 1);
+    txn.abort();
+
+    handle.stop();
+    isar.close_and_delete();
+}
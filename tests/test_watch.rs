@@ -0,0 +1,444 @@
+use crossbeam_channel::unbounded;
+use isar_core::instance::IsarInstance;
+use isar_core::query::query_builder::QueryBuilder;
+use isar_core::schema::Schema;
+use isar_core::watch::debounce::{debounce, debounce_changes};
+use isar_core::watch::lifecycle::LifecycleEvent;
+use isar_core::watch::pause::PausePolicy;
+use isar_core::watch::{ChangeKind, ObjectChange};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_watch_fill_ratio() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let fill_ratio = isar.fill_ratio().unwrap();
+    assert!(fill_ratio > 0.0 && fill_ratio < 1.0);
+
+    let (tx, rx) = unbounded();
+    let handle_low = isar.watch_fill_ratio(0.0, Box::new(move || tx.send(true).unwrap()));
+    let (tx, rx_high) = unbounded();
+    let handle_high = isar.watch_fill_ratio(1.0, Box::new(move || tx.send(true).unwrap()));
+
+    txn!(isar, txn);
+    put!(col, txn, id, obj1 => 1);
+    txn.commit().unwrap();
+
+    assert_eq!(rx.len(), 1);
+    assert_eq!(rx_high.len(), 0);
+
+    // silent txns do not notify watchers
+    let mut txn = isar.begin_txn(true, true).unwrap();
+    put!(col, txn, id, obj2 => 2);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 1);
+
+    handle_low.stop();
+    handle_high.stop();
+    isar.close();
+}
+
+#[test]
+fn test_watch_lifecycle() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let name = rand::random::<u64>().to_string();
+
+    let (tx, rx) = unbounded();
+    let watched_name = name.clone();
+    let handle = IsarInstance::watch_lifecycle(Box::new(move |name, event| {
+        // other tests open instances concurrently
+        if name == watched_name {
+            tx.send(event).unwrap();
+        }
+    }));
+
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let isar = IsarInstance::open(&name, &dir, false, schema).unwrap();
+    let mut copy = dir.clone();
+    copy.push("copy.isar");
+    isar.copy_to_file(&copy).unwrap();
+    let mut compacted = dir.clone();
+    compacted.push("compacted.isar");
+    isar.compact_to_file(&compacted).unwrap();
+    assert!(isar.close());
+
+    let events = rx.try_iter().collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            LifecycleEvent::MigrationStarted,
+            LifecycleEvent::MigrationFinished,
+            LifecycleEvent::Opened,
+            LifecycleEvent::Compacted,
+            LifecycleEvent::Closed,
+        ]
+    );
+
+    // the schema did not change
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let isar = IsarInstance::open(&name, &dir, false, schema).unwrap();
+    isar.verify().unwrap();
+    assert!(isar.close());
+    let events = rx.try_iter().collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            LifecycleEvent::Opened,
+            LifecycleEvent::VerificationPassed,
+            LifecycleEvent::Closed,
+        ]
+    );
+
+    let schema = Schema::new(vec![TestObj::default_schema()])
+        .unwrap()
+        .with_version(2);
+    let isar = IsarInstance::open(&name, &dir, false, schema).unwrap();
+    assert!(isar.close());
+    let schema = Schema::new(vec![TestObj::default_schema()])
+        .unwrap()
+        .with_version(1);
+    assert!(IsarInstance::open(&name, &dir, false, schema).is_err());
+    let events = rx.try_iter().collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            LifecycleEvent::MigrationStarted,
+            LifecycleEvent::MigrationFinished,
+            LifecycleEvent::Opened,
+            LifecycleEvent::Closed,
+            LifecycleEvent::MigrationStarted,
+            LifecycleEvent::MigrationFailed,
+        ]
+    );
+
+    handle.stop();
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let isar = IsarInstance::open(&name, &dir, false, schema).unwrap();
+    assert!(isar.close());
+    assert_eq!(rx.try_iter().count(), 0);
+}
+
+#[test]
+fn test_watch_lifecycle_callback() {
+    let mut dir = std::env::temp_dir();
+    dir.push(rand::random::<u64>().to_string());
+    let name = rand::random::<u64>().to_string();
+
+    // callbacks can use other instances and panics do not reach the caller
+    let (tx, rx) = unbounded();
+    let watched_name = name.clone();
+    let handle = IsarInstance::watch_lifecycle(Box::new(move |name, event| {
+        if name == watched_name {
+            let open = IsarInstance::get_instance(name).is_some();
+            tx.send((event, open)).unwrap();
+            panic!("lifecycle callback");
+        }
+    }));
+
+    let schema = Schema::new(vec![TestObj::default_schema()]).unwrap();
+    let isar = IsarInstance::open(&name, &dir, false, schema).unwrap();
+    assert!(isar.close());
+    handle.stop();
+
+    let events = rx.try_iter().collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            (LifecycleEvent::MigrationStarted, false),
+            (LifecycleEvent::MigrationFinished, false),
+            (LifecycleEvent::Opened, true),
+            (LifecycleEvent::Closed, false),
+        ]
+    );
+}
+
+#[test]
+fn test_watch_id_range() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_id_range(col, 10, 20, Box::new(move || tx.send(true).unwrap()));
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 5, obj2 => 25);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 0);
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj3 => 15, obj4 => 16);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 1);
+
+    txn!(isar, txn);
+    col.delete(&mut txn, 5).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 1);
+
+    txn!(isar, txn);
+    col.delete(&mut txn, 15).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 2);
+
+    txn!(isar, txn);
+    col.clear(&mut txn).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 3);
+
+    handle.stop();
+    txn!(isar, txn);
+    put!(id: col, txn, obj5 => 12);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 3);
+    isar.close();
+}
+
+#[test]
+fn test_watch_object_with_payload() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_object_with_payload(
+        col,
+        1,
+        1000,
+        Box::new(move |payload| tx.send(payload.map(|p| p.to_vec())).unwrap()),
+    );
+    let (tx, rx_small) = unbounded();
+    let handle_small = isar.watch_object_with_payload(
+        col,
+        1,
+        1,
+        Box::new(move |payload| tx.send(payload.is_some()).unwrap()),
+    );
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2);
+    txn.commit().unwrap();
+    assert_eq!(rx.try_recv().unwrap(), Some(obj1.to_bytes(col)));
+    assert_eq!(rx_small.try_recv().unwrap(), false);
+
+    let mut obj1 = obj1;
+    obj1.int = 5;
+    txn!(isar, txn);
+    obj1.save(&mut txn, col);
+    txn.commit().unwrap();
+    assert_eq!(rx.try_recv().unwrap(), Some(obj1.to_bytes(col)));
+
+    txn!(isar, txn);
+    col.delete(&mut txn, 1).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(rx.try_recv().unwrap(), None);
+    assert_eq!(rx.len(), 0);
+
+    handle.stop();
+    handle_small.stop();
+    isar.close();
+}
+
+#[test]
+fn test_watch_errors() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let (tx, rx_errors) = unbounded();
+    let handle_errors = isar.watch_errors(Box::new(move |e| tx.send(e.to_string()).unwrap()));
+    let handle_panic = isar.watch_collection(col, Box::new(|| panic!("listener failed")));
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_collection(col, Box::new(move || tx.send(true).unwrap()));
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1);
+    assert!(txn.commit().is_ok());
+
+    assert_eq!(rx.len(), 1);
+    assert_eq!(rx_errors.try_recv().unwrap(), "listener failed");
+
+    handle_errors.stop();
+    txn!(isar, txn);
+    put!(id: col, txn, obj2 => 2);
+    assert!(txn.commit().is_ok());
+    assert_eq!(rx.len(), 2);
+    assert_eq!(rx_errors.len(), 0);
+
+    handle_panic.stop();
+    handle.stop();
+    isar.close();
+}
+
+#[test]
+fn test_watch_pause() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_collection(col, Box::new(move || tx.send(true).unwrap()));
+    let (tx, rx_payload) = unbounded();
+    let handle_payload = isar.watch_object_with_payload(
+        col,
+        1,
+        1000,
+        Box::new(move |payload| tx.send(payload.map(|p| p.to_vec())).unwrap()),
+    );
+
+    handle.pause(PausePolicy::Buffer);
+    handle_payload.pause(PausePolicy::Buffer);
+    assert!(handle.is_paused());
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1);
+    txn.commit().unwrap();
+    let mut obj1 = obj1;
+    obj1.int = 5;
+    txn!(isar, txn);
+    obj1.save(&mut txn, col);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 0);
+    assert_eq!(rx_payload.len(), 0);
+
+    handle.resume();
+    handle_payload.resume();
+    assert!(!handle.is_paused());
+    assert_eq!(rx.len(), 1);
+    assert_eq!(rx_payload.try_recv().unwrap(), Some(obj1.to_bytes(col)));
+
+    handle.pause(PausePolicy::Drop);
+    txn!(isar, txn);
+    put!(id: col, txn, obj2 => 2);
+    txn.commit().unwrap();
+    handle.resume();
+    assert_eq!(rx.len(), 1);
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj3 => 3);
+    txn.commit().unwrap();
+    assert_eq!(rx.len(), 2);
+
+    handle.stop();
+    handle_payload.stop();
+    isar.close();
+}
+
+#[test]
+fn test_watch_debounce() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let interval = Duration::from_millis(500);
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_collection(
+        col,
+        debounce(Box::new(move || tx.send(true).unwrap()), interval),
+    );
+    let (tx, rx_changes) = unbounded();
+    let handle_changes = isar.watch_collection_with_changes(
+        col,
+        debounce_changes(
+            Box::new(move |changes| tx.send(changes.map(|c| c.to_vec())).unwrap()),
+            interval,
+        ),
+    );
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1);
+    txn.commit().unwrap();
+    txn!(isar, txn);
+    put!(id: col, txn, obj2 => 2);
+    txn.commit().unwrap();
+    txn!(isar, txn);
+    put!(id: col, txn, obj3 => 3);
+    txn.commit().unwrap();
+
+    assert_eq!(rx.len(), 1);
+    assert_eq!(
+        rx_changes.try_recv().unwrap(),
+        Some(vec![ObjectChange {
+            id: 1,
+            kind: ChangeKind::Inserted
+        }])
+    );
+
+    // The other two notifications are merged and delivered after the interval.
+    let timeout = Duration::from_secs(5);
+    rx.try_recv().unwrap();
+    assert_eq!(rx.recv_timeout(timeout), Ok(true));
+    assert_eq!(rx_changes.recv_timeout(timeout), Ok(None));
+    assert_eq!(rx.len(), 0);
+
+    handle.stop();
+    handle_changes.stop();
+    isar.close();
+}
+
+#[test]
+fn test_watch_with_changes() {
+    isar!(isar, col => TestObj::default_schema());
+
+    let (tx, rx) = unbounded();
+    let handle = isar.watch_collection_with_changes(
+        col,
+        Box::new(move |changes| tx.send(changes.map(|c| c.to_vec())).unwrap()),
+    );
+    let query = json!({"filter": {"type": "eq", "property": "int", "value": 5}});
+    let query = QueryBuilder::from_json(col, &query).unwrap().build();
+    let (tx, rx_query) = unbounded();
+    let handle_query = isar.watch_query_with_changes(
+        col,
+        query,
+        Box::new(move |changes| tx.send(changes.map(|c| c.to_vec())).unwrap()),
+    );
+
+    let change = |id, kind| ObjectChange { id, kind };
+
+    txn!(isar, txn);
+    put!(id: col, txn, obj1 => 1, obj2 => 2, obj3 => 3);
+    col.delete(&mut txn, 3).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        Some(vec![
+            change(1, ChangeKind::Inserted),
+            change(2, ChangeKind::Inserted)
+        ])
+    );
+    assert_eq!(rx_query.len(), 0);
+
+    let mut obj1 = obj1;
+    obj1.int = 5;
+    txn!(isar, txn);
+    obj1.save(&mut txn, col);
+    col.delete(&mut txn, 2).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        Some(vec![
+            change(1, ChangeKind::Updated),
+            change(2, ChangeKind::Deleted)
+        ])
+    );
+    assert_eq!(
+        rx_query.try_recv().unwrap(),
+        Some(vec![change(1, ChangeKind::Inserted)])
+    );
+
+    obj1.int = 6;
+    txn!(isar, txn);
+    obj1.save(&mut txn, col);
+    txn.commit().unwrap();
+    assert_eq!(
+        rx_query.try_recv().unwrap(),
+        Some(vec![change(1, ChangeKind::Deleted)])
+    );
+
+    txn!(isar, txn);
+    col.clear(&mut txn).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(rx.try_iter().last().unwrap(), None);
+    assert_eq!(rx_query.try_recv().unwrap(), None);
+
+    handle.stop();
+    handle_query.stop();
+    isar.close();
+}
@@ -0,0 +1,63 @@
+use isar_core::collection::OnConflict;
+use isar_core::object::isar_object::IsarObject;
+use isar_core::schema::link_schema::LinkSchema;
+
+use crate::common::test_obj::TestObj;
+
+mod common;
+
+#[test]
+fn test_write_batch() {
+    let link_schema = LinkSchema::new("link", "obj");
+    let schema = TestObj::schema("obj", &[], &[link_schema]);
+    isar!(isar, col => schema);
+
+    let obj1 = TestObj::default(1);
+    let obj2 = TestObj::default(2);
+    let obj3 = TestObj::default(3);
+    let bytes1 = obj1.to_bytes(col);
+    let bytes2 = obj2.to_bytes(col);
+    let bytes3 = obj3.to_bytes(col);
+
+    let mut batch = isar.write_batch();
+    batch.put(
+        col,
+        Some(1),
+        IsarObject::from_bytes(&bytes1),
+        OnConflict::Abort,
+    );
+    batch.put(
+        col,
+        Some(2),
+        IsarObject::from_bytes(&bytes2),
+        OnConflict::Abort,
+    );
+    batch.put(
+        col,
+        Some(3),
+        IsarObject::from_bytes(&bytes3),
+        OnConflict::Abort,
+    );
+    batch.delete(col, 3);
+    batch.link(col, 0, 1, 2);
+    batch.link(col, 0, 2, 3);
+    assert_eq!(batch.len(), 6);
+    assert_eq!(batch.commit().unwrap(), vec![1, 2, 3]);
+
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj2; "link", 1 => 2);
+    txn.abort();
+
+    // a failing operation discards the whole batch
+    let mut batch = isar.write_batch();
+    batch.delete(col, 1);
+    batch.unlink(col, 0, 1, 2);
+    batch.link(col, 5, 1, 2);
+    assert!(batch.commit().is_err());
+
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj2; "link", 1 => 2);
+    txn.abort();
+
+    isar.close();
+}
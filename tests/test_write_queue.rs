@@ -0,0 +1,91 @@
+use crate::common::test_obj::TestObj;
+use crossbeam_channel::unbounded;
+use isar_core::collection::OnConflict;
+use isar_core::error::IsarError;
+use isar_core::object::isar_object::IsarObject;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn test_write_queue() {
+    isar!(isar, col => TestObj::schema("obj", &[], &[]));
+    let commits = Arc::new(AtomicUsize::new(0));
+    let commits_clone = commits.clone();
+    let handle = isar.watch_collection(
+        col,
+        Box::new(move || {
+            commits_clone.fetch_add(1, Ordering::SeqCst);
+        }),
+    );
+
+    let obj1 = TestObj::default(1);
+    let obj2 = TestObj::default(2);
+    let obj3 = TestObj::default(3);
+    let queue = isar.write_queue(Duration::from_millis(200));
+    let (tx, rx) = unbounded();
+    for (obj, fail) in &[(&obj1, false), (&obj3, true), (&obj2, false)] {
+        let bytes = obj.to_bytes(col);
+        let (id, fail) = (obj.id, *fail);
+        let instance = isar.clone();
+        let tx = tx.clone();
+        queue
+            .submit(
+                move |txn| {
+                    let col = instance.collection_by_name("obj").unwrap();
+                    let object = IsarObject::from_bytes(&bytes);
+                    let id = col.put(txn, Some(id), object, OnConflict::Abort)?;
+                    if fail {
+                        return Err(IsarError::IllegalArg {
+                            message: "failed".to_string(),
+                        });
+                    }
+                    Ok(id)
+                },
+                move |result| tx.send(result).unwrap(),
+            )
+            .unwrap();
+    }
+    drop(queue);
+
+    // The failed job is rolled back and the other jobs are written in a single txn.
+    let results: Vec<_> = rx.try_iter().collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_err());
+    assert_eq!(&results[1..], &[Ok(1), Ok(2)]);
+    assert_eq!(commits.load(Ordering::SeqCst), 1);
+
+    txn!(isar, txn);
+    verify!(txn, col, obj1, obj2);
+    txn.abort();
+    handle.stop();
+    assert!(isar.close());
+}
+
+#[test]
+fn test_write_queue_stopped_worker() {
+    isar!(isar, _col => TestObj::schema("obj", &[], &[]));
+    let queue = isar.write_queue(Duration::from_millis(10));
+
+    queue
+        .submit(
+            |_| -> Result<(), IsarError> { panic!("job failed") },
+            |_| {},
+        )
+        .unwrap();
+    let mut result = Ok(());
+    for _ in 0..100 {
+        result = queue.submit(|_| Ok(()), |_| {});
+        if result.is_err() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(result, Err(IsarError::WorkerStopped {}));
+
+    drop(queue);
+    assert!(isar.close());
+}